@@ -131,6 +131,16 @@ __attribute__((used)) static SysCallReg _shadow_raw_syscall_event(const ShimEven
                 rv.as_i64 = shadow_real_raw_syscall(
                     syscall_event->event_data.syscall.syscall_args.number, regs[0].as_u64,
                     regs[1].as_u64, regs[2].as_u64, regs[3].as_u64, regs[4].as_u64, regs[5].as_u64);
+
+                if (res.event_data.syscall_do_native.report_result) {
+                    // e.g. for the strace log
+                    ShimEvent result_event = {
+                        .event_id = SHD_SHIM_EVENT_SYSCALL_NATIVE_RESULT,
+                        .event_data.syscall_complete.retval = rv,
+                    };
+                    shimevent_sendEventToShadow(ipc, &result_event);
+                    shimevent_recvEventFromShadow(ipc, &result_event, true);
+                }
                 return rv;
             }
             case SHD_SHIM_EVENT_SYSCALL: {
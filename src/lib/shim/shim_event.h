@@ -5,6 +5,7 @@
 // used in both places.
 
 #include <arpa/inet.h>
+#include <stdbool.h>
 #include <stdint.h>
 #include <time.h>
 
@@ -36,7 +37,7 @@ int shadow_hostname_to_addr_ipv4(const char* name, size_t name_len, uint32_t* ad
                                  size_t addr_len);
 
 typedef enum {
-    // Next val: 13
+    // Next val: 14
     SHD_SHIM_EVENT_NULL = 0,
     SHD_SHIM_EVENT_START = 1,
     SHD_SHIM_EVENT_STOP = 2,
@@ -50,6 +51,7 @@ typedef enum {
     SHD_SHIM_EVENT_BLOCK = 10,
     SHD_SHIM_EVENT_ADD_THREAD_REQ = 11,
     SHD_SHIM_EVENT_ADD_THREAD_PARENT_RES = 12,
+    SHD_SHIM_EVENT_SYSCALL_NATIVE_RESULT = 13,
 } ShimEventID;
 
 typedef struct _ShimEvent {
@@ -80,6 +82,12 @@ typedef struct _ShimEvent {
             uint64_t simulation_nanos;
         } syscall_complete;
 
+        struct {
+            // Send the result to Shadow in a SHD_SHIM_EVENT_SYSCALL_NATIVE_RESULT
+            // event after making the syscall, and wait for it to be sent back.
+            bool report_result;
+        } syscall_do_native;
+
         struct {
             ShMemBlockSerialized serial;
             PluginPtr plugin_ptr;
@@ -10,6 +10,15 @@
 
 /* Warning, this file is autogenerated by cbindgen. Don't modify this manually. */
 
+typedef enum HeartbeatFormat {
+  // Don't write heartbeat statistics to a file
+  HEARTBEAT_FORMAT_NONE,
+  // Comma-separated values, with a header row
+  HEARTBEAT_FORMAT_CSV,
+  // A JSON object per line
+  HEARTBEAT_FORMAT_JSON,
+} HeartbeatFormat;
+
 typedef enum InterposeMethod {
   // Attach to child using ptrace and use it to interpose syscalls etc.
   INTERPOSE_METHOD_PTRACE,
@@ -18,11 +27,34 @@ typedef enum InterposeMethod {
   INTERPOSE_METHOD_PRELOAD,
 } InterposeMethod;
 
+typedef enum LogFormat {
+  // Space-separated fields, intended to be read by people
+  LOG_FORMAT_TEXT,
+  // A JSON object per line, intended to be read by other programs
+  LOG_FORMAT_JSON,
+} LogFormat;
+
+typedef enum MemoryLimitAction {
+  // Fail the allocation with ENOMEM
+  MEMORY_LIMIT_ACTION_ENOMEM,
+  // Kill the host's process that is using the most memory
+  MEMORY_LIMIT_ACTION_KILL,
+} MemoryLimitAction;
+
 typedef enum QDiscMode {
   Q_DISC_MODE_FIFO,
   Q_DISC_MODE_ROUND_ROBIN,
 } QDiscMode;
 
+typedef enum RestartPolicy {
+  // Never restart the process
+  RESTART_POLICY_NEVER,
+  // Restart the process if it exits with a non-zero exit code or is killed by a signal
+  RESTART_POLICY_ON_FAILURE,
+  // Restart the process whenever it exits
+  RESTART_POLICY_ALWAYS,
+} RestartPolicy;
+
 // Memory allocated by Shadow, in a remote address space.
 typedef struct AllocdMem_u8 AllocdMem_u8;
 
@@ -53,6 +85,9 @@ typedef struct DescriptorTable DescriptorTable;
 
 typedef struct HostOptions HostOptions;
 
+// A parsed set of per-module log level directives.
+typedef struct LogFilter LogFilter;
+
 // A set of `n` logical processors
 typedef struct LogicalProcessors LogicalProcessors;
 
@@ -61,9 +96,15 @@ typedef struct LogicalProcessors LogicalProcessors;
 // through the MemoryManager to ensure soundness. See MemoryManager::new.
 typedef struct MemoryManager MemoryManager;
 
+// A parsed packet capture filter expression.
+typedef struct PcapFilter PcapFilter;
+
 // An opaque type used when passing `*const AtomicRefCell<File>` to C.
 typedef struct PosixFileArc PosixFileArc;
 
+// The state of a process at the end of the simulation.
+typedef struct ProcessFinalState ProcessFinalState;
+
 // A mutable reference to a slice of plugin memory. Implements DerefMut<[T]>,
 // allowing, e.g.:
 //
@@ -87,4 +128,23 @@ typedef struct ProcessMemoryRef_u8 ProcessMemoryRef_u8;
 
 typedef struct ProcessOptions ProcessOptions;
 
+// The values of the variables that are substituted in a process's `path`, `args`, and
+// `environment`, which differ between the replicas of a host.
+typedef struct ProcessVariables ProcessVariables;
+
+// Statistics about a simulation, collected from multiple worker threads.
+typedef struct SimStats SimStats;
+
+// Tracks the state that start conditions depend on, collected from multiple worker threads.
+typedef struct StartAfter StartAfter;
+
+// The conditions of a single process, passed to C as an opaque pointer.
+typedef struct StartConditions StartConditions;
+
+// Tracks which processes exited, collected from multiple worker threads.
+typedef struct StopCondition StopCondition;
+
+// A condition that stops the simulation before its stop time.
+typedef struct StopWhen StopWhen;
+
 #endif /* main_opaque_bindings_h */
@@ -54,6 +54,9 @@ typedef struct DescriptorTable DescriptorTable;
 
 typedef struct HostOptions HostOptions;
 
+// A parsed set of per-module log level directives.
+typedef struct LogFilter LogFilter;
+
 // A set of `n` logical processors
 typedef struct LogicalProcessors LogicalProcessors;
 
@@ -62,9 +65,15 @@ typedef struct LogicalProcessors LogicalProcessors;
 // through the MemoryManager to ensure soundness. See MemoryManager::new.
 typedef struct MemoryManager MemoryManager;
 
+// A parsed packet capture filter expression.
+typedef struct PcapFilter PcapFilter;
+
 // An opaque type used when passing `*const AtomicRefCell<File>` to C.
 typedef struct PosixFileArc PosixFileArc;
 
+// The state of a process at the end of the simulation.
+typedef struct ProcessFinalState ProcessFinalState;
+
 // A mutable reference to a slice of plugin memory. Implements DerefMut<[T]>,
 // allowing, e.g.:
 //
@@ -88,12 +97,32 @@ typedef struct ProcessMemoryRef_u8 ProcessMemoryRef_u8;
 
 typedef struct ProcessOptions ProcessOptions;
 
+// The values of the variables that are substituted in a process's `path`, `args`, and
+// `environment`, which differ between the replicas of a host.
+typedef struct ProcessVariables ProcessVariables;
+
+// Statistics about a simulation, collected from multiple worker threads.
+typedef struct SimStats SimStats;
+
+// Tracks the state that start conditions depend on, collected from multiple worker threads.
+typedef struct StartAfter StartAfter;
+
+// The conditions of a single process, passed to C as an opaque pointer.
+typedef struct StartConditions StartConditions;
+
+// Tracks which processes exited, collected from multiple worker threads.
+typedef struct StopCondition StopCondition;
+
+// A condition that stops the simulation before its stop time.
+typedef struct StopWhen StopWhen;
+
 typedef uint64_t WatchHandle;
 
 // Flush Rust's log::logger().
 void rustlogger_flush(void);
 
-// Set the max (noisiest) logging level to `level`.
+// Set the logging level of records that aren't matched by the log filter or a
+// host's log level to `level`.
 void rustlogger_setLevel(LogLevel level);
 
 // Whether logging is currently enabled for `level`.
@@ -110,8 +139,9 @@ void rustlogger_log(LogLevel level,
 // Creates a ShadowLogger and installs it as the default logger for Rust's
 // `log` crate. The returned pointer is never deallocated, since loggers
 // registered with the `log` crate are required to live for the life of the
-// program.
-void shadow_logger_init(void);
+// program. Records are written in the given format, and filtered by the
+// optional `filter` in addition to the log level.
+void shadow_logger_init(enum LogFormat format, const struct LogFilter *filter);
 
 // When disabled, the logger thread is notified to write each record as
 // soon as it's created.  The calling thread still isn't blocked on the
@@ -140,6 +170,107 @@ void lps_idleTimerContinue(const struct LogicalProcessors *lps, int lpi);
 
 void lps_idleTimerStop(const struct LogicalProcessors *lps, int lpi);
 
+struct SimStats *simstats_new(void);
+
+void simstats_free(struct SimStats *stats);
+
+// Adds the statistics of a host. `packets_dropped` counts the dropped packets by
+// reason, and may be NULL. Safe to call from multiple threads.
+void simstats_addHost(const struct SimStats *stats,
+                      const char *hostname,
+                      uint64_t events_processed,
+                      uint64_t bytes_sent,
+                      uint64_t bytes_received,
+                      const struct Counter *packets_dropped);
+
+// Adds the statistics of a process that ran on the host `hostname`. `exit_time` is
+// SIMTIME_INVALID if the process was still running at the end of the simulation, and
+// `exit_code` is ignored if the process never started. `wait_status` is the status
+// returned by `waitpid()`, or -1 if it's unknown. `syscall_counts` and
+// `expected_final_state` may be NULL. Safe to call from multiple threads.
+void simstats_addProcess(const struct SimStats *stats,
+                         const char *hostname,
+                         const char *name,
+                         uint32_t pid,
+                         SimulationTime start_time,
+                         SimulationTime exit_time,
+                         bool did_start,
+                         int32_t exit_code,
+                         int32_t wait_status,
+                         uint64_t syscalls,
+                         const struct Counter *syscall_counts,
+                         const struct ProcessFinalState *expected_final_state);
+
+// Logs a table of the processes that didn't end in their expected final state, and
+// returns the number of these processes.
+uint32_t simstats_checkFinalStates(const struct SimStats *stats);
+
+// Writes the statistics as JSON to the file at `path`. Returns false if the file could
+// not be written.
+bool simstats_write(const struct SimStats *stats,
+                    const char *path,
+                    uint64_t wall_time_ns,
+                    SimulationTime sim_time,
+                    uint64_t rounds);
+
+struct StartAfter *startafter_new(void);
+
+void startafter_free(struct StartAfter *start_after);
+
+void startafter_addHost(const struct StartAfter *start_after, const char *name);
+
+// Adds a process while the simulation is being set up. `name` and `conditions` may be
+// NULL.
+void startafter_addProcess(const struct StartAfter *start_after,
+                           const char *name,
+                           const struct StartConditions *conditions);
+
+// Logs an error for each condition that can never be met, and returns false if there were
+// any.
+bool startafter_validate(const struct StartAfter *start_after);
+
+// Records that a process with the given name exited successfully. Safe to call from
+// multiple threads.
+void startafter_addExit(const struct StartAfter *start_after, const char *name);
+
+// Updates the state at the end of a round. Must not be called while hosts are running.
+// `is_listening` returns true if the named host has a TCP socket bound to the port.
+void startafter_endRound(const struct StartAfter *start_after,
+                         bool (*is_listening)(const char*, uint16_t, void*),
+                         void *data);
+
+// Returns true if all of the conditions were met at the end of the last round. Safe to
+// call from multiple threads.
+bool startafter_areMet(const struct StartAfter *start_after,
+                       const struct StartConditions *conditions);
+
+void startconditions_free(struct StartConditions *conditions);
+
+// `stop_when` may be NULL.
+struct StopCondition *stopcondition_new(const struct StopWhen *stop_when);
+
+void stopcondition_free(struct StopCondition *condition);
+
+// Adds a process while the simulation is being set up. `name` may be NULL.
+void stopcondition_addProcess(const struct StopCondition *condition, const char *name);
+
+// Logs an error for each process name that no process has, and returns false if there
+// were any.
+bool stopcondition_validate(const struct StopCondition *condition);
+
+// Records that a process exited and won't be restarted. `name` may be NULL. Safe to call
+// from multiple threads.
+void stopcondition_addExit(const struct StopCondition *condition,
+                           const char *name,
+                           bool terminates_simulation);
+
+// Returns a description of why the simulation should stop, or NULL if it should keep
+// running. Must not be called while hosts are running. The returned string must be freed
+// with `stopcondition_freeString()`.
+char *stopcondition_check(const struct StopCondition *condition);
+
+void stopcondition_freeString(char *string);
+
 struct CliOptions *clioptions_parse(int argc, const char *const *argv);
 
 void clioptions_free(struct CliOptions *options);
@@ -154,12 +285,22 @@ bool clioptions_getShowBuildInfo(const struct CliOptions *options);
 
 bool clioptions_getShowConfig(const struct CliOptions *options);
 
+bool clioptions_getShowConfigSchema(const struct CliOptions *options);
+
+bool clioptions_getValidateConfig(const struct CliOptions *options);
+
 char *clioptions_getConfig(const struct CliOptions *options);
 
-struct ConfigFileOptions *configfile_parse(const char *filename);
+// Parses the configuration file, and the files that it includes, and applies the
+// command-line overrides. A filename of "-" reads from stdin. Returns NULL on error.
+struct ConfigFileOptions *configfile_parse(const char *filename, const struct CliOptions *options);
 
 void configfile_free(struct ConfigFileOptions *config);
 
+// Prints the JSON schema of the configuration file, including the option descriptions and
+// defaults.
+void configfile_showSchema(void);
+
 struct ConfigOptions *config_new(const struct ConfigFileOptions *config_file,
                                  const struct CliOptions *options);
 
@@ -173,8 +314,20 @@ unsigned int config_getSeed(const struct ConfigOptions *config);
 
 LogLevel config_getLogLevel(const struct ConfigOptions *config);
 
+enum LogFormat config_getLogFormat(const struct ConfigOptions *config);
+
+// Returns the log filter, or NULL if there isn't one. The filter is owned by the
+// config.
+const struct LogFilter *config_getLogFilter(const struct ConfigOptions *config);
+
+// Returns the condition that stops the simulation early, or NULL if there isn't one. The
+// condition is owned by the config.
+const struct StopWhen *config_getStopWhen(const struct ConfigOptions *config);
+
 SimulationTime config_getHeartbeatInterval(const struct ConfigOptions *config);
 
+enum HeartbeatFormat config_getHeartbeatFormat(const struct ConfigOptions *config);
+
 SimulationTime config_getRunahead(const struct ConfigOptions *config);
 
 bool config_getUseCpuPinning(const struct ConfigOptions *config);
@@ -215,17 +368,21 @@ char *config_getDataDirectory(const struct ConfigOptions *config);
 
 char *config_getTemplateDirectory(const struct ConfigOptions *config);
 
-uint64_t config_getSocketRecvBuffer(const struct ConfigOptions *config);
-
-uint64_t config_getSocketSendBuffer(const struct ConfigOptions *config);
+char *config_getPcapFile(const struct ConfigOptions *config);
 
-bool config_getSocketSendAutotune(const struct ConfigOptions *config);
+// The capture size of the network-wide capture in `config_getPcapFile()`, from the global
+// host defaults.
+uint64_t config_getPcapCaptureSize(const struct ConfigOptions *config);
 
-bool config_getSocketRecvAutotune(const struct ConfigOptions *config);
+// Returns a copy of the network-wide capture's filter from the global host defaults, or
+// NULL if all packets should be captured. The filter must be freed using
+// `pcapfilter_free()`.
+struct PcapFilter *config_getPcapFilter(const struct ConfigOptions *config);
 
-uint64_t config_getInterfaceBuffer(const struct ConfigOptions *config);
+SimulationTime config_getPcapStartTime(const struct ConfigOptions *config);
 
-enum QDiscMode config_getInterfaceQdisc(const struct ConfigOptions *config);
+// Returns `SIMTIME_MAX` if packets should be captured until the end of the simulation.
+SimulationTime config_getPcapStopTime(const struct ConfigOptions *config);
 
 bool config_getUseLegacyWorkingDir(const struct ConfigOptions *config);
 
@@ -243,22 +400,92 @@ void hostoptions_freeString(char *string);
 
 unsigned int hostoptions_getQuantity(const struct HostOptions *host);
 
+// Returns the hostname of the host with the given counter, where `name` is the name of the
+// host in the config. The counter starts at 1, and is ignored if the host's quantity is 1.
+char *hostoptions_getHostname(const struct HostOptions *host,
+                              const char *name,
+                              unsigned int index);
+
+// Returns -1 if the host wasn't assigned to a network graph node.
+int64_t hostoptions_getNetworkNodeId(const struct HostOptions *host);
+
+// Returns NULL if the host has no exact IP address. Caller must free returned string.
+char *hostoptions_getIpAddr(const struct HostOptions *host);
+
 LogLevel hostoptions_getLogLevel(const struct HostOptions *host);
 
+bool hostoptions_getLogToFile(const struct HostOptions *host);
+
 LogLevel hostoptions_getHeartbeatLogLevel(const struct HostOptions *host);
 
 LogInfoFlags hostoptions_getHeartbeatLogInfo(const struct HostOptions *host);
 
 SimulationTime hostoptions_getHeartbeatInterval(const struct HostOptions *host);
 
+enum HeartbeatFormat hostoptions_getHeartbeatFormat(const struct HostOptions *host);
+
 char *hostoptions_getPcapDirectory(const struct HostOptions *host);
 
+uint64_t hostoptions_getPcapCaptureSize(const struct HostOptions *host);
+
+// Returns a copy of the host's capture filter, or NULL if all packets should be captured.
+// The filter must be freed using `pcapfilter_free()`.
+struct PcapFilter *hostoptions_getPcapFilter(const struct HostOptions *host);
+
+SimulationTime hostoptions_getPcapStartTime(const struct HostOptions *host);
+
+// Returns `SIMTIME_MAX` if packets should be captured until the end of the simulation.
+SimulationTime hostoptions_getPcapStopTime(const struct HostOptions *host);
+
 char *hostoptions_getIpAddressHint(const struct HostOptions *host);
 
 char *hostoptions_getCountryCodeHint(const struct HostOptions *host);
 
 char *hostoptions_getCityCodeHint(const struct HostOptions *host);
 
+bool hostoptions_getFilesystemOverlay(const struct HostOptions *host);
+
+char *hostoptions_getFilesystemBase(const struct HostOptions *host);
+
+SimulationTime hostoptions_getDiskReadLatency(const struct HostOptions *host);
+
+uint64_t hostoptions_getDiskReadBandwidth(const struct HostOptions *host);
+
+SimulationTime hostoptions_getDiskWriteLatency(const struct HostOptions *host);
+
+uint64_t hostoptions_getDiskWriteBandwidth(const struct HostOptions *host);
+
+SimulationTime hostoptions_getDiskSyncLatency(const struct HostOptions *host);
+
+uint64_t hostoptions_getMemoryLimit(const struct HostOptions *host);
+
+enum MemoryLimitAction hostoptions_getMemoryLimitAction(const struct HostOptions *host);
+
+uint32_t hostoptions_getCpuCores(const struct HostOptions *host);
+
+// Returns the CPU frequency in KHz, or 0 if the real CPU frequency should be used.
+uint64_t hostoptions_getCpuFrequency(const struct HostOptions *host);
+
+// Returns the CPU threshold in microseconds, or 0 if disabled.
+uint64_t hostoptions_getCpuThreshold(const struct HostOptions *host);
+
+// Returns the CPU precision in microseconds, or 0 if disabled.
+uint64_t hostoptions_getCpuPrecision(const struct HostOptions *host);
+
+uint64_t hostoptions_getSocketSendBuffer(const struct HostOptions *host);
+
+bool hostoptions_getSocketSendAutotune(const struct HostOptions *host);
+
+uint64_t hostoptions_getSocketRecvBuffer(const struct HostOptions *host);
+
+bool hostoptions_getSocketRecvAutotune(const struct HostOptions *host);
+
+uint64_t hostoptions_getInterfaceBuffer(const struct HostOptions *host);
+
+enum QDiscMode hostoptions_getInterfaceQdisc(const struct HostOptions *host);
+
+uint32_t hostoptions_getInterfaceMtu(const struct HostOptions *host);
+
 uint64_t hostoptions_getBandwidthDown(const struct HostOptions *host);
 
 uint64_t hostoptions_getBandwidthUp(const struct HostOptions *host);
@@ -269,17 +496,32 @@ void hostoptions_iterProcesses(const struct HostOptions *host,
 
 void processoptions_freeString(char *string);
 
+// `host_ip` may be NULL if the host has no IP address. The returned pointer must be freed
+// with `processvariables_free()`.
+struct ProcessVariables *processvariables_new(const char *hostname,
+                                              uint32_t host_index,
+                                              const char *host_ip,
+                                              unsigned int seed);
+
+void processvariables_free(struct ProcessVariables *vars);
+
 // Will return a NULL pointer if the path does not exist.
-char *processoptions_getPath(const struct ProcessOptions *proc);
+char *processoptions_getPath(const struct ProcessOptions *proc,
+                             const struct ProcessVariables *vars);
 
 // Returns the path exactly as specified in the config. Caller must free returned string.
 char *processoptions_getRawPath(const struct ProcessOptions *proc);
 
 void processoptions_getArgs(const struct ProcessOptions *proc,
+                            const struct ProcessVariables *vars,
                             void (*f)(const char*, void*),
                             void *data);
 
-char *processoptions_getEnvironment(const struct ProcessOptions *proc);
+// Calls `f` with the key and value of each environment variable.
+void processoptions_getEnvironment(const struct ProcessOptions *proc,
+                                   const struct ProcessVariables *vars,
+                                   void (*f)(const char*, const char*, void*),
+                                   void *data);
 
 uint32_t processoptions_getQuantity(const struct ProcessOptions *proc);
 
@@ -287,6 +529,44 @@ SimulationTime processoptions_getStartTime(const struct ProcessOptions *proc);
 
 SimulationTime processoptions_getStopTime(const struct ProcessOptions *proc);
 
+// Returns the path of the file that the process reads from its stdin, or NULL if its stdin
+// isn't read from a file. Caller must free the returned string with
+// `processoptions_freeString()`.
+char *processoptions_getStdinPath(const struct ProcessOptions *proc);
+
+// Returns the input that the process reads from its stdin, or NULL if it isn't given in
+// the config. Caller must free the returned string with `processoptions_freeString()`.
+char *processoptions_getStdinContent(const struct ProcessOptions *proc);
+
+bool processoptions_getStraceLogging(const struct ProcessOptions *proc);
+
+bool processoptions_getTerminatesSimulation(const struct ProcessOptions *proc);
+
+int processoptions_getShutdownSignal(const struct ProcessOptions *proc);
+
+SimulationTime processoptions_getShutdownGracePeriod(const struct ProcessOptions *proc);
+
+RestartPolicy processoptions_getRestartPolicy(const struct ProcessOptions *proc);
+
+SimulationTime processoptions_getRestartDelay(const struct ProcessOptions *proc);
+
+// Returns -1 if the process can be restarted any number of times.
+int processoptions_getMaxRestarts(const struct ProcessOptions *proc);
+
+// Returns NULL if the process has no name. Caller must free the returned string with
+// `processoptions_freeString()`.
+char *processoptions_getName(const struct ProcessOptions *proc);
+
+// Returns NULL if the process has no start conditions. Caller must free the returned
+// pointer with `startconditions_free()`.
+struct StartConditions *processoptions_getStartAfter(const struct ProcessOptions *proc);
+
+// Returns NULL if the process has no expected final state. Caller must free the returned
+// pointer with `processfinalstate_free()`.
+struct ProcessFinalState *processoptions_getExpectedFinalState(const struct ProcessOptions *proc);
+
+void processfinalstate_free(struct ProcessFinalState *state);
+
 // Parses a string as bits-per-second. Returns '-1' on error.
 int64_t parse_bandwidth(const char *s);
 
@@ -596,4 +876,15 @@ char *counter_alloc_string(struct Counter *counter);
 // Frees a string previously returned from counter_alloc_string.
 void counter_free_string(struct Counter *counter, char *ptr);
 
+// Returns true if the filter matches the packet. The addresses and ports must be in
+// network byte order.
+bool pcapfilter_matches(const struct PcapFilter *filter,
+                        uint8_t protocol,
+                        in_addr_t src_ip,
+                        in_port_t src_port,
+                        in_addr_t dst_ip,
+                        in_port_t dst_port);
+
+void pcapfilter_free(struct PcapFilter *filter);
+
 #endif /* main_bindings_h */
@@ -11,6 +11,7 @@ pub const SchedulerPolicyType_SP_PARALLEL_THREAD_PERHOST: SchedulerPolicyType =
 pub type SchedulerPolicyType = ::std::os::raw::c_uint;
 pub type size_t = ::std::os::raw::c_ulong;
 pub type guint32 = ::std::os::raw::c_uint;
+pub type gint64 = ::std::os::raw::c_long;
 pub type guint64 = ::std::os::raw::c_ulong;
 pub type gssize = ::std::os::raw::c_long;
 pub type gsize = ::std::os::raw::c_ulong;
@@ -49,12 +50,23 @@ pub const _LogLevel_LOGLEVEL_DEBUG: _LogLevel = 4;
 pub const _LogLevel_LOGLEVEL_TRACE: _LogLevel = 5;
 pub type _LogLevel = ::std::os::raw::c_uint;
 pub use self::_LogLevel as LogLevel;
+pub const HeartbeatFormat_HEARTBEAT_FORMAT_NONE: HeartbeatFormat = 0;
+pub const HeartbeatFormat_HEARTBEAT_FORMAT_CSV: HeartbeatFormat = 1;
+pub const HeartbeatFormat_HEARTBEAT_FORMAT_JSON: HeartbeatFormat = 2;
+pub type HeartbeatFormat = ::std::os::raw::c_uint;
 pub const InterposeMethod_INTERPOSE_METHOD_PTRACE: InterposeMethod = 0;
 pub const InterposeMethod_INTERPOSE_METHOD_PRELOAD: InterposeMethod = 1;
 pub type InterposeMethod = ::std::os::raw::c_uint;
+pub const MemoryLimitAction_MEMORY_LIMIT_ACTION_ENOMEM: MemoryLimitAction = 0;
+pub const MemoryLimitAction_MEMORY_LIMIT_ACTION_KILL: MemoryLimitAction = 1;
+pub type MemoryLimitAction = ::std::os::raw::c_uint;
 pub const QDiscMode_Q_DISC_MODE_FIFO: QDiscMode = 0;
 pub const QDiscMode_Q_DISC_MODE_ROUND_ROBIN: QDiscMode = 1;
 pub type QDiscMode = ::std::os::raw::c_uint;
+pub const RestartPolicy_RESTART_POLICY_NEVER: RestartPolicy = 0;
+pub const RestartPolicy_RESTART_POLICY_ON_FAILURE: RestartPolicy = 1;
+pub const RestartPolicy_RESTART_POLICY_ALWAYS: RestartPolicy = 2;
+pub type RestartPolicy = ::std::os::raw::c_uint;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ChildPidWatcher {
@@ -77,9 +89,39 @@ pub struct MemoryManager {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct PcapFilter {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct PosixFileArc {
     _unused: [u8; 0],
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ProcessFinalState {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SimStats {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct StartAfter {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct StartConditions {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct StopCondition {
+    _unused: [u8; 0],
+}
 pub use self::_Status as Status;
 pub const _Status_STATUS_NONE: _Status = 0;
 pub const _Status_STATUS_DESCRIPTOR_ACTIVE: _Status = 1;
@@ -472,6 +514,15 @@ extern "C" {
 extern "C" {
     pub fn thread_getTidAddress(thread: *mut Thread) -> PluginVirtualPtr;
 }
+extern "C" {
+    pub fn thread_getCPUAvailableTime(thread: *mut Thread) -> SimulationTime;
+}
+extern "C" {
+    pub fn thread_setCPUAvailableTime(thread: *mut Thread, time: SimulationTime);
+}
+extern "C" {
+    pub fn thread_clearSysCallCondition(thread: *mut Thread);
+}
 extern "C" {
     pub fn thread_isLeader(thread: *mut Thread) -> bool;
 }
@@ -496,14 +547,29 @@ extern "C" {
         processID: guint,
         startTime: SimulationTime,
         stopTime: SimulationTime,
+        shutdownSignal: ::std::os::raw::c_int,
+        shutdownGracePeriod: SimulationTime,
+        restartPolicy: RestartPolicy,
+        restartDelay: SimulationTime,
+        maxRestarts: ::std::os::raw::c_int,
+        name: *const gchar,
+        startAfter: *mut StartConditions,
+        terminatesSimulation: bool,
         interposeMethod: InterposeMethod,
         hostName: *const gchar,
         pluginName: *const gchar,
         pluginPath: *const gchar,
         envv: *mut *mut gchar,
         argv: *mut *mut gchar,
+        stdinPath: *const gchar,
+        stdinContent: *const gchar,
+        straceLogging: bool,
+        expectedFinalState: *mut ProcessFinalState,
     ) -> *mut Process;
 }
+extern "C" {
+    pub fn process_newRestart(proc_: *mut Process, processID: guint) -> *mut Process;
+}
 extern "C" {
     pub fn process_ref(proc_: *mut Process);
 }
@@ -513,12 +579,21 @@ extern "C" {
 extern "C" {
     pub fn process_schedule(proc_: *mut Process, nothing: gpointer);
 }
+extern "C" {
+    pub fn process_newStartTask(proc_: *mut Process) -> *mut Task;
+}
+extern "C" {
+    pub fn process_canStart(proc_: *mut Process, startAfter: *mut StartAfter) -> bool;
+}
 extern "C" {
     pub fn process_continue(proc_: *mut Process, thread: *mut Thread);
 }
 extern "C" {
     pub fn process_stop(proc_: *mut Process);
 }
+extern "C" {
+    pub fn process_markKilledAtShutdown(proc_: *mut Process);
+}
 extern "C" {
     pub fn process_detachPlugin(procptr: gpointer, nothing: gpointer);
 }
@@ -534,6 +609,38 @@ extern "C" {
 extern "C" {
     pub fn process_isRunning(proc_: *mut Process) -> gboolean;
 }
+extern "C" {
+    pub fn process_getMemoryUsage(proc_: *mut Process) -> size_t;
+}
+extern "C" {
+    pub fn process_getBreak(proc_: *mut Process) -> u64;
+}
+extern "C" {
+    pub fn process_setBreak(proc_: *mut Process, brk: u64);
+}
+extern "C" {
+    pub fn process_addAnonMapping(proc_: *mut Process, addr: u64, len: size_t);
+}
+extern "C" {
+    pub fn process_removeAnonMapping(proc_: *mut Process, addr: u64, len: size_t);
+}
+extern "C" {
+    pub fn process_getAnonMappingLen(proc_: *mut Process, addr: u64, len: size_t) -> size_t;
+}
+extern "C" {
+    pub fn process_setSignalAction(
+        proc_: *mut Process,
+        sig: ::std::os::raw::c_int,
+        handler: u64,
+        flags: u64,
+    );
+}
+extern "C" {
+    pub fn process_killOutOfMemory(proc_: *mut Process);
+}
+extern "C" {
+    pub fn process_wasOOMKilled(proc_: *mut Process) -> bool;
+}
 extern "C" {
     pub fn process_getName(proc_: *mut Process) -> *const gchar;
 }
@@ -546,6 +653,28 @@ extern "C" {
 extern "C" {
     pub fn process_getNativePid(proc_: *const Process) -> pid_t;
 }
+extern "C" {
+    pub fn process_getHost(proc_: *mut Process) -> *mut Host;
+}
+extern "C" {
+    pub fn process_getArgv(proc_: *mut Process) -> *mut *mut gchar;
+}
+extern "C" {
+    pub fn process_getEnvv(proc_: *mut Process) -> *mut *mut gchar;
+}
+extern "C" {
+    pub fn process_getStartTime(proc_: *mut Process) -> SimulationTime;
+}
+extern "C" {
+    pub fn process_addSyscallCounts(
+        proc_: *mut Process,
+        numSyscalls: guint64,
+        syscallCounts: *mut Counter,
+    );
+}
+extern "C" {
+    pub fn process_getNumThreads(proc_: *mut Process) -> guint;
+}
 extern "C" {
     pub fn process_findNativeTID(
         proc_: *mut Process,
@@ -845,6 +974,18 @@ pub struct _Packet {
 pub type Packet = _Packet;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct _Disk {
+    _unused: [u8; 0],
+}
+pub type Disk = _Disk;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _FsOverlay {
+    _unused: [u8; 0],
+}
+pub type FsOverlay = _FsOverlay;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct _Futex {
     _unused: [u8; 0],
 }
@@ -873,32 +1014,52 @@ pub type HostParameters = _HostParameters;
 pub struct _HostParameters {
     pub id: GQuark,
     pub nodeSeed: guint,
+    pub simulationSeed: guint,
     pub hostname: *mut gchar,
     pub ipHint: *mut gchar,
     pub citycodeHint: *mut gchar,
     pub countrycodeHint: *mut gchar,
+    pub ipAddr: *mut gchar,
+    pub networkNodeId: gint64,
     pub requestedBWDownKiBps: guint64,
     pub requestedBWUpKiBps: guint64,
     pub cpuFrequency: guint64,
     pub cpuThreshold: guint64,
     pub cpuPrecision: guint64,
+    pub cpuCores: guint,
     pub heartbeatInterval: SimulationTime,
     pub heartbeatLogLevel: LogLevel,
     pub heartbeatLogInfo: LogInfoFlags,
+    pub heartbeatFormat: HeartbeatFormat,
     pub logLevel: LogLevel,
+    pub logToFile: gboolean,
     pub pcapDir: *mut gchar,
+    pub pcapCaptureSize: guint32,
+    pub pcapFilter: *mut PcapFilter,
+    pub pcapStartTime: SimulationTime,
+    pub pcapStopTime: SimulationTime,
     pub qdisc: QDiscMode,
     pub recvBufSize: guint64,
     pub autotuneRecvBuf: gboolean,
     pub sendBufSize: guint64,
     pub autotuneSendBuf: gboolean,
     pub interfaceBufSize: guint64,
+    pub interfaceMtu: guint32,
+    pub useFsOverlay: gboolean,
+    pub fsOverlayBase: *mut gchar,
+    pub diskReadLatency: SimulationTime,
+    pub diskReadBandwidth: guint64,
+    pub diskWriteLatency: SimulationTime,
+    pub diskWriteBandwidth: guint64,
+    pub diskSyncLatency: SimulationTime,
+    pub memoryLimit: guint64,
+    pub memoryLimitAction: MemoryLimitAction,
 }
 #[test]
 fn bindgen_test_layout__HostParameters() {
     assert_eq!(
         ::std::mem::size_of::<_HostParameters>(),
-        160usize,
+        304usize,
         concat!("Size of: ", stringify!(_HostParameters))
     );
     assert_eq!(
@@ -927,8 +1088,18 @@ fn bindgen_test_layout__HostParameters() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_HostParameters>())).hostname as *const _ as usize },
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).simulationSeed as *const _ as usize },
         8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(simulationSeed)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).hostname as *const _ as usize },
+        16usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -938,7 +1109,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).ipHint as *const _ as usize },
-        16usize,
+        24usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -948,7 +1119,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).citycodeHint as *const _ as usize },
-        24usize,
+        32usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -958,7 +1129,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).countrycodeHint as *const _ as usize },
-        32usize,
+        40usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -966,11 +1137,31 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(countrycodeHint)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).ipAddr as *const _ as usize },
+        48usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(ipAddr)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).networkNodeId as *const _ as usize },
+        56usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(networkNodeId)
+        )
+    );
     assert_eq!(
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).requestedBWDownKiBps as *const _ as usize
         },
-        40usize,
+        64usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -982,7 +1173,7 @@ fn bindgen_test_layout__HostParameters() {
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).requestedBWUpKiBps as *const _ as usize
         },
-        48usize,
+        72usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -992,7 +1183,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).cpuFrequency as *const _ as usize },
-        56usize,
+        80usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1002,7 +1193,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).cpuThreshold as *const _ as usize },
-        64usize,
+        88usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1012,7 +1203,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).cpuPrecision as *const _ as usize },
-        72usize,
+        96usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1020,11 +1211,21 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(cpuPrecision)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).cpuCores as *const _ as usize },
+        104usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(cpuCores)
+        )
+    );
     assert_eq!(
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).heartbeatInterval as *const _ as usize
         },
-        80usize,
+        112usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1036,7 +1237,7 @@ fn bindgen_test_layout__HostParameters() {
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).heartbeatLogLevel as *const _ as usize
         },
-        88usize,
+        120usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1048,7 +1249,7 @@ fn bindgen_test_layout__HostParameters() {
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).heartbeatLogInfo as *const _ as usize
         },
-        92usize,
+        124usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1056,9 +1257,19 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(heartbeatLogInfo)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).heartbeatFormat as *const _ as usize },
+        128usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(heartbeatFormat)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).logLevel as *const _ as usize },
-        96usize,
+        132usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1066,9 +1277,19 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(logLevel)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).logToFile as *const _ as usize },
+        136usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(logToFile)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).pcapDir as *const _ as usize },
-        104usize,
+        144usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1076,9 +1297,49 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(pcapDir)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).pcapCaptureSize as *const _ as usize },
+        152usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(pcapCaptureSize)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).pcapFilter as *const _ as usize },
+        160usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(pcapFilter)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).pcapStartTime as *const _ as usize },
+        168usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(pcapStartTime)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).pcapStopTime as *const _ as usize },
+        176usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(pcapStopTime)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).qdisc as *const _ as usize },
-        112usize,
+        184usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1088,7 +1349,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).recvBufSize as *const _ as usize },
-        120usize,
+        192usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1098,7 +1359,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).autotuneRecvBuf as *const _ as usize },
-        128usize,
+        200usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1108,7 +1369,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).sendBufSize as *const _ as usize },
-        136usize,
+        208usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1118,7 +1379,7 @@ fn bindgen_test_layout__HostParameters() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_HostParameters>())).autotuneSendBuf as *const _ as usize },
-        144usize,
+        216usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1130,7 +1391,7 @@ fn bindgen_test_layout__HostParameters() {
         unsafe {
             &(*(::std::ptr::null::<_HostParameters>())).interfaceBufSize as *const _ as usize
         },
-        152usize,
+        224usize,
         concat!(
             "Offset of field: ",
             stringify!(_HostParameters),
@@ -1138,6 +1399,114 @@ fn bindgen_test_layout__HostParameters() {
             stringify!(interfaceBufSize)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).interfaceMtu as *const _ as usize },
+        232usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(interfaceMtu)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).useFsOverlay as *const _ as usize },
+        236usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(useFsOverlay)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).fsOverlayBase as *const _ as usize },
+        240usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(fsOverlayBase)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).diskReadLatency as *const _ as usize },
+        248usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(diskReadLatency)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_HostParameters>())).diskReadBandwidth as *const _ as usize
+        },
+        256usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(diskReadBandwidth)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_HostParameters>())).diskWriteLatency as *const _ as usize
+        },
+        264usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(diskWriteLatency)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_HostParameters>())).diskWriteBandwidth as *const _ as usize
+        },
+        272usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(diskWriteBandwidth)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).diskSyncLatency as *const _ as usize },
+        280usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(diskSyncLatency)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_HostParameters>())).memoryLimit as *const _ as usize },
+        288usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(memoryLimit)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_HostParameters>())).memoryLimitAction as *const _ as usize
+        },
+        296usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_HostParameters),
+            "::",
+            stringify!(memoryLimitAction)
+        )
+    );
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -1157,6 +1526,91 @@ pub struct _Router {
 pub type Router = _Router;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct _PCapWriter {
+    _unused: [u8; 0],
+}
+pub type PCapWriter = _PCapWriter;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _NetworkInterfaceCapture {
+    pub pcap: *mut PCapWriter,
+    pub captureSize: guint32,
+    pub filter: *const PcapFilter,
+    pub startTime: SimulationTime,
+    pub stopTime: SimulationTime,
+}
+#[test]
+fn bindgen_test_layout__NetworkInterfaceCapture() {
+    assert_eq!(
+        ::std::mem::size_of::<_NetworkInterfaceCapture>(),
+        40usize,
+        concat!("Size of: ", stringify!(_NetworkInterfaceCapture))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<_NetworkInterfaceCapture>(),
+        8usize,
+        concat!("Alignment of ", stringify!(_NetworkInterfaceCapture))
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_NetworkInterfaceCapture>())).pcap as *const _ as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_NetworkInterfaceCapture),
+            "::",
+            stringify!(pcap)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_NetworkInterfaceCapture>())).captureSize as *const _ as usize
+        },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_NetworkInterfaceCapture),
+            "::",
+            stringify!(captureSize)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_NetworkInterfaceCapture>())).filter as *const _ as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_NetworkInterfaceCapture),
+            "::",
+            stringify!(filter)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_NetworkInterfaceCapture>())).startTime as *const _ as usize
+        },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_NetworkInterfaceCapture),
+            "::",
+            stringify!(startTime)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<_NetworkInterfaceCapture>())).stopTime as *const _ as usize
+        },
+        32usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_NetworkInterfaceCapture),
+            "::",
+            stringify!(stopTime)
+        )
+    );
+}
+pub type NetworkInterfaceCapture = _NetworkInterfaceCapture;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct _NetworkInterface {
     _unused: [u8; 0],
 }
@@ -1202,6 +1656,7 @@ extern "C" {
         topology: *mut Topology,
         rawCPUFreq: guint,
         hostRootPath: *const gchar,
+        mergedCapture: *const NetworkInterfaceCapture,
     );
 }
 extern "C" {
@@ -1224,13 +1679,28 @@ extern "C" {
         host: *mut Host,
         startTime: SimulationTime,
         stopTime: SimulationTime,
+        shutdownSignal: ::std::os::raw::c_int,
+        shutdownGracePeriod: SimulationTime,
+        restartPolicy: RestartPolicy,
+        restartDelay: SimulationTime,
+        maxRestarts: ::std::os::raw::c_int,
+        name: *const gchar,
+        startAfter: *mut StartConditions,
+        terminatesSimulation: bool,
         interposeMethod: InterposeMethod,
         pluginName: *const gchar,
         pluginPath: *const gchar,
         envv: *mut *mut gchar,
         argv: *mut *mut gchar,
+        stdinPath: *const gchar,
+        stdinContent: *const gchar,
+        straceLogging: bool,
+        expectedFinalState: *mut ProcessFinalState,
     );
 }
+extern "C" {
+    pub fn host_restartApplication(host: *mut Host, proc_: *mut Process);
+}
 extern "C" {
     pub fn host_detachAllPlugins(host: *mut Host);
 }
@@ -1249,6 +1719,9 @@ extern "C" {
 extern "C" {
     pub fn host_getCPU(host: *mut Host) -> *mut CPU;
 }
+extern "C" {
+    pub fn host_getDisk(host: *mut Host) -> *mut Disk;
+}
 extern "C" {
     pub fn host_getName(host: *mut Host) -> *mut gchar;
 }
@@ -1276,21 +1749,48 @@ extern "C" {
 extern "C" {
     pub fn host_getConfiguredSendBufSize(host: *mut Host) -> guint64;
 }
+extern "C" {
+    pub fn host_getCPUFrequency(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getCPUCores(host: *mut Host) -> guint;
+}
 extern "C" {
     pub fn host_getUpstreamRouter(host: *mut Host, handle: in_addr_t) -> *mut Router;
 }
 extern "C" {
     pub fn host_returnHandleHack(handle: gint);
 }
+extern "C" {
+    pub fn host_countProcessedEvent(host: *mut Host);
+}
+extern "C" {
+    pub fn host_countDroppedPacket(host: *mut Host, reason: *const gchar);
+}
 extern "C" {
     pub fn host_getTracker(host: *mut Host) -> *mut Tracker;
 }
 extern "C" {
     pub fn host_getLogLevel(host: *mut Host) -> LogLevel;
 }
+extern "C" {
+    pub fn host_getLogToFile(host: *mut Host) -> gboolean;
+}
 extern "C" {
     pub fn host_getDataPath(host: *mut Host) -> *const gchar;
 }
+extern "C" {
+    pub fn host_getFsOverlay(host: *mut Host) -> *mut FsOverlay;
+}
+extern "C" {
+    pub fn host_getMemoryLimit(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getMemoryUsage(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_reserveMemory(host: *mut Host, proc_: *mut Process, numBytes: size_t) -> bool;
+}
 extern "C" {
     pub fn host_doesInterfaceExist(host: *mut Host, interfaceIP: in_addr_t) -> gboolean;
 }
@@ -1304,6 +1804,9 @@ extern "C" {
         peerPort: in_port_t,
     ) -> gboolean;
 }
+extern "C" {
+    pub fn host_isListening(host: *mut Host, port: in_port_t) -> gboolean;
+}
 extern "C" {
     pub fn host_getRandomFreePort(
         host: *mut Host,
@@ -1319,6 +1822,19 @@ extern "C" {
 extern "C" {
     pub fn host_getNativeTID(host: *mut Host, virtualPID: pid_t, virtualTID: pid_t) -> pid_t;
 }
+extern "C" {
+    pub fn host_getProcess(host: *mut Host, virtualPID: pid_t) -> *mut Process;
+}
+pub type HostProcessIterFunc = ::std::option::Option<
+    unsafe extern "C" fn(proc_: *mut Process, data: *mut ::std::os::raw::c_void),
+>;
+extern "C" {
+    pub fn host_iterProcesses(
+        host: *mut Host,
+        func: HostProcessIterFunc,
+        data: *mut ::std::os::raw::c_void,
+    );
+}
 extern "C" {
     pub fn worker_newForThisThread(
         worker_pool: *mut WorkerPool,
@@ -1377,6 +1893,18 @@ extern "C" {
 extern "C" {
     pub fn worker_getDNS() -> *mut DNS;
 }
+extern "C" {
+    pub fn worker_getSimStats() -> *mut SimStats;
+}
+extern "C" {
+    pub fn worker_getStartAfter() -> *mut StartAfter;
+}
+extern "C" {
+    pub fn worker_addWaitingProcess(proc_: *mut Process);
+}
+extern "C" {
+    pub fn worker_getStopCondition() -> *mut StopCondition;
+}
 extern "C" {
     pub fn worker_getTopology() -> *mut Topology;
 }
@@ -1459,6 +1987,8 @@ pub struct _SysCallHandler {
     pub perfSecondsTotal: gdouble,
     pub numSyscalls: ::std::os::raw::c_long,
     pub syscall_counter: *mut Counter,
+    pub straceCall: *mut gchar,
+    pub interrupted: bool,
     pub referenceCount: ::std::os::raw::c_int,
     pub magic: guint,
 }
@@ -1466,7 +1996,7 @@ pub struct _SysCallHandler {
 fn bindgen_test_layout__SysCallHandler() {
     assert_eq!(
         ::std::mem::size_of::<_SysCallHandler>(),
-        96usize,
+        112usize,
         concat!("Size of: ", stringify!(_SysCallHandler))
     );
     assert_eq!(
@@ -1591,8 +2121,28 @@ fn bindgen_test_layout__SysCallHandler() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<_SysCallHandler>())).referenceCount as *const _ as usize },
+        unsafe { &(*(::std::ptr::null::<_SysCallHandler>())).straceCall as *const _ as usize },
         88usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_SysCallHandler),
+            "::",
+            stringify!(straceCall)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_SysCallHandler>())).interrupted as *const _ as usize },
+        96usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(_SysCallHandler),
+            "::",
+            stringify!(interrupted)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<_SysCallHandler>())).referenceCount as *const _ as usize },
+        100usize,
         concat!(
             "Offset of field: ",
             stringify!(_SysCallHandler),
@@ -1602,7 +2152,7 @@ fn bindgen_test_layout__SysCallHandler() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<_SysCallHandler>())).magic as *const _ as usize },
-        92usize,
+        104usize,
         concat!(
             "Offset of field: ",
             stringify!(_SysCallHandler),
@@ -1611,6 +2161,13 @@ fn bindgen_test_layout__SysCallHandler() {
         )
     );
 }
+extern "C" {
+    pub fn syscallhandler_logNativeSyscallResult(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+        retval: ::std::os::raw::c_long,
+    );
+}
 extern "C" {
     pub fn syscallhandler_close(
         sys: *mut SysCallHandler,
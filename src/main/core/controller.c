@@ -12,10 +12,14 @@
 #define __USE_LARGEFILE64
 #endif
 
+#include <elf.h>
 #include <errno.h>
 #include <glib.h>
 #include <glib/gstdio.h>
+#include <inttypes.h>
 #include <stddef.h>
+#include <stdio.h>
+#include <string.h>
 #include <unistd.h>
 
 #include "lib/logger/log_level.h"
@@ -152,13 +156,12 @@ void controller_updateMinTimeJump(Controller* controller, gdouble minPathLatency
     }
 }
 
-static gboolean _controller_loadTopology(Controller* controller) {
-    MAGIC_ASSERT(controller);
-
+/* Returns NULL if the network graph couldn't be loaded. */
+static Topology* _controller_newTopology(ConfigOptions* config) {
     gchar* temporaryFilename =
         utility_getNewTemporaryFilename("shadow-topology-XXXXXX.gml");
 
-    char* topologyString = config_getNetworkGraph(controller->config);
+    char* topologyString = config_getNetworkGraph(config);
 
     /* write the topology to a temporary file */
     GError* error = NULL;
@@ -169,18 +172,26 @@ static gboolean _controller_loadTopology(Controller* controller) {
 
     config_freeString(topologyString);
 
-    /* initialize global routing model */
-    controller->topology = topology_new(temporaryFilename, config_getUseShortestPath(controller->config));
+    Topology* topology = topology_new(temporaryFilename, config_getUseShortestPath(config));
     g_unlink(temporaryFilename);
 
-    if (!controller->topology) {
+    if (!topology) {
         error("fatal error loading topology at path '%s', check your syntax and try again",
               temporaryFilename);
-        g_free(temporaryFilename);
-        return FALSE;
     }
 
     g_free(temporaryFilename);
+    return topology;
+}
+
+static gboolean _controller_loadTopology(Controller* controller) {
+    MAGIC_ASSERT(controller);
+
+    /* initialize global routing model */
+    controller->topology = _controller_newTopology(controller->config);
+    if (!controller->topology) {
+        return FALSE;
+    }
 
     /* initialize global DNS addressing */
     controller->dns = dns_new();
@@ -219,15 +230,21 @@ static void _controller_registerArgCallback(const char* arg, void* _argArray) {
     g_ptr_array_add(argArray, copiedArg);
 }
 
+static void _controller_registerEnvCallback(const char* key, const char* value, void* _envArray) {
+    GPtrArray* envArray = _envArray;
+    g_ptr_array_add(envArray, g_strdup_printf("%s=%s", key, value));
+}
+
 typedef struct _ProcessCallbackArgs {
     Controller* controller;
     const char* hostname;
+    const ProcessVariables* vars;
 } ProcessCallbackArgs;
 
 static void _controller_registerProcessCallback(const ProcessOptions* proc, void* _callbackArgs) {
     ProcessCallbackArgs* callbackArgs = _callbackArgs;
 
-    char* plugin = processoptions_getPath(proc);
+    char* plugin = processoptions_getPath(proc, callbackArgs->vars);
     if (plugin == NULL) {
         error("For host '%s', couldn't find program path: '%s'", callbackArgs->hostname,
               processoptions_getRawPath(proc));
@@ -239,7 +256,8 @@ static void _controller_registerProcessCallback(const ProcessOptions* proc, void
     g_ptr_array_add(argArray, strdup(plugin));
 
     // iterate through the arguments and copy them to our array
-    processoptions_getArgs(proc, _controller_registerArgCallback, (void*)argArray);
+    processoptions_getArgs(proc, callbackArgs->vars, _controller_registerArgCallback,
+                           (void*)argArray);
 
     // the last element of argv must be NULL
     g_ptr_array_add(argArray, NULL);
@@ -247,18 +265,45 @@ static void _controller_registerProcessCallback(const ProcessOptions* proc, void
     // free the GLib array but keep the data
     gchar** argv = (gchar**)g_ptr_array_free(argArray, FALSE);
 
+    // build an array of "key=value" environment entries
+    GPtrArray* envArray = g_ptr_array_new();
+    processoptions_getEnvironment(proc, callbackArgs->vars, _controller_registerEnvCallback,
+                                  (void*)envArray);
+    g_ptr_array_add(envArray, NULL);
+    gchar** environment = (gchar**)g_ptr_array_free(envArray, FALSE);
+
     guint64 quantity = processoptions_getQuantity(proc);
 
-    char* environment = processoptions_getEnvironment(proc);
+    char* name = processoptions_getName(proc);
+    char* stdinPath = processoptions_getStdinPath(proc);
+    char* stdinContent = processoptions_getStdinContent(proc);
+
+    if (stdinPath != NULL && !g_file_test(stdinPath, G_FILE_TEST_IS_REGULAR)) {
+        error("For host '%s', couldn't find stdin file: '%s'", callbackArgs->hostname, stdinPath);
+        exit(EXIT_FAILURE);
+    }
 
     for (guint64 i = 0; i < quantity; i++) {
         manager_addNewVirtualProcess(callbackArgs->controller->manager, callbackArgs->hostname,
                                      plugin, processoptions_getStartTime(proc),
-                                     processoptions_getStopTime(proc), argv, environment);
+                                     processoptions_getStopTime(proc),
+                                     processoptions_getShutdownSignal(proc),
+                                     processoptions_getShutdownGracePeriod(proc),
+                                     processoptions_getRestartPolicy(proc),
+                                     processoptions_getRestartDelay(proc),
+                                     processoptions_getMaxRestarts(proc), name,
+                                     processoptions_getStartAfter(proc),
+                                     processoptions_getTerminatesSimulation(proc), argv,
+                                     environment, stdinPath, stdinContent,
+                                     processoptions_getStraceLogging(proc),
+                                     processoptions_getExpectedFinalState(proc));
     }
 
-    processoptions_freeString(environment);
+    processoptions_freeString(stdinContent);
+    processoptions_freeString(stdinPath);
+    processoptions_freeString(name);
     processoptions_freeString(plugin);
+    g_strfreev(environment);
     g_strfreev(argv);
 }
 
@@ -273,70 +318,381 @@ static void _controller_registerHostCallback(const char* name, const ConfigOptio
 
     guint64 quantity = hostoptions_getQuantity(host);
 
+    gint64 networkNodeId = hostoptions_getNetworkNodeId(host);
+    if (networkNodeId >= 0 && !topology_hasNode(controller->topology, (guint64)networkNodeId)) {
+        error("For host '%s', the network graph has no node with id %" PRId64, name,
+              networkNodeId);
+        exit(EXIT_FAILURE);
+    }
+
+    guint32 interfaceMtu = hostoptions_getInterfaceMtu(host);
+    if (interfaceMtu < CONFIG_MTU_MIN || interfaceMtu > CONFIG_MTU_MAX) {
+        error("For host '%s', the interface MTU %" G_GUINT32_FORMAT
+              " is not between %d and %d",
+              name, interfaceMtu, CONFIG_MTU_MIN, CONFIG_MTU_MAX);
+        exit(EXIT_FAILURE);
+    }
+
     for (guint64 i = 0; i < quantity; i++) {
         HostParameters* params = g_new0(HostParameters, 1);
 
-        GString* hostnameBuffer = g_string_new(name);
-        if (quantity > 1) {
-            g_string_append_printf(hostnameBuffer, "%" G_GUINT64_FORMAT, i + 1);
+        params->hostname = hostoptions_getHostname(host, name, (guint)(i + 1));
+        if (manager_hasHost(controller->manager, params->hostname)) {
+            error("For host '%s', the hostname '%s' is already used by another host", name,
+                  params->hostname);
+            exit(EXIT_FAILURE);
         }
-        params->hostname = hostnameBuffer->str;
 
-        params->cpuFrequency = MAX(0, managerCpuFreq);
-        params->cpuThreshold = 0;
-        params->cpuPrecision = 200;
+        guint64 cpuFreq = hostoptions_getCpuFrequency(host);
+        params->cpuFrequency = cpuFreq > 0 ? cpuFreq : MAX(0, managerCpuFreq);
+        params->cpuThreshold = hostoptions_getCpuThreshold(host);
+        params->cpuPrecision = hostoptions_getCpuPrecision(host);
+        params->cpuCores = hostoptions_getCpuCores(host);
 
         params->logLevel = hostoptions_getLogLevel(host);
+        params->logToFile = hostoptions_getLogToFile(host);
         params->heartbeatLogLevel = hostoptions_getHeartbeatLogLevel(host);
         params->heartbeatLogInfo = hostoptions_getHeartbeatLogInfo(host);
         params->heartbeatInterval = hostoptions_getHeartbeatInterval(host);
+        params->heartbeatFormat = hostoptions_getHeartbeatFormat(host);
 
         params->pcapDir = hostoptions_getPcapDirectory(host);
+        params->pcapCaptureSize = (guint32)MIN(hostoptions_getPcapCaptureSize(host), G_MAXUINT32);
+        params->pcapFilter = hostoptions_getPcapFilter(host);
+        params->pcapStartTime = hostoptions_getPcapStartTime(host);
+        params->pcapStopTime = hostoptions_getPcapStopTime(host);
 
         params->ipHint = hostoptions_getIpAddressHint(host);
+        params->ipAddr = hostoptions_getIpAddr(host);
+        params->networkNodeId = networkNodeId;
         params->countrycodeHint = hostoptions_getCountryCodeHint(host);
         params->citycodeHint = hostoptions_getCityCodeHint(host);
 
+        params->useFsOverlay = hostoptions_getFilesystemOverlay(host);
+        params->fsOverlayBase = hostoptions_getFilesystemBase(host);
+
+        /* the config uses bits per second for bandwidths, but the disk model uses bytes */
+        params->diskReadLatency = hostoptions_getDiskReadLatency(host);
+        params->diskReadBandwidth = hostoptions_getDiskReadBandwidth(host) / 8;
+        params->diskWriteLatency = hostoptions_getDiskWriteLatency(host);
+        params->diskWriteBandwidth = hostoptions_getDiskWriteBandwidth(host) / 8;
+        params->diskSyncLatency = hostoptions_getDiskSyncLatency(host);
+
+        params->memoryLimit = hostoptions_getMemoryLimit(host);
+        params->memoryLimitAction = hostoptions_getMemoryLimitAction(host);
+
         /* shadow uses values in KiB/s, but the config uses b/s */
         /* TODO: use bits or bytes everywhere within Shadow (see also:
          * _topology_findVertexAttributeStringBandwidth()) */
         params->requestedBWDownKiBps = hostoptions_getBandwidthDown(host) / (8 * 1024);
         params->requestedBWUpKiBps = hostoptions_getBandwidthUp(host) / (8 * 1024);
 
-        /* some options come from the config options and not the host options */
-        params->sendBufSize = config_getSocketSendBuffer(config);
-        params->recvBufSize = config_getSocketRecvBuffer(config);
-        params->autotuneSendBuf = config_getSocketSendAutotune(config);
-        params->autotuneRecvBuf = config_getSocketRecvAutotune(config);
-        params->interfaceBufSize = config_getInterfaceBuffer(config);
-        params->qdisc = config_getInterfaceQdisc(config);
+        params->sendBufSize = hostoptions_getSocketSendBuffer(host);
+        params->recvBufSize = hostoptions_getSocketRecvBuffer(host);
+        params->autotuneSendBuf = hostoptions_getSocketSendAutotune(host);
+        params->autotuneRecvBuf = hostoptions_getSocketRecvAutotune(host);
+        params->interfaceBufSize = hostoptions_getInterfaceBuffer(host);
+        params->qdisc = hostoptions_getInterfaceQdisc(host);
+        params->interfaceMtu = interfaceMtu;
 
         manager_addNewVirtualHost(controller->manager, params);
 
+        /* the host's address was registered with the DNS when it was added */
+        Address* address = dns_resolveNameToAddress(controller->dns, params->hostname);
+        ProcessVariables* vars =
+            processvariables_new(params->hostname, (uint32_t)(i + 1),
+                                 address ? address_toHostIPString(address) : NULL,
+                                 config_getSeed(config));
+
         ProcessCallbackArgs processArgs;
         processArgs.controller = controller;
-        processArgs.hostname = hostnameBuffer->str;
+        processArgs.hostname = params->hostname;
+        processArgs.vars = vars;
 
         /* now handle each virtual process the host will run */
         hostoptions_iterProcesses(host, _controller_registerProcessCallback, (void*)&processArgs);
 
         /* cleanup for next pass through the loop */
-        g_string_free(hostnameBuffer, TRUE);
+        processvariables_free(vars);
 
+        hostoptions_freeString(params->hostname);
         hostoptions_freeString(params->pcapDir);
         hostoptions_freeString(params->ipHint);
+        hostoptions_freeString(params->ipAddr);
         hostoptions_freeString(params->countrycodeHint);
         hostoptions_freeString(params->citycodeHint);
+        hostoptions_freeString(params->fsOverlayBase);
 
         g_free(params);
     }
 }
 
+/* Reserves the host's ip_addr if it has one. Logs an error and returns FALSE if the address
+ * can't be reserved. */
+static gboolean _controller_reserveHostIP(DNS* dns, const char* name, const HostOptions* host) {
+    char* ipAddr = hostoptions_getIpAddr(host);
+    if (ipAddr == NULL) {
+        return TRUE;
+    }
+
+    gboolean isSuccess = FALSE;
+    if (hostoptions_getQuantity(host) > 1) {
+        error("For host '%s', ip_addr can't be used with a quantity greater than 1", name);
+    } else if (!dns_reserveIP(dns, ipAddr)) {
+        error("For host '%s', the ip_addr '%s' is a reserved address or is used by another host",
+              name, ipAddr);
+    } else {
+        isSuccess = TRUE;
+    }
+
+    hostoptions_freeString(ipAddr);
+    return isSuccess;
+}
+
+static void _controller_reserveIPCallback(const char* name, const ConfigOptions* config,
+                                          const HostOptions* host, void* _controller) {
+    Controller* controller = _controller;
+    MAGIC_ASSERT(controller);
+
+    if (!_controller_reserveHostIP(controller->dns, name, host)) {
+        exit(EXIT_FAILURE);
+    }
+}
+
 static void _controller_registerHosts(Controller* controller) {
     MAGIC_ASSERT(controller);
+
+    /* reserve the exact addresses first so that they aren't given to other hosts */
+    config_iterHosts(controller->config, _controller_reserveIPCallback, (void*)controller);
     config_iterHosts(controller->config, _controller_registerHostCallback, (void*)controller);
 }
 
+typedef struct _ConfigValidator {
+    ConfigOptions* config;
+    /* NULL if the network graph couldn't be loaded */
+    Topology* topology;
+    DNS* dns;
+    StartAfter* startAfter;
+    StopCondition* stopCondition;
+    /* hostnames of all registered hosts */
+    GHashTable* hostnames;
+    /* host names whose ip_addr was successfully reserved */
+    GHashTable* reservedHosts;
+    /* program paths that were already checked, so that each is only reported once */
+    GHashTable* checkedPaths;
+    guint numErrors;
+} ConfigValidator;
+
+typedef struct _ValidateProcessArgs {
+    ConfigValidator* validator;
+    const char* hostname;
+    const ProcessVariables* vars;
+} ValidateProcessArgs;
+
+/* Returns TRUE if the file at 'path' is a regular executable file that starts with the ELF
+ * magic bytes. */
+static gboolean _controller_isExecutableElf(const gchar* path) {
+    if (!g_file_test(path, G_FILE_TEST_IS_REGULAR) ||
+        !g_file_test(path, G_FILE_TEST_IS_EXECUTABLE)) {
+        return FALSE;
+    }
+
+    FILE* file = fopen(path, "rb");
+    if (file == NULL) {
+        return FALSE;
+    }
+
+    unsigned char magic[SELFMAG] = {0};
+    size_t numRead = fread(magic, 1, SELFMAG, file);
+    fclose(file);
+
+    return numRead == SELFMAG && memcmp(magic, ELFMAG, SELFMAG) == 0;
+}
+
+static void _controller_validateProcessCallback(const ProcessOptions* proc, void* _args) {
+    ValidateProcessArgs* args = _args;
+    ConfigValidator* validator = args->validator;
+
+    char* plugin = processoptions_getPath(proc, args->vars);
+    if (plugin == NULL) {
+        char* rawPath = processoptions_getRawPath(proc);
+        error("For host '%s', couldn't find program path: '%s'", args->hostname, rawPath);
+        processoptions_freeString(rawPath);
+        validator->numErrors++;
+    } else if (!g_hash_table_contains(validator->checkedPaths, plugin)) {
+        g_hash_table_add(validator->checkedPaths, g_strdup(plugin));
+        if (!_controller_isExecutableElf(plugin)) {
+            error("For host '%s', the program path '%s' is not an executable ELF file",
+                  args->hostname, plugin);
+            validator->numErrors++;
+        }
+    }
+
+    char* stdinPath = processoptions_getStdinPath(proc);
+    if (stdinPath != NULL && !g_file_test(stdinPath, G_FILE_TEST_IS_REGULAR)) {
+        error("For host '%s', couldn't find stdin file: '%s'", args->hostname, stdinPath);
+        validator->numErrors++;
+    }
+
+    char* name = processoptions_getName(proc);
+    StartConditions* startAfter = processoptions_getStartAfter(proc);
+
+    guint64 quantity = processoptions_getQuantity(proc);
+    for (guint64 i = 0; i < quantity; i++) {
+        startafter_addProcess(validator->startAfter, name, startAfter);
+        stopcondition_addProcess(validator->stopCondition, name);
+    }
+
+    if (startAfter != NULL) {
+        startconditions_free(startAfter);
+    }
+    processoptions_freeString(name);
+    processoptions_freeString(stdinPath);
+    processoptions_freeString(plugin);
+}
+
+static void _controller_validateReserveIPCallback(const char* name, const ConfigOptions* config,
+                                                  const HostOptions* host, void* _validator) {
+    ConfigValidator* validator = _validator;
+
+    char* ipAddr = hostoptions_getIpAddr(host);
+    if (ipAddr == NULL) {
+        return;
+    }
+    hostoptions_freeString(ipAddr);
+
+    if (_controller_reserveHostIP(validator->dns, name, host)) {
+        g_hash_table_add(validator->reservedHosts, g_strdup(name));
+    } else {
+        validator->numErrors++;
+    }
+}
+
+static void _controller_validateHostCallback(const char* name, const ConfigOptions* config,
+                                             const HostOptions* host, void* _validator) {
+    ConfigValidator* validator = _validator;
+
+    if (validator->topology != NULL) {
+        gint64 networkNodeId = hostoptions_getNetworkNodeId(host);
+        if (networkNodeId >= 0 &&
+            !topology_hasNode(validator->topology, (guint64)networkNodeId)) {
+            error("For host '%s', the network graph has no node with id %" PRId64, name,
+                  networkNodeId);
+            validator->numErrors++;
+        }
+
+        /* a simulation would fall back to any node, which is unlikely to be intended */
+        char* citycodeHint = hostoptions_getCityCodeHint(host);
+        char* countrycodeHint = hostoptions_getCountryCodeHint(host);
+        if (networkNodeId < 0 &&
+            !topology_matchesHints(validator->topology, citycodeHint, countrycodeHint)) {
+            error("For host '%s', no network graph node matches the city or country code hint",
+                  name);
+            validator->numErrors++;
+        }
+        hostoptions_freeString(citycodeHint);
+        hostoptions_freeString(countrycodeHint);
+    }
+
+    guint32 interfaceMtu = hostoptions_getInterfaceMtu(host);
+    if (interfaceMtu < CONFIG_MTU_MIN || interfaceMtu > CONFIG_MTU_MAX) {
+        error("For host '%s', the interface MTU %" G_GUINT32_FORMAT
+              " is not between %d and %d",
+              name, interfaceMtu, CONFIG_MTU_MIN, CONFIG_MTU_MAX);
+        validator->numErrors++;
+    }
+
+    char* ipHint = hostoptions_getIpAddressHint(host);
+    char* ipAddr = hostoptions_getIpAddr(host);
+    gboolean isReserved = g_hash_table_contains(validator->reservedHosts, name);
+
+    guint64 quantity = hostoptions_getQuantity(host);
+    for (guint64 i = 0; i < quantity; i++) {
+        char* hostname = hostoptions_getHostname(host, name, (guint)(i + 1));
+        if (g_hash_table_contains(validator->hostnames, hostname)) {
+            error("For host '%s', the hostname '%s' is already used by another host", name,
+                  hostname);
+            validator->numErrors++;
+            hostoptions_freeString(hostname);
+            continue;
+        }
+        g_hash_table_add(validator->hostnames, g_strdup(hostname));
+
+        /* resolve the address the same way that the host would during setup */
+        GQuark id = g_quark_from_string(hostname);
+        Address* address = isReserved
+                               ? dns_registerReserved(validator->dns, id, hostname, ipAddr)
+                               : dns_register(validator->dns, id, hostname, ipHint);
+        startafter_addHost(validator->startAfter, hostname);
+
+        ProcessVariables* vars =
+            processvariables_new(hostname, (uint32_t)(i + 1), address_toHostIPString(address),
+                                 config_getSeed(validator->config));
+
+        ValidateProcessArgs processArgs;
+        processArgs.validator = validator;
+        processArgs.hostname = hostname;
+        processArgs.vars = vars;
+
+        hostoptions_iterProcesses(host, _controller_validateProcessCallback, (void*)&processArgs);
+
+        processvariables_free(vars);
+        address_unref(address);
+        hostoptions_freeString(hostname);
+    }
+
+    hostoptions_freeString(ipHint);
+    hostoptions_freeString(ipAddr);
+}
+
+gboolean controller_validateConfig(ConfigOptions* config) {
+    utility_assert(config);
+
+    ConfigValidator validator = {0};
+    validator.config = config;
+    validator.topology = _controller_newTopology(config);
+    validator.dns = dns_new();
+    validator.startAfter = startafter_new();
+    validator.stopCondition = stopcondition_new(config_getStopWhen(config));
+    validator.hostnames = g_hash_table_new_full(g_str_hash, g_str_equal, g_free, NULL);
+    validator.reservedHosts = g_hash_table_new_full(g_str_hash, g_str_equal, g_free, NULL);
+    validator.checkedPaths = g_hash_table_new_full(g_str_hash, g_str_equal, g_free, NULL);
+
+    if (validator.topology == NULL) {
+        validator.numErrors++;
+    }
+
+    /* keep going after errors so that they are all reported at once */
+    config_iterHosts(config, _controller_validateReserveIPCallback, (void*)&validator);
+    config_iterHosts(config, _controller_validateHostCallback, (void*)&validator);
+
+    if (!startafter_validate(validator.startAfter)) {
+        error("Processes have start_after conditions that can never be met");
+        validator.numErrors++;
+    }
+
+    if (!stopcondition_validate(validator.stopCondition)) {
+        error("The stop_when condition can never be met");
+        validator.numErrors++;
+    }
+
+    g_hash_table_destroy(validator.checkedPaths);
+    g_hash_table_destroy(validator.reservedHosts);
+    g_hash_table_destroy(validator.hostnames);
+    stopcondition_free(validator.stopCondition);
+    startafter_free(validator.startAfter);
+    dns_free(validator.dns);
+    if (validator.topology != NULL) {
+        topology_free(validator.topology);
+    }
+
+    if (validator.numErrors > 0) {
+        error("Found %u error(s) in the configuration", validator.numErrors);
+    }
+
+    return validator.numErrors == 0;
+}
+
 gint controller_run(Controller* controller) {
     MAGIC_ASSERT(controller);
 
@@ -366,6 +722,16 @@ gint controller_run(Controller* controller) {
      * this must be done after managers are available so we can send them messages */
     _controller_registerHosts(controller);
 
+    if (!startafter_validate(manager_getStartAfter(controller->manager))) {
+        error("Processes have start_after conditions that can never be met");
+        exit(EXIT_FAILURE);
+    }
+
+    if (!stopcondition_validate(manager_getStopCondition(controller->manager))) {
+        error("The stop_when condition can never be met");
+        exit(EXIT_FAILURE);
+    }
+
     info("running simulation");
 
     /* dont buffer log messages in trace mode */
@@ -399,6 +765,15 @@ gboolean controller_managerFinishedCurrentRound(Controller* controller,
     /* TODO: once we get multiple managers, we have to block them here
      * until they have all notified us that they are finished */
 
+    /* stop early if the processes that the simulation waits for exited during
+     * the round that just finished */
+    char* stopReason = stopcondition_check(manager_getStopCondition(controller->manager));
+    if (stopReason) {
+        info("stopping the simulation before its stop time because %s", stopReason);
+        stopcondition_freeString(stopReason);
+        return FALSE;
+    }
+
     /* update our detected min jump time */
     controller->minJumpTime = controller->nextMinJumpTime;
 
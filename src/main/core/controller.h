@@ -21,6 +21,10 @@ Controller* controller_new(ConfigOptions*);
 void controller_free(Controller*);
 gint controller_run(Controller*);
 
+/* Checks the configuration for errors without running the simulation. Every error is logged,
+ * and returns FALSE if there were any. */
+gboolean controller_validateConfig(ConfigOptions* config);
+
 void controller_updateMinTimeJump(Controller*, gdouble);
 gdouble controller_getRunTimeElapsed(Controller*);
 
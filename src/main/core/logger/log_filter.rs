@@ -0,0 +1,309 @@
+/*!
+Per-module log level directives in the style of the `RUST_LOG` environment variable used by
+`env_logger` (ex: `info,tcp=trace,epoll=debug`). A directive without a module sets the default
+level, which applies to records that no other directive matches.
+
+Shadow logs records from both Rust and C code. A directive's module matches a Rust record
+whose module path contains it as whole `::`-separated components (ex: `tcp` and
+`descriptor::tcp` both match `shadow_rs::host::descriptor::tcp`), or a record of either
+language whose source file has the same name without its extension (ex: `tcp` matches records
+logged from `tcp.c`). When several directives match, the one with the longest module wins.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use log::LevelFilter;
+use schemars::JsonSchema;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    /// The module that the directive applies to, or `None` for all modules.
+    module: Option<String>,
+    level: LevelFilter,
+}
+
+impl Directive {
+    fn matches(&self, module_path: Option<&str>, file: Option<&str>) -> bool {
+        let module = match &self.module {
+            Some(x) => x.as_str(),
+            None => return false,
+        };
+
+        if let Some(path) = module_path {
+            let path_parts: Vec<&str> = path.split("::").collect();
+            let module_parts: Vec<&str> = module.split("::").collect();
+            if path_parts
+                .windows(module_parts.len())
+                .any(|w| w == module_parts.as_slice())
+            {
+                return true;
+            }
+        }
+
+        if let Some(file) = file {
+            let name = file.rsplit('/').next().unwrap();
+            let stem = name.split('.').next().unwrap();
+            if stem == module {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Directives with longer modules are more specific.
+    fn specificity(&self) -> usize {
+        self.module.as_ref().map(|x| x.len()).unwrap_or(0)
+    }
+}
+
+/// A parsed set of per-module log level directives.
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    directives: Vec<Directive>,
+    source: String,
+}
+
+impl LogFilter {
+    /// The level of the most specific module directive that matches a record with the given
+    /// module path and source file, or `None` if no module directive matches.
+    pub fn level_for(&self, module_path: Option<&str>, file: Option<&str>) -> Option<LevelFilter> {
+        let mut best: Option<&Directive> = None;
+        for directive in &self.directives {
+            if !directive.matches(module_path, file) {
+                continue;
+            }
+            // later directives override earlier ones that are just as specific
+            if best.map_or(true, |b| directive.specificity() >= b.specificity()) {
+                best = Some(directive);
+            }
+        }
+        best.map(|d| d.level)
+    }
+
+    /// The level of the last directive without a module, if any.
+    pub fn default_level(&self) -> Option<LevelFilter> {
+        self.directives
+            .iter()
+            .rev()
+            .find(|d| d.module.is_none())
+            .map(|d| d.level)
+    }
+
+    /// The most verbose level of any directive.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|d| d.level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+}
+
+impl PartialEq for LogFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.directives == other.directives
+    }
+}
+
+fn parse_level(s: &str) -> Result<LevelFilter, String> {
+    // accept the same names as the 'log_level' options
+    match s {
+        "warning" => Ok(LevelFilter::Warn),
+        _ => LevelFilter::from_str(s).map_err(|_| format!("Invalid log level '{}'", s)),
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = Vec::new();
+
+        for directive in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let directive = match directive.find('=') {
+                Some(pos) => {
+                    let module = directive[..pos].trim();
+                    let level = &directive[(pos + 1)..];
+                    if module.is_empty() || module.split("::").any(|x| x.is_empty()) {
+                        return Err(format!("Invalid module '{}' in log filter", module));
+                    }
+                    Directive {
+                        module: Some(module.to_string()),
+                        level: parse_level(level.trim())?,
+                    }
+                }
+                None => Directive {
+                    module: None,
+                    level: parse_level(directive)?,
+                },
+            };
+            directives.push(directive);
+        }
+
+        if directives.is_empty() {
+            return Err("The log filter has no directives".to_string());
+        }
+
+        Ok(Self {
+            directives,
+            source: s.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for LogFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl JsonSchema for LogFilter {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "LogFilter".to_owned()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            format: Some("LogFilter".to_owned()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(filter: &str, module_path: Option<&str>, file: Option<&str>) -> Option<LevelFilter> {
+        filter
+            .parse::<LogFilter>()
+            .unwrap()
+            .level_for(module_path, file)
+    }
+
+    #[test]
+    fn test_matching() {
+        let rust_tcp = Some("shadow_rs::host::descriptor::tcp");
+        let rust_tcp_file = Some("src/main/host/descriptor/tcp.rs");
+        let c_tcp_file = Some("src/main/host/descriptor/tcp.c");
+
+        assert_eq!(level("tcp=trace", rust_tcp, None), Some(LevelFilter::Trace));
+        assert_eq!(
+            level("descriptor::tcp=trace", rust_tcp, None),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(
+            level("shadow_rs::host=debug", rust_tcp, None),
+            Some(LevelFilter::Debug)
+        );
+        assert_eq!(
+            level("tcp=trace", Some("tcp_processPacket"), c_tcp_file),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(
+            level("tcp=trace", None, rust_tcp_file),
+            Some(LevelFilter::Trace)
+        );
+
+        // partial components don't match
+        assert_eq!(level("tc=trace", rust_tcp, c_tcp_file), None);
+        assert_eq!(level("host::tcp=trace", rust_tcp, None), None);
+        assert_eq!(level("epoll=trace", rust_tcp, c_tcp_file), None);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let module = Some("shadow_rs::host::descriptor::tcp");
+
+        assert_eq!(level("warn", module, None), None);
+        assert_eq!(
+            level("warn,tcp=trace", module, None),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(
+            level("tcp=trace,warn", module, None),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(
+            level("descriptor::tcp=info,tcp=trace", module, None),
+            Some(LevelFilter::Info)
+        );
+        assert_eq!(
+            level("tcp=info,tcp=debug", module, None),
+            Some(LevelFilter::Debug)
+        );
+        assert_eq!(level("tcp=off", module, None), Some(LevelFilter::Off));
+    }
+
+    #[test]
+    fn test_default_level() {
+        let filter: LogFilter = "tcp=trace".parse().unwrap();
+        assert_eq!(filter.default_level(), None);
+        let filter: LogFilter = "warn,tcp=trace,info".parse().unwrap();
+        assert_eq!(filter.default_level(), Some(LevelFilter::Info));
+    }
+
+    #[test]
+    fn test_max_level() {
+        let filter: LogFilter = "error,tcp=trace,epoll=info".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        let filter: LogFilter = "tcp=warning".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for s in &[
+            "",
+            ",",
+            "tcp",
+            "tcp=",
+            "=trace",
+            "tcp=loud",
+            "::tcp=info",
+            "a::=info",
+        ] {
+            assert!(s.parse::<LogFilter>().is_err(), "'{}' should not parse", s);
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let filter: LogFilter = serde_yaml::from_str("\"info, tcp=trace\"").unwrap();
+        assert_eq!(filter, "info,tcp=trace".parse().unwrap());
+        assert_eq!(filter.to_string(), "info, tcp=trace");
+
+        let yaml = serde_yaml::to_string(&filter).unwrap();
+        assert_eq!(serde_yaml::from_str::<LogFilter>(&yaml).unwrap(), filter);
+        assert!(serde_yaml::from_str::<LogFilter>("\"tcp=loud\"").is_err());
+    }
+}
@@ -36,11 +36,12 @@ pub fn c_to_rust_log_level(level: log_bindings::LogLevel) -> Option<log::Level>
     }
 }
 
-/// Set the max (noisiest) logging level to `level`.
+/// Set the logging level of records that aren't matched by the log filter or a
+/// host's log level to `level`.
 #[no_mangle]
 pub unsafe extern "C" fn rustlogger_setLevel(level: log_bindings::LogLevel) {
     let level = c_to_rust_log_level(level).unwrap();
-    log::set_max_level(level.to_level_filter());
+    super::shadow_logger::set_level(level.to_level_filter());
 }
 
 /// Whether logging is currently enabled for `level`.
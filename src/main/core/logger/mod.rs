@@ -1,2 +1,3 @@
+pub mod log_filter;
 pub mod log_wrapper;
 mod shadow_logger;
@@ -1,12 +1,20 @@
+use super::log_filter::LogFilter;
+use crate::core::support::configuration::LogFormat;
 use crate::core::support::simulation_time::SimulationTime;
 use crate::core::worker::Worker;
 use crate::host::host::HostInfo;
+use crate::host::process::ProcessId;
+use crate::host::thread::ThreadId;
 use crossbeam::queue::ArrayQueue;
-use log::{Level, Log, Metadata, Record, SetLoggerError};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 use log_bindings as c_log;
 use once_cell::sync::Lazy;
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::sync::{Mutex, RwLock};
@@ -25,6 +33,11 @@ const SYNC_FLUSH_QD_LINES_THRESHOLD: usize = 10 * ASYNC_FLUSH_QD_LINES_THRESHOLD
 /// Logging thread flushes at least this often.
 const MIN_FLUSH_FREQUENCY: Duration = Duration::from_secs(10);
 
+/// At most this many host log files are open at once, so that simulations with
+/// many hosts don't run out of file descriptors (the default limit is 1024).
+/// The least recently used file is closed to open another one.
+const MAX_OPEN_HOST_FILES: usize = 256;
+
 static SHADOW_LOGGER: Lazy<ShadowLogger> = Lazy::new(|| ShadowLogger::new());
 
 /// Helper for formatting times.
@@ -78,8 +91,11 @@ fn test_time_parts() {
     );
 }
 
-/// Initialize the Shadow logger.
-pub fn init() -> Result<(), SetLoggerError> {
+/// Initialize the Shadow logger, which will write records in the given format. Records are
+/// filtered by `filter`, if given, in addition to the log level.
+pub fn init(format: LogFormat, filter: Option<LogFilter>) -> Result<(), SetLoggerError> {
+    SHADOW_LOGGER.set_format(format);
+    SHADOW_LOGGER.set_filter(filter);
     log::set_logger(&*SHADOW_LOGGER)?;
 
     // Start the thread that will receive log records and flush them to output.
@@ -103,6 +119,11 @@ pub fn init() -> Result<(), SetLoggerError> {
     Ok(())
 }
 
+/// Set the level of records that aren't matched by the filter or a host's log level.
+pub fn set_level(level: LevelFilter) {
+    SHADOW_LOGGER.set_level(level);
+}
+
 /// A logger specialized for Shadow. It attaches simulation context to log
 /// entries (e.g. sim time, running process, etc.). It's also designed for
 /// high performance to accomodate heavy logging from multiple threads.
@@ -133,6 +154,19 @@ pub struct ShadowLogger {
     // When false, sends a (still-asynchronous) flush command to the logger
     // thread every time a record is pushed into `records`.
     buffering_enabled: RwLock<bool>,
+
+    // How records are written to stdout.
+    format: RwLock<LogFormat>,
+
+    // The level of records that aren't matched by `filter` or a host's log level.
+    level: RwLock<LevelFilter>,
+
+    // Per-module levels, which take precedence over the other levels.
+    filter: RwLock<Option<LogFilter>>,
+
+    // Log files of hosts that don't log to stdout, opened when their first
+    // record is written. Only accessed while flushing.
+    host_files: Mutex<HostFiles>,
 }
 
 thread_local!(static SENDER: RefCell<Option<Sender<LoggerCommand>>> = RefCell::new(None));
@@ -175,6 +209,10 @@ impl ShadowLogger {
             command_sender: Mutex::new(sender),
             command_receiver: Mutex::new(receiver),
             buffering_enabled: RwLock::new(false),
+            format: RwLock::new(LogFormat::Text),
+            level: RwLock::new(LevelFilter::Off),
+            filter: RwLock::new(None),
+            host_files: Mutex::new(HostFiles::new(MAX_OPEN_HOST_FILES)),
         };
         logger
     }
@@ -201,7 +239,7 @@ impl ShadowLogger {
     // self.records. If `done_sender` is provided, it's notified after the flush
     // has completed.
     fn flush_records(&self, done_sender: Option<Sender<()>>) -> std::io::Result<()> {
-        use std::io::Write;
+        let format = *self.format.read().unwrap();
 
         // Only flush records that are already in the queue, not ones that
         // arrive while we're flushing. Otherwise callers who perform a
@@ -213,6 +251,7 @@ impl ShadowLogger {
         let stdout_unlocked = std::io::stdout();
         let stdout_locked = stdout_unlocked.lock();
         let mut stdout = std::io::BufWriter::new(stdout_locked);
+        let mut host_files = self.host_files.lock().unwrap();
         while toflush > 0 {
             let record = match self.records.pop() {
                 Some(r) => r,
@@ -224,63 +263,24 @@ impl ShadowLogger {
                 }
             };
             toflush -= 1;
-            {
-                let parts = TimeParts::from_nanos(record.wall_time.as_nanos());
-                write!(
-                    stdout,
-                    "{:02}:{:02}:{:02}.{:06}",
-                    parts.hours,
-                    parts.mins,
-                    parts.secs,
-                    parts.nanos / 1000
-                )?;
-            }
-            write!(stdout, " [{}]", record.thread_name)?;
-            if let Some(sim_time) = record.sim_time {
-                let parts = TimeParts::from_nanos(sim_time.as_nanos());
-                write!(
-                    stdout,
-                    " {:02}:{:02}:{:02}.{:09}",
-                    parts.hours, parts.mins, parts.secs, parts.nanos
-                )?;
-            } else {
-                write!(stdout, " n/a")?;
-            }
-            write!(stdout, " [{level}]", level = record.level)?;
-            if let Some(host) = record.host_info {
-                write!(
-                    stdout,
-                    " [{hostname}:{ip}]",
-                    hostname = host.name,
-                    ip = host.default_ip,
-                )?;
-            } else {
-                write!(stdout, " [n/a]",)?;
-            }
-            write!(
-                stdout,
-                " [{file}:",
-                file = record
-                    .file
-                    .map(|f| if let Some(sep_pos) = f.rfind('/') {
-                        &f[(sep_pos + 1)..]
-                    } else {
-                        f
-                    })
-                    .unwrap_or("n/a"),
-            )?;
-            if let Some(line) = record.line {
-                write!(stdout, "{line}", line = line)?;
-            } else {
-                write!(stdout, "n/a")?;
+
+            let host_file = match record.host_info.as_ref().and_then(|h| h.log_file.as_ref()) {
+                Some(path) => host_files.get(path),
+                None => None,
+            };
+            let to_stdout = match host_file {
+                Some(file) => {
+                    write_record(file, format, &record)?;
+                    // also show errors on stdout, since they usually end the simulation
+                    record.level == Level::Error
+                }
+                None => true,
+            };
+            if to_stdout {
+                write_record(&mut stdout, format, &record)?;
             }
-            write!(
-                stdout,
-                "] [{module}] {msg}\n",
-                module = record.module_path.unwrap_or("n/a"),
-                msg = record.message
-            )?;
         }
+        host_files.flush()?;
         if let Some(done_sender) = done_sender {
             // We can't log from this thread without risking deadlock, so in the
             // unlikely case that the calling thread has gone away, just print
@@ -296,6 +296,73 @@ impl ShadowLogger {
         Ok(())
     }
 
+    /// Sets the format in which records are written.
+    pub fn set_format(&self, format: LogFormat) {
+        let mut writer = self.format.write().unwrap();
+        *writer = format;
+    }
+
+    /// Sets the level of records that aren't matched by the filter or a host's
+    /// log level.
+    pub fn set_level(&self, level: LevelFilter) {
+        *self.level.write().unwrap() = level;
+        self.update_max_level();
+    }
+
+    /// Sets the per-module levels, which take precedence over the other levels.
+    pub fn set_filter(&self, filter: Option<LogFilter>) {
+        *self.filter.write().unwrap() = filter;
+        self.update_max_level();
+    }
+
+    // The `log` crate drops records above its max level before they reach the
+    // logger, so it must be at least as verbose as any of our levels.
+    fn update_max_level(&self) {
+        let filter = self.filter.read().unwrap();
+        let level = self.default_level(filter.as_ref());
+        let filter_level = filter
+            .as_ref()
+            .map(|f| f.max_level())
+            .unwrap_or(LevelFilter::Off);
+        log::set_max_level(std::cmp::max(level, filter_level));
+    }
+
+    // The level of records that aren't matched by any other level.
+    fn default_level(&self, filter: Option<&LogFilter>) -> LevelFilter {
+        filter
+            .and_then(|f| f.default_level())
+            .unwrap_or(*self.level.read().unwrap())
+    }
+
+    // The most verbose level that a record of any module could be logged at.
+    fn max_level(&self) -> LevelFilter {
+        let filter = self.filter.read().unwrap();
+        let level = match Worker::with_active_host_info(|host| host.log_level) {
+            Some(Some(level)) => level,
+            _ => self.default_level(filter.as_ref()),
+        };
+        match filter.as_ref() {
+            Some(filter) => std::cmp::max(level, filter.max_level()),
+            None => level,
+        }
+    }
+
+    // Whether the record should be logged, taking its module into account.
+    fn record_enabled(&self, record: &Record) -> bool {
+        let filter = self.filter.read().unwrap();
+        let module_level = filter
+            .as_ref()
+            .and_then(|f| f.level_for(record.module_path(), record.file()));
+        let level = match module_level {
+            Some(level) => level,
+            None => match Worker::with_active_host_info(|host| host.log_level) {
+                Some(Some(level)) => level,
+                _ => self.default_level(filter.as_ref()),
+            },
+        };
+        record.level() <= level
+    }
+
     /// When disabled, the logger thread is notified to write each record as
     /// soon as it's created.  The calling thread still isn't blocked on the
     /// record actually being written, though.
@@ -349,15 +416,13 @@ impl ShadowLogger {
 
 impl Log for ShadowLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        let filter = match Worker::with_active_host_info(|host| host.log_level) {
-            Some(Some(level)) => level,
-            _ => log::max_level(),
-        };
-        metadata.level() <= filter
+        // The metadata doesn't include the module of C records, so we can only
+        // rule out records that no module would log.
+        metadata.level() <= self.max_level()
     }
 
     fn log(&self, record: &Record) {
-        if !self.enabled(record.metadata()) {
+        if !self.record_enabled(record) {
             return;
         }
 
@@ -376,6 +441,8 @@ impl Log for ShadowLogger {
             }),
 
             sim_time: Worker::current_time(),
+            process_id: Worker::active_process_id(),
+            thread_id: Worker::active_thread_id(),
             thread_name: THREAD_NAME
                 .try_with(|name| (*name).clone())
                 .unwrap_or_else(|_| get_thread_name()),
@@ -421,10 +488,346 @@ struct ShadowLogRecord {
     wall_time: Duration,
 
     sim_time: Option<SimulationTime>,
+    process_id: Option<ProcessId>,
+    thread_id: Option<ThreadId>,
     thread_name: String,
     host_info: Option<Arc<HostInfo>>,
 }
 
+impl ShadowLogRecord {
+    /// The name of the source file, without its directories.
+    fn file_name(&self) -> Option<&'static str> {
+        self.file.map(|f| match f.rfind('/') {
+            Some(sep_pos) => &f[(sep_pos + 1)..],
+            None => f,
+        })
+    }
+}
+
+enum HostFile {
+    Open {
+        writer: BufWriter<File>,
+        // when the file was last used, which is its key in `HostFiles::lru`
+        last_used: u64,
+    },
+    // closed to stay under the limit of open files, and reopened for appending
+    Closed,
+    // couldn't be opened, so the host's records are written to stdout instead
+    Failed,
+}
+
+/// The log files of hosts, of which at most `max_open` are open at once.
+struct HostFiles {
+    files: HashMap<PathBuf, HostFile>,
+    // the paths of the open files, with the least recently used first
+    lru: BTreeMap<u64, PathBuf>,
+    counter: u64,
+    max_open: usize,
+}
+
+impl HostFiles {
+    fn new(max_open: usize) -> Self {
+        assert!(max_open > 0);
+        Self {
+            files: HashMap::new(),
+            lru: BTreeMap::new(),
+            counter: 0,
+            max_open,
+        }
+    }
+
+    /// Returns the host's log file, opening it if needed, or `None` if it
+    /// couldn't be opened. The file is truncated when it's first opened.
+    fn get(&mut self, path: &Path) -> Option<&mut BufWriter<File>> {
+        self.counter += 1;
+        let now = self.counter;
+
+        let is_open = match self.files.get_mut(path) {
+            Some(HostFile::Open { last_used, .. }) => {
+                self.lru.remove(last_used);
+                *last_used = now;
+                true
+            }
+            Some(HostFile::Failed) => return None,
+            Some(HostFile::Closed) | None => false,
+        };
+
+        if !is_open {
+            if self.lru.len() >= self.max_open {
+                self.close_least_recently_used();
+            }
+
+            let result = match self.files.get(path) {
+                Some(_) => OpenOptions::new().append(true).open(path),
+                None => File::create(path),
+            };
+            let file = match result {
+                Ok(f) => HostFile::Open {
+                    writer: BufWriter::new(f),
+                    last_used: now,
+                },
+                Err(e) => {
+                    // We can't log from the logger thread, so print directly.
+                    println!(
+                        "WARNING: Couldn't open log file {}, writing to stdout instead: {}",
+                        path.display(),
+                        e
+                    );
+                    HostFile::Failed
+                }
+            };
+            self.files.insert(path.to_path_buf(), file);
+        }
+
+        match self.files.get_mut(path).unwrap() {
+            HostFile::Open { writer, .. } => {
+                self.lru.insert(now, path.to_path_buf());
+                Some(writer)
+            }
+            _ => None,
+        }
+    }
+
+    fn close_least_recently_used(&mut self) {
+        let last_used = match self.lru.keys().next() {
+            Some(x) => *x,
+            None => return,
+        };
+        let path = self.lru.remove(&last_used).unwrap();
+
+        if let Some(HostFile::Open { mut writer, .. }) =
+            self.files.insert(path.clone(), HostFile::Closed)
+        {
+            if let Err(e) = writer.flush() {
+                println!("WARNING: Couldn't write log file {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        for file in self.files.values_mut() {
+            if let HostFile::Open { writer, .. } = file {
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the record in the given format.
+fn write_record(
+    out: &mut impl Write,
+    format: LogFormat,
+    record: &ShadowLogRecord,
+) -> std::io::Result<()> {
+    match format {
+        LogFormat::Text => write_text_record(out, record),
+        LogFormat::Json => write_json_record(out, record),
+    }
+}
+
+/// Writes the record as a line of space-separated fields.
+fn write_text_record(out: &mut impl Write, record: &ShadowLogRecord) -> std::io::Result<()> {
+    {
+        let parts = TimeParts::from_nanos(record.wall_time.as_nanos());
+        write!(
+            out,
+            "{:02}:{:02}:{:02}.{:06}",
+            parts.hours,
+            parts.mins,
+            parts.secs,
+            parts.nanos / 1000
+        )?;
+    }
+    write!(out, " [{}]", record.thread_name)?;
+    if let Some(sim_time) = record.sim_time {
+        let parts = TimeParts::from_nanos(sim_time.as_nanos());
+        write!(
+            out,
+            " {:02}:{:02}:{:02}.{:09}",
+            parts.hours, parts.mins, parts.secs, parts.nanos
+        )?;
+    } else {
+        write!(out, " n/a")?;
+    }
+    write!(out, " [{level}]", level = record.level)?;
+    if let Some(host) = &record.host_info {
+        write!(
+            out,
+            " [{hostname}:{ip}]",
+            hostname = host.name,
+            ip = host.default_ip,
+        )?;
+    } else {
+        write!(out, " [n/a]",)?;
+    }
+    write!(out, " [{file}:", file = record.file_name().unwrap_or("n/a"))?;
+    if let Some(line) = record.line {
+        write!(out, "{line}", line = line)?;
+    } else {
+        write!(out, "n/a")?;
+    }
+    write!(
+        out,
+        "] [{module}] {msg}\n",
+        module = record.module_path.unwrap_or("n/a"),
+        msg = record.message
+    )
+}
+
+/// Writes the record as a JSON object on a single line. Fields that are
+/// unknown (for example the host of a record logged outside of any host) are
+/// written as `null`.
+fn write_json_record(out: &mut impl Write, record: &ShadowLogRecord) -> std::io::Result<()> {
+    write!(out, "{{\"wall_time_us\":{}", record.wall_time.as_micros())?;
+    write!(out, ",\"sim_time_ns\":")?;
+    write_json_opt(out, record.sim_time.map(|t| t.as_nanos()))?;
+    write!(out, ",\"level\":")?;
+    write_json_str(out, record.level.as_str())?;
+    write!(out, ",\"worker_thread\":")?;
+    write_json_str(out, &record.thread_name)?;
+    write!(out, ",\"host\":")?;
+    match &record.host_info {
+        Some(host) => write_json_str(out, &host.name)?,
+        None => write!(out, "null")?,
+    }
+    write!(out, ",\"host_ip\":")?;
+    match &record.host_info {
+        Some(host) => write_json_str(out, &host.default_ip.to_string())?,
+        None => write!(out, "null")?,
+    }
+    write!(out, ",\"pid\":")?;
+    write_json_opt(out, record.process_id.map(u32::from))?;
+    write!(out, ",\"tid\":")?;
+    write_json_opt(out, record.thread_id.map(u32::from))?;
+    write!(out, ",\"module\":")?;
+    match record.module_path {
+        Some(module) => write_json_str(out, module)?,
+        None => write!(out, "null")?,
+    }
+    write!(out, ",\"file\":")?;
+    match record.file_name() {
+        Some(file) => write_json_str(out, file)?,
+        None => write!(out, "null")?,
+    }
+    write!(out, ",\"line\":")?;
+    write_json_opt(out, record.line)?;
+    write!(out, ",\"message\":")?;
+    write_json_str(out, &record.message)?;
+    write!(out, "}}\n")
+}
+
+/// Writes a number, or `null` if there isn't one.
+fn write_json_opt(
+    out: &mut impl Write,
+    val: Option<impl std::fmt::Display>,
+) -> std::io::Result<()> {
+    match val {
+        Some(val) => write!(out, "{}", val),
+        None => write!(out, "null"),
+    }
+}
+
+/// Writes a quoted JSON string, escaping characters as needed.
+fn write_json_str(out: &mut impl Write, s: &str) -> std::io::Result<()> {
+    out.write_all(b"\"")?;
+    // Write runs of characters that don't need escaping all at once.
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c != '"' && c != '\\' && !c.is_control() {
+            continue;
+        }
+        out.write_all(s[start..i].as_bytes())?;
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c => write!(out, "\\u{:04x}", c as u32)?,
+        }
+        start = i + c.len_utf8();
+    }
+    out.write_all(s[start..].as_bytes())?;
+    out.write_all(b"\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_str(s: &str) -> String {
+        let mut out = Vec::new();
+        write_json_str(&mut out, s).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_json_str() {
+        assert_eq!(json_str("hello"), r#""hello""#);
+        assert_eq!(json_str(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(json_str("a\nb\tc\u{1}"), r#""a\nb\tc\u0001""#);
+        assert_eq!(json_str("héllo"), r#""héllo""#);
+    }
+
+    #[test]
+    fn test_host_files() {
+        let dir = std::env::temp_dir().join(format!("shadow-test-logs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PathBuf> = ["a", "b", "c"].iter().map(|x| dir.join(x)).collect();
+        std::fs::write(&paths[0], "old\n").unwrap();
+
+        let mut files = HostFiles::new(2);
+        for path in paths.iter().chain(paths.iter()) {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            writeln!(files.get(path).unwrap(), "{}", name).unwrap();
+            assert!(files.lru.len() <= 2);
+        }
+        files.flush().unwrap();
+
+        // the existing file was truncated once, and then appended to when it was reopened
+        for path in &paths {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert_eq!(
+                std::fs::read_to_string(path).unwrap(),
+                format!("{}\n{}\n", name, name)
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_json_record() {
+        let record = ShadowLogRecord {
+            level: Level::Warn,
+            file: Some("src/main/host/host.c"),
+            module_path: Some("shadow_rs::host"),
+            line: Some(12),
+            message: "a \"quoted\" message".into(),
+            wall_time: Duration::from_micros(1_500_000),
+            sim_time: None,
+            process_id: Some(ProcessId::from(1000)),
+            thread_id: None,
+            thread_name: "worker-0".into(),
+            host_info: None,
+        };
+
+        let mut out = Vec::new();
+        write_json_record(&mut out, &record).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"wall_time_us":1500000,"sim_time_ns":null,"level":"WARN","#,
+                r#""worker_thread":"worker-0","host":null,"host_ip":null,"pid":1000,"#,
+                r#""tid":null,"module":"shadow_rs::host","file":"host.c","line":12,"#,
+                r#""message":"a \"quoted\" message"}"#,
+                "\n"
+            )
+        );
+    }
+}
+
 enum LoggerCommand {
     // Flush; takes an optional one-shot channel to notify that the flush has completed.
     Flush(Option<Sender<()>>),
@@ -436,10 +839,12 @@ mod export {
     /// Creates a ShadowLogger and installs it as the default logger for Rust's
     /// `log` crate. The returned pointer is never deallocated, since loggers
     /// registered with the `log` crate are required to live for the life of the
-    /// program.
+    /// program. Records are written in the given format, and filtered by the
+    /// optional `filter` in addition to the log level.
     #[no_mangle]
-    pub unsafe extern "C" fn shadow_logger_init() -> () {
-        init().unwrap()
+    pub unsafe extern "C" fn shadow_logger_init(format: LogFormat, filter: *const LogFilter) -> () {
+        let filter = unsafe { filter.as_ref() }.cloned();
+        init(format, filter).unwrap()
     }
 
     /// When disabled, the logger thread is notified to write each record as
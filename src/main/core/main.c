@@ -188,21 +188,23 @@ gint main_runShadow(gint argc, gchar* argv[]) {
         return EXIT_SUCCESS;
     }
 
+    if (clioptions_getShowConfigSchema(options)) {
+        configfile_showSchema();
+
+        clioptions_free(options);
+        return EXIT_SUCCESS;
+    }
+
     char* configName = clioptions_getConfig(options);
 
-    /* since we've already checked the two exclusive flags above (--show-build-info and
-     * --shm-cleanup), configName should never be NULL*/
+    /* since we've already checked the exclusive flags above (--show-build-info, --shm-cleanup,
+     * and --show-config-schema), configName should never be NULL*/
     if (!configName) {
         utility_panic("Could not get configuration file path");
     }
 
-    /* read config from file or stdin */
-    ConfigFileOptions* configFile = NULL;
-    if (strcmp(configName, "-") != 0) {
-        configFile = configfile_parse(configName);
-    } else {
-        configFile = configfile_parse("/dev/stdin");
-    }
+    /* read config from file or stdin ("-"), along with the files it includes */
+    ConfigFileOptions* configFile = configfile_parse(configName, options);
 
     clioptions_freeString(configName);
 
@@ -229,7 +231,7 @@ gint main_runShadow(gint argc, gchar* argv[]) {
     LogLevel logLevel = config_getLogLevel(config);
 
     /* start up the logging subsystem to handle all future messages */
-    shadow_logger_init();
+    shadow_logger_init(config_getLogFormat(config), config_getLogFilter(config));
     logger_setDefault(rustlogger_new(logLevel));
     logger_setLevel(logger_getDefault(), logLevel);
 
@@ -243,6 +245,17 @@ gint main_runShadow(gint argc, gchar* argv[]) {
     }
 #endif
 
+    if (clioptions_getValidateConfig(options)) {
+        gboolean isValid = controller_validateConfig(config);
+
+        clioptions_free(options);
+        config_free(config);
+        logger_flush(logger_getDefault());
+
+        g_printerr("** The configuration is %s\n", isValid ? "valid" : "invalid");
+        return isValid ? EXIT_SUCCESS : EXIT_FAILURE;
+    }
+
     // before we run the simluation, clean up any orphaned shared memory
     shmemcleanup_tryCleanup();
 
@@ -21,12 +21,17 @@
 #include "main/core/scheduler/scheduler_policy.h"
 #include "main/core/support/config_handlers.h"
 #include "main/core/support/definitions.h"
+#include "main/core/work/event.h"
+#include "main/core/work/task.h"
 #include "main/host/host.h"
 #include "main/host/network_interface.h"
+#include "main/host/process.h"
 #include "main/routing/address.h"
 #include "main/routing/dns.h"
 #include "main/routing/topology.h"
+#include "main/utility/pcap_writer.h"
 #include "main/utility/random.h"
+#include "main/utility/stats_writer.h"
 #include "main/utility/utility.h"
 
 #define PRELOAD_SHIM_LIB_STR "libshadow-shim.so"
@@ -77,6 +82,27 @@ struct _Manager {
     gchar* dataPath;
     gchar* hostsPath;
 
+    /* the network-wide packet capture, which uses the global capture options */
+    NetworkInterfaceCapture pcap;
+    /* owned copy of the filter in `pcap`, or NULL */
+    PcapFilter* pcapFilter;
+
+    /* machine-readable copy of the heartbeat messages, or NULL if disabled */
+    StatsWriter* heartbeatWriter;
+
+    /* statistics written to the data directory at the end of the simulation */
+    SimStats* stats;
+    /* the state that processes' start conditions depend on */
+    StartAfter* startAfter;
+    /* processes waiting for their start conditions, protected by the lock */
+    GQueue* waitingProcesses;
+    /* the conditions that stop the simulation before its end time */
+    StopCondition* stopCondition;
+    gint64 wallStartTime;
+    guint64 numRounds;
+    SimulationTime endTime;
+    SimulationTime simClockEnd;
+
     // Path to the shim that we preload for every managed process.
     gchar* preloadShimPath;
     // Path to the openssl rng lib that we preload for requesting managed processes.
@@ -192,6 +218,13 @@ Manager* manager_new(Controller* controller, ConfigOptions* config, SimulationTi
     manager->config = config;
     manager->random = random_new(randomSeed);
     manager->bootstrapEndTime = unlimBWEndTime;
+    manager->endTime = endTime;
+
+    manager->stats = simstats_new();
+    manager->startAfter = startafter_new();
+    manager->waitingProcesses = g_queue_new();
+    manager->stopCondition = stopcondition_new(config_getStopWhen(config));
+    manager->wallStartTime = g_get_monotonic_time();
 
     manager->rawFrequencyKHz = utility_getRawCPUFrequency(CONFIG_CPU_MAX_FREQ_FILE);
     if (manager->rawFrequencyKHz == 0) {
@@ -275,6 +308,31 @@ Manager* manager_new(Controller* controller, ConfigOptions* config, SimulationTi
     /* now make sure the hosts path exists, as it may not have been in the template */
     g_mkdir_with_parents(manager->hostsPath, 0775);
 
+    char* pcapFile = config_getPcapFile(config);
+    if (pcapFile != NULL) {
+        gchar* pcapPath = g_path_is_absolute(pcapFile)
+                              ? g_strdup(pcapFile)
+                              : g_build_filename(manager->dataPath, pcapFile, NULL);
+        gchar* seedString = g_strdup_printf("seed %u", config_getSeed(config));
+        const gchar* comments[] = {"network-wide capture of all hosts", seedString, NULL};
+
+        manager->pcapFilter = config_getPcapFilter(config);
+        manager->pcap = (NetworkInterfaceCapture){
+            .pcap = pcapwriter_new(pcapPath, comments, TRUE),
+            .captureSize = (guint32)MIN(config_getPcapCaptureSize(config), G_MAXUINT32),
+            .filter = manager->pcapFilter,
+            .startTime = config_getPcapStartTime(config),
+            .stopTime = config_getPcapStopTime(config),
+        };
+
+        g_free(seedString);
+        g_free(pcapPath);
+        config_freeString(pcapFile);
+    }
+
+    manager->heartbeatWriter =
+        statswriter_new(manager->dataPath, "heartbeat", config_getHeartbeatFormat(config));
+
     return manager;
 }
 
@@ -297,6 +355,42 @@ gint manager_free(Manager* manager) {
         scheduler_unref(manager->scheduler);
     }
 
+    /* the hosts have been freed, so nothing else will write to the capture */
+    if (manager->pcap.pcap) {
+        pcapwriter_free(manager->pcap.pcap);
+        manager->pcap.pcap = NULL;
+    }
+    if (manager->pcapFilter) {
+        pcapfilter_free(manager->pcapFilter);
+        manager->pcapFilter = NULL;
+        manager->pcap.filter = NULL;
+    }
+
+    statswriter_free(manager->heartbeatWriter);
+    manager->heartbeatWriter = NULL;
+
+    /* the hosts and processes have been freed, so their statistics are complete */
+    if (manager->stats) {
+        if (simstats_checkFinalStates(manager->stats) > 0) {
+            returnCode = -1;
+        }
+
+        gchar* statsPath = g_build_filename(manager->dataPath, "sim-stats.json", NULL);
+        guint64 wallTimeNanos = (guint64)(g_get_monotonic_time() - manager->wallStartTime) * 1000;
+        simstats_write(manager->stats, statsPath, wallTimeNanos, manager->simClockEnd,
+                       manager->numRounds);
+        g_free(statsPath);
+        simstats_free(manager->stats);
+        manager->stats = NULL;
+    }
+
+    startafter_free(manager->startAfter);
+    manager->startAfter = NULL;
+    g_queue_free(manager->waitingProcesses);
+    manager->waitingProcesses = NULL;
+    stopcondition_free(manager->stopCondition);
+    manager->stopCondition = NULL;
+
     if (manager->syscall_counter) {
         char* str = counter_alloc_string(manager->syscall_counter);
         info("Global syscall counts: %s", str);
@@ -376,15 +470,23 @@ void manager_addNewVirtualHost(Manager* manager, HostParameters* params) {
     /* quarks are unique per manager process, so do the conversion here */
     params->id = g_quark_from_string(params->hostname);
     params->nodeSeed = _manager_nextRandomUInt(manager);
+    params->simulationSeed = config_getSeed(manager->config);
 
     Host* host = host_new(params);
     host_setup(host, manager_getDNS(manager), manager_getTopology(manager),
-               manager_getRawCPUFrequency(manager), manager_getHostsRootPath(manager));
+               manager_getRawCPUFrequency(manager), manager_getHostsRootPath(manager),
+               &manager->pcap);
     scheduler_addHost(manager->scheduler, host);
+    startafter_addHost(manager->startAfter, params->hostname);
+}
+
+gboolean manager_hasHost(Manager* manager, const gchar* hostname) {
+    MAGIC_ASSERT(manager);
+    return scheduler_getHost(manager->scheduler, g_quark_from_string(hostname)) != NULL;
 }
 
 static gchar** _manager_generateEnvv(Manager* manager, InterposeMethod interposeMethod,
-                                     const gchar* environment) {
+                                     gchar** environment) {
     MAGIC_ASSERT(manager);
 
     /* start with an empty environment */
@@ -429,12 +531,9 @@ static gchar** _manager_generateEnvv(Manager* manager, InterposeMethod interpose
     /* now we also have to scan the other env variables that were given in the shadow conf file */
 
     if (environment) {
-        /* entries are split by ';' */
-        gchar** envTokens = g_strsplit(environment, ";", 0);
-
-        for (gint i = 0; envTokens[i] != NULL; i++) {
+        for (gint i = 0; environment[i] != NULL; i++) {
             /* each env entry is key=value, get 2 tokens max */
-            gchar** items = g_strsplit(envTokens[i], "=", 2);
+            gchar** items = g_strsplit(environment[i], "=", 2);
 
             gchar* key = items[0];
             gchar* value = items[1];
@@ -484,8 +583,6 @@ static gchar** _manager_generateEnvv(Manager* manager, InterposeMethod interpose
 
             g_strfreev(items);
         }
-
-        g_strfreev(envTokens);
     }
 
     /* must be NULL terminated for g_strjoinv */
@@ -505,10 +602,19 @@ static gchar** _manager_generateEnvv(Manager* manager, InterposeMethod interpose
 }
 
 void manager_addNewVirtualProcess(Manager* manager, const gchar* hostName, gchar* pluginPath,
-                                  SimulationTime startTime, SimulationTime stopTime, gchar** argv,
-                                  char* environment) {
+                                  SimulationTime startTime, SimulationTime stopTime,
+                                  int shutdownSignal, SimulationTime shutdownGracePeriod,
+                                  RestartPolicy restartPolicy, SimulationTime restartDelay,
+                                  int maxRestarts, const gchar* name,
+                                  StartConditions* startAfter, bool terminatesSimulation,
+                                  gchar** argv, gchar** environment, const gchar* stdinPath,
+                                  const gchar* stdinContent, bool straceLogging,
+                                  ProcessFinalState* expectedFinalState) {
     MAGIC_ASSERT(manager);
 
+    startafter_addProcess(manager->startAfter, name, startAfter);
+    stopcondition_addProcess(manager->stopCondition, name);
+
     /* quarks are unique per process, so do the conversion here */
     GQuark hostID = g_quark_from_string(hostName);
 
@@ -525,13 +631,83 @@ void manager_addNewVirtualProcess(Manager* manager, const gchar* hostName, gchar
         utility_panic("Could not get basename of plugin path");
     }
 
-    host_addApplication(
-        host, startTime, stopTime, interposeMethod, pluginName, pluginPath, envv, argv);
+    host_addApplication(host, startTime, stopTime, shutdownSignal, shutdownGracePeriod,
+                        restartPolicy, restartDelay, maxRestarts, name, startAfter,
+                        terminatesSimulation, interposeMethod, pluginName, pluginPath, envv, argv,
+                        stdinPath, stdinContent, straceLogging, expectedFinalState);
     g_free(pluginName);
 
     host_stopExecutionTimer(host);
 }
 
+SimStats* manager_getSimStats(Manager* manager) {
+    MAGIC_ASSERT(manager);
+    return manager->stats;
+}
+
+StartAfter* manager_getStartAfter(Manager* manager) {
+    MAGIC_ASSERT(manager);
+    return manager->startAfter;
+}
+
+void manager_addWaitingProcess(Manager* manager, Process* proc) {
+    MAGIC_ASSERT(manager);
+    process_ref(proc);
+    _manager_lock(manager);
+    g_queue_push_tail(manager->waitingProcesses, proc);
+    _manager_unlock(manager);
+}
+
+/* Schedules the start of the waiting processes whose conditions were met at the
+ * end of the round, at the time the round ended. Returns true if any were. */
+static gboolean _manager_startWaitingProcesses(Manager* manager, SimulationTime roundEndTime) {
+    MAGIC_ASSERT(manager);
+    gboolean started = FALSE;
+
+    GList* link = manager->waitingProcesses->head;
+    while (link) {
+        GList* next = link->next;
+        Process* proc = link->data;
+
+        if (process_canStart(proc, manager->startAfter)) {
+            Host* host = process_getHost(proc);
+            Task* startProcessTask = process_newStartTask(proc);
+            Event* event = event_new_(startProcessTask, roundEndTime, host, host);
+            task_unref(startProcessTask);
+
+            if (scheduler_pushBetweenRounds(manager->scheduler, event, host)) {
+                started = TRUE;
+            }
+
+            g_queue_delete_link(manager->waitingProcesses, link);
+            process_unref(proc);
+        }
+
+        link = next;
+    }
+
+    return started;
+}
+
+StopCondition* manager_getStopCondition(Manager* manager) {
+    MAGIC_ASSERT(manager);
+    return manager->stopCondition;
+}
+
+/* Returns true if the host has a TCP socket listening on the port on any of its
+ * interfaces. Only called between rounds, when the hosts aren't running. */
+static bool _manager_isListening(const char* hostName, uint16_t port, void* _manager) {
+    Manager* manager = _manager;
+    MAGIC_ASSERT(manager);
+
+    Host* host = scheduler_getHost(manager->scheduler, g_quark_from_string(hostName));
+    if (!host) {
+        return false;
+    }
+
+    return host_isListening(host, htons(port));
+}
+
 DNS* manager_getDNS(Manager* manager) {
     MAGIC_ASSERT(manager);
     return controller_getDNS(manager->controller);
@@ -605,6 +781,20 @@ static void _manager_heartbeat(Manager* manager, SimulationTime simClockNow) {
                  "ru_nvcsw=%li, ru_nivcsw=%li",
                  simClockNow, maxMemory, userTimeMinutes, systemTimeMinutes, resources.ru_nvcsw,
                  resources.ru_nivcsw);
+
+            if (manager->heartbeatWriter) {
+                StatsWriter* writer = manager->heartbeatWriter;
+                statswriter_beginRow(writer);
+                statswriter_addUInt(writer, "time_ns", simClockNow);
+                statswriter_addInt(writer, "max_rss_kib", resources.ru_maxrss);
+                statswriter_addDouble(writer, "user_time_seconds",
+                                      resources.ru_utime.tv_sec + resources.ru_utime.tv_usec / 1e6);
+                statswriter_addDouble(writer, "system_time_seconds",
+                                      resources.ru_stime.tv_sec + resources.ru_stime.tv_usec / 1e6);
+                statswriter_addInt(writer, "voluntary_context_switches", resources.ru_nvcsw);
+                statswriter_addInt(writer, "involuntary_context_switches", resources.ru_nivcsw);
+                statswriter_endRow(writer);
+            }
         } else {
             warning("unable to print process resources usage: error %i in getrusage: %s", errno,
                     g_strerror(errno));
@@ -639,12 +829,32 @@ void manager_run(Manager* manager) {
               "] next event at %" G_GUINT64_FORMAT,
               windowStart, windowEnd, minNextEventTime);
 
+        manager->numRounds++;
+        manager->simClockEnd = MIN(windowEnd, manager->endTime);
+
+        /* processes waiting for their start conditions see the state at the end
+         * of this round, regardless of the order the hosts ran in */
+        startafter_endRound(manager->startAfter, _manager_isListening, manager);
+        if (_manager_startWaitingProcesses(manager, windowEnd)) {
+            minNextEventTime = MIN(minNextEventTime, windowEnd);
+        }
+
+        /* no packet captured in a later round is earlier than the packets
+         * captured in this round, so the network-wide capture stays ordered */
+        pcapwriter_flush(manager->pcap.pcap);
+
         /* notify controller that we finished this round, and the time of our
          * next event in order to fast-forward our execute window if possible */
         keepRunning = controller_managerFinishedCurrentRound(
             manager->controller, minNextEventTime, &windowStart, &windowEnd);
     }
 
+    /* processes whose conditions were never met */
+    Process* proc = NULL;
+    while ((proc = g_queue_pop_head(manager->waitingProcesses)) != NULL) {
+        process_unref(proc);
+    }
+
     scheduler_finish(manager->scheduler);
 }
 
@@ -23,6 +23,7 @@
 #define SHD_MANAGER_H_
 
 #include <glib.h>
+#include <stdbool.h>
 #include <netinet/in.h>
 
 #include "main/core/controller.h"
@@ -42,6 +43,12 @@ ChildPidWatcher* manager_childpidwatcher(Manager* manager);
 gboolean manager_isForced(Manager* manager);
 guint manager_getRawCPUFrequency(Manager* manager);
 DNS* manager_getDNS(Manager* manager);
+SimStats* manager_getSimStats(Manager* manager);
+StartAfter* manager_getStartAfter(Manager* manager);
+/* Starts the process at the end of the first round in which its start_after
+ * conditions are met. Safe to call from multiple threads. */
+void manager_addWaitingProcess(Manager* manager, Process* proc);
+StopCondition* manager_getStopCondition(Manager* manager);
 Topology* manager_getTopology(Manager* manager);
 guint32 manager_getNodeBandwidthUp(Manager* manager, GQuark nodeID, in_addr_t ip);
 guint32 manager_getNodeBandwidthDown(Manager* manager, GQuark nodeID, in_addr_t ip);
@@ -61,9 +68,16 @@ gboolean manager_schedulerIsRunning(Manager* manager);
 void manager_addNewProgram(Manager* manager, const gchar* name, const gchar* path,
                            const gchar* startSymbol);
 void manager_addNewVirtualHost(Manager* manager, HostParameters* params);
+gboolean manager_hasHost(Manager* manager, const gchar* hostname);
 void manager_addNewVirtualProcess(Manager* manager, const gchar* hostName, gchar* pluginName,
-                                  SimulationTime startTime, SimulationTime stopTime, gchar** argv,
-                                  char* environment);
+                                  SimulationTime startTime, SimulationTime stopTime,
+                                  int shutdownSignal, SimulationTime shutdownGracePeriod,
+                                  RestartPolicy restartPolicy, SimulationTime restartDelay,
+                                  int maxRestarts, const gchar* name,
+                                  StartConditions* startAfter, bool terminatesSimulation,
+                                  gchar** argv, gchar** environment, const gchar* stdinPath,
+                                  const gchar* stdinContent, bool straceLogging,
+                                  ProcessFinalState* expectedFinalState);
 
 // Increment a global counter for the allocation of the object with the given name.
 // This should be paired with an increment of the dealloc counter with the
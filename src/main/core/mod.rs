@@ -1,4 +1,7 @@
 pub mod logger;
 pub mod logical_processor;
+pub mod sim_stats;
+pub mod start_after;
+pub mod stop_condition;
 pub mod support;
 pub mod worker;
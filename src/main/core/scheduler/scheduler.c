@@ -254,6 +254,25 @@ gboolean scheduler_push(Scheduler* scheduler, Event* event, Host* sender, Host*
     return TRUE;
 }
 
+gboolean scheduler_pushBetweenRounds(Scheduler* scheduler, Event* event, Host* receiver) {
+    MAGIC_ASSERT(scheduler);
+
+    SimulationTime eventTime = event_getTime(event);
+    if(eventTime >= scheduler->endTime) {
+        event_unref(event);
+        return FALSE;
+    }
+
+    utility_assert(receiver);
+    utility_assert(receiver == event_getHost(event));
+
+    /* the host sends the event to itself, so the policy doesn't delay it. there
+     * is no worker on this thread, so the manager tracks the event time. */
+    scheduler->policy->push(scheduler->policy, event, receiver, receiver, scheduler->currentRound.endTime);
+
+    return TRUE;
+}
+
 void scheduler_addHost(Scheduler* scheduler, Host* host) {
     MAGIC_ASSERT(scheduler);
 
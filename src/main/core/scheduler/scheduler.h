@@ -32,6 +32,9 @@ SimulationTime scheduler_awaitNextRound(Scheduler*);
 void scheduler_finish(Scheduler*);
 
 gboolean scheduler_push(Scheduler*, Event*, Host* sender, Host* receiver);
+/* Pushes an event for the host from the manager thread while the workers are
+ * waiting for the next round. */
+gboolean scheduler_pushBetweenRounds(Scheduler*, Event*, Host* receiver);
 Event* scheduler_pop(Scheduler*);
 
 void scheduler_addHost(Scheduler*, Host*);
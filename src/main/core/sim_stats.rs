@@ -0,0 +1,542 @@
+/*!
+Statistics about a simulation, which are collected from the hosts and processes as they shut
+down, and written to a "sim-stats.json" file in the data directory when the simulation ends.
+The file is intended to be read by other programs (ex: tests that check the results of a
+simulation), so fields should only be added, not renamed or removed.
+*/
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::core::support::configuration::{ProcessFinalState, Signal};
+use crate::cshadow as c;
+use crate::utility::counter::Counter;
+
+/// Returns the state of a process at the end of the simulation, where `wait_status` is the
+/// status returned by `waitpid()`, or -1 if it's unknown.
+fn final_state(did_start: bool, exited: bool, wait_status: i32) -> Option<ProcessFinalState> {
+    if !did_start {
+        return None;
+    }
+    if !exited {
+        return Some(ProcessFinalState::Running);
+    }
+    if wait_status == -1 {
+        return None;
+    }
+
+    if libc::WIFEXITED(wait_status) {
+        Some(ProcessFinalState::Exited(libc::WEXITSTATUS(wait_status)))
+    } else if libc::WIFSIGNALED(wait_status) {
+        Signal::try_from(libc::WTERMSIG(wait_status))
+            .ok()
+            .map(ProcessFinalState::Signaled)
+    } else {
+        None
+    }
+}
+
+/// Statistics about a simulation, collected from multiple worker threads.
+#[derive(Debug)]
+pub struct SimStats {
+    hosts: Mutex<BTreeMap<String, HostStats>>,
+}
+
+#[derive(Debug, Serialize)]
+struct HostStats {
+    events_processed: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    packets_dropped: Counter,
+    syscalls: u64,
+    /// Only collected if syscall counters are enabled.
+    syscall_counts: Option<Counter>,
+    processes: Vec<ProcessStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProcessStats {
+    name: String,
+    pid: u32,
+    start_time_ns: u64,
+    /// `None` if the process was still running at the end of the simulation.
+    exit_time_ns: Option<u64>,
+    /// `None` if the process never started.
+    exit_code: Option<i32>,
+    /// `None` if the process never started or its exit status is unknown.
+    final_state: Option<ProcessFinalState>,
+    /// `None` if the process's configuration doesn't have an expected final state.
+    expected_final_state: Option<ProcessFinalState>,
+    /// Set when the stats are written, since running processes run until the end of the
+    /// simulation.
+    runtime_ns: u64,
+    syscalls: u64,
+    /// Only collected if syscall counters are enabled.
+    syscall_counts: Option<Counter>,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    wall_time_ns: u64,
+    sim_time_ns: u64,
+    rounds: u64,
+    hosts: &'a BTreeMap<String, HostStats>,
+}
+
+impl HostStats {
+    fn new() -> Self {
+        Self {
+            events_processed: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            packets_dropped: Counter::new(),
+            syscalls: 0,
+            syscall_counts: None,
+            processes: Vec::new(),
+        }
+    }
+}
+
+impl SimStats {
+    pub fn new() -> Self {
+        Self {
+            hosts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn with_host<F: FnOnce(&mut HostStats)>(&self, hostname: &str, f: F) {
+        let mut hosts = self.hosts.lock().unwrap();
+        let host = hosts
+            .entry(hostname.to_string())
+            .or_insert_with(HostStats::new);
+        f(host)
+    }
+
+    /// Returns the processes that didn't end in their expected final state, as rows of
+    /// (process name, expected state, actual state).
+    fn unexpected_final_states(&self) -> Vec<[String; 3]> {
+        let mut hosts = self.hosts.lock().unwrap();
+
+        let mut rows = Vec::new();
+        for host in hosts.values_mut() {
+            host.processes.sort_by_key(|p| p.pid);
+            for process in &host.processes {
+                let expected = match process.expected_final_state {
+                    Some(x) => x,
+                    None => continue,
+                };
+                if process.final_state == Some(expected) {
+                    continue;
+                }
+                let actual = match (process.final_state, process.exit_code) {
+                    (Some(x), _) => x.to_string(),
+                    (None, None) => "not started".to_string(),
+                    (None, Some(_)) => "unknown".to_string(),
+                };
+                rows.push([process.name.clone(), expected.to_string(), actual]);
+            }
+        }
+
+        rows
+    }
+
+    fn add_host(
+        &self,
+        hostname: &str,
+        events_processed: u64,
+        bytes_sent: u64,
+        bytes_received: u64,
+        packets_dropped: Option<&Counter>,
+    ) {
+        self.with_host(hostname, |host| {
+            host.events_processed += events_processed;
+            host.bytes_sent += bytes_sent;
+            host.bytes_received += bytes_received;
+            if let Some(dropped) = packets_dropped {
+                host.packets_dropped.add_counter(dropped);
+            }
+        })
+    }
+
+    fn add_process(&self, hostname: &str, process: ProcessStats) {
+        self.with_host(hostname, |host| {
+            host.syscalls += process.syscalls;
+            if let Some(counts) = &process.syscall_counts {
+                host.syscall_counts
+                    .get_or_insert_with(Counter::new)
+                    .add_counter(counts);
+            }
+            host.processes.push(process);
+        })
+    }
+
+    /// Writes the statistics as JSON. `sim_time_ns` is the simulation time at which the
+    /// simulation ended.
+    fn write(
+        &self,
+        writer: impl Write,
+        wall_time_ns: u64,
+        sim_time_ns: u64,
+        rounds: u64,
+    ) -> serde_json::Result<()> {
+        let mut hosts = self.hosts.lock().unwrap();
+
+        for host in hosts.values_mut() {
+            host.processes.sort_by_key(|p| p.pid);
+            for process in &mut host.processes {
+                process.runtime_ns = match process.exit_code {
+                    Some(_) => process
+                        .exit_time_ns
+                        .unwrap_or(sim_time_ns)
+                        .saturating_sub(process.start_time_ns),
+                    None => 0,
+                };
+            }
+        }
+
+        let summary = Summary {
+            wall_time_ns,
+            sim_time_ns,
+            rounds,
+            hosts: &hosts,
+        };
+
+        serde_json::to_writer_pretty(writer, &summary)
+    }
+}
+
+impl Default for SimStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod export {
+    use super::*;
+
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    use crate::core::support::simulation_time::SIMTIME_INVALID;
+
+    #[no_mangle]
+    pub extern "C" fn simstats_new() -> *mut SimStats {
+        Box::into_raw(Box::new(SimStats::new()))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn simstats_free(stats: *mut SimStats) {
+        if stats.is_null() {
+            return;
+        }
+        drop(unsafe { Box::from_raw(stats) });
+    }
+
+    /// Adds the statistics of a host. `packets_dropped` counts the dropped packets by
+    /// reason, and may be NULL. Safe to call from multiple threads.
+    #[no_mangle]
+    pub extern "C" fn simstats_addHost(
+        stats: *const SimStats,
+        hostname: *const c_char,
+        events_processed: u64,
+        bytes_sent: u64,
+        bytes_received: u64,
+        packets_dropped: *const Counter,
+    ) {
+        assert!(!stats.is_null());
+        assert!(!hostname.is_null());
+
+        let stats = unsafe { &*stats };
+        let hostname = unsafe { CStr::from_ptr(hostname) }.to_str().unwrap();
+        let packets_dropped = unsafe { packets_dropped.as_ref() };
+
+        stats.add_host(
+            hostname,
+            events_processed,
+            bytes_sent,
+            bytes_received,
+            packets_dropped,
+        );
+    }
+
+    /// Adds the statistics of a process that ran on the host `hostname`. `exit_time` is
+    /// SIMTIME_INVALID if the process was still running at the end of the simulation, and
+    /// `exit_code` is ignored if the process never started. `wait_status` is the status
+    /// returned by `waitpid()`, or -1 if it's unknown. `syscall_counts` and
+    /// `expected_final_state` may be NULL. Safe to call from multiple threads.
+    #[allow(clippy::too_many_arguments)]
+    #[no_mangle]
+    pub extern "C" fn simstats_addProcess(
+        stats: *const SimStats,
+        hostname: *const c_char,
+        name: *const c_char,
+        pid: u32,
+        start_time: c::SimulationTime,
+        exit_time: c::SimulationTime,
+        did_start: bool,
+        exit_code: i32,
+        wait_status: i32,
+        syscalls: u64,
+        syscall_counts: *const Counter,
+        expected_final_state: *const ProcessFinalState,
+    ) {
+        assert!(!stats.is_null());
+        assert!(!hostname.is_null());
+        assert!(!name.is_null());
+
+        let stats = unsafe { &*stats };
+        let hostname = unsafe { CStr::from_ptr(hostname) }.to_str().unwrap();
+        let name = unsafe { CStr::from_ptr(name) }.to_str().unwrap();
+        let syscall_counts = unsafe { syscall_counts.as_ref() };
+        let expected_final_state = unsafe { expected_final_state.as_ref() };
+
+        let process = ProcessStats {
+            name: name.to_string(),
+            pid,
+            start_time_ns: start_time,
+            exit_time_ns: if exit_time == SIMTIME_INVALID {
+                None
+            } else {
+                Some(exit_time)
+            },
+            exit_code: if did_start { Some(exit_code) } else { None },
+            final_state: final_state(did_start, exit_time != SIMTIME_INVALID, wait_status),
+            expected_final_state: expected_final_state.copied(),
+            runtime_ns: 0,
+            syscalls,
+            syscall_counts: syscall_counts.cloned(),
+        };
+
+        stats.add_process(hostname, process);
+    }
+
+    /// Logs a table of the processes that didn't end in their expected final state, and
+    /// returns the number of these processes.
+    #[no_mangle]
+    pub extern "C" fn simstats_checkFinalStates(stats: *const SimStats) -> u32 {
+        assert!(!stats.is_null());
+        let stats = unsafe { &*stats };
+
+        let rows = stats.unexpected_final_states();
+        if rows.is_empty() {
+            return 0;
+        }
+
+        let header = [
+            "process".to_string(),
+            "expected".to_string(),
+            "actual".to_string(),
+        ];
+        let mut widths = [0; 3];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = std::cmp::max(*width, value.len());
+            }
+        }
+
+        log::warn!(
+            "{} process(es) did not end in their expected final state:",
+            rows.len()
+        );
+        for row in std::iter::once(&header).chain(&rows) {
+            log::warn!(
+                "  {:w0$}  {:w1$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                w0 = widths[0],
+                w1 = widths[1]
+            );
+        }
+
+        rows.len() as u32
+    }
+
+    /// Writes the statistics as JSON to the file at `path`. Returns false if the file could
+    /// not be written.
+    #[no_mangle]
+    pub extern "C" fn simstats_write(
+        stats: *const SimStats,
+        path: *const c_char,
+        wall_time_ns: u64,
+        sim_time: c::SimulationTime,
+        rounds: u64,
+    ) -> bool {
+        assert!(!stats.is_null());
+        assert!(!path.is_null());
+
+        let stats = unsafe { &*stats };
+        let path = Path::new(unsafe { CStr::from_ptr(path) }.to_str().unwrap());
+
+        let result = std::fs::File::create(path)
+            .map_err(serde_json::Error::io)
+            .and_then(|file| {
+                let mut writer = std::io::BufWriter::new(file);
+                stats.write(&mut writer, wall_time_ns, sim_time, rounds)?;
+                writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+                writer.flush().map_err(serde_json::Error::io)
+            });
+
+        if let Err(e) = result {
+            log::warn!("Could not write '{}': {}", path.display(), e);
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(
+        name: &str,
+        pid: u32,
+        exit_time_ns: Option<u64>,
+        exit_code: Option<i32>,
+    ) -> ProcessStats {
+        ProcessStats {
+            name: name.to_string(),
+            pid,
+            start_time_ns: 1_000,
+            exit_time_ns,
+            exit_code,
+            final_state: None,
+            expected_final_state: None,
+            runtime_ns: 0,
+            syscalls: 3,
+            syscall_counts: None,
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        let stats = SimStats::new();
+
+        let mut dropped = Counter::new();
+        dropped.add_value("path_loss", 2);
+        stats.add_host("server", 10, 100, 200, Some(&dropped));
+        stats.add_host("client", 5, 200, 100, None);
+
+        let mut counts = Counter::new();
+        counts.add_value("read", 3);
+        let mut p = process("server.app.1001", 1001, Some(4_000), Some(0));
+        p.syscall_counts = Some(counts.clone());
+        stats.add_process("server", p);
+        stats.add_process("server", process("server.app.1000", 1000, None, Some(137)));
+        stats.add_process("server", process("server.app.1002", 1002, None, None));
+
+        let mut output = Vec::new();
+        stats.write(&mut output, 42, 10_000, 7).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(value["wall_time_ns"], 42);
+        assert_eq!(value["sim_time_ns"], 10_000);
+        assert_eq!(value["rounds"], 7);
+
+        let client = &value["hosts"]["client"];
+        assert_eq!(client["events_processed"], 5);
+        assert_eq!(client["packets_dropped"], serde_json::json!({}));
+        assert_eq!(client["syscall_counts"], serde_json::Value::Null);
+        assert_eq!(client["processes"], serde_json::json!([]));
+
+        let server = &value["hosts"]["server"];
+        assert_eq!(server["bytes_sent"], 100);
+        assert_eq!(server["bytes_received"], 200);
+        assert_eq!(
+            server["packets_dropped"],
+            serde_json::json!({"path_loss": 2})
+        );
+        assert_eq!(server["syscalls"], 9);
+        assert_eq!(server["syscall_counts"], serde_json::json!({"read": 3}));
+
+        // sorted by pid
+        let processes = server["processes"].as_array().unwrap();
+        let pids: Vec<_> = processes
+            .iter()
+            .map(|p| p["pid"].as_u64().unwrap())
+            .collect();
+        assert_eq!(pids, vec![1000, 1001, 1002]);
+
+        // still running at the end of the simulation
+        assert_eq!(processes[0]["exit_time_ns"], serde_json::Value::Null);
+        assert_eq!(processes[0]["exit_code"], 137);
+        assert_eq!(processes[0]["runtime_ns"], 9_000);
+        // exited
+        assert_eq!(processes[1]["exit_time_ns"], 4_000);
+        assert_eq!(processes[1]["runtime_ns"], 3_000);
+        // never started
+        assert_eq!(processes[2]["exit_code"], serde_json::Value::Null);
+        assert_eq!(processes[2]["runtime_ns"], 0);
+    }
+
+    #[test]
+    fn test_final_state() {
+        use nix::sys::signal::Signal::SIGTERM;
+
+        // wait statuses as returned by waitpid()
+        let exited_1 = 1 << 8;
+        let signaled_term = SIGTERM as i32;
+
+        assert_eq!(final_state(false, false, -1), None);
+        assert_eq!(
+            final_state(true, false, -1),
+            Some(ProcessFinalState::Running)
+        );
+        assert_eq!(final_state(true, true, -1), None);
+        assert_eq!(
+            final_state(true, true, exited_1),
+            Some(ProcessFinalState::Exited(1))
+        );
+        assert_eq!(
+            final_state(true, true, signaled_term),
+            Some(ProcessFinalState::Signaled(
+                Signal::try_from(SIGTERM as i32).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unexpected_final_states() {
+        let stats = SimStats::new();
+
+        let mut p = process("host.ok.1000", 1000, Some(4_000), Some(0));
+        p.final_state = Some(ProcessFinalState::Exited(0));
+        p.expected_final_state = Some(ProcessFinalState::Exited(0));
+        stats.add_process("host", p);
+
+        let mut p = process("host.running.1001", 1001, None, Some(137));
+        p.final_state = Some(ProcessFinalState::Running);
+        p.expected_final_state = Some(ProcessFinalState::Exited(0));
+        stats.add_process("host", p);
+
+        let mut p = process("host.unchecked.1002", 1002, Some(4_000), Some(1));
+        p.final_state = Some(ProcessFinalState::Exited(1));
+        stats.add_process("host", p);
+
+        let mut p = process("host.never.1003", 1003, None, None);
+        p.expected_final_state = Some(ProcessFinalState::Running);
+        stats.add_process("host", p);
+
+        assert_eq!(
+            stats.unexpected_final_states(),
+            vec![
+                [
+                    "host.running.1001".to_string(),
+                    "exited: 0".to_string(),
+                    "running".to_string()
+                ],
+                [
+                    "host.never.1003".to_string(),
+                    "running".to_string(),
+                    "not started".to_string()
+                ],
+            ]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+/*!
+Conditions that processes wait for before they start, in addition to their `start_time`.
+
+Processes on different hosts run in parallel during a scheduling round, so the state that the
+conditions depend on (which processes exited and which ports are listened on) is only updated by the
+manager between rounds. A process waiting for its conditions sees the same state regardless of
+how the hosts of the round were scheduled, which keeps the simulation deterministic.
+*/
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+
+use crate::core::support::configuration::StartCondition;
+
+/// The conditions of a single process, passed to C as an opaque pointer.
+#[derive(Debug, Clone)]
+pub struct StartConditions(Vec<StartCondition>);
+
+impl StartConditions {
+    pub fn new(conditions: Vec<StartCondition>) -> Self {
+        Self(conditions)
+    }
+}
+
+/// The state of the simulation at the end of the last round.
+#[derive(Debug, Default)]
+struct RoundState {
+    /// The number of successful exits of the processes with each name.
+    exits: HashMap<String, u32>,
+    /// The (host, port) pairs that had a listening TCP socket.
+    listening: HashSet<(String, u16)>,
+}
+
+/// Tracks the state that start conditions depend on, collected from multiple worker threads.
+#[derive(Debug, Default)]
+pub struct StartAfter {
+    /// The names of the hosts in the simulation.
+    hosts: Mutex<HashSet<String>>,
+    /// The number of processes with each name.
+    processes: Mutex<HashMap<String, u32>>,
+    /// The conditions of all processes, used to validate them and to know which ports to check.
+    conditions: Mutex<Vec<StartCondition>>,
+    /// Successful exits during the current round, which are added to the state when it ends.
+    pending_exits: Mutex<HashMap<String, u32>>,
+    state: RwLock<RoundState>,
+}
+
+impl StartAfter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_host(&self, name: &str) {
+        self.hosts.lock().unwrap().insert(name.to_string());
+    }
+
+    pub fn add_process(&self, name: Option<&str>, conditions: Option<&StartConditions>) {
+        if let Some(name) = name {
+            *self
+                .processes
+                .lock()
+                .unwrap()
+                .entry(name.to_string())
+                .or_insert(0) += 1;
+        }
+        if let Some(conditions) = conditions {
+            self.conditions
+                .lock()
+                .unwrap()
+                .extend(conditions.0.iter().cloned());
+        }
+    }
+
+    /// Returns an error message for each condition that refers to a process name or host that
+    /// doesn't exist, since the processes waiting for it would never start.
+    pub fn validate(&self) -> Vec<String> {
+        let hosts = self.hosts.lock().unwrap();
+        let processes = self.processes.lock().unwrap();
+        let mut errors = Vec::new();
+
+        for condition in self.conditions.lock().unwrap().iter() {
+            match condition {
+                StartCondition::ProcessExited(name) if !processes.contains_key(name) => {
+                    errors.push(format!(
+                        "start_after refers to the process name '{}', but no process has this name",
+                        name
+                    ));
+                }
+                StartCondition::Listening { host, .. } if !hosts.contains(host) => {
+                    errors.push(format!(
+                        "start_after refers to the host '{}', which does not exist",
+                        host
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        errors
+    }
+
+    /// Records that a process with the given name exited successfully. Safe to call from
+    /// multiple threads.
+    pub fn add_exit(&self, name: &str) {
+        *self
+            .pending_exits
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Updates the state at the end of a round. `is_listening` is called for each (host, port)
+    /// pair that a condition depends on.
+    pub fn end_round(&self, mut is_listening: impl FnMut(&str, u16) -> bool) {
+        let ports: BTreeSet<(String, u16)> = self
+            .conditions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|c| match c {
+                StartCondition::Listening { host, port } => Some((host.clone(), *port)),
+                _ => None,
+            })
+            .collect();
+
+        let mut state = self.state.write().unwrap();
+
+        for (name, count) in self.pending_exits.lock().unwrap().drain() {
+            *state.exits.entry(name).or_insert(0) += count;
+        }
+
+        state.listening = ports
+            .into_iter()
+            .filter(|(host, port)| is_listening(host, *port))
+            .collect();
+    }
+
+    /// Returns true if all of the conditions were met at the end of the last round. A
+    /// `process_exited` condition is met once every process with that name exited successfully.
+    pub fn are_met(&self, conditions: &StartConditions) -> bool {
+        let processes = self.processes.lock().unwrap();
+        let state = self.state.read().unwrap();
+
+        conditions.0.iter().all(|c| match c {
+            StartCondition::ProcessExited(name) => {
+                let expected = processes.get(name).copied().unwrap_or(0);
+                state.exits.get(name).copied().unwrap_or(0) >= expected
+            }
+            StartCondition::Listening { host, port } => {
+                state.listening.contains(&(host.clone(), *port))
+            }
+        })
+    }
+}
+
+mod export {
+    use super::*;
+
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_void};
+
+    unsafe fn str_from_ptr<'a>(s: *const c_char) -> Option<&'a str> {
+        if s.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(s).to_str().unwrap())
+    }
+
+    #[no_mangle]
+    pub extern "C" fn startafter_new() -> *mut StartAfter {
+        Box::into_raw(Box::new(StartAfter::new()))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn startafter_free(start_after: *mut StartAfter) {
+        if start_after.is_null() {
+            return;
+        }
+        drop(unsafe { Box::from_raw(start_after) });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn startafter_addHost(start_after: *const StartAfter, name: *const c_char) {
+        assert!(!start_after.is_null());
+        assert!(!name.is_null());
+
+        let start_after = unsafe { &*start_after };
+        start_after.add_host(unsafe { str_from_ptr(name) }.unwrap());
+    }
+
+    /// Adds a process while the simulation is being set up. `name` and `conditions` may be
+    /// NULL.
+    #[no_mangle]
+    pub extern "C" fn startafter_addProcess(
+        start_after: *const StartAfter,
+        name: *const c_char,
+        conditions: *const StartConditions,
+    ) {
+        assert!(!start_after.is_null());
+
+        let start_after = unsafe { &*start_after };
+        let name = unsafe { str_from_ptr(name) };
+        let conditions = unsafe { conditions.as_ref() };
+
+        start_after.add_process(name, conditions);
+    }
+
+    /// Logs an error for each condition that can never be met, and returns false if there were
+    /// any.
+    #[no_mangle]
+    pub extern "C" fn startafter_validate(start_after: *const StartAfter) -> bool {
+        assert!(!start_after.is_null());
+        let start_after = unsafe { &*start_after };
+
+        let errors = start_after.validate();
+        for e in &errors {
+            log::error!("{}", e);
+        }
+        errors.is_empty()
+    }
+
+    /// Records that a process with the given name exited successfully. Safe to call from
+    /// multiple threads.
+    #[no_mangle]
+    pub extern "C" fn startafter_addExit(start_after: *const StartAfter, name: *const c_char) {
+        assert!(!start_after.is_null());
+        assert!(!name.is_null());
+
+        let start_after = unsafe { &*start_after };
+        start_after.add_exit(unsafe { str_from_ptr(name) }.unwrap());
+    }
+
+    /// Updates the state at the end of a round. Must not be called while hosts are running.
+    /// `is_listening` returns true if the named host has a TCP socket listening on the port.
+    #[no_mangle]
+    pub extern "C" fn startafter_endRound(
+        start_after: *const StartAfter,
+        is_listening: unsafe extern "C" fn(*const c_char, u16, *mut c_void) -> bool,
+        data: *mut c_void,
+    ) {
+        assert!(!start_after.is_null());
+        let start_after = unsafe { &*start_after };
+
+        start_after.end_round(|host, port| {
+            let host = std::ffi::CString::new(host).unwrap();
+            unsafe { is_listening(host.as_ptr(), port, data) }
+        });
+    }
+
+    /// Returns true if all of the conditions were met at the end of the last round. Safe to
+    /// call from multiple threads.
+    #[no_mangle]
+    pub extern "C" fn startafter_areMet(
+        start_after: *const StartAfter,
+        conditions: *const StartConditions,
+    ) -> bool {
+        assert!(!start_after.is_null());
+        assert!(!conditions.is_null());
+
+        let start_after = unsafe { &*start_after };
+        let conditions = unsafe { &*conditions };
+
+        start_after.are_met(conditions)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn startconditions_free(conditions: *mut StartConditions) {
+        if !conditions.is_null() {
+            drop(unsafe { Box::from_raw(conditions) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exited(name: &str) -> StartCondition {
+        StartCondition::ProcessExited(name.to_string())
+    }
+
+    fn listening(host: &str, port: u16) -> StartCondition {
+        StartCondition::Listening {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn test_process_exited() {
+        let start_after = StartAfter::new();
+        let conditions = StartConditions::new(vec![exited("setup")]);
+
+        start_after.add_process(Some("setup"), None);
+        start_after.add_process(Some("setup"), None);
+        start_after.add_process(None, Some(&conditions));
+        assert!(start_after.validate().is_empty());
+
+        start_after.end_round(|_, _| false);
+        assert!(!start_after.are_met(&conditions));
+
+        // not visible until the end of the round
+        start_after.add_exit("setup");
+        assert!(!start_after.are_met(&conditions));
+        start_after.end_round(|_, _| false);
+        assert!(!start_after.are_met(&conditions));
+
+        // all processes with the name must exit
+        start_after.add_exit("setup");
+        start_after.end_round(|_, _| false);
+        assert!(start_after.are_met(&conditions));
+    }
+
+    #[test]
+    fn test_listening() {
+        let start_after = StartAfter::new();
+        let conditions = StartConditions::new(vec![listening("server", 80), exited("setup")]);
+
+        start_after.add_host("server");
+        start_after.add_process(Some("setup"), None);
+        start_after.add_process(None, Some(&conditions));
+        start_after.add_exit("setup");
+
+        let mut checked = Vec::new();
+        start_after.end_round(|host, port| {
+            checked.push((host.to_string(), port));
+            false
+        });
+        assert_eq!(checked, vec![("server".to_string(), 80)]);
+        assert!(!start_after.are_met(&conditions));
+
+        start_after.end_round(|host, port| host == "server" && port == 80);
+        assert!(start_after.are_met(&conditions));
+
+        // the socket was closed
+        start_after.end_round(|_, _| false);
+        assert!(!start_after.are_met(&conditions));
+    }
+
+    #[test]
+    fn test_validate() {
+        let start_after = StartAfter::new();
+        start_after.add_host("server");
+        start_after.add_process(Some("setup"), None);
+        start_after.add_process(
+            None,
+            Some(&StartConditions::new(vec![
+                exited("setup"),
+                exited("missing"),
+                listening("server", 80),
+                listening("nohost", 80),
+            ])),
+        );
+
+        let errors = start_after.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("'missing'"));
+        assert!(errors[1].contains("'nohost'"));
+    }
+}
@@ -0,0 +1,249 @@
+/*!
+Conditions that stop the simulation before its `stop_time`, once the processes that do the
+interesting work have exited.
+
+Processes exit while their hosts run in parallel during a scheduling round, so the conditions are
+only checked between rounds, and the simulation stops at the end of the round in which they were
+met.
+*/
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::core::support::configuration::StopWhen;
+
+/// Tracks which processes exited, collected from multiple worker threads.
+#[derive(Debug)]
+pub struct StopCondition {
+    stop_when: Option<StopWhen>,
+    /// The number of processes that haven't exited, by name. Processes without a name are
+    /// counted under `None`.
+    running: Mutex<HashMap<Option<String>, u32>>,
+    /// Set when a process with `terminates_simulation` exits.
+    terminated: AtomicBool,
+}
+
+impl StopCondition {
+    pub fn new(stop_when: Option<StopWhen>) -> Self {
+        Self {
+            stop_when,
+            running: Mutex::new(HashMap::new()),
+            terminated: AtomicBool::new(false),
+        }
+    }
+
+    pub fn add_process(&self, name: Option<&str>) {
+        *self
+            .running
+            .lock()
+            .unwrap()
+            .entry(name.map(str::to_string))
+            .or_insert(0) += 1;
+    }
+
+    /// Returns an error message for each process name in `stop_when` that no process has, since
+    /// the simulation would never stop early.
+    pub fn validate(&self) -> Vec<String> {
+        let running = self.running.lock().unwrap();
+
+        match &self.stop_when {
+            Some(StopWhen::ProcessesExited(names)) => names
+                .iter()
+                .filter(|name| !running.contains_key(&Some(name.to_string())))
+                .map(|name| {
+                    format!(
+                        "stop_when refers to the process name '{}', but no process has this name",
+                        name
+                    )
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Records that a process exited and won't be restarted. Safe to call from multiple threads.
+    pub fn add_exit(&self, name: Option<&str>, terminates_simulation: bool) {
+        if let Some(count) = self
+            .running
+            .lock()
+            .unwrap()
+            .get_mut(&name.map(str::to_string))
+        {
+            *count = count.saturating_sub(1);
+        }
+
+        if terminates_simulation {
+            self.terminated.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns a description of why the simulation should stop, or `None` if it should keep
+    /// running. Must not be called while hosts are running.
+    pub fn check(&self) -> Option<String> {
+        if self.terminated.load(Ordering::SeqCst) {
+            return Some("a process with terminates_simulation exited".to_string());
+        }
+
+        let running = self.running.lock().unwrap();
+        let exited = |name: &str| running.get(&Some(name.to_string())).copied().unwrap_or(0) == 0;
+
+        match &self.stop_when {
+            Some(StopWhen::AllProcessesExited) if running.values().all(|count| *count == 0) => {
+                Some("all processes exited".to_string())
+            }
+            Some(StopWhen::ProcessesExited(names)) if names.iter().all(|name| exited(name)) => {
+                Some(format!("the processes {:?} exited", names))
+            }
+            _ => None,
+        }
+    }
+}
+
+mod export {
+    use super::*;
+
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_char;
+
+    unsafe fn str_from_ptr<'a>(s: *const c_char) -> Option<&'a str> {
+        if s.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(s).to_str().unwrap())
+    }
+
+    /// `stop_when` may be NULL.
+    #[no_mangle]
+    pub extern "C" fn stopcondition_new(stop_when: *const StopWhen) -> *mut StopCondition {
+        let stop_when = unsafe { stop_when.as_ref() }.cloned();
+        Box::into_raw(Box::new(StopCondition::new(stop_when)))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn stopcondition_free(condition: *mut StopCondition) {
+        if condition.is_null() {
+            return;
+        }
+        drop(unsafe { Box::from_raw(condition) });
+    }
+
+    /// Adds a process while the simulation is being set up. `name` may be NULL.
+    #[no_mangle]
+    pub extern "C" fn stopcondition_addProcess(
+        condition: *const StopCondition,
+        name: *const c_char,
+    ) {
+        assert!(!condition.is_null());
+
+        let condition = unsafe { &*condition };
+        condition.add_process(unsafe { str_from_ptr(name) });
+    }
+
+    /// Logs an error for each process name that no process has, and returns false if there
+    /// were any.
+    #[no_mangle]
+    pub extern "C" fn stopcondition_validate(condition: *const StopCondition) -> bool {
+        assert!(!condition.is_null());
+        let condition = unsafe { &*condition };
+
+        let errors = condition.validate();
+        for e in &errors {
+            log::error!("{}", e);
+        }
+        errors.is_empty()
+    }
+
+    /// Records that a process exited and won't be restarted. `name` may be NULL. Safe to call
+    /// from multiple threads.
+    #[no_mangle]
+    pub extern "C" fn stopcondition_addExit(
+        condition: *const StopCondition,
+        name: *const c_char,
+        terminates_simulation: bool,
+    ) {
+        assert!(!condition.is_null());
+
+        let condition = unsafe { &*condition };
+        condition.add_exit(unsafe { str_from_ptr(name) }, terminates_simulation);
+    }
+
+    /// Returns a description of why the simulation should stop, or NULL if it should keep
+    /// running. Must not be called while hosts are running. The returned string must be freed
+    /// with `stopcondition_freeString()`.
+    #[no_mangle]
+    pub extern "C" fn stopcondition_check(condition: *const StopCondition) -> *mut c_char {
+        assert!(!condition.is_null());
+        let condition = unsafe { &*condition };
+
+        match condition.check() {
+            Some(reason) => CString::new(reason).unwrap().into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn stopcondition_freeString(string: *mut c_char) {
+        if !string.is_null() {
+            drop(unsafe { CString::from_raw(string) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_processes_exited() {
+        let condition = StopCondition::new(Some(StopWhen::AllProcessesExited));
+        condition.add_process(Some("client"));
+        condition.add_process(None);
+        assert!(condition.validate().is_empty());
+        assert!(condition.check().is_none());
+
+        condition.add_exit(None, false);
+        assert!(condition.check().is_none());
+
+        condition.add_exit(Some("client"), false);
+        assert!(condition.check().is_some());
+    }
+
+    #[test]
+    fn test_processes_exited() {
+        let names = vec!["client".to_string()];
+        let condition = StopCondition::new(Some(StopWhen::ProcessesExited(names)));
+        condition.add_process(Some("client"));
+        condition.add_process(Some("client"));
+        condition.add_process(Some("server"));
+        assert!(condition.validate().is_empty());
+
+        condition.add_exit(Some("client"), false);
+        assert!(condition.check().is_none());
+        condition.add_exit(Some("client"), false);
+        assert!(condition.check().is_some());
+    }
+
+    #[test]
+    fn test_terminates_simulation() {
+        let condition = StopCondition::new(None);
+        condition.add_process(Some("client"));
+        condition.add_process(None);
+
+        condition.add_exit(Some("client"), false);
+        assert!(condition.check().is_none());
+        condition.add_exit(None, true);
+        assert!(condition.check().is_some());
+    }
+
+    #[test]
+    fn test_validate() {
+        let names = vec!["client".to_string(), "missing".to_string()];
+        let condition = StopCondition::new(Some(StopWhen::ProcessesExited(names)));
+        condition.add_process(Some("client"));
+
+        let errors = condition.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("'missing'"));
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::ffi::{CStr, CString, OsStr, OsString};
+use std::io::Read;
 use std::os::unix::ffi::OsStrExt;
 
 use clap::ArgEnum;
@@ -10,9 +12,11 @@ use schemars::{schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 use std::num::NonZeroU32;
 
-use super::simulation_time::{SIMTIME_ONE_NANOSECOND, SIMTIME_ONE_SECOND};
+use super::simulation_time::{SIMTIME_MAX, SIMTIME_ONE_NANOSECOND, SIMTIME_ONE_SECOND};
 use super::units::{self, Unit};
+use crate::core::logger::log_filter::LogFilter;
 use crate::cshadow as c;
+use crate::utility::pcap_filter::PcapFilter;
 use log_bindings as c_log;
 
 const END_HELP_TEXT: &str = "\
@@ -26,7 +30,7 @@ const END_HELP_TEXT: &str = "\
 #[clap(name = "Shadow", version = std::env!("CARGO_PKG_VERSION"), after_help = END_HELP_TEXT)]
 pub struct CliOptions {
     /// Path to the Shadow configuration file. Use '-' to read from stdin
-    #[clap(required_unless_present_any(&["show-build-info", "shm-cleanup"]))]
+    #[clap(required_unless_present_any(&["show-build-info", "shm-cleanup", "show-config-schema"]))]
     config: Option<String>,
 
     /// Pause to allow gdb to attach
@@ -45,6 +49,19 @@ pub struct CliOptions {
     #[clap(long)]
     show_config: bool,
 
+    /// Exit after printing the JSON schema of the configuration file
+    #[clap(long, exclusive(true))]
+    show_config_schema: bool,
+
+    /// Exit after checking the configuration for errors, without running the simulation
+    #[clap(long)]
+    validate_config: bool,
+
+    /// Override a configuration file option, for example `--set general.seed=2` or
+    /// `--set hosts.server.processes.0.args="-p 80"`. Can be given multiple times
+    #[clap(long, value_name = "key=value", number_of_values = 1)]
+    set: Vec<String>,
+
     #[clap(flatten)]
     general: GeneralOptions,
 
@@ -62,6 +79,10 @@ pub struct CliOptions {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigFileOptions {
+    /// Other configuration files to merge into this one, in order
+    #[serde(default)]
+    include: Vec<String>,
+
     general: GeneralOptions,
 
     network: NetworkOptions,
@@ -83,12 +104,24 @@ pub struct ConfigOptions {
 
     network: NetworkOptions,
 
+    // the global host defaults, which are also used for the network-wide packet capture
+    host_defaults: HostDefaultOptions,
+
     experimental: ExperimentalOptions,
 
     // we use a BTreeMap so that the hosts are sorted by their hostname (useful for determinism)
     hosts: BTreeMap<String, HostOptions>,
 }
 
+impl ConfigFileOptions {
+    /// Parses the configuration file at `path` along with the files that it includes, and
+    /// applies the `--set` overrides. A path of "-" reads from stdin.
+    pub fn load(path: &OsStr, overrides: &[String]) -> Result<Self, String> {
+        let config = load_config_yaml(path, overrides)?;
+        serde_yaml::from_value(config).map_err(|e| format!("Could not parse yaml: {}", e))
+    }
+}
+
 impl ConfigOptions {
     pub fn new(mut config_file: ConfigFileOptions, options: CliOptions) -> Self {
         // override config options with command line options
@@ -99,17 +132,39 @@ impl ConfigOptions {
             .with_defaults(config_file.host_defaults);
         config_file.experimental = options.experimental.with_defaults(config_file.experimental);
 
+        // the experimental socket and interface options are used for hosts that don't set them
+        let experimental = &config_file.experimental;
+        let network_defaults = HostDefaultOptions {
+            socket_send_buffer: experimental.socket_send_buffer,
+            socket_send_autotune: experimental.socket_send_autotune,
+            socket_recv_buffer: experimental.socket_recv_buffer,
+            socket_recv_autotune: experimental.socket_recv_autotune,
+            interface_buffer: experimental.interface_buffer,
+            interface_qdisc: experimental.interface_qdisc,
+            ..HostDefaultOptions::new_empty()
+        };
+
+        let host_defaults = config_file.host_defaults.clone();
+
         // copy the host defaults to all of the hosts
         for (_, host) in &mut config_file.hosts {
             host.options = host
                 .options
                 .clone()
-                .with_defaults(config_file.host_defaults.clone());
+                .with_defaults(config_file.host_defaults.clone())
+                .with_defaults(network_defaults.clone());
+
+            for proc in &mut host.processes {
+                if proc.strace_logging.is_none() {
+                    proc.strace_logging = host.options.strace_logging;
+                }
+            }
         }
 
         Self {
             general: config_file.general,
             network: config_file.network,
+            host_defaults,
             experimental: config_file.experimental,
             hosts: config_file.hosts,
         }
@@ -132,6 +187,14 @@ pub struct GeneralOptions {
     #[clap(about = GENERAL_HELP.get("stop_time").unwrap())]
     stop_time: Option<units::Time<units::TimePrefixUpper>>,
 
+    /// Stop the simulation before `stop_time` once processes have exited:
+    /// "all_processes_exited", or "{processes_exited: [<name>, ...]}" for the processes with the
+    /// given names. Doesn't stop early if not set
+    #[clap(long, value_name = "condition")]
+    #[clap(about = GENERAL_HELP.get("stop_when").unwrap())]
+    #[serde(default)]
+    stop_when: Option<StopWhen>,
+
     /// Initialize randomness using seed N
     #[clap(long, value_name = "N")]
     #[clap(about = GENERAL_HELP.get("seed").unwrap())]
@@ -159,12 +222,34 @@ pub struct GeneralOptions {
     #[serde(default = "default_some_info")]
     log_level: Option<LogLevel>,
 
+    /// Per-module log levels, as a comma-separated list of 'module=level' directives (ex:
+    /// "tcp=trace,epoll=debug"). Modules match Rust module paths and source file names. A
+    /// directive without a module replaces the 'log_level' option
+    #[clap(long, value_name = "filter")]
+    #[clap(about = GENERAL_HELP.get("log_filter").unwrap())]
+    #[serde(default)]
+    log_filter: Option<LogFilter>,
+
+    /// Format of the log messages written on stdout. The 'json' format writes one JSON object
+    /// per line
+    #[clap(long, value_name = "format")]
+    #[clap(about = GENERAL_HELP.get("log_format").unwrap())]
+    #[serde(default = "default_some_log_format_text")]
+    log_format: Option<LogFormat>,
+
     /// Interval at which to print heartbeat messages
     #[clap(long, value_name = "seconds")]
     #[clap(about = GENERAL_HELP.get("heartbeat_interval").unwrap())]
     #[serde(default = "default_some_time_1")]
     heartbeat_interval: Option<units::Time<units::TimePrefixUpper>>,
 
+    /// Format of the "heartbeat" statistics file in the data directory, which records Shadow's
+    /// resource usage at each heartbeat interval. No file is written if set to "none"
+    #[clap(long, value_name = "format")]
+    #[clap(about = GENERAL_HELP.get("heartbeat_format").unwrap())]
+    #[serde(default = "default_some_heartbeat_format_none")]
+    heartbeat_format: Option<HeartbeatFormat>,
+
     /// Path to store simulation output
     #[clap(long, short = 'd', value_name = "path")]
     #[clap(about = GENERAL_HELP.get("data_directory").unwrap())]
@@ -176,6 +261,13 @@ pub struct GeneralOptions {
     #[clap(about = GENERAL_HELP.get("template_directory").unwrap())]
     #[serde(default)]
     template_directory: Option<String>,
+
+    /// Path of a pcapng file (relative to the data directory) in which to capture the packets of
+    /// all hosts. Disabled if unset
+    #[clap(long, value_name = "path")]
+    #[clap(about = GENERAL_HELP.get("pcap_file").unwrap())]
+    #[serde(default)]
+    pcap_file: Option<String>,
 }
 
 impl GeneralOptions {
@@ -397,6 +489,12 @@ pub struct HostDefaultOptions {
     #[clap(about = HOST_HELP.get("log_level").unwrap())]
     log_level: Option<LogLevel>,
 
+    /// Write the host's log messages to a "shadow.log" file in the host's data directory
+    /// instead of stdout
+    #[clap(long, value_name = "bool")]
+    #[clap(about = HOST_HELP.get("log_to_file").unwrap())]
+    log_to_file: Option<bool>,
+
     /// Log level at which to print host statistics
     #[clap(long = "host-heartbeat-log-level", name = "host-heartbeat-log-level")]
     #[clap(value_name = "level")]
@@ -416,11 +514,50 @@ pub struct HostDefaultOptions {
     #[clap(about = HOST_HELP.get("heartbeat_interval").unwrap())]
     heartbeat_interval: Option<units::Time<units::TimePrefixUpper>>,
 
-    /// Where to save the pcap files (relative to the host directory)
+    /// Format of the heartbeat statistics files in the host's data directory, which record the
+    /// information selected by 'heartbeat_log_info' at each heartbeat. No files are written if
+    /// set to "none"
+    #[clap(long = "host-heartbeat-format", name = "host-heartbeat-format")]
+    #[clap(value_name = "format")]
+    #[clap(about = HOST_HELP.get("heartbeat_format").unwrap())]
+    heartbeat_format: Option<HeartbeatFormat>,
+
+    /// Where to save the host's pcapng file (relative to the host directory)
     #[clap(long, value_name = "path")]
     #[clap(about = HOST_HELP.get("pcap_directory").unwrap())]
     pcap_directory: Option<String>,
 
+    /// How many bytes of each packet to capture, starting with the packet headers. Packets
+    /// longer than this are truncated in the capture (ex: "66 B" captures only the headers of
+    /// TCP packets)
+    #[clap(long, value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("pcap_capture_size").unwrap())]
+    pcap_capture_size: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// Only capture packets matching this tcpdump-style filter, built from `tcp`, `udp`,
+    /// `[src|dst] port <port>`, and `[src|dst] host <ip>` with `and`, `or`, `not`, and
+    /// parentheses (ex: "tcp port 80 and not host 11.0.0.1")
+    #[clap(long, value_name = "filter")]
+    #[clap(about = HOST_HELP.get("pcap_filter").unwrap())]
+    pcap_filter: Option<PcapFilter>,
+
+    /// Simulation time at which to start capturing packets
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("pcap_start_time").unwrap())]
+    pcap_start_time: Option<units::Time<units::TimePrefixUpper>>,
+
+    /// Simulation time at which to stop capturing packets. Packets are captured until the end of
+    /// the simulation if not set
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("pcap_stop_time").unwrap())]
+    pcap_stop_time: Option<units::Time<units::TimePrefixUpper>>,
+
+    /// Log the syscalls made by the host's processes, along with their results, to a "strace"
+    /// file next to each process's stdout and stderr files
+    #[clap(long, value_name = "bool")]
+    #[clap(about = HOST_HELP.get("strace_logging").unwrap())]
+    strace_logging: Option<bool>,
+
     /// IPv4 address hint for Shadow's name and routing system (ex: "100.0.0.1")
     #[clap(long, value_name = "ip")]
     #[clap(about = HOST_HELP.get("ip_address_hint").unwrap())]
@@ -435,19 +572,165 @@ pub struct HostDefaultOptions {
     #[clap(long, value_name = "city")]
     #[clap(about = HOST_HELP.get("city_code_hint").unwrap())]
     city_code_hint: Option<String>,
+
+    /// Give the host an isolated copy-on-write view of the filesystem. Files that the host
+    /// modifies are copied into the "fs" directory within the host's data directory, so hosts
+    /// can write to the same absolute paths without interfering with each other
+    #[clap(long, value_name = "bool")]
+    #[clap(about = HOST_HELP.get("filesystem_overlay").unwrap())]
+    filesystem_overlay: Option<bool>,
+
+    /// Directory tree to use as the read-only base of the host's filesystem view, in place of
+    /// the real root directory. Only used when `filesystem_overlay` is enabled
+    #[clap(long, value_name = "path")]
+    #[clap(about = HOST_HELP.get("filesystem_base").unwrap())]
+    filesystem_base: Option<String>,
+
+    /// Simulated time taken by each read from a file, in addition to the transfer time. Disk io
+    /// takes no simulated time unless at least one of the `disk_*` options is set
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("disk_read_latency").unwrap())]
+    disk_read_latency: Option<units::Time<units::TimePrefix>>,
+
+    /// Throughput of reads from a file (ex: "4 Gbit")
+    #[clap(long, value_name = "bandwidth")]
+    #[clap(about = HOST_HELP.get("disk_read_bandwidth").unwrap())]
+    disk_read_bandwidth: Option<units::BitsPerSec<units::SiPrefixUpper>>,
+
+    /// Simulated time taken by each write to a file, in addition to the transfer time
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("disk_write_latency").unwrap())]
+    disk_write_latency: Option<units::Time<units::TimePrefix>>,
+
+    /// Throughput of writes to a file (ex: "2 Gbit")
+    #[clap(long, value_name = "bandwidth")]
+    #[clap(about = HOST_HELP.get("disk_write_bandwidth").unwrap())]
+    disk_write_bandwidth: Option<units::BitsPerSec<units::SiPrefixUpper>>,
+
+    /// Simulated time taken by each fsync, fdatasync, syncfs, or sync_file_range call
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("disk_sync_latency").unwrap())]
+    disk_sync_latency: Option<units::Time<units::TimePrefix>>,
+
+    /// Maximum amount of memory that the host's processes can allocate in total (ex: "2 GiB")
+    #[clap(long, value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("memory_limit").unwrap())]
+    memory_limit: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// What to do when an allocation would exceed the host's memory limit
+    #[clap(long, value_name = "action")]
+    #[clap(about = HOST_HELP.get("memory_limit_action").unwrap())]
+    memory_limit_action: Option<MemoryLimitAction>,
+
+    /// Number of virtual CPU cores. Threads of the host's processes that are ready to run at the
+    /// same time contend for these cores
+    #[clap(long, value_name = "cores")]
+    #[clap(about = HOST_HELP.get("cpu_cores").unwrap())]
+    cpu_cores: Option<NonZeroU32>,
+
+    /// Frequency of the host's virtual CPU in KHz. Measured execution time is scaled by the ratio
+    /// of the real CPU frequency to this frequency. Uses the real CPU frequency if unset
+    #[clap(long, value_name = "khz")]
+    #[clap(about = HOST_HELP.get("cpu_frequency").unwrap())]
+    cpu_frequency: Option<u64>,
+
+    /// Amount of unprocessed CPU time that the host can build up before its events are delayed.
+    /// Measured execution time does not delay events if unset. Setting this makes simulation
+    /// results depend on the real execution time of the plugins
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("cpu_threshold").unwrap())]
+    cpu_threshold: Option<units::Time<units::TimePrefix>>,
+
+    /// Granularity to which measured CPU time is rounded
+    #[clap(long, value_name = "seconds")]
+    #[clap(about = HOST_HELP.get("cpu_precision").unwrap())]
+    cpu_precision: Option<units::Time<units::TimePrefix>>,
+
+    /// Initial size of the socket's send buffer. Uses `experimental.socket_send_buffer` if unset
+    #[clap(long = "host-socket-send-buffer", name = "host-socket-send-buffer")]
+    #[clap(value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("socket_send_buffer").unwrap())]
+    socket_send_buffer: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// Enable send window autotuning. Uses `experimental.socket_send_autotune` if unset
+    #[clap(long = "host-socket-send-autotune", name = "host-socket-send-autotune")]
+    #[clap(value_name = "bool")]
+    #[clap(about = HOST_HELP.get("socket_send_autotune").unwrap())]
+    socket_send_autotune: Option<bool>,
+
+    /// Initial size of the socket's receive buffer. Uses `experimental.socket_recv_buffer` if
+    /// unset
+    #[clap(long = "host-socket-recv-buffer", name = "host-socket-recv-buffer")]
+    #[clap(value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("socket_recv_buffer").unwrap())]
+    socket_recv_buffer: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// Enable receive window autotuning. Uses `experimental.socket_recv_autotune` if unset
+    #[clap(long = "host-socket-recv-autotune", name = "host-socket-recv-autotune")]
+    #[clap(value_name = "bool")]
+    #[clap(about = HOST_HELP.get("socket_recv_autotune").unwrap())]
+    socket_recv_autotune: Option<bool>,
+
+    /// Size of the interface receive buffer that accepts incoming packets. Uses
+    /// `experimental.interface_buffer` if unset
+    #[clap(long = "host-interface-buffer", name = "host-interface-buffer")]
+    #[clap(value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("interface_buffer").unwrap())]
+    interface_buffer: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The queueing discipline to use at the network interface. Uses
+    /// `experimental.interface_qdisc` if unset
+    #[clap(long = "host-interface-qdisc", name = "host-interface-qdisc")]
+    #[clap(value_name = "mode")]
+    #[clap(about = HOST_HELP.get("interface_qdisc").unwrap())]
+    interface_qdisc: Option<QDiscMode>,
+
+    /// Maximum size in bytes of the IP packets sent by the network interface. Larger UDP
+    /// datagrams are split into IP fragments. Must be between 68 and 65535
+    #[clap(long = "host-interface-mtu", name = "host-interface-mtu")]
+    #[clap(value_name = "bytes")]
+    #[clap(about = HOST_HELP.get("interface_mtu").unwrap())]
+    interface_mtu: Option<u32>,
 }
 
 impl HostDefaultOptions {
     pub fn new_empty() -> Self {
         Self {
             log_level: None,
+            log_to_file: None,
             heartbeat_log_level: None,
             heartbeat_log_info: None,
             heartbeat_interval: None,
+            heartbeat_format: None,
             pcap_directory: None,
+            pcap_capture_size: None,
+            pcap_filter: None,
+            pcap_start_time: None,
+            pcap_stop_time: None,
+            strace_logging: None,
             ip_address_hint: None,
             country_code_hint: None,
             city_code_hint: None,
+            filesystem_overlay: None,
+            filesystem_base: None,
+            disk_read_latency: None,
+            disk_read_bandwidth: None,
+            disk_write_latency: None,
+            disk_write_bandwidth: None,
+            disk_sync_latency: None,
+            memory_limit: None,
+            memory_limit_action: None,
+            cpu_cores: None,
+            cpu_frequency: None,
+            cpu_threshold: None,
+            cpu_precision: None,
+            socket_send_buffer: None,
+            socket_send_autotune: None,
+            socket_recv_buffer: None,
+            socket_recv_autotune: None,
+            interface_buffer: None,
+            interface_qdisc: None,
+            interface_mtu: None,
         }
     }
 
@@ -462,13 +745,41 @@ impl Default for HostDefaultOptions {
     fn default() -> Self {
         Self {
             log_level: None,
+            log_to_file: Some(false),
             heartbeat_log_level: Some(LogLevel::Info),
             heartbeat_log_info: Some(std::array::IntoIter::new([LogInfoFlag::Node]).collect()),
             heartbeat_interval: Some(units::Time::new(1, units::TimePrefixUpper::Sec)),
+            heartbeat_format: Some(HeartbeatFormat::None),
             pcap_directory: None,
+            pcap_capture_size: Some(units::Bytes::new(65535, units::SiPrefixUpper::Base)),
+            pcap_filter: None,
+            pcap_start_time: Some(units::Time::new(0, units::TimePrefixUpper::Sec)),
+            pcap_stop_time: None,
+            strace_logging: Some(false),
             ip_address_hint: None,
             country_code_hint: None,
             city_code_hint: None,
+            filesystem_overlay: Some(false),
+            filesystem_base: None,
+            disk_read_latency: None,
+            disk_read_bandwidth: None,
+            disk_write_latency: None,
+            disk_write_bandwidth: None,
+            disk_sync_latency: None,
+            memory_limit: None,
+            memory_limit_action: Some(MemoryLimitAction::Enomem),
+            cpu_cores: default_some_nz_1(),
+            cpu_frequency: None,
+            cpu_threshold: None,
+            cpu_precision: Some(units::Time::new(200, units::TimePrefix::Micro)),
+            // these use the experimental options if unset
+            socket_send_buffer: None,
+            socket_send_autotune: None,
+            socket_recv_buffer: None,
+            socket_recv_autotune: None,
+            interface_buffer: None,
+            interface_qdisc: None,
+            interface_mtu: Some(1500),
         }
     }
 }
@@ -476,28 +787,84 @@ impl Default for HostDefaultOptions {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ProcessOptions {
+    /// Path to the executable. A leading `~/` is expanded to the user's home directory
     path: std::path::PathBuf,
 
     /// Process arguments
     #[serde(default = "default_args_empty")]
     args: ProcessArgs,
 
-    /// Environment variables passed when executing this process. Multiple variables can be
-    /// specified by using a semicolon separator (ex: `ENV_A=1;ENV_B=2`)
-    #[serde(default)]
-    environment: String,
+    /// Environment variables passed when executing this process, as a map (ex: `{ENV_A: 1,
+    /// ENV_B: 2}`) or as a string with a semicolon separator (ex: `ENV_A=1;ENV_B=2`)
+    #[serde(default = "default_environment_empty")]
+    environment: ProcessEnvironment,
 
     /// The number of replicas of this process to execute
     #[serde(default)]
     quantity: Quantity,
 
+    /// A name for the process, which other processes can refer to in their `start_after`
+    /// conditions. Multiple processes can have the same name
+    #[serde(default)]
+    name: Option<String>,
+
     /// The simulated time at which to execute the process
     #[serde(default)]
     start_time: units::Time<units::TimePrefixUpper>,
 
-    /// The simulated time at which to send a SIGKILL signal to the process
+    /// Conditions that must all be met before the process is executed, in addition to its
+    /// `start_time` (ex: `[{process_exited: setup}, {listening: {host: server, port: 80}}]`)
+    #[serde(default)]
+    start_after: Vec<StartCondition>,
+
+    /// The simulated time at which to send the `shutdown_signal` to the process
     #[serde(default)]
     stop_time: Option<units::Time<units::TimePrefixUpper>>,
+
+    /// The signal sent to the process at its `stop_time` (ex: "SIGTERM"). Defaults to SIGTERM if
+    /// not set
+    #[serde(default)]
+    shutdown_signal: Option<Signal>,
+
+    /// How long to wait after sending the `shutdown_signal` before sending a SIGKILL signal to
+    /// the process if it hasn't exited. Defaults to 10 seconds if not set
+    #[serde(default)]
+    shutdown_grace_period: Option<units::Time<units::TimePrefix>>,
+
+    /// Stop the simulation once this process exits, even if other processes are still running
+    #[serde(default)]
+    terminates_simulation: Option<bool>,
+
+    /// Whether to restart the process after it exits: `never`, `on-failure` (if it exits with a
+    /// non-zero exit code or is killed by a signal), or `always`. Defaults to `never` if not set
+    #[serde(default)]
+    restart: Option<RestartPolicy>,
+
+    /// How long to wait after the process exits before restarting it. Defaults to 1 second if
+    /// not set
+    #[serde(default)]
+    restart_delay: Option<units::Time<units::TimePrefix>>,
+
+    /// The maximum number of times to restart the process. The process is restarted any number
+    /// of times if not set
+    #[serde(default)]
+    max_restarts: Option<u32>,
+
+    /// The input that the process reads from its stdin: the contents of a file (ex: `{path:
+    /// input.txt}`), or the given content (ex: `{content: "quit\n"}`). Stdin is empty if not set
+    #[serde(default)]
+    stdin: Option<ProcessStdin>,
+
+    /// Log the process's syscalls to a "strace" file. Uses the host's `strace_logging` option
+    /// if not set
+    #[serde(default)]
+    strace_logging: Option<bool>,
+
+    /// The state that the process should be in at the end of the simulation (ex: `{exited:
+    /// 0}`, `{signaled: SIGTERM}`, or `running`). Shadow exits with an error if the process
+    /// ends in a different state
+    #[serde(default)]
+    expected_final_state: Option<ProcessFinalState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -509,6 +876,23 @@ pub struct HostOptions {
     #[serde(default)]
     quantity: Quantity,
 
+    /// The hostnames of the hosts if `quantity` is greater than 1, where `{index}` is replaced
+    /// by the host's counter (ex: `relay{index:03}` for `relay001`, `relay002`, ...). Defaults
+    /// to the host's name followed by the counter if not set
+    #[serde(default)]
+    hostname_template: Option<HostnameTemplate>,
+
+    /// The id of the network graph node to attach the host to. The host's
+    /// `ip_address_hint`, `country_code_hint`, and `city_code_hint` options are not used to
+    /// choose a node if set
+    #[serde(default)]
+    network_node_id: Option<u32>,
+
+    /// The IP address of the host. Unlike `ip_address_hint`, Shadow exits with an error if the
+    /// address can't be assigned to the host. Can't be used if `quantity` is greater than 1
+    #[serde(default)]
+    ip_addr: Option<std::net::Ipv4Addr>,
+
     /// Downstream bandwidth capacity of the host
     #[serde(default)]
     bandwidth_down: Option<units::BitsPerSec<units::SiPrefixUpper>>,
@@ -539,6 +923,62 @@ impl std::str::FromStr for LogLevel {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[repr(C)]
+pub enum LogFormat {
+    /// Space-separated fields, intended to be read by people
+    Text,
+    /// A JSON object per line, intended to be read by other programs
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[repr(C)]
+pub enum HeartbeatFormat {
+    /// Don't write heartbeat statistics to a file
+    None,
+    /// Comma-separated values, with a header row
+    Csv,
+    /// A JSON object per line
+    Json,
+}
+
+impl std::str::FromStr for HeartbeatFormat {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+/// A condition that stops the simulation before its stop time.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum StopWhen {
+    /// All processes exited
+    AllProcessesExited,
+    /// All processes with the given names exited
+    ProcessesExited(Vec<String>),
+}
+
+impl std::str::FromStr for StopWhen {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
 impl LogLevel {
     pub fn to_c_loglevel(&self) -> c_log::LogLevel {
         match self {
@@ -655,6 +1095,202 @@ impl std::str::FromStr for QDiscMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[repr(C)]
+pub enum MemoryLimitAction {
+    /// Fail the allocation with ENOMEM
+    Enomem,
+    /// Kill the host's process that is using the most memory
+    Kill,
+}
+
+impl std::str::FromStr for MemoryLimitAction {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[repr(C)]
+pub enum RestartPolicy {
+    /// Never restart the process
+    Never,
+    /// Restart the process if it exits with a non-zero exit code or is killed by a signal
+    OnFailure,
+    /// Restart the process whenever it exits
+    Always,
+}
+
+impl std::str::FromStr for RestartPolicy {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+/// A template for the hostnames of the replicas of a host, with an `{index}` placeholder for the
+/// replica's counter. The counter can be zero-padded to a width (ex: `{index:03}`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "String", into = "String")]
+pub struct HostnameTemplate(String);
+
+impl HostnameTemplate {
+    /// Returns the text before the placeholder, the width of the counter, and the text after
+    /// the placeholder.
+    fn parse(template: &str) -> Result<(&str, usize, &str), String> {
+        let start = template.find('{');
+        let end = template.find('}');
+
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) if start < end => (start, end),
+            _ => {
+                return Err(format!(
+                    "Hostname template '{}' has no {{index}} placeholder",
+                    template
+                ))
+            }
+        };
+
+        let (prefix, spec, suffix) = (
+            &template[..start],
+            &template[start + 1..end],
+            &template[end + 1..],
+        );
+
+        if suffix.contains(&['{', '}'][..]) {
+            return Err(format!(
+                "Hostname template '{}' must have exactly one placeholder",
+                template
+            ));
+        }
+
+        let width = match spec {
+            "index" => 0,
+            _ => spec
+                .strip_prefix("index:0")
+                .and_then(|width| width.parse().ok())
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown placeholder '{{{}}}' in hostname template '{}'",
+                        spec, template
+                    )
+                })?,
+        };
+
+        Ok((prefix, width, suffix))
+    }
+
+    /// Returns the hostname of the replica with the given counter.
+    pub fn format(&self, index: u32) -> String {
+        let (prefix, width, suffix) = Self::parse(&self.0).unwrap();
+        format!("{}{:0width$}{}", prefix, index, suffix, width = width)
+    }
+}
+
+impl TryFrom<String> for HostnameTemplate {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)?;
+        Ok(Self(s))
+    }
+}
+
+impl From<HostnameTemplate> for String {
+    fn from(template: HostnameTemplate) -> Self {
+        template.0
+    }
+}
+
+/// A signal, given by its name (ex: "SIGTERM").
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "String", into = "String")]
+pub struct Signal(#[schemars(with = "String")] nix::sys::signal::Signal);
+
+impl Signal {
+    pub fn as_raw(&self) -> i32 {
+        self.0 as i32
+    }
+}
+
+impl TryFrom<String> for Signal {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+            .map(Self)
+            .map_err(|_| format!("Unknown signal '{}'", s))
+    }
+}
+
+impl TryFrom<i32> for Signal {
+    type Error = nix::Error;
+
+    fn try_from(signal: i32) -> Result<Self, Self::Error> {
+        nix::sys::signal::Signal::try_from(signal).map(Self)
+    }
+}
+
+impl From<Signal> for String {
+    fn from(signal: Signal) -> Self {
+        signal.0.as_str().to_string()
+    }
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.as_str())
+    }
+}
+
+/// The state of a process at the end of the simulation.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessFinalState {
+    /// The process exited with the given exit code
+    Exited(i32),
+    /// The process was killed by the given signal
+    Signaled(Signal),
+    /// The process was still running when the simulation ended
+    Running,
+}
+
+/// The input of a process on its stdin.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum ProcessStdin {
+    /// A file that the process reads, relative to Shadow's working directory
+    Path(std::path::PathBuf),
+    /// The input that the process reads
+    Content(String),
+}
+
+/// A condition that must be met before a process is executed.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum StartCondition {
+    /// All processes with the given name exited with an exit code of 0
+    ProcessExited(String),
+    /// The host has a TCP socket in the listening state on the port
+    Listening { host: String, port: u16 },
+}
+
+impl std::fmt::Display for ProcessFinalState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // use the same format as the configuration file
+        match self {
+            Self::Exited(code) => write!(f, "exited: {}", code),
+            Self::Signaled(signal) => write!(f, "signaled: {}", signal),
+            Self::Running => write!(f, "running"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 enum CustomGraph {
@@ -694,11 +1330,128 @@ pub enum ProcessArgs {
     Str(String),
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProcessEnvironment {
+    Map(BTreeMap<String, EnvironmentValue>),
+    Str(String),
+}
+
+impl ProcessEnvironment {
+    /// Returns the (key, value) pairs of the environment. Entries of the string form without a
+    /// '=' are ignored.
+    fn pairs(&self) -> Vec<(String, String)> {
+        match self {
+            Self::Map(x) => x.iter().map(|(k, v)| (k.clone(), v.to_string())).collect(),
+            Self::Str(x) => x
+                .split(';')
+                .filter_map(|entry| {
+                    let mut parts = entry.splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(k), Some(v)) => Some((k.to_string(), v.to_string())),
+                        _ => None,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A value in the map form of the environment. YAML numbers and booleans are accepted so that
+/// they don't need to be quoted.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum EnvironmentValue {
+    Str(String),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl std::fmt::Display for EnvironmentValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Str(x) => write!(f, "{}", x),
+            Self::Int(x) => write!(f, "{}", x),
+            Self::UInt(x) => write!(f, "{}", x),
+            Self::Float(x) => write!(f, "{}", x),
+            Self::Bool(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// The values of the variables that are substituted in a process's `path`, `args`, and
+/// `environment`, which differ between the replicas of a host.
+#[derive(Debug, Clone)]
+pub struct ProcessVariables {
+    hostname: String,
+    host_index: u32,
+    host_ip: String,
+    seed: u32,
+}
+
+impl ProcessVariables {
+    pub fn new(hostname: &str, host_index: u32, host_ip: &str, seed: u32) -> Self {
+        Self {
+            hostname: hostname.to_string(),
+            host_index,
+            host_ip: host_ip.to_string(),
+            seed,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "HOSTNAME" => Some(self.hostname.clone()),
+            "HOST_INDEX" => Some(self.host_index.to_string()),
+            "HOST_IP" => Some(self.host_ip.clone()),
+            "SEED" => Some(self.seed.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Replaces each `${NAME}` in the string with the value of the variable. Unknown variables
+    /// are left unchanged.
+    pub fn substitute(&self, s: &str) -> String {
+        let mut result = String::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest
+                .find('}')
+                .and_then(|end| Some((end, self.get(&rest[2..end])?)));
+
+            match value {
+                Some((end, value)) => {
+                    result.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    result.push_str("${");
+                    rest = &rest[2..];
+                }
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
 /// Helper function for serde default `ProcessArgs::Str("")` values.
 fn default_args_empty() -> ProcessArgs {
     ProcessArgs::Str("".to_string())
 }
 
+/// Helper function for serde default `ProcessEnvironment::Str("")` values.
+fn default_environment_empty() -> ProcessEnvironment {
+    ProcessEnvironment::Str("".to_string())
+}
+
 /// Helper function for serde default `Some(0)` values.
 fn default_some_time_0() -> Option<units::Time<units::TimePrefixUpper>> {
     Some(units::Time::new(0, units::TimePrefixUpper::Sec))
@@ -729,6 +1482,15 @@ fn default_some_info() -> Option<LogLevel> {
     Some(LogLevel::Info)
 }
 
+/// Helper function for serde default `Some(LogFormat::Text)` values.
+fn default_some_log_format_text() -> Option<LogFormat> {
+    Some(LogFormat::Text)
+}
+
+fn default_some_heartbeat_format_none() -> Option<HeartbeatFormat> {
+    Some(HeartbeatFormat::None)
+}
+
 // when updating this graph, make sure to also update the copy in docs/shadow_config_spec.md
 const ONE_GBIT_SWITCH_GRAPH: &str = r#"graph [
   directed 0
@@ -746,6 +1508,11 @@ const ONE_GBIT_SWITCH_GRAPH: &str = r#"graph [
   ]
 ]"#;
 
+/// The JSON schema of the configuration file.
+pub fn config_file_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(ConfigFileOptions)).unwrap()
+}
+
 /// Generate help strings for objects in a JSON schema, including the Serde defaults if available.
 fn generate_help_strs(
     schema: schemars::schema::RootSchema,
@@ -767,8 +1534,255 @@ fn generate_help_strs(
     defaults
 }
 
-fn tilde_expansion(path: &str) -> std::path::PathBuf {
-    // if the path begins with a "~"
+/// Reads the configuration file at `path`, merged with the files that it includes, and applies
+/// the "key=value" overrides.
+fn load_config_yaml(path: &OsStr, overrides: &[String]) -> Result<serde_yaml::Value, String> {
+    let mut config = load_yaml_with_includes(std::path::Path::new(path))?;
+    for set in overrides {
+        apply_override(&mut config, set)?;
+    }
+    Ok(config)
+}
+
+/// The keys that the sources of included files are nested under while a file and the files that
+/// it includes are parsed as one document, followed by the index of the include.
+const INCLUDED_KEY_PREFIX: &str = "__shadow_included_";
+
+/// Reads a YAML config file, merged on top of the config files listed in its `include` option.
+/// A path of "-" reads from stdin.
+fn load_yaml_with_includes(path: &std::path::Path) -> Result<serde_yaml::Value, String> {
+    let (source, has_includes) = expand_includes(path, &mut Vec::new())?;
+
+    // without includes, the line numbers of parse errors are the lines of the file
+    let config: serde_yaml::Value = serde_yaml::from_str(&source).map_err(|e| {
+        if has_includes {
+            format!(
+                "Could not parse yaml in {:?} or its included files: {}",
+                path, e
+            )
+        } else {
+            format!("Could not parse yaml in {:?}: {}", path, e)
+        }
+    })?;
+
+    Ok(merge_included(config))
+}
+
+/// Returns the YAML source of the file at `path`, with the sources of the files that it includes
+/// nested under keys that precede the file's own options, so that aliases in a file can refer to
+/// anchors in the files that it includes and in the files included before it. Also returns
+/// whether the file includes any files. `parents` are the files that are currently being
+/// included, used to detect include cycles.
+fn expand_includes(
+    path: &std::path::Path,
+    parents: &mut Vec<std::path::PathBuf>,
+) -> Result<(String, bool), String> {
+    let is_stdin = path == std::path::Path::new("-");
+
+    let contents = if is_stdin {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s).map(|_| s)
+    } else {
+        std::fs::read_to_string(path)
+    };
+    let contents = contents.map_err(|e| format!("Could not open config file {:?}: {}", path, e))?;
+
+    let includes = find_includes(&contents)
+        .map_err(|e| format!("Invalid include option in {:?}: {}", path, e))?;
+    if includes.is_empty() {
+        return Ok((contents, false));
+    }
+
+    // relative paths are relative to the directory of the file that includes them
+    let dir = match path.parent() {
+        Some(dir) if !is_stdin => dir.to_path_buf(),
+        _ => std::path::PathBuf::from("."),
+    };
+
+    if !is_stdin {
+        parents.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+
+    let mut source = String::new();
+    for (i, include) in includes.iter().enumerate() {
+        let include = dir.join(tilde_expansion(include));
+        let canonical = include
+            .canonicalize()
+            .map_err(|e| format!("Could not open config file {:?}: {}", include, e))?;
+        if parents.contains(&canonical) {
+            return Err(format!("Config file {:?} includes itself", include));
+        }
+
+        let (included, _) = expand_includes(&include, parents)?;
+        source.push_str(&format!("{}{}:\n", INCLUDED_KEY_PREFIX, i));
+        for line in document_lines(&included) {
+            source.push_str("  ");
+            source.push_str(line);
+            source.push('\n');
+        }
+    }
+    for line in document_lines(&contents) {
+        source.push_str(line);
+        source.push('\n');
+    }
+
+    if !is_stdin {
+        parents.pop();
+    }
+
+    Ok((source, true))
+}
+
+/// Returns the lines of a YAML document without the comments, blank lines, and "---" start
+/// marker that precede its content, so that it can be combined with other documents.
+fn document_lines(source: &str) -> impl Iterator<Item = &str> {
+    source.lines().skip_while(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#') || line == "---"
+    })
+}
+
+/// Returns the files listed in the top-level `include` option of a YAML config file.
+fn find_includes(contents: &str) -> Result<Vec<String>, String> {
+    // the file can't be parsed on its own if it uses aliases of anchors in the included files
+    let option = match serde_yaml::from_str::<serde_yaml::Value>(contents) {
+        Ok(serde_yaml::Value::Mapping(mut mapping)) => mapping.remove(&"include".into()),
+        Ok(_) => None,
+        Err(_) => find_include_lines(contents)?,
+    };
+
+    match option {
+        Some(option) => Ok(serde_yaml::from_value::<Option<Vec<String>>>(option)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Returns the value of the top-level `include` option of a YAML config file that can't be
+/// parsed on its own. Only the lines of the option are parsed.
+fn find_include_lines(contents: &str) -> Result<Option<serde_yaml::Value>, String> {
+    let mut lines = contents
+        .lines()
+        .skip_while(|line| !line.starts_with("include:"));
+
+    let first = match lines.next() {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+
+    // the option continues on indented lines, on lines of a list at the same indentation, and
+    // past comments
+    let option: Vec<&str> = std::iter::once(first)
+        .chain(lines.take_while(|line| {
+            line.trim().is_empty()
+                || line.starts_with(' ')
+                || line.starts_with('-')
+                || line.starts_with('#')
+        }))
+        .collect();
+
+    let mut option: serde_yaml::Mapping =
+        serde_yaml::from_str(&option.join("\n")).map_err(|e| e.to_string())?;
+
+    Ok(option.remove(&"include".into()))
+}
+
+/// Merges the configs of the included files that `expand_includes` nested in `config`, in
+/// order, with `config` on top. Later files override the earlier ones, and the including file
+/// overrides them all.
+fn merge_included(mut config: serde_yaml::Value) -> serde_yaml::Value {
+    let mut merged = serde_yaml::Value::Null;
+
+    if let Some(mapping) = config.as_mapping_mut() {
+        mapping.remove(&"include".into());
+        for i in 0.. {
+            let key = format!("{}{}", INCLUDED_KEY_PREFIX, i);
+            match mapping.remove(&key.into()) {
+                Some(included) => merge_yaml(&mut merged, merge_included(included)),
+                None => break,
+            }
+        }
+    }
+
+    merge_yaml(&mut merged, config);
+    merged
+}
+
+/// Merges `overlay` into `base`. Mappings are merged key by key, and any other value replaces
+/// the value in `base`. Like `Option` fields with the `merge` crate, null values are ignored.
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    use serde_yaml::Value;
+
+    match (base, overlay) {
+        (_, Value::Null) => {}
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Sets the option at a "path.to.key=value" override, where the value is parsed as YAML.
+/// Components of the path that are numbers index into lists.
+fn apply_override(config: &mut serde_yaml::Value, set: &str) -> Result<(), String> {
+    use serde_yaml::Value;
+
+    let mut parts = set.splitn(2, '=');
+    let (key, value) = match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) if !key.is_empty() => (key, value),
+        _ => return Err(format!("Invalid override {:?}, expected 'key=value'", set)),
+    };
+
+    // a value that isn't valid YAML (for example "a: b: c") is used as a string
+    let value: Value =
+        serde_yaml::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+
+    let mut node = config;
+    for component in key.split('.') {
+        if node.is_null() {
+            *node = Value::Mapping(serde_yaml::Mapping::new());
+        }
+        node = match node {
+            Value::Mapping(m) => m.entry(component.into()).or_insert(Value::Null),
+            Value::Sequence(seq) => {
+                let len = seq.len();
+                match component
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(move |i| seq.get_mut(i))
+                {
+                    Some(x) => x,
+                    None => {
+                        return Err(format!(
+                            "Invalid override {:?}, {:?} is not an index of a list of length {}",
+                            set, component, len
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid override {:?}, {:?} is not an option",
+                    set, component
+                ))
+            }
+        };
+    }
+
+    *node = value;
+    Ok(())
+}
+
+fn tilde_expansion(path: &str) -> std::path::PathBuf {
+    // if the path begins with a "~"
     if let Some(x) = path.strip_prefix("~") {
         // get the tilde-prefix (everything before the first separator)
         let mut parts = x.splitn(2, "/");
@@ -831,6 +1845,7 @@ fn parse_string_as_args(args_str: &OsStr) -> Result<Vec<OsString>, String> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::simulation_time::SIMTIME_ONE_MILLISECOND;
     use super::*;
 
     #[test]
@@ -907,11 +1922,555 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_process_final_state() {
+        let states = [
+            ("{exited: 0}", "exited: 0"),
+            ("{exited: -1}", "exited: -1"),
+            ("{signaled: SIGTERM}", "signaled: SIGTERM"),
+            ("running", "running"),
+        ];
+
+        for (yaml, display) in &states {
+            let state: ProcessFinalState = serde_yaml::from_str(yaml).unwrap();
+            assert_eq!(&state.to_string(), display);
+            // the displayed state can also be parsed
+            assert_eq!(
+                serde_yaml::from_str::<ProcessFinalState>(display).unwrap(),
+                state
+            );
+        }
+
+        assert!(serde_yaml::from_str::<ProcessFinalState>("{signaled: SIGFOO}").is_err());
+        assert!(serde_yaml::from_str::<ProcessFinalState>("exited").is_err());
+    }
+
+    #[test]
+    fn test_process_shutdown() {
+        let proc: ProcessOptions = serde_yaml::from_str("path: /bin/true").unwrap();
+        assert_eq!(
+            export::processoptions_getShutdownSignal(&proc),
+            libc::SIGTERM
+        );
+        assert_eq!(
+            export::processoptions_getShutdownGracePeriod(&proc),
+            10 * SIMTIME_ONE_SECOND
+        );
+
+        let proc: ProcessOptions = serde_yaml::from_str(
+            "{path: /bin/true, shutdown_signal: SIGINT, shutdown_grace_period: 500 ms}",
+        )
+        .unwrap();
+        assert_eq!(
+            export::processoptions_getShutdownSignal(&proc),
+            libc::SIGINT
+        );
+        assert_eq!(
+            export::processoptions_getShutdownGracePeriod(&proc),
+            500 * SIMTIME_ONE_MILLISECOND
+        );
+
+        assert!(serde_yaml::from_str::<ProcessOptions>(
+            "{path: /bin/true, shutdown_signal: SIGFOO}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_process_restart() {
+        let proc: ProcessOptions = serde_yaml::from_str("path: /bin/true").unwrap();
+        assert_eq!(
+            export::processoptions_getRestartPolicy(&proc),
+            RestartPolicy::Never
+        );
+        assert_eq!(
+            export::processoptions_getRestartDelay(&proc),
+            SIMTIME_ONE_SECOND
+        );
+        assert_eq!(export::processoptions_getMaxRestarts(&proc), -1);
+
+        let proc: ProcessOptions = serde_yaml::from_str(
+            "{path: /bin/true, restart: on-failure, restart_delay: 5 ms, max_restarts: 3}",
+        )
+        .unwrap();
+        assert_eq!(
+            export::processoptions_getRestartPolicy(&proc),
+            RestartPolicy::OnFailure
+        );
+        assert_eq!(
+            export::processoptions_getRestartDelay(&proc),
+            5 * SIMTIME_ONE_MILLISECOND
+        );
+        assert_eq!(export::processoptions_getMaxRestarts(&proc), 3);
+
+        assert!(
+            serde_yaml::from_str::<ProcessOptions>("{path: /bin/true, restart: sometimes}")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_process_start_after() {
+        let proc: ProcessOptions = serde_yaml::from_str("path: /bin/true").unwrap();
+        assert!(export::processoptions_getName(&proc).is_null());
+        assert!(export::processoptions_getStartAfter(&proc).is_null());
+
+        let yaml = r#"
+            path: /bin/true
+            name: client
+            start_after:
+              - process_exited: setup
+              - listening: {host: server, port: 80}
+        "#;
+        let proc: ProcessOptions = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(proc.name.as_deref(), Some("client"));
+        assert_eq!(
+            proc.start_after,
+            vec![
+                StartCondition::ProcessExited("setup".to_string()),
+                StartCondition::Listening {
+                    host: "server".to_string(),
+                    port: 80
+                },
+            ]
+        );
+
+        let conditions = export::processoptions_getStartAfter(&proc);
+        assert!(!conditions.is_null());
+        drop(unsafe { Box::from_raw(conditions) });
+
+        assert!(serde_yaml::from_str::<ProcessOptions>(
+            "{path: /bin/true, start_after: [{listening: {host: server}}]}"
+        )
+        .is_err());
+        assert!(serde_yaml::from_str::<ProcessOptions>(
+            "{path: /bin/true, start_after: [{started: server}]}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_process_stdin() {
+        let proc: ProcessOptions = serde_yaml::from_str("path: /bin/true").unwrap();
+        assert!(export::processoptions_getStdinPath(&proc).is_null());
+        assert!(export::processoptions_getStdinContent(&proc).is_null());
+
+        let proc: ProcessOptions =
+            serde_yaml::from_str("{path: /bin/true, stdin: {path: input.txt}}").unwrap();
+        let path = export::processoptions_getStdinPath(&proc);
+        assert_eq!(
+            unsafe { CStr::from_ptr(path) }.to_str().unwrap(),
+            "input.txt"
+        );
+        export::processoptions_freeString(path);
+        assert!(export::processoptions_getStdinContent(&proc).is_null());
+
+        let proc: ProcessOptions =
+            serde_yaml::from_str("{path: /bin/true, stdin: {content: \"quit\\n\"}}").unwrap();
+        let content = export::processoptions_getStdinContent(&proc);
+        assert_eq!(
+            unsafe { CStr::from_ptr(content) }.to_str().unwrap(),
+            "quit\n"
+        );
+        export::processoptions_freeString(content);
+        assert!(export::processoptions_getStdinPath(&proc).is_null());
+
+        assert!(serde_yaml::from_str::<ProcessOptions>(
+            "{path: /bin/true, stdin: {path: a, content: b}}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_hostname_template() {
+        let template = HostnameTemplate::try_from("relay{index:03}".to_string()).unwrap();
+        assert_eq!(template.format(1), "relay001");
+        assert_eq!(template.format(1234), "relay1234");
+
+        let template = HostnameTemplate::try_from("{index}.client".to_string()).unwrap();
+        assert_eq!(template.format(12), "12.client");
+
+        for bad in [
+            "relay",
+            "relay{id}",
+            "relay{index:3}",
+            "{index}{index}",
+            "}{index",
+        ] {
+            assert!(
+                HostnameTemplate::try_from(bad.to_string()).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_placement() {
+        let get_hostname = |host: &HostOptions, index| {
+            let name = CString::new("relay").unwrap();
+            let hostname = export::hostoptions_getHostname(host, name.as_ptr(), index);
+            let s = unsafe { CStr::from_ptr(hostname) }
+                .to_str()
+                .unwrap()
+                .to_string();
+            export::hostoptions_freeString(hostname);
+            s
+        };
+
+        let host: HostOptions = serde_yaml::from_str("processes: []").unwrap();
+        assert_eq!(get_hostname(&host, 1), "relay");
+        assert_eq!(export::hostoptions_getNetworkNodeId(&host), -1);
+        assert!(export::hostoptions_getIpAddr(&host).is_null());
+
+        let host: HostOptions = serde_yaml::from_str("{processes: [], quantity: 2}").unwrap();
+        assert_eq!(get_hostname(&host, 2), "relay2");
+
+        let host: HostOptions = serde_yaml::from_str(
+            "{processes: [], quantity: 2, hostname_template: 'r{index:02}', network_node_id: 3}",
+        )
+        .unwrap();
+        assert_eq!(get_hostname(&host, 2), "r02");
+        assert_eq!(export::hostoptions_getNetworkNodeId(&host), 3);
+
+        let host: HostOptions = serde_yaml::from_str("{processes: [], ip_addr: 11.0.0.5}").unwrap();
+        let ip = export::hostoptions_getIpAddr(&host);
+        assert_eq!(unsafe { CStr::from_ptr(ip) }.to_str().unwrap(), "11.0.0.5");
+        export::hostoptions_freeString(ip);
+
+        assert!(serde_yaml::from_str::<HostOptions>("{processes: [], ip_addr: 11.0.0}").is_err());
+        assert!(
+            serde_yaml::from_str::<HostOptions>("{processes: [], hostname_template: r}").is_err()
+        );
+    }
+
+    #[test]
+    fn test_process_environment() {
+        unsafe extern "C" fn collect(
+            key: *const libc::c_char,
+            value: *const libc::c_char,
+            data: *mut libc::c_void,
+        ) {
+            let env = &mut *(data as *mut Vec<(String, String)>);
+            env.push((
+                CStr::from_ptr(key).to_str().unwrap().to_string(),
+                CStr::from_ptr(value).to_str().unwrap().to_string(),
+            ));
+        }
+
+        let get_env = |yaml: &str| {
+            let proc: ProcessOptions = serde_yaml::from_str(yaml).unwrap();
+            let vars = ProcessVariables::new("client3", 3, "11.0.0.3", 1);
+            let mut env = Vec::<(String, String)>::new();
+            export::processoptions_getEnvironment(
+                &proc,
+                &vars,
+                collect,
+                &mut env as *mut _ as *mut libc::c_void,
+            );
+            env
+        };
+
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+
+        assert_eq!(get_env("path: /bin/true"), vec![]);
+        assert_eq!(
+            get_env("{path: /bin/true, environment: \"A=1;B=x=${HOSTNAME};C\"}"),
+            vec![pair("A", "1"), pair("B", "x=client3")]
+        );
+        assert_eq!(
+            get_env("{path: /bin/true, environment: {PORT: 80, ON: true, PATHS: \"a;b\"}}"),
+            vec![pair("ON", "true"), pair("PATHS", "a;b"), pair("PORT", "80")]
+        );
+    }
+
+    #[test]
+    fn test_process_variables() {
+        let vars = ProcessVariables::new("client3", 3, "11.0.0.3", 1);
+
+        assert_eq!(vars.substitute("${HOSTNAME}.log"), "client3.log");
+        assert_eq!(
+            vars.substitute("--id=${HOST_INDEX} --ip ${HOST_IP} --seed=${SEED}"),
+            "--id=3 --ip 11.0.0.3 --seed=1"
+        );
+        assert_eq!(vars.substitute("${HOME}/${HOSTNAME}"), "${HOME}/client3");
+        assert_eq!(
+            vars.substitute("$HOSTNAME ${HOSTNAME"),
+            "$HOSTNAME ${HOSTNAME"
+        );
+    }
+
+    #[test]
+    fn test_stop_when() {
+        assert_eq!(
+            "all_processes_exited".parse::<StopWhen>().unwrap(),
+            StopWhen::AllProcessesExited
+        );
+        assert_eq!(
+            "{processes_exited: [client, server]}"
+                .parse::<StopWhen>()
+                .unwrap(),
+            StopWhen::ProcessesExited(vec!["client".to_string(), "server".to_string()])
+        );
+        assert!("some_processes_exited".parse::<StopWhen>().is_err());
+
+        let proc: ProcessOptions = serde_yaml::from_str("path: /bin/true").unwrap();
+        assert!(!export::processoptions_getTerminatesSimulation(&proc));
+        let proc: ProcessOptions =
+            serde_yaml::from_str("{path: /bin/true, terminates_simulation: true}").unwrap();
+        assert!(export::processoptions_getTerminatesSimulation(&proc));
+    }
+
+    #[test]
+    fn test_host_network_options() {
+        let config_file: ConfigFileOptions = serde_yaml::from_str(
+            r#"
+general: {stop_time: 10}
+network: {graph: {type: 1_gbit_switch}}
+experimental: {socket_send_buffer: 1000 B, interface_qdisc: roundrobin}
+host_defaults: {socket_recv_buffer: 2000 B}
+hosts:
+  client: {processes: []}
+  server:
+    options: {socket_send_buffer: 3 KB, socket_recv_autotune: false, interface_qdisc: fifo,
+              interface_mtu: 9000}
+    processes: []
+"#,
+        )
+        .unwrap();
+        let options = CliOptions::try_parse_from(&["shadow", "-"]).unwrap();
+        let config = ConfigOptions::new(config_file, options);
+
+        // the experimental options are used if not set for the host
+        let client = &config.hosts["client"];
+        assert_eq!(export::hostoptions_getSocketSendBuffer(client), 1000);
+        assert_eq!(export::hostoptions_getSocketRecvBuffer(client), 2000);
+        assert!(export::hostoptions_getSocketRecvAutotune(client));
+        assert_eq!(export::hostoptions_getInterfaceBuffer(client), 1_024_000);
+        assert_eq!(
+            export::hostoptions_getInterfaceQdisc(client),
+            QDiscMode::RoundRobin
+        );
+        assert_eq!(export::hostoptions_getInterfaceMtu(client), 1500);
+
+        let server = &config.hosts["server"];
+        assert_eq!(export::hostoptions_getSocketSendBuffer(server), 3000);
+        assert_eq!(export::hostoptions_getSocketRecvBuffer(server), 2000);
+        assert!(!export::hostoptions_getSocketRecvAutotune(server));
+        assert_eq!(
+            export::hostoptions_getInterfaceQdisc(server),
+            QDiscMode::Fifo
+        );
+        assert_eq!(export::hostoptions_getInterfaceMtu(server), 9000);
+    }
+
+    #[test]
+    fn test_cpu_cores() {
+        let parse = |cores: &str| {
+            serde_yaml::from_str::<ConfigFileOptions>(&format!(
+                r#"
+general: {{stop_time: 10}}
+network: {{graph: {{type: 1_gbit_switch}}}}
+hosts:
+  server:
+    options: {{cpu_cores: {}}}
+    processes: []
+"#,
+                cores
+            ))
+        };
+
+        let options = CliOptions::try_parse_from(&["shadow", "-"]).unwrap();
+        let config = ConfigOptions::new(parse("4").unwrap(), options);
+        assert_eq!(export::hostoptions_getCpuCores(&config.hosts["server"]), 4);
+
+        // a host must have at least one core
+        assert!(parse("0").is_err());
+    }
+
+    #[test]
+    fn test_config_includes() {
+        let dir = std::env::temp_dir().join(format!("shadow-test-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("common")).unwrap();
+        std::fs::write(
+            dir.join("common/base.yaml"),
+            "general: {stop_time: 10, seed: 2}\nhosts:\n  server: {processes: [{path: /bin/a}]}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common/more.yaml"),
+            "include: [base.yaml]\ngeneral: {stop_time: 20}\nhosts:\n  client: {quantity: 3, processes: [{path: /bin/b}]}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("main.yaml"),
+            "include: [common/more.yaml]\ngeneral: {seed: 3, stop_time: ~}\nhosts:\n  client: {quantity: 5}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("cycle.yaml"), "include: [cycle.yaml]\n").unwrap();
+
+        let config = load_config_yaml(dir.join("main.yaml").as_os_str(), &[]).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "general: {stop_time: 20, seed: 3}\nhosts:\n  server: {processes: [{path: /bin/a}]}\n  client: {quantity: 5, processes: [{path: /bin/b}]}\n",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        let config = load_config_yaml(
+            dir.join("main.yaml").as_os_str(),
+            &[
+                "general.stop_time=30 s".to_string(),
+                "hosts.client.processes.0.args=--port=80".to_string(),
+                "network.graph.type=1_gbit_switch".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            config["general"]["stop_time"],
+            serde_yaml::Value::from("30 s")
+        );
+        assert_eq!(
+            config["hosts"]["client"]["processes"][0]["args"],
+            serde_yaml::Value::from("--port=80")
+        );
+        assert_eq!(
+            config["network"]["graph"]["type"],
+            serde_yaml::Value::from("1_gbit_switch")
+        );
+
+        assert!(
+            load_config_yaml(dir.join("main.yaml").as_os_str(), &["general".to_string()]).is_err()
+        );
+        assert!(load_config_yaml(
+            dir.join("main.yaml").as_os_str(),
+            &["general.seed.x=1".to_string()]
+        )
+        .is_err());
+        assert!(load_config_yaml(
+            dir.join("main.yaml").as_os_str(),
+            &["hosts.client.processes.1.args=a".to_string()]
+        )
+        .is_err());
+        // aliases can refer to anchors in included files and in files included before them
+        std::fs::write(
+            dir.join("common/anchors.yaml"),
+            "---\nx-process: &process\n  path: /bin/c\n  args: --x\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common/uses-anchors.yaml"),
+            "hosts:\n  relay: {processes: [*process]}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("anchors.yaml"),
+            "include:\n- common/anchors.yaml\n- common/uses-anchors.yaml\nhosts:\n  exit: {processes: [*process]}\n",
+        )
+        .unwrap();
+
+        let config = load_config_yaml(dir.join("anchors.yaml").as_os_str(), &[]).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "x-process: {path: /bin/c, args: --x}\nhosts:\n  relay: {processes: [{path: /bin/c, args: --x}]}\n  exit: {processes: [{path: /bin/c, args: --x}]}\n",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        assert!(load_config_yaml(dir.join("cycle.yaml").as_os_str(), &[]).is_err());
+        assert!(load_config_yaml(dir.join("missing.yaml").as_os_str(), &[]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_include_document_start() {
+        let dir = std::env::temp_dir().join(format!(
+            "shadow-test-include-document-start-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // the "---" marker usually follows a comment header
+        std::fs::write(
+            dir.join("base.yaml"),
+            "# shared options\n\n---\nx-process: &process {path: /bin/a}\ngeneral: {seed: 2}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("main.yaml"),
+            "# the main config\n---\ninclude: [base.yaml]\nhosts:\n  server: {processes: [*process]}\n",
+        )
+        .unwrap();
+
+        let config = load_config_yaml(dir.join("main.yaml").as_os_str(), &[]).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "x-process: {path: /bin/a}\ngeneral: {seed: 2}\nhosts:\n  server: {processes: [{path: /bin/a}]}\n",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_include_comments() {
+        let dir = std::env::temp_dir().join(format!(
+            "shadow-test-include-comments-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(dir.join("a.yaml"), "x-process: &process {path: /bin/a}\n").unwrap();
+        std::fs::write(dir.join("b.yaml"), "general: {seed: 2}\n").unwrap();
+
+        // comments in the list don't end the option, whether or not the file can be parsed
+        // without its included files
+        std::fs::write(
+            dir.join("parsed.yaml"),
+            "include:\n- a.yaml\n# the seed\n- b.yaml\nhosts: {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("spliced.yaml"),
+            "include:\n- a.yaml\n# the seed\n- b.yaml\nhosts:\n  server: {processes: [*process]}\n",
+        )
+        .unwrap();
+
+        let config = load_config_yaml(dir.join("parsed.yaml").as_os_str(), &[]).unwrap();
+        assert_eq!(config["general"]["seed"], serde_yaml::Value::from(2));
+
+        let config = load_config_yaml(dir.join("spliced.yaml").as_os_str(), &[]).unwrap();
+        assert_eq!(config["general"]["seed"], serde_yaml::Value::from(2));
+        assert_eq!(
+            config["hosts"]["server"]["processes"][0]["path"],
+            serde_yaml::Value::from("/bin/a")
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_file_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&config_file_schema()).unwrap();
+
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&"general".into()));
+        assert!(required.contains(&"hosts".into()));
+
+        let seed = &schema["definitions"]["GeneralOptions"]["properties"]["seed"];
+        assert_eq!(seed["description"], "Initialize randomness using seed N");
+        assert_eq!(seed["default"], 1);
+
+        let args = &schema["definitions"]["ProcessOptions"]["properties"]["args"];
+        assert_eq!(args["description"], "Process arguments");
+    }
 }
 
 mod export {
     use super::*;
 
+    use crate::core::start_after::StartConditions;
+
     #[no_mangle]
     pub extern "C" fn clioptions_parse(
         argc: libc::c_int,
@@ -979,6 +2538,20 @@ mod export {
         options.show_config
     }
 
+    #[no_mangle]
+    pub extern "C" fn clioptions_getShowConfigSchema(options: *const CliOptions) -> bool {
+        assert!(!options.is_null());
+        let options = unsafe { &*options };
+        options.show_config_schema
+    }
+
+    #[no_mangle]
+    pub extern "C" fn clioptions_getValidateConfig(options: *const CliOptions) -> bool {
+        assert!(!options.is_null());
+        let options = unsafe { &*options };
+        options.validate_config
+    }
+
     #[no_mangle]
     pub extern "C" fn clioptions_getConfig(options: *const CliOptions) -> *mut libc::c_char {
         assert!(!options.is_null());
@@ -990,28 +2563,25 @@ mod export {
         }
     }
 
+    /// Parses the configuration file, and the files that it includes, and applies the
+    /// command-line overrides. A filename of "-" reads from stdin. Returns NULL on error.
     #[no_mangle]
-    pub extern "C" fn configfile_parse(filename: *const libc::c_char) -> *mut ConfigFileOptions {
+    pub extern "C" fn configfile_parse(
+        filename: *const libc::c_char,
+        options: *const CliOptions,
+    ) -> *mut ConfigFileOptions {
         assert!(!filename.is_null());
+        assert!(!options.is_null());
         let filename = OsStr::from_bytes(unsafe { CStr::from_ptr(filename).to_bytes() });
+        let options = unsafe { &*options };
 
-        let file = match std::fs::File::open(filename) {
-            Ok(x) => x,
-            Err(e) => {
-                eprintln!("Could not open config file {:?}: {}", filename, e);
-                return std::ptr::null_mut();
-            }
-        };
-
-        let config: ConfigFileOptions = match serde_yaml::from_reader(file) {
-            Ok(x) => x,
+        match ConfigFileOptions::load(filename, &options.set) {
+            Ok(config) => Box::into_raw(Box::new(config)),
             Err(e) => {
-                eprintln!("Could not parse yaml: {}", e);
-                return std::ptr::null_mut();
+                eprintln!("{}", e);
+                std::ptr::null_mut()
             }
-        };
-
-        Box::into_raw(Box::new(config))
+        }
     }
 
     #[no_mangle]
@@ -1020,6 +2590,13 @@ mod export {
         unsafe { Box::from_raw(config) };
     }
 
+    /// Prints the JSON schema of the configuration file, including the option descriptions and
+    /// defaults.
+    #[no_mangle]
+    pub extern "C" fn configfile_showSchema() {
+        println!("{}", config_file_schema());
+    }
+
     #[no_mangle]
     pub extern "C" fn config_new(
         config_file: *const ConfigFileOptions,
@@ -1070,6 +2647,39 @@ mod export {
         config.general.log_level.as_ref().unwrap().to_c_loglevel()
     }
 
+    #[no_mangle]
+    pub extern "C" fn config_getLogFormat(config: *const ConfigOptions) -> LogFormat {
+        assert!(!config.is_null());
+        let config = unsafe { &*config };
+        config.general.log_format.unwrap()
+    }
+
+    /// Returns the log filter, or NULL if there isn't one. The filter is owned by the
+    /// config.
+    #[no_mangle]
+    pub extern "C" fn config_getLogFilter(config: *const ConfigOptions) -> *const LogFilter {
+        assert!(!config.is_null());
+        let config = unsafe { &*config };
+
+        match &config.general.log_filter {
+            Some(x) => x,
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Returns the condition that stops the simulation early, or NULL if there isn't one. The
+    /// condition is owned by the config.
+    #[no_mangle]
+    pub extern "C" fn config_getStopWhen(config: *const ConfigOptions) -> *const StopWhen {
+        assert!(!config.is_null());
+        let config = unsafe { &*config };
+
+        match &config.general.stop_when {
+            Some(x) => x,
+            None => std::ptr::null(),
+        }
+    }
+
     #[no_mangle]
     pub extern "C" fn config_getHeartbeatInterval(
         config: *const ConfigOptions,
@@ -1087,6 +2697,13 @@ mod export {
             * SIMTIME_ONE_SECOND
     }
 
+    #[no_mangle]
+    pub extern "C" fn config_getHeartbeatFormat(config: *const ConfigOptions) -> HeartbeatFormat {
+        assert!(!config.is_null());
+        let config = unsafe { &*config };
+        config.general.heartbeat_format.unwrap()
+    }
+
     #[no_mangle]
     pub extern "C" fn config_getRunahead(config: *const ConfigOptions) -> c::SimulationTime {
         assert!(!config.is_null());
@@ -1277,69 +2894,55 @@ mod export {
     }
 
     #[no_mangle]
-    pub extern "C" fn config_getSocketRecvBuffer(config: *const ConfigOptions) -> u64 {
-        assert!(!config.is_null());
-        let config = unsafe { &*config };
-
-        config
-            .experimental
-            .socket_recv_buffer
-            .unwrap()
-            .convert(units::SiPrefixUpper::Base)
-            .unwrap()
-            .value() as u64
-    }
-
-    #[no_mangle]
-    pub extern "C" fn config_getSocketSendBuffer(config: *const ConfigOptions) -> u64 {
+    pub extern "C" fn config_getPcapFile(config: *const ConfigOptions) -> *mut libc::c_char {
         assert!(!config.is_null());
         let config = unsafe { &*config };
 
-        config
-            .experimental
-            .socket_send_buffer
-            .unwrap()
-            .convert(units::SiPrefixUpper::Base)
-            .unwrap()
-            .value() as u64
+        match config.general.pcap_file {
+            Some(ref x) => {
+                let x = tilde_expansion(x);
+                CString::into_raw(CString::new(x.to_str().unwrap()).unwrap())
+            }
+            None => std::ptr::null_mut(),
+        }
     }
 
+    /// The capture size of the network-wide capture in `config_getPcapFile()`, from the global
+    /// host defaults.
     #[no_mangle]
-    pub extern "C" fn config_getSocketSendAutotune(config: *const ConfigOptions) -> bool {
+    pub extern "C" fn config_getPcapCaptureSize(config: *const ConfigOptions) -> u64 {
         assert!(!config.is_null());
         let config = unsafe { &*config };
 
-        config.experimental.socket_send_autotune.unwrap()
+        pcap_capture_size(&config.host_defaults)
     }
 
+    /// Returns a copy of the network-wide capture's filter from the global host defaults, or
+    /// NULL if all packets should be captured. The filter must be freed using
+    /// `pcapfilter_free()`.
     #[no_mangle]
-    pub extern "C" fn config_getSocketRecvAutotune(config: *const ConfigOptions) -> bool {
+    pub extern "C" fn config_getPcapFilter(config: *const ConfigOptions) -> *mut PcapFilter {
         assert!(!config.is_null());
         let config = unsafe { &*config };
 
-        config.experimental.socket_recv_autotune.unwrap()
+        pcap_filter(&config.host_defaults)
     }
 
     #[no_mangle]
-    pub extern "C" fn config_getInterfaceBuffer(config: *const ConfigOptions) -> u64 {
+    pub extern "C" fn config_getPcapStartTime(config: *const ConfigOptions) -> c::SimulationTime {
         assert!(!config.is_null());
         let config = unsafe { &*config };
 
-        config
-            .experimental
-            .interface_buffer
-            .unwrap()
-            .convert(units::SiPrefixUpper::Base)
-            .unwrap()
-            .value()
+        pcap_start_time(&config.host_defaults)
     }
 
+    /// Returns `SIMTIME_MAX` if packets should be captured until the end of the simulation.
     #[no_mangle]
-    pub extern "C" fn config_getInterfaceQdisc(config: *const ConfigOptions) -> QDiscMode {
+    pub extern "C" fn config_getPcapStopTime(config: *const ConfigOptions) -> c::SimulationTime {
         assert!(!config.is_null());
         let config = unsafe { &*config };
 
-        config.experimental.interface_qdisc.unwrap()
+        pcap_stop_time(&config.host_defaults)
     }
 
     #[no_mangle]
@@ -1426,6 +3029,49 @@ mod export {
         *host.quantity
     }
 
+    /// Returns the hostname of the host with the given counter, where `name` is the name of the
+    /// host in the config. The counter starts at 1, and is ignored if the host's quantity is 1.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getHostname(
+        host: *const HostOptions,
+        name: *const libc::c_char,
+        index: libc::c_uint,
+    ) -> *mut libc::c_char {
+        assert!(!host.is_null());
+        assert!(!name.is_null());
+        let host = unsafe { &*host };
+        let name = unsafe { CStr::from_ptr(name) }.to_str().unwrap();
+
+        let hostname = match (&host.hostname_template, *host.quantity) {
+            (_, 1) => name.to_string(),
+            (Some(template), _) => template.format(index),
+            (None, _) => format!("{}{}", name, index),
+        };
+
+        CString::into_raw(CString::new(hostname).unwrap())
+    }
+
+    /// Returns -1 if the host wasn't assigned to a network graph node.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getNetworkNodeId(host: *const HostOptions) -> i64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.network_node_id.map(i64::from).unwrap_or(-1)
+    }
+
+    /// Returns NULL if the host has no exact IP address. Caller must free returned string.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getIpAddr(host: *const HostOptions) -> *mut libc::c_char {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.ip_addr {
+            Some(x) => CString::into_raw(CString::new(x.to_string()).unwrap()),
+            None => std::ptr::null_mut(),
+        }
+    }
+
     #[no_mangle]
     pub extern "C" fn hostoptions_getLogLevel(host: *const HostOptions) -> c_log::LogLevel {
         assert!(!host.is_null());
@@ -1437,6 +3083,14 @@ mod export {
         }
     }
 
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getLogToFile(host: *const HostOptions) -> bool {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.log_to_file.unwrap()
+    }
+
     #[no_mangle]
     pub extern "C" fn hostoptions_getHeartbeatLogLevel(
         host: *const HostOptions,
@@ -1480,6 +3134,14 @@ mod export {
             * SIMTIME_ONE_SECOND
     }
 
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getHeartbeatFormat(host: *const HostOptions) -> HeartbeatFormat {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.heartbeat_format.unwrap()
+    }
+
     #[no_mangle]
     pub extern "C" fn hostoptions_getPcapDirectory(host: *const HostOptions) -> *mut libc::c_char {
         assert!(!host.is_null());
@@ -1494,10 +3156,78 @@ mod export {
         }
     }
 
-    #[no_mangle]
-    pub extern "C" fn hostoptions_getIpAddressHint(host: *const HostOptions) -> *mut libc::c_char {
-        assert!(!host.is_null());
-        let host = unsafe { &*host };
+    fn pcap_capture_size(options: &HostDefaultOptions) -> u64 {
+        options
+            .pcap_capture_size
+            .unwrap()
+            .convert(units::SiPrefixUpper::Base)
+            .unwrap()
+            .value()
+    }
+
+    fn pcap_filter(options: &HostDefaultOptions) -> *mut PcapFilter {
+        match &options.pcap_filter {
+            Some(filter) => Box::into_raw(Box::new(filter.clone())),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    fn pcap_start_time(options: &HostDefaultOptions) -> c::SimulationTime {
+        options
+            .pcap_start_time
+            .unwrap()
+            .convert(units::TimePrefixUpper::Sec)
+            .unwrap()
+            .value()
+            * SIMTIME_ONE_SECOND
+    }
+
+    fn pcap_stop_time(options: &HostDefaultOptions) -> c::SimulationTime {
+        match options.pcap_stop_time {
+            Some(x) => x.convert(units::TimePrefixUpper::Sec).unwrap().value() * SIMTIME_ONE_SECOND,
+            None => SIMTIME_MAX,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getPcapCaptureSize(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        pcap_capture_size(&host.options)
+    }
+
+    /// Returns a copy of the host's capture filter, or NULL if all packets should be captured.
+    /// The filter must be freed using `pcapfilter_free()`.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getPcapFilter(host: *const HostOptions) -> *mut PcapFilter {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        pcap_filter(&host.options)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getPcapStartTime(host: *const HostOptions) -> c::SimulationTime {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        pcap_start_time(&host.options)
+    }
+
+    /// Returns `SIMTIME_MAX` if packets should be captured until the end of the simulation.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getPcapStopTime(host: *const HostOptions) -> c::SimulationTime {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        pcap_stop_time(&host.options)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getIpAddressHint(host: *const HostOptions) -> *mut libc::c_char {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
 
         match host.options.ip_address_hint.as_ref() {
             Some(x) => CString::into_raw(CString::new(x.to_string()).unwrap()),
@@ -1529,6 +3259,222 @@ mod export {
         }
     }
 
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getFilesystemOverlay(host: *const HostOptions) -> bool {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.filesystem_overlay.unwrap()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getFilesystemBase(host: *const HostOptions) -> *mut libc::c_char {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match &host.options.filesystem_base {
+            Some(base) => {
+                let base = std::env::current_dir().unwrap().join(tilde_expansion(base));
+                CString::into_raw(CString::new(base.to_str().unwrap()).unwrap())
+            }
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getDiskReadLatency(
+        host: *const HostOptions,
+    ) -> c::SimulationTime {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.disk_read_latency {
+            Some(x) => x.convert(units::TimePrefix::Nano).unwrap().value() * SIMTIME_ONE_NANOSECOND,
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getDiskReadBandwidth(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.disk_read_bandwidth {
+            Some(x) => x.convert(units::SiPrefixUpper::Base).unwrap().value(),
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getDiskWriteLatency(
+        host: *const HostOptions,
+    ) -> c::SimulationTime {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.disk_write_latency {
+            Some(x) => x.convert(units::TimePrefix::Nano).unwrap().value() * SIMTIME_ONE_NANOSECOND,
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getDiskWriteBandwidth(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.disk_write_bandwidth {
+            Some(x) => x.convert(units::SiPrefixUpper::Base).unwrap().value(),
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getDiskSyncLatency(
+        host: *const HostOptions,
+    ) -> c::SimulationTime {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.disk_sync_latency {
+            Some(x) => x.convert(units::TimePrefix::Nano).unwrap().value() * SIMTIME_ONE_NANOSECOND,
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getMemoryLimit(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.memory_limit {
+            Some(x) => x.convert(units::SiPrefixUpper::Base).unwrap().value(),
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getMemoryLimitAction(
+        host: *const HostOptions,
+    ) -> MemoryLimitAction {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.memory_limit_action.unwrap()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getCpuCores(host: *const HostOptions) -> u32 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.cpu_cores.unwrap().get()
+    }
+
+    /// Returns the CPU frequency in KHz, or 0 if the real CPU frequency should be used.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getCpuFrequency(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.cpu_frequency.unwrap_or(0)
+    }
+
+    /// Returns the CPU threshold in microseconds, or 0 if disabled.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getCpuThreshold(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.cpu_threshold {
+            Some(x) => x.convert_lossy(units::TimePrefix::Micro).value(),
+            None => 0,
+        }
+    }
+
+    /// Returns the CPU precision in microseconds, or 0 if disabled.
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getCpuPrecision(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        match host.options.cpu_precision {
+            Some(x) => x.convert_lossy(units::TimePrefix::Micro).value(),
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getSocketSendBuffer(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options
+            .socket_send_buffer
+            .unwrap()
+            .convert(units::SiPrefixUpper::Base)
+            .unwrap()
+            .value()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getSocketSendAutotune(host: *const HostOptions) -> bool {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.socket_send_autotune.unwrap()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getSocketRecvBuffer(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options
+            .socket_recv_buffer
+            .unwrap()
+            .convert(units::SiPrefixUpper::Base)
+            .unwrap()
+            .value()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getSocketRecvAutotune(host: *const HostOptions) -> bool {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.socket_recv_autotune.unwrap()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getInterfaceBuffer(host: *const HostOptions) -> u64 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options
+            .interface_buffer
+            .unwrap()
+            .convert(units::SiPrefixUpper::Base)
+            .unwrap()
+            .value()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getInterfaceQdisc(host: *const HostOptions) -> QDiscMode {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.interface_qdisc.unwrap()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn hostoptions_getInterfaceMtu(host: *const HostOptions) -> u32 {
+        assert!(!host.is_null());
+        let host = unsafe { &*host };
+
+        host.options.interface_mtu.unwrap()
+    }
+
     #[no_mangle]
     pub extern "C" fn hostoptions_getBandwidthDown(host: *const HostOptions) -> u64 {
         assert!(!host.is_null());
@@ -1572,13 +3518,46 @@ mod export {
         }
     }
 
+    /// `host_ip` may be NULL if the host has no IP address. The returned pointer must be freed
+    /// with `processvariables_free()`.
+    #[no_mangle]
+    pub extern "C" fn processvariables_new(
+        hostname: *const libc::c_char,
+        host_index: u32,
+        host_ip: *const libc::c_char,
+        seed: libc::c_uint,
+    ) -> *mut ProcessVariables {
+        assert!(!hostname.is_null());
+        let hostname = unsafe { CStr::from_ptr(hostname) }.to_str().unwrap();
+        let host_ip = match host_ip.is_null() {
+            false => unsafe { CStr::from_ptr(host_ip) }.to_str().unwrap(),
+            true => "",
+        };
+
+        Box::into_raw(Box::new(ProcessVariables::new(
+            hostname, host_index, host_ip, seed,
+        )))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processvariables_free(vars: *mut ProcessVariables) {
+        if !vars.is_null() {
+            unsafe { Box::from_raw(vars) };
+        }
+    }
+
     /// Will return a NULL pointer if the path does not exist.
     #[no_mangle]
-    pub extern "C" fn processoptions_getPath(proc: *const ProcessOptions) -> *mut libc::c_char {
+    pub extern "C" fn processoptions_getPath(
+        proc: *const ProcessOptions,
+        vars: *const ProcessVariables,
+    ) -> *mut libc::c_char {
         assert!(!proc.is_null());
+        assert!(!vars.is_null());
         let proc = unsafe { &*proc };
+        let vars = unsafe { &*vars };
 
-        let expanded = tilde_expansion(&proc.path.to_str().unwrap());
+        let expanded = tilde_expansion(&vars.substitute(proc.path.to_str().unwrap()));
 
         match expanded.canonicalize() {
             Ok(path) => CString::into_raw(CString::new(path.to_str().unwrap()).unwrap()),
@@ -1601,32 +3580,47 @@ mod export {
     #[no_mangle]
     pub extern "C" fn processoptions_getArgs(
         proc: *const ProcessOptions,
+        vars: *const ProcessVariables,
         f: unsafe extern "C" fn(*const libc::c_char, *mut libc::c_void),
         data: *mut libc::c_void,
     ) {
         assert!(!proc.is_null());
+        assert!(!vars.is_null());
         let proc = unsafe { &*proc };
+        let vars = unsafe { &*vars };
 
-        let args = match &proc.args {
+        let args: Vec<OsString> = match &proc.args {
             ProcessArgs::List(x) => x.iter().map(|y| OsStr::new(y).to_os_string()).collect(),
             ProcessArgs::Str(x) => parse_string_as_args(OsStr::new(&x.trim())).unwrap(),
         };
 
         for arg in &args {
+            // substitute after parsing so that values containing spaces stay a single argument
+            let arg = vars.substitute(arg.to_str().unwrap());
             // bind the string to a local variable so it's not dropped before f() runs
-            let arg = CString::new(arg.as_bytes()).unwrap();
+            let arg = CString::new(arg).unwrap();
             unsafe { f(arg.as_c_str().as_ptr(), data) }
         }
     }
 
+    /// Calls `f` with the key and value of each environment variable.
     #[no_mangle]
     pub extern "C" fn processoptions_getEnvironment(
         proc: *const ProcessOptions,
-    ) -> *mut libc::c_char {
+        vars: *const ProcessVariables,
+        f: unsafe extern "C" fn(*const libc::c_char, *const libc::c_char, *mut libc::c_void),
+        data: *mut libc::c_void,
+    ) {
         assert!(!proc.is_null());
+        assert!(!vars.is_null());
         let proc = unsafe { &*proc };
+        let vars = unsafe { &*vars };
 
-        CString::into_raw(CString::new(proc.environment.clone()).unwrap())
+        for (key, value) in proc.environment.pairs() {
+            let key = CString::new(key).unwrap();
+            let value = CString::new(vars.substitute(&value)).unwrap();
+            unsafe { f(key.as_ptr(), value.as_ptr(), data) }
+        }
     }
 
     #[no_mangle]
@@ -1662,4 +3656,165 @@ mod export {
             None => 0,
         }
     }
+
+    /// Returns the path of the file that the process reads from its stdin, or NULL if its stdin
+    /// isn't read from a file. Caller must free the returned string with
+    /// `processoptions_freeString()`.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getStdinPath(
+        proc: *const ProcessOptions,
+    ) -> *mut libc::c_char {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match &proc.stdin {
+            Some(ProcessStdin::Path(path)) => {
+                let expanded = tilde_expansion(path.to_str().unwrap());
+                CString::into_raw(CString::new(expanded.as_os_str().as_bytes()).unwrap())
+            }
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    /// Returns the input that the process reads from its stdin, or NULL if it isn't given in
+    /// the config. Caller must free the returned string with `processoptions_freeString()`.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getStdinContent(
+        proc: *const ProcessOptions,
+    ) -> *mut libc::c_char {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match &proc.stdin {
+            Some(ProcessStdin::Content(content)) => {
+                CString::into_raw(CString::new(content.as_str()).unwrap())
+            }
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getStraceLogging(proc: *const ProcessOptions) -> bool {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        proc.strace_logging.unwrap_or(false)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getTerminatesSimulation(proc: *const ProcessOptions) -> bool {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        proc.terminates_simulation.unwrap_or(false)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getShutdownSignal(proc: *const ProcessOptions) -> libc::c_int {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match proc.shutdown_signal {
+            Some(signal) => signal.as_raw(),
+            None => libc::SIGTERM,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getShutdownGracePeriod(
+        proc: *const ProcessOptions,
+    ) -> c::SimulationTime {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match proc.shutdown_grace_period {
+            Some(x) => x.convert(units::TimePrefix::Nano).unwrap().value() * SIMTIME_ONE_NANOSECOND,
+            None => 10 * SIMTIME_ONE_SECOND,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getRestartPolicy(
+        proc: *const ProcessOptions,
+    ) -> RestartPolicy {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        proc.restart.unwrap_or(RestartPolicy::Never)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processoptions_getRestartDelay(
+        proc: *const ProcessOptions,
+    ) -> c::SimulationTime {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match proc.restart_delay {
+            Some(x) => x.convert(units::TimePrefix::Nano).unwrap().value() * SIMTIME_ONE_NANOSECOND,
+            None => SIMTIME_ONE_SECOND,
+        }
+    }
+
+    /// Returns -1 if the process can be restarted any number of times.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getMaxRestarts(proc: *const ProcessOptions) -> libc::c_int {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match proc.max_restarts {
+            Some(x) => libc::c_int::try_from(x).unwrap_or(libc::c_int::MAX),
+            None => -1,
+        }
+    }
+
+    /// Returns NULL if the process has no name. Caller must free the returned string with
+    /// `processoptions_freeString()`.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getName(proc: *const ProcessOptions) -> *mut libc::c_char {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match &proc.name {
+            Some(name) => CString::into_raw(CString::new(name.as_str()).unwrap()),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Returns NULL if the process has no start conditions. Caller must free the returned
+    /// pointer with `startconditions_free()`.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getStartAfter(
+        proc: *const ProcessOptions,
+    ) -> *mut StartConditions {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        if proc.start_after.is_empty() {
+            return std::ptr::null_mut();
+        }
+        Box::into_raw(Box::new(StartConditions::new(proc.start_after.clone())))
+    }
+
+    /// Returns NULL if the process has no expected final state. Caller must free the returned
+    /// pointer with `processfinalstate_free()`.
+    #[no_mangle]
+    pub extern "C" fn processoptions_getExpectedFinalState(
+        proc: *const ProcessOptions,
+    ) -> *mut ProcessFinalState {
+        assert!(!proc.is_null());
+        let proc = unsafe { &*proc };
+
+        match proc.expected_final_state {
+            Some(state) => Box::into_raw(Box::new(state)),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn processfinalstate_free(state: *mut ProcessFinalState) {
+        if !state.is_null() {
+            drop(unsafe { Box::from_raw(state) });
+        }
+    }
 }
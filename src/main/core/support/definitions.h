@@ -172,6 +172,22 @@ typedef guint64 EmulatedTime;
  */
 #define CONFIG_HEADER_SIZE_UDPIPETH 42
 
+/**
+ * Size of the UDP part of CONFIG_HEADER_SIZE_UDPIPETH, which IP fragments
+ * other than the first don't have
+ */
+#define CONFIG_HEADER_SIZE_UDP 8
+
+/**
+ * Size of the IP header in an IP packet, without options
+ */
+#define CONFIG_HEADER_SIZE_IP 20
+
+/**
+ * Size of the TCP header (with options) in CONFIG_HEADER_SIZE_TCPIPETH
+ */
+#define CONFIG_HEADER_SIZE_TCP 32
+
 /**
  * Header size of a packet with TCP encapsulation
  * 14 bytes eth2, 20 bytes IP, 32 bytes UDP
@@ -184,6 +200,24 @@ typedef guint64 EmulatedTime;
  */
 #define CONFIG_MTU 1500
 
+/**
+ * Smallest and largest MTU that can be configured for a network interface
+ */
+#define CONFIG_MTU_MIN 68
+#define CONFIG_MTU_MAX 65535
+
+/**
+ * MTU of loopback interfaces, which is large enough that datagrams sent on
+ * them are never fragmented
+ */
+#define CONFIG_LOOPBACK_MTU 65536
+
+/**
+ * Maximum time that the fragments of an incomplete IP datagram are kept
+ * waiting for the rest of its fragments
+ */
+#define CONFIG_IP_FRAGMENT_TIMEOUT (30 * SIMTIME_ONE_SECOND)
+
 /**
  * Maximum size of a datagram we are allowed to send out over the network
  */
@@ -85,6 +85,7 @@ void event_execute(Event* event) {
         host_continueExecutionTimer(event->dstHost);
         task_execute(event->task, event->dstHost);
         host_stopExecutionTimer(event->dstHost);
+        host_countProcessedEvent(event->dstHost);
     }
 
     worker_setActiveHost(NULL);
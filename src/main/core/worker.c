@@ -369,6 +369,18 @@ int worker_getAffinity() {
 
 DNS* worker_getDNS() { return manager_getDNS(_worker_pool()->manager); }
 
+SimStats* worker_getSimStats() { return manager_getSimStats(_worker_pool()->manager); }
+
+StartAfter* worker_getStartAfter() { return manager_getStartAfter(_worker_pool()->manager); }
+
+void worker_addWaitingProcess(Process* proc) {
+    manager_addWaitingProcess(_worker_pool()->manager, proc);
+}
+
+StopCondition* worker_getStopCondition() {
+    return manager_getStopCondition(_worker_pool()->manager);
+}
+
 Address* worker_resolveIPToAddress(in_addr_t ip) {
     DNS* dns = worker_getDNS();
     return dns_resolveIPToAddress(dns, ip);
@@ -575,6 +587,7 @@ void worker_sendPacket(Host* srcHost, Packet* packet) {
         scheduler_push(scheduler, packetEvent, srcHost, dstHost);
     } else {
         packet_addDeliveryStatus(packet, PDS_INET_DROPPED);
+        host_countDroppedPacket(srcHost, "path_loss");
     }
 }
 
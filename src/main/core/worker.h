@@ -72,6 +72,12 @@ void worker_setRoundEndTime(SimulationTime newRoundEndTime);
 
 int worker_getAffinity();
 DNS* worker_getDNS();
+SimStats* worker_getSimStats();
+StartAfter* worker_getStartAfter();
+// Hands a process whose start_after conditions aren't met to the manager, which
+// starts it at the end of the first round in which they are.
+void worker_addWaitingProcess(Process* proc);
+StopCondition* worker_getStopCondition();
 Topology* worker_getTopology();
 ChildPidWatcher* worker_getChildPidWatcher();
 const ConfigOptions* worker_getConfig();
@@ -23,7 +23,6 @@ struct ProcessInfo {
 }
 
 struct ThreadInfo {
-    #[allow(dead_code)]
     id: ThreadId,
     native_tid: Pid,
 }
@@ -176,6 +175,10 @@ impl Worker {
         Worker::with(|w| w.active_process_info.as_ref().map(|p| p.id)).flatten()
     }
 
+    pub fn active_thread_id() -> Option<ThreadId> {
+        Worker::with(|w| w.active_thread_info.as_ref().map(|t| t.id)).flatten()
+    }
+
     pub fn active_thread_native_tid() -> Option<nix::unistd::Pid> {
         Worker::with(|w| w.active_thread_info.as_ref().map(|t| t.native_tid)).flatten()
     }
@@ -19,20 +19,26 @@ struct _CPU {
     SimulationTime threshold;
     SimulationTime precision;
     SimulationTime now;
-    SimulationTime timeCPUAvailable;
+    /* the time at which each virtual core becomes available */
+    guint cores;
+    SimulationTime* timeCoreAvailable;
     MAGIC_DECLARE;
 };
 
-CPU* cpu_new(guint64 frequencyKHz, guint64 rawFrequencyKHz, guint64 threshold, guint64 precision) {
+CPU* cpu_new(guint64 frequencyKHz, guint64 rawFrequencyKHz, guint64 threshold, guint64 precision,
+             guint cores) {
     utility_assert(frequencyKHz > 0);
     utility_assert(rawFrequencyKHz > 0);
+    utility_assert(cores > 0);
     CPU* cpu = g_new0(CPU, 1);
     MAGIC_INIT(cpu);
 
     cpu->frequencyKHz = frequencyKHz;
     cpu->threshold = threshold > 0 ? (threshold * SIMTIME_ONE_MICROSECOND) : SIMTIME_INVALID;
     cpu->precision = precision > 0 ? (precision * SIMTIME_ONE_MICROSECOND) : SIMTIME_INVALID;
-    cpu->timeCPUAvailable = cpu->now = 0;
+    cpu->now = 0;
+    cpu->cores = cores;
+    cpu->timeCoreAvailable = g_new0(SimulationTime, cores);
 
     cpu->rawFrequencyKHz = rawFrequencyKHz;
     cpu->frequencyRatio = (gdouble)((gdouble)cpu->rawFrequencyKHz) / ((gdouble)cpu->frequencyKHz);
@@ -42,21 +48,57 @@ CPU* cpu_new(guint64 frequencyKHz, guint64 rawFrequencyKHz, guint64 threshold, g
 
 void cpu_free(CPU* cpu) {
     MAGIC_ASSERT(cpu);
+    g_free(cpu->timeCoreAvailable);
     MAGIC_CLEAR(cpu);
     g_free(cpu);
 }
 
+guint cpu_getCores(CPU* cpu) {
+    MAGIC_ASSERT(cpu);
+    return cpu->cores;
+}
+
+/* Returns the index of the core that becomes available the earliest. Ties go to
+ * the lowest index so that the choice is deterministic. */
+static guint _cpu_getEarliestCore(CPU* cpu) {
+    guint earliest = 0;
+    for (guint i = 1; i < cpu->cores; i++) {
+        if (cpu->timeCoreAvailable[i] < cpu->timeCoreAvailable[earliest]) {
+            earliest = i;
+        }
+    }
+    return earliest;
+}
+
 SimulationTime cpu_getDelay(CPU* cpu) {
     MAGIC_ASSERT(cpu);
 
-    /* we only have delay if we've crossed the threshold */
-    SimulationTime builtUpDelay = cpu->timeCPUAvailable - cpu->now;
+    /* we only have delay if we've crossed the threshold on every core, since
+     * otherwise there is still a core available to run the next task */
+    SimulationTime builtUpDelay =
+        cpu->timeCoreAvailable[_cpu_getEarliestCore(cpu)] - cpu->now;
     if(builtUpDelay > cpu->threshold) {
         return builtUpDelay;
     }
     return 0;
 }
 
+/* Returns how long a thread whose previous work finishes at `threadAvailableTime`
+ * must wait before it continues. A thread only runs on one core at a time, so
+ * unlike the host's other events it can't move on to a free core. */
+SimulationTime cpu_getThreadDelay(CPU* cpu, SimulationTime threadAvailableTime) {
+    MAGIC_ASSERT(cpu);
+    if (cpu->threshold == SIMTIME_INVALID || threadAvailableTime <= cpu->now) {
+        return 0;
+    }
+
+    SimulationTime builtUpDelay = threadAvailableTime - cpu->now;
+    if (builtUpDelay > cpu->threshold) {
+        return builtUpDelay;
+    }
+    return 0;
+}
+
 gboolean cpu_isBlocked(CPU* cpu) {
     MAGIC_ASSERT(cpu);
     if(cpu->threshold == SIMTIME_INVALID) {
@@ -72,10 +114,14 @@ void cpu_updateTime(CPU* cpu, SimulationTime now) {
     /* the time available is now if we have no delay, otherwise no change
      * this is important so that our delay is added from now or into the future
      */
-    cpu->timeCPUAvailable = (SimulationTime) MAX(cpu->timeCPUAvailable, now);
+    for (guint i = 0; i < cpu->cores; i++) {
+        cpu->timeCoreAvailable[i] = (SimulationTime)MAX(cpu->timeCoreAvailable[i], now);
+    }
 }
 
-void cpu_addDelay(CPU* cpu, SimulationTime delay) {
+/* Charges `delay` of work, which can't start before `notBefore`, to the host's
+ * cores. Returns the time at which the work is done. */
+SimulationTime cpu_addDelay(CPU* cpu, SimulationTime delay, SimulationTime notBefore) {
     MAGIC_ASSERT(cpu);
 
     /* first normalize the physical CPU to the virtual CPU */
@@ -96,5 +142,9 @@ void cpu_addDelay(CPU* cpu, SimulationTime delay) {
         }
     }
 
-    cpu->timeCPUAvailable += adjustedDelay;
+    /* the work runs on whichever core frees up first */
+    guint core = _cpu_getEarliestCore(cpu);
+    SimulationTime start = MAX(cpu->timeCoreAvailable[core], notBefore);
+    cpu->timeCoreAvailable[core] = start + adjustedDelay;
+    return cpu->timeCoreAvailable[core];
 }
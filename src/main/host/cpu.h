@@ -13,12 +13,16 @@
 
 typedef struct _CPU CPU;
 
-CPU* cpu_new(guint64 frequencyKHz, guint64 rawFrequencyKHz, guint64 threshold, guint64 precision);
+CPU* cpu_new(guint64 frequencyKHz, guint64 rawFrequencyKHz, guint64 threshold, guint64 precision,
+             guint cores);
 void cpu_free(CPU* cpu);
 
+guint cpu_getCores(CPU* cpu);
+
 gboolean cpu_isBlocked(CPU* cpu);
 void cpu_updateTime(CPU* cpu, SimulationTime now);
-void cpu_addDelay(CPU* cpu, SimulationTime delay);
+SimulationTime cpu_addDelay(CPU* cpu, SimulationTime delay, SimulationTime notBefore);
 SimulationTime cpu_getDelay(CPU* cpu);
+SimulationTime cpu_getThreadDelay(CPU* cpu, SimulationTime threadAvailableTime);
 
 #endif /* SHD_CPU_H_ */
@@ -28,6 +28,9 @@ struct _CompatSocket {
     CompatSocketObject object;
 };
 
+/* a callback used when iterating over a collection of sockets */
+typedef void (*CompatSocketIterFunc)(const CompatSocket* socket, void* data);
+
 CompatSocket compatsocket_fromLegacySocket(Socket* socket);
 
 /* reference counting */
@@ -26,7 +26,11 @@
 #include "lib/logger/logger.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
+#include "main/host/dir_listing.h"
+#include "main/host/fs_overlay.h"
 #include "main/host/host.h"
+#include "main/host/process.h"
+#include "main/host/procfs.h"
 #include "main/host/syscall/kernel_types.h"
 #include "main/routing/dns.h"
 #include "main/utility/random.h"
@@ -41,12 +45,15 @@ enum _FileType {
     FILE_TYPE_RANDOM,    // special handling for /dev/random etc.
     FILE_TYPE_HOSTS,     // special handling for /etc/hosts
     FILE_TYPE_LOCALTIME, // special handling for /etc/localtime
+    FILE_TYPE_PROC,      // special handling for /proc
 };
 
 struct _File {
     /* File is a sub-type of a descriptor. */
     LegacyDescriptor super;
     FileType type;
+    /* Whether io on the file is charged to the simulated host's disk. */
+    bool isDiskBacked;
     /* Info related to our OS-backed file. */
     struct {
         int fd;
@@ -54,6 +61,10 @@ struct _File {
         mode_t mode;
         char* abspath;
     } osfile;
+    /* If the file is a directory whose entries, as seen by the plugin, differ from those of the
+     * native directory, the entries and the index of the next entry to return from getdents. */
+    DirListing* dirListing;
+    off_t dirOffset;
     MAGIC_DECLARE;
 };
 
@@ -85,6 +96,27 @@ static inline int _file_getOSBackedFD(File* file) {
 }
 int file_getOSBackedFD(File* file) { return _file_getOSBackedFD(file); }
 
+bool file_isDiskBacked(File* file) {
+    MAGIC_ASSERT(file);
+    return file->isDiskBacked;
+}
+
+size_t file_getBytesUntilEOF(File* file, off_t offset) {
+    MAGIC_ASSERT(file);
+
+    if (offset < 0) {
+        offset = lseek(_file_getOSBackedFD(file), 0, SEEK_CUR);
+    }
+
+    struct stat statbuf = {0};
+    if (offset < 0 || fstat(_file_getOSBackedFD(file), &statbuf) < 0 ||
+        statbuf.st_size <= offset) {
+        return 0;
+    }
+
+    return (size_t)(statbuf.st_size - offset);
+}
+
 static void _file_closeHelper(File* file) {
     if (file && file->osfile.fd != OSFILE_INVALID) {
         trace("On file %i, closing os-backed file %i", _file_getFD(file),
@@ -124,6 +156,10 @@ static void _file_free(LegacyDescriptor* desc) {
         free(file->osfile.abspath);
     }
 
+    if (file->dirListing) {
+        dirlisting_unref(file->dirListing);
+    }
+
     descriptor_clear((LegacyDescriptor*)file);
     MAGIC_CLEAR(file);
     free(file);
@@ -169,6 +205,7 @@ File* file_dup(File* file, int* dupError) {
     File* newFile = file_new();
 
     newFile->type = file->type;
+    newFile->isDiskBacked = file->isDiskBacked;
 
     newFile->osfile.fd = newFd;
     newFile->osfile.flags = file->osfile.flags;
@@ -178,6 +215,12 @@ File* file_dup(File* file, int* dupError) {
     // CLOEXEC is a descriptor flag and it is not copied during a dup()
     newFile->osfile.flags &= ~O_CLOEXEC;
 
+    if (file->dirListing) {
+        dirlisting_ref(file->dirListing);
+        newFile->dirListing = file->dirListing;
+        newFile->dirOffset = file->dirOffset;
+    }
+
     return newFile;
 }
 
@@ -252,8 +295,33 @@ static void _file_print_flags(int flags) {
 #undef CHECK_FLAG
 #endif
 
+/* Regenerates the file's directory listing if it is a directory whose entries, as seen by the
+ * plugin, are not the entries of the native directory. */
+static void _file_refreshDirListing(File* file) {
+    if (file->dirListing) {
+        dirlisting_unref(file->dirListing);
+        file->dirListing = NULL;
+    }
+
+    Process* proc = descriptor_getOwnerProcess(&file->super);
+    struct stat statbuf;
+    if (!proc || !file->osfile.abspath || fstat(file->osfile.fd, &statbuf) < 0 ||
+        !S_ISDIR(statbuf.st_mode)) {
+        return;
+    }
+
+    /* the host's filesystem overlay merges the base and upper directories */
+    FsOverlay* overlay = host_getFsOverlay(process_getHost(proc));
+    if (overlay && file->type == FILE_TYPE_REGULAR) {
+        file->dirListing = fsoverlay_listDirectory(overlay, file->osfile.abspath, true);
+    } else if (file->type == FILE_TYPE_PROC) {
+        /* /proc only lists the entries that we emulate */
+        file->dirListing = procfs_listDirectory(process_getHost(proc), proc, file->osfile.abspath);
+    }
+}
+
 int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t mode,
-                const char* workingDir) {
+                const char* workingDir, Host* host) {
     MAGIC_ASSERT(file);
     utility_assert(file->osfile.fd == OSFILE_INVALID);
 
@@ -286,6 +354,8 @@ int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t m
         }
     } else if (!strcmp("/etc/localtime", abspath)) {
         file->type = FILE_TYPE_LOCALTIME;
+    } else if (procfs_isProcPath(abspath) && descriptor_getOwnerProcess(&file->super)) {
+        file->type = FILE_TYPE_PROC;
     } else {
         file->type = FILE_TYPE_REGULAR;
     }
@@ -297,12 +367,36 @@ int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t m
         // timezone info in the correct format for UTC.
         osfd = -1;
         errcode = ENOENT;
+    } else if (file->type == FILE_TYPE_PROC) {
+        // Generate the contents from the state of the simulated host.
+        Process* proc = descriptor_getOwnerProcess(&file->super);
+        osfd = procfs_open(process_getHost(proc), proc, abspath, flags, mode);
+        if (osfd < 0) {
+            errcode = -osfd;
+            osfd = -1;
+        }
     } else {
+        // The plugin sees the host's view of the filesystem, which may differ from the native
+        // one. We keep the plugin's path in abspath so that relative lookups are consistent.
+        FsOverlay* overlay = host ? host_getFsOverlay(host) : NULL;
+        char* nativePath = abspath;
+        if (overlay && file->type == FILE_TYPE_REGULAR) {
+            bool isWrite = (flags & O_ACCMODE) != O_RDONLY || (flags & (O_CREAT | O_TRUNC));
+            nativePath =
+                fsoverlay_resolve(overlay, abspath,
+                                  isWrite ? FSOVERLAY_ACCESS_WRITE : FSOVERLAY_ACCESS_READ,
+                                  !(flags & O_NOFOLLOW));
+        }
+
         // TODO: we should open the os-backed file in non-blocking mode even if a
         // non-block is not requested, and then properly handle the io by, e.g.,
         // epolling on all such files with a shadow support thread.
-        osfd = open(abspath, flags, mode);
+        osfd = open(nativePath, flags, mode);
         errcode = errno;
+
+        if (nativePath != abspath) {
+            free(nativePath);
+        }
     }
 
     if (osfd < 0) {
@@ -321,6 +415,11 @@ int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t m
     file->osfile.flags = flags;
     file->osfile.mode = mode;
 
+    /* Shadow's own output files (opened without a host) are not charged to the host's disk. */
+    file->isDiskBacked = host != NULL && file->type == FILE_TYPE_REGULAR;
+
+    _file_refreshDirListing(file);
+
     trace("File %i opened os-backed file %i at absolute path %s",
           _file_getFD(file), _file_getOSBackedFD(file), file->osfile.abspath);
 
@@ -331,8 +430,9 @@ int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t m
     return fd;
 }
 
-int file_open(File* file, const char* pathname, int flags, mode_t mode, const char* workingDir) {
-    return file_openat(file, NULL, pathname, flags, mode, workingDir);
+int file_open(File* file, const char* pathname, int flags, mode_t mode, const char* workingDir,
+              Host* host) {
+    return file_openat(file, NULL, pathname, flags, mode, workingDir, host);
 }
 
 static void _file_readRandomBytes(File* file, Host* host, void* buf, size_t numBytes) {
@@ -743,6 +843,26 @@ off_t file_lseek(File* file, off_t offset, int whence) {
     trace("File %i lseek os-backed file %i", _file_getFD(file),
           _file_getOSBackedFD(file));
 
+    if (file->dirListing) {
+        /* the offsets in a generated listing are entry indices */
+        off_t newOffset = -1;
+        if (whence == SEEK_SET) {
+            newOffset = offset;
+        } else if (whence == SEEK_CUR) {
+            newOffset = file->dirOffset + offset;
+        }
+        if (newOffset < 0) {
+            return -EINVAL;
+        }
+
+        /* rewinding the directory shows any entries that changed since it was opened */
+        if (newOffset == 0) {
+            _file_refreshDirListing(file);
+        }
+        file->dirOffset = newOffset;
+        return newOffset;
+    }
+
     ssize_t result = lseek(_file_getOSBackedFD(file), offset, whence);
     return (result < 0) ? -errno : result;
 }
@@ -757,6 +877,10 @@ int file_getdents(File* file, struct linux_dirent* dirp, unsigned int count) {
     trace("File %i getdents os-backed file %i", _file_getFD(file),
           _file_getOSBackedFD(file));
 
+    if (file->dirListing) {
+        return dirlisting_getdents(file->dirListing, &file->dirOffset, dirp, count);
+    }
+
     // getdents is not available for a direct call
     int result =
         (int)syscall(SYS_getdents, _file_getOSBackedFD(file), dirp, count);
@@ -774,6 +898,10 @@ int file_getdents64(File* file, struct linux_dirent64* dirp,
     trace("File %i getdents64 os-backed file %i", _file_getFD(file),
           _file_getOSBackedFD(file));
 
+    if (file->dirListing) {
+        return dirlisting_getdents64(file->dirListing, &file->dirOffset, dirp, count);
+    }
+
     int result =
         (int)syscall(SYS_getdents64, _file_getOSBackedFD(file), dirp, count);
     return (result < 0) ? -errno : result;
@@ -837,17 +965,52 @@ static inline int _file_getOSDirFD(File* dir) {
     }
 }
 
-int file_fstatat(File* dir, const char* pathname, struct stat* statbuf, int flags,
-                 const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+/* Computes the os-backed directory fd and path to pass to a native *at() function in order to
+ * access `pathname` relative to `dir`, as seen by the plugin running on `host`. The directory fd
+ * is returned in `osDirFD`. If the returned path is not the same pointer as `pathname`, it must
+ * be freed by the caller. */
+static const char* _file_getNativeAtPath(File* dir, const char* pathname, const char* workingDir,
+                                         Host* host, FsOverlayAccess access, bool followFinal,
+                                         int* osDirFD) {
+    FsOverlay* overlay = host ? host_getFsOverlay(host) : NULL;
+    *osDirFD = _file_getOSDirFD(dir);
 
-    trace("File %i fstatat os-backed file %i, flags %d", dir ? _file_getFD(dir) : -1, osFd, flags);
+    if (overlay && pathname[0] != '\0') {
+        /* relative lookups must also go through the overlay, so use absolute paths */
+        *osDirFD = -1;
+        char* abspath = _file_getAbsolutePath(dir, pathname, workingDir);
+        char* nativePath = fsoverlay_resolve(overlay, abspath, access, followFinal);
+        free(abspath);
+        return nativePath;
+    }
+
+    if (*osDirFD == AT_FDCWD) {
+        *osDirFD = -1;
+        return _file_getAbsolutePath(NULL, pathname, workingDir);
+    }
+
+    return pathname;
+}
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
+/* Tells the host's filesystem overlay (if any) that `pathname` was removed. */
+static void _file_markRemoved(File* dir, const char* pathname, const char* workingDir,
+                              Host* host) {
+    FsOverlay* overlay = host ? host_getFsOverlay(host) : NULL;
+    if (overlay && pathname[0] != '\0') {
+        char* abspath = _file_getAbsolutePath(dir, pathname, workingDir);
+        fsoverlay_markRemoved(overlay, abspath);
+        free(abspath);
     }
+}
+
+int file_fstatat(File* dir, const char* pathname, struct stat* statbuf, int flags,
+                 const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_READ,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
+
+    trace("File %i fstatat os-backed file %i, flags %d", dir ? _file_getFD(dir) : -1, osFd, flags);
 
     int result = fstatat(osFd, pathnameTmp, statbuf, flags);
 
@@ -859,17 +1022,14 @@ int file_fstatat(File* dir, const char* pathname, struct stat* statbuf, int flag
 }
 
 int file_fchownat(File* dir, const char* pathname, uid_t owner, gid_t group, int flags,
-                  const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+                  const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_WRITE,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
 
     trace("File %i fchownat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = fchownat(osFd, pathnameTmp, owner, group, flags);
 
     if (pathnameTmp != pathname) {
@@ -879,17 +1039,15 @@ int file_fchownat(File* dir, const char* pathname, uid_t owner, gid_t group, int
     return (result < 0) ? -errno : result;
 }
 
-int file_fchmodat(File* dir, const char* pathname, mode_t mode, int flags, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+int file_fchmodat(File* dir, const char* pathname, mode_t mode, int flags, const char* workingDir,
+                  Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_WRITE,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
 
     trace("File %i fchmodat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = fchmodat(osFd, pathnameTmp, mode, flags);
 
     if (pathnameTmp != pathname) {
@@ -900,17 +1058,13 @@ int file_fchmodat(File* dir, const char* pathname, mode_t mode, int flags, const
 }
 
 int file_futimesat(File* dir, const char* pathname, const struct timeval times[2],
-                   const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+                   const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(dir, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, true, &osFd);
 
     trace("File %i futimesat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = futimesat(osFd, pathnameTmp, times);
 
     if (pathnameTmp != pathname) {
@@ -921,17 +1075,14 @@ int file_futimesat(File* dir, const char* pathname, const struct timeval times[2
 }
 
 int file_utimensat(File* dir, const char* pathname, const struct timespec times[2], int flags,
-                   const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+                   const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_WRITE,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
 
     trace("File %i utimesat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = utimensat(osFd, pathnameTmp, times, flags);
 
     if (pathnameTmp != pathname) {
@@ -941,17 +1092,15 @@ int file_utimensat(File* dir, const char* pathname, const struct timespec times[
     return (result < 0) ? -errno : result;
 }
 
-int file_faccessat(File* dir, const char* pathname, int mode, int flags, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+int file_faccessat(File* dir, const char* pathname, int mode, int flags, const char* workingDir,
+                   Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_READ,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
 
     trace("File %i faccessat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = faccessat(osFd, pathnameTmp, mode, flags);
 
     if (pathnameTmp != pathname) {
@@ -961,17 +1110,14 @@ int file_faccessat(File* dir, const char* pathname, int mode, int flags, const c
     return (result < 0) ? -errno : result;
 }
 
-int file_mkdirat(File* dir, const char* pathname, mode_t mode, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+int file_mkdirat(File* dir, const char* pathname, mode_t mode, const char* workingDir,
+                 Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(dir, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, false, &osFd);
 
     trace("File %i mkdirat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = mkdirat(osFd, pathnameTmp, mode);
 
     if (pathnameTmp != pathname) {
@@ -981,17 +1127,14 @@ int file_mkdirat(File* dir, const char* pathname, mode_t mode, const char* worki
     return (result < 0) ? -errno : result;
 }
 
-int file_mknodat(File* dir, const char* pathname, mode_t mode, dev_t dev, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+int file_mknodat(File* dir, const char* pathname, mode_t mode, dev_t dev, const char* workingDir,
+                 Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(dir, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, false, &osFd);
 
     trace("File %i mknodat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = mknodat(osFd, pathnameTmp, mode, dev);
 
     if (pathnameTmp != pathname) {
@@ -1002,24 +1145,18 @@ int file_mknodat(File* dir, const char* pathname, mode_t mode, dev_t dev, const
 }
 
 int file_linkat(File* oldDir, const char* oldPath, File* newDir, const char* newPath, int flags,
-                const char* workingDir) {
-    int oldOsFd = _file_getOSDirFD(oldDir);
-    int newOsFd = _file_getOSDirFD(newDir);
-    const char* oldPathTmp = oldPath;
-    const char* newPathTmp = newPath;
+                const char* workingDir, Host* host) {
+    int oldOsFd = -1, newOsFd = -1;
+    /* the old path is copied up so that the new link is on the same filesystem */
+    const char* oldPathTmp =
+        _file_getNativeAtPath(oldDir, oldPath, workingDir, host, FSOVERLAY_ACCESS_WRITE,
+                              (flags & AT_SYMLINK_FOLLOW) != 0, &oldOsFd);
+    const char* newPathTmp = _file_getNativeAtPath(newDir, newPath, workingDir, host,
+                                                   FSOVERLAY_ACCESS_WRITE, false, &newOsFd);
 
     trace("Files %i, %i linkat os-backed files %i, %i", oldDir ? _file_getFD(oldDir) : -1,
           newDir ? _file_getFD(newDir) : -1, oldOsFd, newOsFd);
 
-    if (oldOsFd == AT_FDCWD) {
-        oldOsFd = -1;
-        oldPathTmp = _file_getAbsolutePath(NULL, oldPath, workingDir);
-    }
-    if (newOsFd == AT_FDCWD) {
-        newOsFd = -1;
-        newPathTmp = _file_getAbsolutePath(NULL, newPath, workingDir);
-    }
-
     int result = linkat(oldOsFd, oldPathTmp, newOsFd, newPathTmp, flags);
 
     if (oldPathTmp != oldPath) {
@@ -1032,37 +1169,63 @@ int file_linkat(File* oldDir, const char* oldPath, File* newDir, const char* new
     return (result < 0) ? -errno : result;
 }
 
-int file_unlinkat(File* dir, const char* pathname, int flags, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+/* Returns -ENOTEMPTY if the host's filesystem overlay (if any) shows entries in the directory
+ * `pathname`, even though the native directory that would be removed may be empty. */
+static int _file_checkOverlayDirEmpty(File* dir, const char* pathname, const char* workingDir,
+                                      Host* host) {
+    FsOverlay* overlay = host ? host_getFsOverlay(host) : NULL;
+    if (!overlay || pathname[0] == '\0') {
+        return 0;
+    }
 
-    trace("File %i unlinkat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
+    char* abspath = _file_getAbsolutePath(dir, pathname, workingDir);
+    DirListing* listing = fsoverlay_listDirectory(overlay, abspath, false);
+    free(abspath);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
+    int result = 0;
+    if (listing) {
+        result = dirlisting_isEmpty(listing) ? 0 : -ENOTEMPTY;
+        dirlisting_unref(listing);
+    }
+    return result;
+}
+
+int file_unlinkat(File* dir, const char* pathname, int flags, const char* workingDir, Host* host) {
+    if (flags & AT_REMOVEDIR) {
+        int errcode = _file_checkOverlayDirEmpty(dir, pathname, workingDir, host);
+        if (errcode < 0) {
+            return errcode;
+        }
     }
 
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(dir, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, false, &osFd);
+
+    trace("File %i unlinkat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
+
     int result = unlinkat(osFd, pathnameTmp, flags);
+    int errcode = errno;
 
     if (pathnameTmp != pathname) {
         free((char*)pathnameTmp);
     }
 
-    return (result < 0) ? -errno : result;
+    if (result == 0) {
+        _file_markRemoved(dir, pathname, workingDir, host);
+    }
+
+    return (result < 0) ? -errcode : result;
 }
 
-int file_symlinkat(File* dir, const char* linkpath, const char* target, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* linkpathTmp = linkpath;
+int file_symlinkat(File* dir, const char* linkpath, const char* target, const char* workingDir,
+                   Host* host) {
+    int osFd = -1;
+    const char* linkpathTmp = _file_getNativeAtPath(dir, linkpath, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, false, &osFd);
 
     trace("File %i symlinkat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        linkpathTmp = _file_getAbsolutePath(NULL, linkpath, workingDir);
-    }
-
     int result = symlinkat(target, osFd, linkpathTmp);
 
     if (linkpathTmp != linkpath) {
@@ -1073,17 +1236,13 @@ int file_symlinkat(File* dir, const char* linkpath, const char* target, const ch
 }
 
 ssize_t file_readlinkat(File* dir, const char* pathname, char* buf, size_t bufsize,
-                        const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+                        const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(dir, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_READ, false, &osFd);
 
     trace("File %i readlinkat os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     ssize_t result = readlinkat(osFd, pathnameTmp, buf, bufsize);
 
     if (pathnameTmp != pathname) {
@@ -1093,27 +1252,44 @@ ssize_t file_readlinkat(File* dir, const char* pathname, char* buf, size_t bufsi
     return (result < 0) ? -errno : result;
 }
 
-int file_renameat2(File* oldDir, const char* oldPath, File* newDir, const char* newPath,
-                   unsigned int flags, const char* workingDir) {
-    int oldOsFd = _file_getOSDirFD(oldDir);
-    int newOsFd = _file_getOSDirFD(newDir);
-    const char* oldPathTmp = oldPath;
-    const char* newPathTmp = newPath;
+/* Returns -EXDEV if `pathname` is a directory with entries in the base tree of the host's
+ * filesystem overlay (if any), since renaming it would only move the host's (possibly empty)
+ * copy of the directory. */
+static int _file_checkOverlayRenameDir(File* dir, const char* pathname, const char* workingDir,
+                                       Host* host) {
+    FsOverlay* overlay = host ? host_getFsOverlay(host) : NULL;
+    if (!overlay || pathname[0] == '\0') {
+        return 0;
+    }
 
-    trace("Files %i, %i renameat2 os-backed files %i, %i", oldDir ? _file_getFD(oldDir) : -1,
-          newDir ? _file_getFD(newDir) : -1, oldOsFd, newOsFd);
+    char* abspath = _file_getAbsolutePath(dir, pathname, workingDir);
+    int result = fsoverlay_hasBaseEntries(overlay, abspath) ? -EXDEV : 0;
+    free(abspath);
+    return result;
+}
 
-    if (oldOsFd == AT_FDCWD) {
-        oldOsFd = -1;
-        oldPathTmp = _file_getAbsolutePath(NULL, oldPath, workingDir);
+int file_renameat2(File* oldDir, const char* oldPath, File* newDir, const char* newPath,
+                   unsigned int flags, const char* workingDir, Host* host) {
+    /* check before the paths are copied into the host's directory */
+    int errcode = _file_checkOverlayRenameDir(oldDir, oldPath, workingDir, host);
+    if (errcode == 0 && (flags & RENAME_EXCHANGE)) {
+        errcode = _file_checkOverlayRenameDir(newDir, newPath, workingDir, host);
     }
-
-    if (newOsFd == AT_FDCWD) {
-        newOsFd = -1;
-        newPathTmp = _file_getAbsolutePath(NULL, newPath, workingDir);
+    if (errcode < 0) {
+        return errcode;
     }
 
+    int oldOsFd = -1, newOsFd = -1;
+    const char* oldPathTmp = _file_getNativeAtPath(oldDir, oldPath, workingDir, host,
+                                                   FSOVERLAY_ACCESS_WRITE, false, &oldOsFd);
+    const char* newPathTmp = _file_getNativeAtPath(newDir, newPath, workingDir, host,
+                                                   FSOVERLAY_ACCESS_WRITE, false, &newOsFd);
+
+    trace("Files %i, %i renameat2 os-backed files %i, %i", oldDir ? _file_getFD(oldDir) : -1,
+          newDir ? _file_getFD(newDir) : -1, oldOsFd, newOsFd);
+
     int result = (int)syscall(SYS_renameat2, oldOsFd, oldPathTmp, newOsFd, newPathTmp, flags);
+    errcode = errno;
 
     if (oldPathTmp != oldPath) {
         free((char*)oldPathTmp);
@@ -1122,22 +1298,24 @@ int file_renameat2(File* oldDir, const char* oldPath, File* newDir, const char*
         free((char*)newPathTmp);
     }
 
-    return (result < 0) ? -errno : result;
+    /* after an exchange, both paths still exist */
+    if (result == 0 && !(flags & RENAME_EXCHANGE)) {
+        _file_markRemoved(oldDir, oldPath, workingDir, host);
+    }
+
+    return (result < 0) ? -errcode : result;
 }
 
 #ifdef SYS_statx
 int file_statx(File* dir, const char* pathname, int flags, unsigned int mask,
-               struct statx* statxbuf, const char* workingDir) {
-    int osFd = _file_getOSDirFD(dir);
-    const char* pathnameTmp = pathname;
+               struct statx* statxbuf, const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp =
+        _file_getNativeAtPath(dir, pathname, workingDir, host, FSOVERLAY_ACCESS_READ,
+                              !(flags & AT_SYMLINK_NOFOLLOW), &osFd);
 
     trace("File %i statx os-backed file %i", dir ? _file_getFD(dir) : -1, osFd);
 
-    if (osFd == AT_FDCWD) {
-        osFd = -1;
-        pathnameTmp = _file_getAbsolutePath(NULL, pathname, workingDir);
-    }
-
     int result = syscall(SYS_statx, osFd, pathnameTmp, flags, mask, statxbuf);
 
     if (pathnameTmp != pathname) {
@@ -1147,3 +1325,116 @@ int file_statx(File* dir, const char* pathname, int flags, unsigned int mask,
     return (result < 0) ? -errno : result;
 }
 #endif
+
+////////////////////////////////////////////////////////
+// Operations on paths relative to the working directory
+////////////////////////////////////////////////////////
+
+int file_truncate(const char* pathname, off_t length, const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, true, &osFd);
+
+    trace("truncate path '%s'", pathnameTmp);
+
+    int result = truncate(pathnameTmp, length);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
+
+int file_statfs(const char* pathname, struct statfs* statbuf, const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_READ, true, &osFd);
+
+    trace("statfs path '%s'", pathnameTmp);
+
+    int result = statfs(pathnameTmp, statbuf);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
+
+int file_setxattr(const char* pathname, const char* name, const void* value, size_t size,
+                  int flags, bool followFinal, const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, followFinal, &osFd);
+
+    trace("setxattr path '%s'", pathnameTmp);
+
+    int result = followFinal ? setxattr(pathnameTmp, name, value, size, flags)
+                             : lsetxattr(pathnameTmp, name, value, size, flags);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
+
+ssize_t file_getxattr(const char* pathname, const char* name, void* value, size_t size,
+                      bool followFinal, const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_READ, followFinal, &osFd);
+
+    trace("getxattr path '%s'", pathnameTmp);
+
+    ssize_t result = followFinal ? getxattr(pathnameTmp, name, value, size)
+                                 : lgetxattr(pathnameTmp, name, value, size);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
+
+ssize_t file_listxattr(const char* pathname, char* list, size_t size, bool followFinal,
+                       const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_READ, followFinal, &osFd);
+
+    trace("listxattr path '%s'", pathnameTmp);
+
+    ssize_t result = followFinal ? listxattr(pathnameTmp, list, size)
+                                 : llistxattr(pathnameTmp, list, size);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
+
+int file_removexattr(const char* pathname, const char* name, bool followFinal,
+                     const char* workingDir, Host* host) {
+    int osFd = -1;
+    const char* pathnameTmp = _file_getNativeAtPath(NULL, pathname, workingDir, host,
+                                                    FSOVERLAY_ACCESS_WRITE, followFinal, &osFd);
+
+    trace("removexattr path '%s'", pathnameTmp);
+
+    int result = followFinal ? removexattr(pathnameTmp, name) : lremovexattr(pathnameTmp, name);
+    int errcode = errno;
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errcode : result;
+}
@@ -7,6 +7,7 @@
 #define SRC_MAIN_HOST_DESCRIPTOR_FILE_H_
 
 #include <poll.h>
+#include <stdbool.h>
 #include <stddef.h>
 #include <sys/stat.h>
 #include <sys/statfs.h>
@@ -33,6 +34,10 @@ typedef struct _File File;
  *   that represents a directory from which a relative path is computed.
  *   These calls usually end with "at". The directory File* can be null, in
  *   which case the current working directory (AT_FDCWD) will be used instead.
+ *
+ * Functions that operate on paths also take the host whose view of the
+ * filesystem the paths refer to (see fs_overlay.h). The host may be NULL to
+ * access the native filesystem directly.
  */
 
 // ************************
@@ -41,9 +46,10 @@ typedef struct _File File;
 
 File* file_new(); // Close the file with descriptor_close()
 File* file_dup(File* file, int* dupError);
-int file_open(File* file, const char* pathname, int flags, mode_t mode, const char* workingDir);
+int file_open(File* file, const char* pathname, int flags, mode_t mode, const char* workingDir,
+              Host* host);
 int file_openat(File* file, File* dir, const char* pathname, int flags, mode_t mode,
-                const char* workingDir);
+                const char* workingDir, Host* host);
 
 // ************************
 // Accessors
@@ -58,6 +64,15 @@ mode_t file_getMode(File* file);
 /* Returns the linux-backed fd that shadow uses to perform the file operations.  */
 int file_getOSBackedFD(File* file);
 
+/* Returns true if io on the file should take simulated time according to the
+ * host's disk model. */
+bool file_isDiskBacked(File* file);
+
+/* Returns the number of bytes between `offset` (or the current file position
+ * if `offset` is negative) and the end of the file. Used to estimate how much
+ * data a read will transfer. */
+size_t file_getBytesUntilEOF(File* file, off_t offset);
+
 // ****************************************
 // Operations that require a non-null File*
 // ****************************************
@@ -106,28 +121,51 @@ int file_poll(File* file, struct pollfd* pfd);
 // ******************************************
 
 int file_fstatat(File* dir, const char* pathname, struct stat* statbuf, int flags,
-                 const char* workingDir);
+                 const char* workingDir, Host* host);
 int file_fchownat(File* dir, const char* pathname, uid_t owner, gid_t group, int flags,
-                  const char* workingDir);
-int file_fchmodat(File* dir, const char* pathname, mode_t mode, int flags, const char* workingDir);
+                  const char* workingDir, Host* host);
+int file_fchmodat(File* dir, const char* pathname, mode_t mode, int flags, const char* workingDir,
+                  Host* host);
 int file_futimesat(File* dir, const char* pathname, const struct timeval times[2],
-                   const char* workingDir);
+                   const char* workingDir, Host* host);
 int file_utimensat(File* dir, const char* pathname, const struct timespec times[2], int flags,
-                   const char* workingDir);
-int file_faccessat(File* dir, const char* pathname, int mode, int flags, const char* workingDir);
-int file_mkdirat(File* dir, const char* pathname, mode_t mode, const char* workingDir);
-int file_mknodat(File* dir, const char* pathname, mode_t mode, dev_t dev, const char* workingDir);
+                   const char* workingDir, Host* host);
+int file_faccessat(File* dir, const char* pathname, int mode, int flags, const char* workingDir,
+                   Host* host);
+int file_mkdirat(File* dir, const char* pathname, mode_t mode, const char* workingDir,
+                 Host* host);
+int file_mknodat(File* dir, const char* pathname, mode_t mode, dev_t dev, const char* workingDir,
+                 Host* host);
 int file_linkat(File* olddir, const char* oldpath, File* newdir, const char* newpath, int flags,
-                const char* workingDir);
-int file_unlinkat(File* dir, const char* pathname, int flags, const char* workingDir);
-int file_symlinkat(File* dir, const char* linkpath, const char* target, const char* workingDir);
+                const char* workingDir, Host* host);
+int file_unlinkat(File* dir, const char* pathname, int flags, const char* workingDir, Host* host);
+int file_symlinkat(File* dir, const char* linkpath, const char* target, const char* workingDir,
+                   Host* host);
 ssize_t file_readlinkat(File* dir, const char* pathname, char* buf, size_t bufsize,
-                        const char* workingDir);
+                        const char* workingDir, Host* host);
 int file_renameat2(File* olddir, const char* oldpath, File* newdir, const char* newpath,
-                   unsigned int flags, const char* workingDir);
+                   unsigned int flags, const char* workingDir, Host* host);
 #ifdef SYS_statx
 int file_statx(File* dir, const char* pathname, int flags, unsigned int mask,
-               struct statx* statxbuf, const char* workingDir);
+               struct statx* statxbuf, const char* workingDir, Host* host);
 #endif
 
+// ****************************************************
+// Operations on paths relative to the working directory
+// ****************************************************
+
+/* These follow the final symbolic link in `pathname` if `followFinal` is true,
+ * like the versions of the syscalls without the "l" prefix. */
+
+int file_truncate(const char* pathname, off_t length, const char* workingDir, Host* host);
+int file_statfs(const char* pathname, struct statfs* statbuf, const char* workingDir, Host* host);
+int file_setxattr(const char* pathname, const char* name, const void* value, size_t size,
+                  int flags, bool followFinal, const char* workingDir, Host* host);
+ssize_t file_getxattr(const char* pathname, const char* name, void* value, size_t size,
+                      bool followFinal, const char* workingDir, Host* host);
+ssize_t file_listxattr(const char* pathname, char* list, size_t size, bool followFinal,
+                       const char* workingDir, Host* host);
+int file_removexattr(const char* pathname, const char* name, bool followFinal,
+                     const char* workingDir, Host* host);
+
 #endif /* SRC_MAIN_HOST_DESCRIPTOR_FILE_H_ */
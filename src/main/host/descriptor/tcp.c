@@ -409,6 +409,32 @@ static guint _tcp_calculateRTT(TCP* tcp, Host* host) {
     return rtt;
 }
 
+/* Returns the largest payload of a segment whose IP packet fits in the MTU of
+ * the interface that the segment is sent on. */
+static gsize _tcp_getMaxSegmentSize(TCP* tcp, Host* host) {
+    MAGIC_ASSERT(tcp);
+
+    in_addr_t sourceIP = tcp_getIP(tcp);
+    in_addr_t destinationIP = tcp_getPeerIP(tcp);
+
+    if(sourceIP == htonl(INADDR_ANY)) {
+        /* source interface depends on destination */
+        if(destinationIP == htonl(INADDR_LOOPBACK)) {
+            sourceIP = htonl(INADDR_LOOPBACK);
+        } else {
+            sourceIP = host_getDefaultIP(host);
+        }
+    }
+
+    NetworkInterface* interface = host_lookupInterface(host, sourceIP);
+    utility_assert(interface);
+
+    /* the interface MTU is also the path MTU, since routers don't fragment */
+    gsize mtuSegmentSize =
+        networkinterface_getMTU(interface) - CONFIG_HEADER_SIZE_IP - CONFIG_HEADER_SIZE_TCP;
+    return MIN(CONFIG_MTU - CONFIG_HEADER_SIZE_TCPIPETH, mtuSegmentSize);
+}
+
 static gsize _tcp_computeRTTMEM(TCP* tcp, Host* host, gboolean isRMEM) {
     Address* address = host_getDefaultAddress(host);
     in_addr_t ip = (in_addr_t)address_toNetworkIP(address);
@@ -1674,7 +1700,7 @@ static GList* _tcp_removeSacks(GList* selectiveACKs, gint sequence) {
     return unacked;
 }
 
-TCPProcessFlags _tcp_dataProcessing(TCP* tcp, Packet* packet, PacketTCPHeader *header) {
+TCPProcessFlags _tcp_dataProcessing(TCP* tcp, Host* host, Packet* packet, PacketTCPHeader *header) {
     MAGIC_ASSERT(tcp);
 
     trace("processing data");
@@ -1688,6 +1714,7 @@ TCPProcessFlags _tcp_dataProcessing(TCP* tcp, Packet* packet, PacketTCPHeader *h
         /* its too far ahead to accept now, but they should re-send it */
         flags |= TCP_PF_PROCESSED;
         packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+        host_countDroppedPacket(host, "socket");
     } else if(header->sequence >= tcp->receive.next) {
         /* its in our window, so we can accept the data */
         flags |= TCP_PF_PROCESSED;
@@ -1735,6 +1762,7 @@ TCPProcessFlags _tcp_dataProcessing(TCP* tcp, Packet* packet, PacketTCPHeader *h
         } else {
             trace("no space for packet even though its in our window");
             packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+            host_countDroppedPacket(host, "socket");
         }
     }
 
@@ -2085,6 +2113,7 @@ static void _tcp_processPacket(Socket* socket, Host* host, Packet* packet) {
         case TCPS_CLOSED: {
             /* stray packet, drop without retransmit */
             packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+            host_countDroppedPacket(host, "socket");
             trace("already closed and won't send response");
             return;
             break;
@@ -2095,6 +2124,7 @@ static void _tcp_processPacket(Socket* socket, Host* host, Packet* packet) {
     if(tcp->state == TCPS_LISTEN) {
         if(!(flags & TCP_PF_PROCESSED)) {
             packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+            host_countDroppedPacket(host, "socket");
         }
         trace("listener does not respond to packets");
         return;
@@ -2104,7 +2134,7 @@ static void _tcp_processPacket(Socket* socket, Host* host, Packet* packet) {
 
     /* if TCPE_RECEIVE_EOF, we are not supposed to receive any more */
     if(packetLength > 0 && !(tcp->error & TCPE_RECEIVE_EOF)) {
-        flags |= _tcp_dataProcessing(tcp, packet, header);
+        flags |= _tcp_dataProcessing(tcp, host, packet, header);
     }
 
     if(header->flags & PTCP_ACK) {
@@ -2117,6 +2147,7 @@ static void _tcp_processPacket(Socket* socket, Host* host, Packet* packet) {
         trace("dropping packet that had no useful info for us");
         utility_assert(responseFlags == PTCP_NONE);
         packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+        host_countDroppedPacket(host, "socket");
         return;
     }
 
@@ -2261,7 +2292,7 @@ static gssize _tcp_sendUserData(Transport* transport, Thread* thread, PluginVirt
     gsize remaining = MIN(acceptable, space);
 
     /* break data into segments and send each in a packet */
-    gsize maxPacketLength = CONFIG_MTU - CONFIG_HEADER_SIZE_TCPIPETH;
+    gsize maxPacketLength = _tcp_getMaxSegmentSize(tcp, thread_getHost(thread));
     gsize bytesCopied = 0;
 
     /* create as many packets as needed */
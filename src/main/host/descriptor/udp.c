@@ -18,6 +18,7 @@
 #include "main/host/descriptor/socket.h"
 #include "main/host/descriptor/transport.h"
 #include "main/host/host.h"
+#include "main/host/network_interface.h"
 #include "main/host/protocol.h"
 #include "main/host/tracker.h"
 #include "main/routing/packet.h"
@@ -40,6 +41,10 @@ struct _UDP {
     enum UDPState state;
     enum UDPState stateLast;
 
+    /* the IP_MTU_DISCOVER mode, which decides if datagrams that don't fit in
+     * the MTU are fragmented or rejected */
+    gint mtuDiscover;
+
     MAGIC_DECLARE;
 };
 
@@ -91,6 +96,7 @@ static void _udp_processPacket(Socket* socket, Host* host, Packet* packet) {
     /* UDP packet can be buffered immediately */
     if (!socket_addToInputBuffer((Socket*)udp, host, packet)) {
         packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+        host_countDroppedPacket(host, "socket");
     }
 }
 
@@ -101,6 +107,59 @@ static void _udp_dropPacket(Socket* socket, Host* host, Packet* packet) {
     /* do nothing */
 }
 
+/* returns the address of the interface that sends datagrams to destinationIP */
+static in_addr_t _udp_getSourceIP(UDP* udp, Host* host, in_addr_t destinationIP) {
+    in_addr_t sourceIP = 0;
+    socket_getSocketName(&(udp->super), &sourceIP, NULL);
+
+    if (sourceIP == htonl(INADDR_ANY)) {
+        /* source interface depends on destination */
+        if (destinationIP == htonl(INADDR_LOOPBACK)) {
+            sourceIP = htonl(INADDR_LOOPBACK);
+        } else {
+            sourceIP = host_getDefaultIP(host);
+        }
+    }
+
+    return sourceIP;
+}
+
+/* splits the datagram into IP fragments that fit in the MTU, and buffers them
+ * in the transport layer. takes ownership of the datagram reference. either all
+ * of the fragments are buffered, or none of them are, so that a datagram that
+ * is retried later isn't also partially sent under its old packet id. */
+static gboolean _udp_bufferFragments(UDP* udp, Host* host, Packet* datagram, guint32 mtu) {
+    /* the data of each fragment but the last is a multiple of 8 bytes, and the
+     * data of the first fragment includes the UDP header */
+    guint fragmentSize = (mtu - CONFIG_HEADER_SIZE_IP) & ~7u;
+    guint datagramLength = packet_getDatagramLength(datagram);
+
+    /* the fragments' data adds up to the datagram's data */
+    if (datagramLength > socket_getOutputBufferSpace((Socket*)udp)) {
+        packet_unref(datagram);
+        return FALSE;
+    }
+
+    guint offset = 0;
+    while (offset < datagramLength) {
+        guint length = (offset == 0) ? fragmentSize - CONFIG_HEADER_SIZE_UDP : fragmentSize;
+        length = MIN(length, datagramLength - offset);
+
+        Packet* fragment = packet_newFragment(datagram, offset, length);
+        if (!socket_addToOutputBuffer((Socket*)udp, host, fragment)) {
+            utility_panic("unable to buffer a fragment after checking the buffer space");
+        }
+
+        offset += length;
+    }
+
+    trace("split %u byte datagram into fragments of at most %u bytes", datagramLength,
+          fragmentSize);
+
+    packet_unref(datagram);
+    return TRUE;
+}
+
 /*
  * this function builds a UDP packet and sends to the virtual node given by the
  * ip and port parameters. this function assumes that the socket is already
@@ -116,32 +175,33 @@ static gssize _udp_sendUserData(Transport* transport, Thread* thread, PluginVirt
         return -EMSGSIZE;
     }
 
-    gsize space = socket_getOutputBufferSpace(&(udp->super));
-    if(space < nBytes) {
-        /* not enough space to buffer the data */
-        return -EWOULDBLOCK;
-    }
-
     /* use default destination if none was specified */
     in_addr_t destinationIP = (ip != 0) ? ip : udp->super.peerIP;
     in_port_t destinationPort = (port != 0) ? port : udp->super.peerPort;
 
-    in_addr_t sourceIP = 0;
     in_port_t sourcePort = 0;
-    socket_getSocketName(&(udp->super), &sourceIP, &sourcePort);
+    socket_getSocketName(&(udp->super), NULL, &sourcePort);
 
     Host* host = thread_getHost(thread);
-    if (sourceIP == htonl(INADDR_ANY)) {
-        /* source interface depends on destination */
-        if (destinationIP == htonl(INADDR_LOOPBACK)) {
-            sourceIP = htonl(INADDR_LOOPBACK);
-        } else {
-            sourceIP = host_getDefaultIP(host);
-        }
-    }
+    in_addr_t sourceIP = _udp_getSourceIP(udp, host, destinationIP);
 
     utility_assert(sourceIP && sourcePort && destinationIP && destinationPort);
 
+    /* the interface MTU is also the path MTU, since routers don't fragment */
+    guint32 mtu = networkinterface_getMTU(host_lookupInterface(host, sourceIP));
+    gboolean needsFragments = nBytes + CONFIG_HEADER_SIZE_UDP + CONFIG_HEADER_SIZE_IP > mtu;
+    if (needsFragments &&
+        (udp->mtuDiscover == IP_PMTUDISC_DO || udp->mtuDiscover == IP_PMTUDISC_PROBE)) {
+        /* the don't fragment flag is set */
+        return -EMSGSIZE;
+    }
+
+    gsize space = socket_getOutputBufferSpace(&(udp->super));
+    if(space < nBytes) {
+        /* not enough space to buffer the data */
+        return -EWOULDBLOCK;
+    }
+
     /* create the UDP packet */
     Packet* packet = packet_new(host);
     packet_setPayload(packet, thread, buffer, nBytes);
@@ -149,7 +209,8 @@ static gssize _udp_sendUserData(Transport* transport, Thread* thread, PluginVirt
     packet_addDeliveryStatus(packet, PDS_SND_CREATED);
 
     /* buffer it in the transport layer, to be sent out when possible */
-    gboolean success = socket_addToOutputBuffer((Socket*)udp, host, packet);
+    gboolean success = needsFragments ? _udp_bufferFragments(udp, host, packet, mtu)
+                                      : socket_addToOutputBuffer((Socket*)udp, host, packet);
 
     gsize bytes_sent = 0;
     /* counter maintenance */
@@ -236,6 +297,32 @@ static gboolean _udp_close(LegacyDescriptor* descriptor, Host* host) {
     return TRUE;
 }
 
+gint udp_getMTUDiscover(UDP* udp) {
+    MAGIC_ASSERT(udp);
+    return udp->mtuDiscover;
+}
+
+void udp_setMTUDiscover(UDP* udp, gint mode) {
+    MAGIC_ASSERT(udp);
+    udp->mtuDiscover = mode;
+}
+
+gint udp_getPathMTU(UDP* udp, Host* host) {
+    MAGIC_ASSERT(udp);
+
+    if (udp->state != UDPS_ESTABLISHED) {
+        return -ENOTCONN;
+    }
+
+    in_addr_t sourceIP = _udp_getSourceIP(udp, host, udp->super.peerIP);
+    NetworkInterface* interface = host_lookupInterface(host, sourceIP);
+    if (interface == NULL) {
+        return -ENOTCONN;
+    }
+
+    return (gint)networkinterface_getMTU(interface);
+}
+
 gint udp_shutdown(UDP* udp, gint how) {
     MAGIC_ASSERT(udp);
 
@@ -261,6 +348,7 @@ UDP* udp_new(Host* host, guint receiveBufferSize, guint sendBufferSize) {
 
     udp->state = UDPS_CLOSED;
     udp->stateLast = UDPS_CLOSED;
+    udp->mtuDiscover = IP_PMTUDISC_WANT;
 
     /* we are immediately active because UDP doesnt wait for accept or connect */
     descriptor_adjustStatus(
@@ -16,4 +16,13 @@ typedef struct _UDP UDP;
 UDP* udp_new(Host* host, guint receiveBufferSize, guint sendBufferSize);
 gint udp_shutdown(UDP* udp, gint how);
 
+/* The IP_MTU_DISCOVER socket option, one of the IP_PMTUDISC_* modes. With
+ * IP_PMTUDISC_DO and IP_PMTUDISC_PROBE, sending a datagram that doesn't fit in
+ * the MTU fails with EMSGSIZE, otherwise the datagram is fragmented. */
+gint udp_getMTUDiscover(UDP* udp);
+void udp_setMTUDiscover(UDP* udp, gint mode);
+/* Returns the MTU of the path to the connected peer, or -ENOTCONN if the
+ * socket isn't connected. */
+gint udp_getPathMTU(UDP* udp, Host* host);
+
 #endif /* SHD_UDP_H_ */
@@ -0,0 +1,186 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/host/dir_listing.h"
+
+#include <dirent.h>
+#include <errno.h>
+#include <glib.h>
+#include <stddef.h>
+#include <string.h>
+
+#include "main/utility/utility.h"
+
+typedef struct _DirListingEntry DirListingEntry;
+struct _DirListingEntry {
+    ino64_t ino;
+    unsigned char type;
+    gchar* name;
+};
+
+struct _DirListing {
+    /* the entries, in the order they are listed */
+    GPtrArray* entries;
+    /* the names of the entries, to skip duplicates */
+    GHashTable* names;
+    int referenceCount;
+    MAGIC_DECLARE;
+};
+
+static void _dirlisting_freeEntry(gpointer data) {
+    DirListingEntry* entry = data;
+    g_free(entry->name);
+    g_free(entry);
+}
+
+DirListing* dirlisting_new() {
+    DirListing* listing = g_new0(DirListing, 1);
+    MAGIC_INIT(listing);
+
+    listing->entries = g_ptr_array_new_with_free_func(_dirlisting_freeEntry);
+    listing->names = g_hash_table_new(g_str_hash, g_str_equal);
+    listing->referenceCount = 1;
+
+    return listing;
+}
+
+void dirlisting_ref(DirListing* listing) {
+    MAGIC_ASSERT(listing);
+    listing->referenceCount++;
+}
+
+void dirlisting_unref(DirListing* listing) {
+    MAGIC_ASSERT(listing);
+    listing->referenceCount--;
+    utility_assert(listing->referenceCount >= 0);
+    if (listing->referenceCount > 0) {
+        return;
+    }
+
+    /* the table borrows the names from the entries */
+    g_hash_table_destroy(listing->names);
+    g_ptr_array_free(listing->entries, TRUE);
+
+    MAGIC_CLEAR(listing);
+    g_free(listing);
+}
+
+void dirlisting_add(DirListing* listing, const char* name, ino64_t ino, unsigned char type) {
+    MAGIC_ASSERT(listing);
+
+    if (g_hash_table_contains(listing->names, name)) {
+        return;
+    }
+
+    DirListingEntry* entry = g_new0(DirListingEntry, 1);
+    entry->ino = ino;
+    entry->type = type;
+    entry->name = g_strdup(name);
+
+    g_ptr_array_add(listing->entries, entry);
+    g_hash_table_add(listing->names, entry->name);
+}
+
+int dirlisting_addNative(DirListing* listing, const char* path, DirListingFilterFunc filter,
+                         void* filterData) {
+    MAGIC_ASSERT(listing);
+
+    DIR* dir = opendir(path);
+    if (!dir) {
+        return -errno;
+    }
+
+    struct dirent* dirent = NULL;
+    while ((dirent = readdir(dir)) != NULL) {
+        if (!filter || filter(dirent->d_name, filterData)) {
+            dirlisting_add(listing, dirent->d_name, dirent->d_ino, dirent->d_type);
+        }
+    }
+
+    closedir(dir);
+    return 0;
+}
+
+bool dirlisting_isEmpty(DirListing* listing) {
+    MAGIC_ASSERT(listing);
+
+    for (guint i = 0; i < listing->entries->len; i++) {
+        DirListingEntry* entry = g_ptr_array_index(listing->entries, i);
+        if (strcmp(entry->name, ".") && strcmp(entry->name, "..")) {
+            return false;
+        }
+    }
+    return true;
+}
+
+/* Records are aligned like the kernel aligns them. */
+static size_t _dirlisting_align(size_t len, size_t alignment) {
+    return (len + alignment - 1) & ~(alignment - 1);
+}
+
+int dirlisting_getdents(DirListing* listing, off_t* offset, struct linux_dirent* dirp,
+                        unsigned int count) {
+    MAGIC_ASSERT(listing);
+
+    size_t written = 0;
+    while (*offset >= 0 && *offset < (off_t)listing->entries->len) {
+        DirListingEntry* entry = g_ptr_array_index(listing->entries, *offset);
+
+        /* the name is followed by a NULL byte and the type */
+        size_t reclen = _dirlisting_align(
+            offsetof(struct linux_dirent, d_name) + strlen(entry->name) + 2, sizeof(long));
+        if (written + reclen > count) {
+            break;
+        }
+
+        struct linux_dirent* record = (struct linux_dirent*)((char*)dirp + written);
+        memset(record, 0, reclen);
+        record->d_ino = entry->ino;
+        record->d_off = *offset + 1;
+        record->d_reclen = reclen;
+        strcpy(record->d_name, entry->name);
+        ((char*)record)[reclen - 1] = entry->type;
+
+        written += reclen;
+        (*offset)++;
+    }
+
+    if (written == 0 && *offset >= 0 && *offset < (off_t)listing->entries->len) {
+        return -EINVAL;
+    }
+    return (int)written;
+}
+
+int dirlisting_getdents64(DirListing* listing, off_t* offset, struct linux_dirent64* dirp,
+                          unsigned int count) {
+    MAGIC_ASSERT(listing);
+
+    size_t written = 0;
+    while (*offset >= 0 && *offset < (off_t)listing->entries->len) {
+        DirListingEntry* entry = g_ptr_array_index(listing->entries, *offset);
+
+        size_t reclen = _dirlisting_align(
+            offsetof(struct linux_dirent64, d_name) + strlen(entry->name) + 1, sizeof(ino64_t));
+        if (written + reclen > count) {
+            break;
+        }
+
+        struct linux_dirent64* record = (struct linux_dirent64*)((char*)dirp + written);
+        memset(record, 0, reclen);
+        record->d_ino = entry->ino;
+        record->d_off = *offset + 1;
+        record->d_reclen = reclen;
+        record->d_type = entry->type;
+        strcpy(record->d_name, entry->name);
+
+        written += reclen;
+        (*offset)++;
+    }
+
+    if (written == 0 && *offset >= 0 && *offset < (off_t)listing->entries->len) {
+        return -EINVAL;
+    }
+    return (int)written;
+}
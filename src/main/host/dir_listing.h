@@ -0,0 +1,53 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef SRC_MAIN_HOST_DIR_LISTING_H_
+#define SRC_MAIN_HOST_DIR_LISTING_H_
+
+#include <stdbool.h>
+#include <sys/types.h>
+
+#include "main/host/syscall/kernel_types.h"
+
+/* The entries of a directory as seen by the plugin, when they differ from the
+ * entries of the native directory (e.g., when a filesystem overlay merges two
+ * trees, or when we hide the parts of /proc that we don't emulate). The
+ * entries are returned from getdents() in the order that they were added. */
+
+/* Opaque object to store the state needed to implement the module. */
+typedef struct _DirListing DirListing;
+
+/* Returns true if the entry `name` of the native directory should be listed. */
+typedef bool (*DirListingFilterFunc)(const char* name, void* data);
+
+/* Listings are reference counted, and only modified while they are being
+ * built, so they can be shared by duplicated file descriptors. */
+DirListing* dirlisting_new();
+void dirlisting_ref(DirListing* listing);
+void dirlisting_unref(DirListing* listing);
+
+/* Adds an entry of type `type` (one of the DT_* constants from dirent.h),
+ * unless the listing already has an entry called `name`. */
+void dirlisting_add(DirListing* listing, const char* name, ino64_t ino, unsigned char type);
+
+/* Adds the entries of the native directory at `path` for which `filter`
+ * returns true, or all of them if `filter` is NULL. Returns 0 on success, or a
+ * negative errno value if the directory can't be read. */
+int dirlisting_addNative(DirListing* listing, const char* path, DirListingFilterFunc filter,
+                         void* filterData);
+
+/* Returns true if the listing has no entries other than "." and "..". */
+bool dirlisting_isEmpty(DirListing* listing);
+
+/* Write the entries starting at index `*offset` to `dirp` in the format of the
+ * getdents() and getdents64() syscalls, and advance `*offset` past them.
+ * Returns the number of bytes written, 0 at the end of the listing, or -EINVAL
+ * if `count` is too small for the next entry. */
+int dirlisting_getdents(DirListing* listing, off_t* offset, struct linux_dirent* dirp,
+                        unsigned int count);
+int dirlisting_getdents64(DirListing* listing, off_t* offset, struct linux_dirent64* dirp,
+                          unsigned int count);
+
+#endif /* SRC_MAIN_HOST_DIR_LISTING_H_ */
@@ -0,0 +1,77 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/host/disk.h"
+
+#include <glib.h>
+#include <stddef.h>
+
+#include "lib/logger/logger.h"
+#include "main/utility/utility.h"
+
+struct _Disk {
+    SimulationTime readLatency;
+    guint64 readBandwidth;
+    SimulationTime writeLatency;
+    guint64 writeBandwidth;
+    SimulationTime syncLatency;
+    /* the time at which all previously added operations will have completed */
+    SimulationTime timeDiskAvailable;
+    MAGIC_DECLARE;
+};
+
+Disk* disk_new(SimulationTime readLatency, guint64 readBandwidth, SimulationTime writeLatency,
+               guint64 writeBandwidth, SimulationTime syncLatency) {
+    Disk* disk = g_new0(Disk, 1);
+    MAGIC_INIT(disk);
+
+    disk->readLatency = readLatency;
+    disk->readBandwidth = readBandwidth;
+    disk->writeLatency = writeLatency;
+    disk->writeBandwidth = writeBandwidth;
+    disk->syncLatency = syncLatency;
+    disk->timeDiskAvailable = 0;
+
+    return disk;
+}
+
+void disk_free(Disk* disk) {
+    MAGIC_ASSERT(disk);
+    MAGIC_CLEAR(disk);
+    g_free(disk);
+}
+
+static SimulationTime _disk_getTransferTime(guint64 bandwidth, size_t numBytes) {
+    if (bandwidth == 0 || numBytes == 0) {
+        return 0;
+    }
+    /* round up so that any transfer takes a non-zero amount of time */
+    return (SimulationTime)((numBytes * SIMTIME_ONE_SECOND + bandwidth - 1) / bandwidth);
+}
+
+SimulationTime disk_addOperation(Disk* disk, SimulationTime now, DiskOperation operation,
+                                 size_t numBytes) {
+    MAGIC_ASSERT(disk);
+
+    SimulationTime cost = 0;
+    switch (operation) {
+        case DISK_OPERATION_READ:
+            cost = disk->readLatency + _disk_getTransferTime(disk->readBandwidth, numBytes);
+            break;
+        case DISK_OPERATION_WRITE:
+            cost = disk->writeLatency + _disk_getTransferTime(disk->writeBandwidth, numBytes);
+            break;
+        case DISK_OPERATION_SYNC: cost = disk->syncLatency; break;
+        default: utility_panic("Unknown disk operation %d", (int)operation);
+    }
+
+    /* the operation can't start until the disk finishes the ones before it */
+    disk->timeDiskAvailable = MAX(disk->timeDiskAvailable, now) + cost;
+
+    trace("disk operation %d of %zu bytes will complete in %" G_GUINT64_FORMAT " ns",
+          (int)operation, numBytes, disk->timeDiskAvailable - now);
+
+    return disk->timeDiskAvailable - now;
+}
@@ -0,0 +1,38 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef SHD_DISK_H_
+#define SHD_DISK_H_
+
+#include <glib.h>
+#include <stddef.h>
+
+#include "main/core/support/definitions.h"
+
+/* A simple model of a host's storage device. Operations are serviced one at a
+ * time in the order in which they are added, and each operation takes a fixed
+ * latency plus the time needed to transfer its bytes at the configured
+ * bandwidth. */
+typedef struct _Disk Disk;
+
+typedef enum _DiskOperation DiskOperation;
+enum _DiskOperation {
+    DISK_OPERATION_READ,
+    DISK_OPERATION_WRITE,
+    DISK_OPERATION_SYNC,
+};
+
+/* Bandwidths are in bytes per second, and a bandwidth of 0 means that
+ * transfers are only charged the latency of the operation. */
+Disk* disk_new(SimulationTime readLatency, guint64 readBandwidth, SimulationTime writeLatency,
+               guint64 writeBandwidth, SimulationTime syncLatency);
+void disk_free(Disk* disk);
+
+/* Queue an operation that transfers `numBytes` bytes at time `now`, and return
+ * the amount of time until the operation completes. */
+SimulationTime disk_addOperation(Disk* disk, SimulationTime now, DiskOperation operation,
+                                 size_t numBytes);
+
+#endif /* SHD_DISK_H_ */
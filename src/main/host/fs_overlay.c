@@ -0,0 +1,521 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/host/fs_overlay.h"
+
+#include <errno.h>
+#include <fcntl.h>
+#include <glib.h>
+#include <glib/gstdio.h>
+#include <limits.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "lib/logger/logger.h"
+#include "main/core/worker.h"
+#include "main/utility/utility.h"
+
+/* Native paths that are never overlaid, since they don't refer to regular
+ * files or are already virtualized elsewhere (e.g., /proc). */
+static const char* const _fsOverlayNativePaths[] = {"/proc", "/dev", "/sys", NULL};
+
+/* The maximum number of links followed during one lookup, like the kernel's MAXSYMLINKS. */
+#define FSOVERLAY_MAX_SYMLINKS 40
+
+struct _FsOverlay {
+    /* The read-only tree, without a trailing separator ("" for the real root). */
+    gchar* basePath;
+    /* The writable per-host tree, without a trailing separator. */
+    gchar* upperPath;
+    /* Paths that are accessed natively rather than through the overlay. */
+    gchar** passthroughPaths;
+    /* Normalized paths that were removed from the host's view, and therefore
+     * should not fall through to the base tree. */
+    GHashTable* whiteouts;
+
+    MAGIC_DECLARE;
+};
+
+/* Lexically normalizes the absolute path `path` by removing ".", "..", and
+ * duplicate separators. Symbolic links are not resolved. */
+static gchar* _fsoverlay_normalize(const char* path) {
+    utility_assert(path && path[0] == '/');
+
+    gchar** parts = g_strsplit(path, "/", -1);
+    GPtrArray* stack = g_ptr_array_new();
+
+    for (int i = 0; parts[i] != NULL; i++) {
+        if (parts[i][0] == '\0' || !strcmp(parts[i], ".")) {
+            continue;
+        } else if (!strcmp(parts[i], "..")) {
+            if (stack->len > 0) {
+                g_ptr_array_remove_index(stack, stack->len - 1);
+            }
+        } else {
+            g_ptr_array_add(stack, parts[i]);
+        }
+    }
+
+    GString* normPath = g_string_new(NULL);
+    for (guint i = 0; i < stack->len; i++) {
+        g_string_append_c(normPath, '/');
+        g_string_append(normPath, g_ptr_array_index(stack, i));
+    }
+    if (normPath->len == 0) {
+        g_string_append_c(normPath, '/');
+    }
+
+    g_ptr_array_free(stack, TRUE);
+    g_strfreev(parts);
+    return g_string_free(normPath, FALSE);
+}
+
+/* Returns the normalized form of `path` that can be used as a prefix when
+ * joining paths, i.e., without a trailing separator ("" for the root). */
+static gchar* _fsoverlay_normalizeRoot(const char* path) {
+    gchar* normPath = _fsoverlay_normalize(path);
+    if (!strcmp(normPath, "/")) {
+        normPath[0] = '\0';
+    }
+    return normPath;
+}
+
+FsOverlay* fsoverlay_new(const char* basePath, const char* upperPath,
+                         const char* const* passthroughPaths) {
+    utility_assert(basePath && basePath[0] == '/');
+    utility_assert(upperPath && upperPath[0] == '/');
+
+    FsOverlay* overlay = g_new0(FsOverlay, 1);
+    MAGIC_INIT(overlay);
+
+    overlay->basePath = _fsoverlay_normalizeRoot(basePath);
+    overlay->upperPath = _fsoverlay_normalizeRoot(upperPath);
+    overlay->whiteouts = g_hash_table_new_full(g_str_hash, g_str_equal, g_free, NULL);
+
+    GPtrArray* passthrough = g_ptr_array_new();
+    for (int i = 0; _fsOverlayNativePaths[i] != NULL; i++) {
+        g_ptr_array_add(passthrough, g_strdup(_fsOverlayNativePaths[i]));
+    }
+    for (int i = 0; passthroughPaths && passthroughPaths[i] != NULL; i++) {
+        g_ptr_array_add(passthrough, _fsoverlay_normalizeRoot(passthroughPaths[i]));
+    }
+    /* never overlay the upper directory itself */
+    g_ptr_array_add(passthrough, g_strdup(overlay->upperPath));
+    g_ptr_array_add(passthrough, NULL);
+    overlay->passthroughPaths = (gchar**)g_ptr_array_free(passthrough, FALSE);
+
+    if (g_mkdir_with_parents(overlay->upperPath, 0775) != 0) {
+        utility_panic("Unable to create filesystem overlay directory '%s': %s",
+                      overlay->upperPath, g_strerror(errno));
+    }
+
+    debug("Created filesystem overlay with base '%s' and upper '%s'",
+          basePath, overlay->upperPath);
+
+    worker_count_allocation(FsOverlay);
+    return overlay;
+}
+
+void fsoverlay_free(FsOverlay* overlay) {
+    MAGIC_ASSERT(overlay);
+
+    g_free(overlay->basePath);
+    g_free(overlay->upperPath);
+    g_strfreev(overlay->passthroughPaths);
+    g_hash_table_destroy(overlay->whiteouts);
+
+    MAGIC_CLEAR(overlay);
+    g_free(overlay);
+    worker_count_deallocation(FsOverlay);
+}
+
+static bool _fsoverlay_hasPrefix(const char* path, const char* prefix) {
+    size_t len = strlen(prefix);
+    return len > 0 && !strncmp(path, prefix, len) && (path[len] == '\0' || path[len] == '/');
+}
+
+static bool _fsoverlay_isPassthrough(FsOverlay* overlay, const char* normPath) {
+    for (int i = 0; overlay->passthroughPaths[i] != NULL; i++) {
+        if (_fsoverlay_hasPrefix(normPath, overlay->passthroughPaths[i])) {
+            return true;
+        }
+    }
+    return false;
+}
+
+/* Returns true if `normPath` or any of its ancestors were removed from the
+ * host's view, in which case the base tree must not be consulted. */
+static bool _fsoverlay_isWhitedOut(FsOverlay* overlay, const char* normPath) {
+    if (g_hash_table_size(overlay->whiteouts) == 0) {
+        return false;
+    }
+
+    gchar* prefix = g_strdup(normPath);
+    bool found = false;
+
+    /* check the path, then repeatedly truncate it at the last separator */
+    while (prefix[0] != '\0' && !found) {
+        found = g_hash_table_contains(overlay->whiteouts, prefix);
+        char* sep = strrchr(prefix, '/');
+        *sep = '\0';
+    }
+
+    g_free(prefix);
+    return found;
+}
+
+static char* _fsoverlay_join(const char* root, const char* normPath) {
+    char* path = NULL;
+    if (asprintf(&path, "%s%s", root, normPath) < 0) {
+        utility_panic("asprintf could not allocate a string for path %s", normPath);
+    }
+    return path;
+}
+
+static bool _fsoverlay_copyFile(const char* src, const char* dst, mode_t mode) {
+    int srcfd = open(src, O_RDONLY | O_CLOEXEC);
+    if (srcfd < 0) {
+        return false;
+    }
+
+    int dstfd = open(dst, O_WRONLY | O_CREAT | O_EXCL | O_CLOEXEC, mode);
+    if (dstfd < 0) {
+        close(srcfd);
+        return false;
+    }
+
+    char buf[4096];
+    bool isSuccess = true;
+
+    while (isSuccess) {
+        ssize_t nread = read(srcfd, buf, sizeof(buf));
+        if (nread == 0) {
+            break;
+        } else if (nread < 0) {
+            isSuccess = (errno == EINTR);
+            continue;
+        }
+
+        ssize_t nwritten = 0;
+        while (isSuccess && nwritten < nread) {
+            ssize_t rv = write(dstfd, buf + nwritten, nread - nwritten);
+            if (rv < 0) {
+                isSuccess = (errno == EINTR);
+            } else {
+                nwritten += rv;
+            }
+        }
+    }
+
+    close(srcfd);
+    close(dstfd);
+    return isSuccess;
+}
+
+/* Copies the single file, directory, or symlink at `basePath` to `upperPath`,
+ * which must not exist. Directories are copied without their contents. */
+static bool _fsoverlay_copyUp(const char* basePath, const char* upperPath,
+                              const struct stat* statbuf) {
+    mode_t mode = statbuf->st_mode & 07777;
+    bool isSuccess = false;
+
+    if (S_ISDIR(statbuf->st_mode)) {
+        /* make sure we can still create children in the copy */
+        isSuccess = (mkdir(upperPath, mode | S_IRWXU) == 0);
+    } else if (S_ISREG(statbuf->st_mode)) {
+        isSuccess = _fsoverlay_copyFile(basePath, upperPath, mode | S_IWUSR);
+    } else if (S_ISLNK(statbuf->st_mode)) {
+        char target[PATH_MAX + 1] = {0};
+        ssize_t len = readlink(basePath, target, PATH_MAX);
+        isSuccess = (len >= 0 && symlink(target, upperPath) == 0);
+    } else {
+        /* fifos, sockets, and devices can't be sensibly copied */
+        errno = EPERM;
+    }
+
+    if (isSuccess) {
+        trace("Copied '%s' up to '%s'", basePath, upperPath);
+    } else {
+        warning("Unable to copy '%s' up to '%s': %s", basePath, upperPath, g_strerror(errno));
+    }
+
+    return isSuccess;
+}
+
+/* Makes sure that the path exists in the upper directory if it exists in the
+ * host's view of the base tree. */
+static void _fsoverlay_copyUpIfNeeded(FsOverlay* overlay, const char* normPath) {
+    char* upperPath = _fsoverlay_join(overlay->upperPath, normPath);
+    char* basePath = _fsoverlay_join(overlay->basePath, normPath);
+    struct stat statbuf;
+
+    if (lstat(upperPath, &statbuf) != 0 && !_fsoverlay_isWhitedOut(overlay, normPath) &&
+        lstat(basePath, &statbuf) == 0) {
+        _fsoverlay_copyUp(basePath, upperPath, &statbuf);
+    }
+
+    free(upperPath);
+    free(basePath);
+}
+
+/* Returns the native path at which the host currently sees `normPath`: the
+ * upper path if the path was modified or removed, otherwise the base path. */
+static char* _fsoverlay_getVisiblePath(FsOverlay* overlay, const char* normPath) {
+    char* upperPath = _fsoverlay_join(overlay->upperPath, normPath);
+    struct stat statbuf;
+
+    if (lstat(upperPath, &statbuf) == 0 || _fsoverlay_isWhitedOut(overlay, normPath)) {
+        return upperPath;
+    }
+
+    free(upperPath);
+    return _fsoverlay_join(overlay->basePath, normPath);
+}
+
+/* Resolves symbolic links in the host's view of `normPath` so that links are
+ * followed inside the overlay rather than on the native filesystem. The final
+ * path component is only followed if `followFinal` is true. Returns a new
+ * normalized path. */
+static gchar* _fsoverlay_resolveLinks(FsOverlay* overlay, const char* normPath, bool followFinal) {
+    gchar* current = g_strdup(normPath);
+
+    for (int numLinks = 0; numLinks < FSOVERLAY_MAX_SYMLINKS; numLinks++) {
+        gchar* resolved = NULL;
+
+        for (char* sep = strchr(current + 1, '/'); resolved == NULL; sep = strchr(sep + 1, '/')) {
+            bool isFinal = (sep == NULL);
+            if (isFinal && (!followFinal || !strcmp(current, "/"))) {
+                break;
+            }
+            if (!isFinal) {
+                *sep = '\0';
+            }
+
+            /* native paths handle their own links */
+            if (_fsoverlay_isPassthrough(overlay, current)) {
+                if (!isFinal) {
+                    *sep = '/';
+                }
+                break;
+            }
+
+            char* visiblePath = _fsoverlay_getVisiblePath(overlay, current);
+            char target[PATH_MAX + 1] = {0};
+            ssize_t len = readlink(visiblePath, target, PATH_MAX);
+            free(visiblePath);
+
+            if (len > 0) {
+                /* relative targets are relative to the directory containing the link */
+                gchar* linkDir = g_path_get_dirname(current);
+                const char* rest = isFinal ? "" : sep + 1;
+                gchar* newPath = (target[0] == '/')
+                                     ? g_build_path("/", target, rest, NULL)
+                                     : g_build_path("/", linkDir, target, rest, NULL);
+                resolved = _fsoverlay_normalize(newPath);
+                g_free(newPath);
+                g_free(linkDir);
+            }
+
+            if (isFinal) {
+                break;
+            }
+            *sep = '/';
+        }
+
+        if (resolved == NULL) {
+            /* no links left to follow */
+            return current;
+        }
+
+        g_free(current);
+        current = resolved;
+    }
+
+    /* too many levels of links; let the native syscall report ELOOP */
+    g_free(current);
+    return g_strdup(normPath);
+}
+
+char* fsoverlay_resolve(FsOverlay* overlay, const char* path, FsOverlayAccess access,
+                        bool followFinal) {
+    MAGIC_ASSERT(overlay);
+
+    gchar* normPath = _fsoverlay_normalize(path);
+
+    if (!_fsoverlay_isPassthrough(overlay, normPath)) {
+        gchar* linkFree = _fsoverlay_resolveLinks(overlay, normPath, followFinal);
+        g_free(normPath);
+        normPath = linkFree;
+    }
+
+    if (_fsoverlay_isPassthrough(overlay, normPath)) {
+        char* nativePath = strdup(normPath);
+        g_free(normPath);
+        return nativePath;
+    }
+
+    char* nativePath = NULL;
+
+    if (access == FSOVERLAY_ACCESS_WRITE) {
+        /* copy each ancestor directory, and then the path itself */
+        for (char* sep = strchr(normPath + 1, '/'); sep != NULL; sep = strchr(sep + 1, '/')) {
+            *sep = '\0';
+            _fsoverlay_copyUpIfNeeded(overlay, normPath);
+            *sep = '/';
+        }
+        if (strcmp(normPath, "/")) {
+            _fsoverlay_copyUpIfNeeded(overlay, normPath);
+        }
+
+        /* writes always go to the upper directory */
+        nativePath = _fsoverlay_join(overlay->upperPath, normPath);
+    } else {
+        nativePath = _fsoverlay_getVisiblePath(overlay, normPath);
+    }
+
+    trace("Resolved overlay path '%s' to '%s'", path, nativePath);
+
+    g_free(normPath);
+    return nativePath;
+}
+
+typedef struct _FsOverlayListFilter FsOverlayListFilter;
+struct _FsOverlayListFilter {
+    FsOverlay* overlay;
+    const char* normDirPath;
+};
+
+/* Skips the entries of a base directory that were removed from the host's view. */
+static bool _fsoverlay_isListed(const char* name, void* data) {
+    FsOverlayListFilter* filter = data;
+    if (!strcmp(name, ".") || !strcmp(name, "..")) {
+        return true;
+    }
+
+    gchar* normPath = g_build_path("/", filter->normDirPath, name, NULL);
+    bool isListed = !_fsoverlay_isWhitedOut(filter->overlay, normPath);
+    g_free(normPath);
+    return isListed;
+}
+
+static bool _fsoverlay_isDirectory(const char* nativePath) {
+    struct stat statbuf;
+    return lstat(nativePath, &statbuf) == 0 && S_ISDIR(statbuf.st_mode);
+}
+
+DirListing* fsoverlay_listDirectory(FsOverlay* overlay, const char* path, bool followFinal) {
+    MAGIC_ASSERT(overlay);
+
+    gchar* normPath = _fsoverlay_normalize(path);
+
+    if (!_fsoverlay_isPassthrough(overlay, normPath)) {
+        gchar* linkFree = _fsoverlay_resolveLinks(overlay, normPath, followFinal);
+        g_free(normPath);
+        normPath = linkFree;
+    }
+
+    if (_fsoverlay_isPassthrough(overlay, normPath)) {
+        g_free(normPath);
+        return NULL;
+    }
+
+    char* upperPath = _fsoverlay_join(overlay->upperPath, normPath);
+    char* basePath = _fsoverlay_join(overlay->basePath, normPath);
+    struct stat statbuf;
+
+    /* a file in the upper directory hides a directory in the base tree */
+    bool upperExists = (lstat(upperPath, &statbuf) == 0);
+    bool isUpperDir = upperExists && S_ISDIR(statbuf.st_mode);
+    bool isBaseDir = !(upperExists && !isUpperDir) &&
+                     !_fsoverlay_isWhitedOut(overlay, normPath) &&
+                     _fsoverlay_isDirectory(basePath);
+
+    DirListing* listing = NULL;
+
+    if (isUpperDir || isBaseDir) {
+        listing = dirlisting_new();
+
+        /* entries in the upper directory hide base entries with the same name */
+        if (isUpperDir) {
+            dirlisting_addNative(listing, upperPath, NULL, NULL);
+        }
+        if (isBaseDir) {
+            FsOverlayListFilter filter = {.overlay = overlay, .normDirPath = normPath};
+            dirlisting_addNative(listing, basePath, _fsoverlay_isListed, &filter);
+        }
+    }
+
+    free(upperPath);
+    free(basePath);
+    g_free(normPath);
+    return listing;
+}
+
+bool fsoverlay_hasBaseEntries(FsOverlay* overlay, const char* path) {
+    MAGIC_ASSERT(overlay);
+
+    gchar* normPath = _fsoverlay_normalize(path);
+
+    if (!_fsoverlay_isPassthrough(overlay, normPath)) {
+        gchar* linkFree = _fsoverlay_resolveLinks(overlay, normPath, false);
+        g_free(normPath);
+        normPath = linkFree;
+    }
+
+    if (_fsoverlay_isPassthrough(overlay, normPath) || _fsoverlay_isWhitedOut(overlay, normPath)) {
+        g_free(normPath);
+        return false;
+    }
+
+    char* upperPath = _fsoverlay_join(overlay->upperPath, normPath);
+    char* basePath = _fsoverlay_join(overlay->basePath, normPath);
+    struct stat statbuf;
+
+    /* a file in the upper directory hides a directory in the base tree */
+    bool hasEntries = false;
+    if ((lstat(upperPath, &statbuf) != 0 || S_ISDIR(statbuf.st_mode)) &&
+        _fsoverlay_isDirectory(basePath)) {
+        DirListing* listing = dirlisting_new();
+        FsOverlayListFilter filter = {.overlay = overlay, .normDirPath = normPath};
+        dirlisting_addNative(listing, basePath, _fsoverlay_isListed, &filter);
+        hasEntries = !dirlisting_isEmpty(listing);
+        dirlisting_unref(listing);
+    }
+
+    free(upperPath);
+    free(basePath);
+    g_free(normPath);
+    return hasEntries;
+}
+
+void fsoverlay_markRemoved(FsOverlay* overlay, const char* path) {
+    MAGIC_ASSERT(overlay);
+
+    gchar* normPath = _fsoverlay_normalize(path);
+
+    if (!_fsoverlay_isPassthrough(overlay, normPath)) {
+        gchar* linkFree = _fsoverlay_resolveLinks(overlay, normPath, false);
+        g_free(normPath);
+        normPath = linkFree;
+    }
+
+    if (!_fsoverlay_isPassthrough(overlay, normPath)) {
+        char* basePath = _fsoverlay_join(overlay->basePath, normPath);
+        struct stat statbuf;
+
+        if (lstat(basePath, &statbuf) == 0) {
+            trace("Hiding removed path '%s' from the base tree", normPath);
+            g_hash_table_add(overlay->whiteouts, normPath);
+            normPath = NULL;
+        }
+
+        free(basePath);
+    }
+
+    g_free(normPath);
+}
@@ -0,0 +1,77 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef SRC_MAIN_HOST_FS_OVERLAY_H_
+#define SRC_MAIN_HOST_FS_OVERLAY_H_
+
+#include <stdbool.h>
+
+#include "main/host/dir_listing.h"
+
+/* A per-host view of the filesystem. Paths are looked up first in a writable
+ * per-host "upper" directory, and then in a read-only "base" tree (the real
+ * root directory or a configured template tree). Any path that is modified is
+ * first copied from the base tree into the upper directory, so that hosts can
+ * write to fixed absolute paths like /var/lib/app without seeing or clobbering
+ * each other's files, and without modifying the base tree.
+ *
+ * Removed paths that still exist in the base tree are hidden by an in-memory
+ * whiteout. Directory listings are merged, so a directory that was copied into
+ * the upper directory still lists the entries of the same directory in the
+ * base tree. */
+
+/* Opaque object to store the state needed to implement the module. */
+typedef struct _FsOverlay FsOverlay;
+
+/* How a path is going to be used, which determines whether it needs to be
+ * copied into the upper directory before the native path is returned. */
+typedef enum _FsOverlayAccess FsOverlayAccess;
+enum _FsOverlayAccess {
+    /* the path will only be read from */
+    FSOVERLAY_ACCESS_READ,
+    /* the path, or its parent directory, may be modified or created */
+    FSOVERLAY_ACCESS_WRITE,
+};
+
+/* Create a new overlay that reads from `basePath` and writes to `upperPath`.
+ * `upperPath` is created if it does not exist. Paths that begin with any of
+ * the strings in the NULL-terminated `passthroughPaths` (e.g., the host's
+ * data directory) are not overlaid and always refer to the native path. */
+FsOverlay* fsoverlay_new(const char* basePath, const char* upperPath,
+                         const char* const* passthroughPaths);
+void fsoverlay_free(FsOverlay* overlay);
+
+/* Returns the native path that should be used to access the absolute path
+ * `path` as seen by the host. The returned string must be freed with free().
+ * Symbolic links are followed within the host's view of the filesystem; the
+ * last component of `path` is only followed if `followFinal` is true. For
+ * FSOVERLAY_ACCESS_WRITE, the path and its parent directories are copied into
+ * the upper directory first if they only exist in the base tree. */
+char* fsoverlay_resolve(FsOverlay* overlay, const char* path, FsOverlayAccess access,
+                        bool followFinal);
+
+/* Returns the entries of the directory at the absolute path `path` as seen by
+ * the host, i.e., the entries of the directory in the upper directory followed
+ * by those of the same directory in the base tree that were not removed.
+ * Returns NULL if the path is not overlaid (in which case the native directory
+ * should be listed) or is not a directory in the host's view. The final
+ * component of `path` is only followed if it is a link and `followFinal` is
+ * true. The returned listing must be released with dirlisting_unref(). */
+DirListing* fsoverlay_listDirectory(FsOverlay* overlay, const char* path, bool followFinal);
+
+/* Returns true if the absolute path `path` is a directory in the host's view
+ * that lists entries of the same directory in the base tree. The final
+ * component of `path` is not followed. Such a directory can't be renamed,
+ * since only its upper directory would be moved, so like overlayfs (without
+ * redirect_dir), renaming it should fail with EXDEV so that tools fall back to
+ * copying its contents. */
+bool fsoverlay_hasBaseEntries(FsOverlay* overlay, const char* path);
+
+/* Notify the overlay that `path` was successfully removed (unlinked, rmdir'd,
+ * or renamed) from the upper directory, so that any version of it in the base
+ * tree will be hidden from now on. */
+void fsoverlay_markRemoved(FsOverlay* overlay, const char* path);
+
+#endif /* SRC_MAIN_HOST_FS_OVERLAY_H_ */
@@ -19,9 +19,11 @@
 
 #include "lib/logger/log_level.h"
 #include "lib/logger/logger.h"
+#include "main/bindings/c/bindings.h"
 #include "main/core/support/definitions.h"
 #include "main/core/worker.h"
 #include "main/host/cpu.h"
+#include "main/host/disk.h"
 #include "main/host/descriptor/channel.h"
 #include "main/host/descriptor/compat_socket.h"
 #include "main/host/descriptor/descriptor.h"
@@ -60,12 +62,20 @@ struct _Host {
     Address* defaultAddress;
     CPU* cpu;
 
+    /* the model of our storage device, or NULL if disk io takes no simulated time */
+    Disk* disk;
+
     /* the virtual processes this host is running */
     GQueue* processes;
 
     /* a statistics tracker for in/out bytes, CPU, memory, etc. */
     Tracker* tracker;
 
+    /* totals for the end-of-simulation statistics */
+    guint64 numEventsProcessed;
+    /* dropped packets by reason, created when the first packet is dropped */
+    Counter* droppedPackets;
+
     /* virtual process and event id counter */
     guint processIDCounter;
     guint64 eventIDCounter;
@@ -90,6 +100,12 @@ struct _Host {
 
     gchar* dataDirPath;
 
+    /* the host's view of the filesystem, or NULL if it uses the native filesystem */
+    FsOverlay* fsOverlay;
+
+    /* captures the packets of all of the host's interfaces, or NULL if disabled */
+    PCapWriter* pcap;
+
     gint referenceCount;
     MAGIC_DECLARE;
 };
@@ -107,7 +123,7 @@ Host* host_new(HostParameters* params) {
     host->executionTimer = g_timer_new();
 #endif
 
-    /* first copy the entire struct of params */
+    /* first copy the entire struct of params, taking ownership of the capture filter */
     host->params = *params;
 
     /* now dup the strings so we own them */
@@ -116,7 +132,9 @@ Host* host_new(HostParameters* params) {
     if(params->ipHint) host->params.ipHint = g_strdup(params->ipHint);
     if(params->citycodeHint) host->params.citycodeHint = g_strdup(params->citycodeHint);
     if(params->countrycodeHint) host->params.countrycodeHint = g_strdup(params->countrycodeHint);
+    if(params->ipAddr) host->params.ipAddr = g_strdup(params->ipAddr);
     if(params->pcapDir) host->params.pcapDir = g_strdup(params->pcapDir);
+    if(params->fsOverlayBase) host->params.fsOverlayBase = g_strdup(params->fsOverlayBase);
 
     /* thread-level event communication with other nodes */
     g_mutex_init(&(host->lock));
@@ -147,12 +165,16 @@ Host* host_new(HostParameters* params) {
 }
 
 /* this function is called by manager before the workers exist */
-void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, const gchar* hostRootPath) {
+void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, const gchar* hostRootPath,
+                const NetworkInterfaceCapture* mergedCapture) {
     MAGIC_ASSERT(host);
 
     /* get unique virtual address identifiers for each network interface */
     Address* loopbackAddress = dns_register(dns, host->params.id, host->params.hostname, "127.0.0.1");
-    Address* ethernetAddress = dns_register(dns, host->params.id, host->params.hostname, host->params.ipHint);
+    Address* ethernetAddress =
+        host->params.ipAddr
+            ? dns_registerReserved(dns, host->params.id, host->params.hostname, host->params.ipAddr)
+            : dns_register(dns, host->params.id, host->params.hostname, host->params.ipHint);
     host->defaultAddress = ethernetAddress;
     address_ref(host->defaultAddress);
 
@@ -161,15 +183,34 @@ void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, cons
         g_mkdir_with_parents(host->dataDirPath, 0775);
     }
 
+    if (host->params.useFsOverlay) {
+        /* the host's own data directory (including its working directory) is never overlaid */
+        gchar* upperPath = g_build_filename(host->dataDirPath, "fs", NULL);
+        const char* passthroughPaths[] = {host->dataDirPath, NULL};
+        const char* basePath = host->params.fsOverlayBase ? host->params.fsOverlayBase : "/";
+        host->fsOverlay = fsoverlay_new(basePath, upperPath, passthroughPaths);
+        g_free(upperPath);
+    }
+
     host->random = random_new(host->params.nodeSeed);
-    host->cpu = cpu_new(host->params.cpuFrequency, (guint64)rawCPUFreq, host->params.cpuThreshold, host->params.cpuPrecision);
+    host->cpu = cpu_new(host->params.cpuFrequency, (guint64)rawCPUFreq, host->params.cpuThreshold,
+                        host->params.cpuPrecision, host->params.cpuCores);
+
+    if (host->params.diskReadLatency || host->params.diskReadBandwidth ||
+        host->params.diskWriteLatency || host->params.diskWriteBandwidth ||
+        host->params.diskSyncLatency) {
+        host->disk = disk_new(host->params.diskReadLatency, host->params.diskReadBandwidth,
+                              host->params.diskWriteLatency, host->params.diskWriteBandwidth,
+                              host->params.diskSyncLatency);
+    }
 
     // Table to track futexes used by processes/threads
     host->futexTable = futextable_new();
 
     /* connect to topology and get the default bandwidth */
     guint64 bwDownKiBps = 0, bwUpKiBps = 0;
-    topology_attach(topology, ethernetAddress, host->random, host->params.ipHint,
+    topology_attach(topology, ethernetAddress, host->random, host->params.networkNodeId,
+                    host->params.ipAddr ? host->params.ipAddr : host->params.ipHint,
                     host->params.citycodeHint, host->params.countrycodeHint, &bwDownKiBps,
                     &bwUpKiBps);
 
@@ -181,13 +222,52 @@ void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, cons
         bwUpKiBps = host->params.requestedBWUpKiBps;
     }
 
+    if (host->params.pcapDir) {
+        gchar* ipString = g_strdup_printf("ip %s", address_toHostIPString(ethernetAddress));
+        gchar* hostString = g_strdup_printf("host %s", host->params.hostname);
+        gchar* seedString = g_strdup_printf("seed %u", host->params.simulationSeed);
+        const gchar* comments[] = {hostString, ipString, seedString, NULL};
+
+        /* relative paths are relative to the host's data directory */
+        gchar* pcapDir = g_path_is_absolute(host->params.pcapDir)
+                             ? g_strdup(host->params.pcapDir)
+                             : g_build_filename(host->dataDirPath, host->params.pcapDir, NULL);
+        g_mkdir_with_parents(pcapDir, 0775);
+
+        gchar* filename = g_strdup_printf("%s.pcapng", host->params.hostname);
+        gchar* path = g_build_filename(pcapDir, filename, NULL);
+        host->pcap = pcapwriter_new(path, comments, FALSE);
+
+        g_free(path);
+        g_free(pcapDir);
+        g_free(filename);
+        g_free(seedString);
+        g_free(hostString);
+        g_free(ipString);
+    }
+
+    utility_assert(mergedCapture);
+    NetworkInterfacePCapOptions pcapOptions = {
+        .host =
+            {
+                .pcap = host->pcap,
+                .captureSize = host->params.pcapCaptureSize,
+                .filter = host->params.pcapFilter,
+                .startTime = host->params.pcapStartTime,
+                .stopTime = host->params.pcapStopTime,
+            },
+        .merged = *mergedCapture,
+    };
+
     /* virtual addresses and interfaces for managing network I/O */
     NetworkInterface* loopback =
-        networkinterface_new(host, loopbackAddress, G_MAXUINT32, G_MAXUINT32, host->params.pcapDir,
-                             host->params.qdisc, host->params.interfaceBufSize);
+        networkinterface_new(host, loopbackAddress, "lo", G_MAXUINT32, G_MAXUINT32, &pcapOptions,
+                             host->params.qdisc, host->params.interfaceBufSize,
+                             CONFIG_LOOPBACK_MTU);
     NetworkInterface* ethernet =
-        networkinterface_new(host, ethernetAddress, bwDownKiBps, bwUpKiBps, host->params.pcapDir,
-                             host->params.qdisc, host->params.interfaceBufSize);
+        networkinterface_new(host, ethernetAddress, "eth0", bwDownKiBps, bwUpKiBps, &pcapOptions,
+                             host->params.qdisc, host->params.interfaceBufSize,
+                             host->params.interfaceMtu);
 
     g_hash_table_replace(host->interfaces, GUINT_TO_POINTER((guint)address_toNetworkIP(ethernetAddress)), ethernet);
     g_hash_table_replace(host->interfaces, GUINT_TO_POINTER((guint)htonl(INADDR_LOOPBACK)), loopback);
@@ -205,11 +285,11 @@ void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, cons
          "%" G_GUINT64_FORMAT " bwUpKiBps, %" G_GUINT64_FORMAT " bwDownKiBps, "
          "%" G_GUINT64_FORMAT " initSockSendBufSize, %" G_GUINT64_FORMAT " initSockRecvBufSize, "
          "%" G_GUINT64_FORMAT " cpuFrequency, %" G_GUINT64_FORMAT " cpuThreshold, "
-         "%" G_GUINT64_FORMAT " cpuPrecision",
+         "%" G_GUINT64_FORMAT " cpuPrecision, %u cpuCores",
          (guint)host->params.id, host->params.hostname, host->params.nodeSeed,
          address_toHostIPString(host->defaultAddress), bwUpKiBps, bwDownKiBps,
          host->params.sendBufSize, host->params.recvBufSize, host->params.cpuFrequency,
-         host->params.cpuThreshold, host->params.cpuPrecision);
+         host->params.cpuThreshold, host->params.cpuPrecision, cpu_getCores(host->cpu));
 }
 
 static void _host_free(Host* host) {
@@ -230,6 +310,14 @@ void host_shutdown(Host* host) {
 
     debug("shutting down host %s", host->params.hostname);
 
+    simstats_addHost(worker_getSimStats(), host->params.hostname, host->numEventsProcessed,
+                     tracker_getTotalBytesSent(host->tracker),
+                     tracker_getTotalBytesReceived(host->tracker), host->droppedPackets);
+    if (host->droppedPackets) {
+        counter_free(host->droppedPackets);
+        host->droppedPackets = NULL;
+    }
+
     if(host->processes) {
         g_queue_free(host->processes);
     }
@@ -243,6 +331,12 @@ void host_shutdown(Host* host) {
         g_hash_table_destroy(host->interfaces);
     }
 
+    /* the interfaces write to the capture, so it must outlive them */
+    if (host->pcap) {
+        pcapwriter_free(host->pcap);
+        host->pcap = NULL;
+    }
+
     if(host->router) {
         router_unref(host->router);
     }
@@ -258,6 +352,9 @@ void host_shutdown(Host* host) {
     if(host->cpu) {
         cpu_free(host->cpu);
     }
+    if (host->disk) {
+        disk_free(host->disk);
+    }
     if(host->tracker) {
         tracker_free(host->tracker);
     }
@@ -269,7 +366,16 @@ void host_shutdown(Host* host) {
     if(host->params.ipHint) g_free(host->params.ipHint);
     if(host->params.citycodeHint) g_free(host->params.citycodeHint);
     if(host->params.countrycodeHint) g_free(host->params.countrycodeHint);
+    if(host->params.ipAddr) g_free(host->params.ipAddr);
     if(host->params.pcapDir) g_free(host->params.pcapDir);
+    if(host->params.fsOverlayBase) g_free(host->params.fsOverlayBase);
+    if (host->params.pcapFilter) {
+        pcapfilter_free(host->params.pcapFilter);
+    }
+
+    if (host->fsOverlay) {
+        fsoverlay_free(host->fsOverlay);
+    }
 
     g_mutex_clear(&(host->lock));
 
@@ -338,8 +444,9 @@ void host_boot(Host* host) {
     MAGIC_ASSERT(host);
 
     /* must be done after the default IP exists so tracker_heartbeat works */
-    host->tracker = tracker_new(host, host->params.heartbeatInterval,
-                                host->params.heartbeatLogLevel, host->params.heartbeatLogInfo);
+    host->tracker =
+        tracker_new(host, host->params.heartbeatInterval, host->params.heartbeatLogLevel,
+                    host->params.heartbeatLogInfo, host->params.heartbeatFormat);
 
     /* start refilling the token buckets for all interfaces */
     GHashTableIter iter;
@@ -376,28 +483,53 @@ guint64 host_getNewPacketID(Host* host) {
 }
 
 void host_addApplication(Host* host, SimulationTime startTime, SimulationTime stopTime,
+                         int shutdownSignal, SimulationTime shutdownGracePeriod,
+                         RestartPolicy restartPolicy, SimulationTime restartDelay, int maxRestarts,
+                         const gchar* name, StartConditions* startAfter, bool terminatesSimulation,
                          InterposeMethod interposeMethod, const gchar* pluginName,
-                         const gchar* pluginPath, gchar** envv, gchar** argv) {
+                         const gchar* pluginPath, gchar** envv, gchar** argv,
+                         const gchar* stdinPath, const gchar* stdinContent, bool straceLogging,
+                         ProcessFinalState* expectedFinalState) {
     MAGIC_ASSERT(host);
     guint processID = host_getNewProcessID(host);
     Process* proc = process_new(host,
                                 processID,
                                 startTime,
                                 stopTime,
+                                shutdownSignal,
+                                shutdownGracePeriod,
+                                restartPolicy,
+                                restartDelay,
+                                maxRestarts,
+                                name,
+                                startAfter,
+                                terminatesSimulation,
                                 interposeMethod,
                                 host_getName(host),
                                 pluginName,
                                 pluginPath,
                                 envv,
-                                argv);
+                                argv,
+                                stdinPath,
+                                stdinContent,
+                                straceLogging,
+                                expectedFinalState);
     g_queue_push_tail(host->processes, proc);
 }
 
+void host_restartApplication(Host* host, Process* proc) {
+    MAGIC_ASSERT(host);
+    Process* restarted = process_newRestart(proc, host_getNewProcessID(host));
+    g_queue_push_tail(host->processes, restarted);
+    process_schedule(restarted, NULL);
+}
+
 void host_freeAllApplications(Host* host) {
     MAGIC_ASSERT(host);
     trace("start freeing applications for host '%s'", host->params.hostname);
     while(!g_queue_is_empty(host->processes)) {
         Process* proc = g_queue_pop_head(host->processes);
+        process_markKilledAtShutdown(proc);
         process_stop(proc);
         process_unref(proc);
     }
@@ -427,6 +559,11 @@ CPU* host_getCPU(Host* host) {
     return host->cpu;
 }
 
+Disk* host_getDisk(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->disk;
+}
+
 gchar* host_getName(Host* host) {
     MAGIC_ASSERT(host);
     return host->params.hostname;
@@ -489,6 +626,19 @@ void host_associateInterface(Host* host, const CompatSocket* socket, in_addr_t b
     }
 }
 
+void host_iterAssociatedSockets(Host* host, CompatSocketIterFunc func, void* data) {
+    MAGIC_ASSERT(host);
+
+    GHashTableIter iter;
+    gpointer key, value;
+    g_hash_table_iter_init(&iter, host->interfaces);
+
+    while (g_hash_table_iter_next(&iter, &key, &value)) {
+        NetworkInterface* interface = value;
+        networkinterface_iterAssociatedSockets(interface, func, data);
+    }
+}
+
 void host_disassociateInterface(Host* host, const CompatSocket* socket) {
     if (socket == NULL) {
         return;
@@ -526,6 +676,21 @@ guint64 host_getConfiguredSendBufSize(Host* host) {
     return host->params.sendBufSize;
 }
 
+FsOverlay* host_getFsOverlay(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->fsOverlay;
+}
+
+guint64 host_getCPUFrequency(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.cpuFrequency;
+}
+
+guint host_getCPUCores(Host* host) {
+    MAGIC_ASSERT(host);
+    return cpu_getCores(host->cpu);
+}
+
 gboolean host_doesInterfaceExist(Host* host, in_addr_t interfaceIP) {
     MAGIC_ASSERT(host);
 
@@ -575,6 +740,22 @@ gboolean host_isInterfaceAvailable(Host* host, ProtocolType type,
     return isAvailable;
 }
 
+gboolean host_isListening(Host* host, in_port_t port) {
+    MAGIC_ASSERT(host);
+
+    GHashTableIter iter;
+    gpointer key, value;
+    g_hash_table_iter_init(&iter, host->interfaces);
+
+    while (g_hash_table_iter_next(&iter, &key, &value)) {
+        if (networkinterface_isListening(value, port)) {
+            return TRUE;
+        }
+    }
+
+    return FALSE;
+}
+
 static in_port_t _host_getRandomPort(Host* host) {
     gdouble randomFraction = random_nextDouble(host->random);
     gdouble numPotentialPorts = (gdouble)(UINT16_MAX - MIN_RANDOM_PORT);
@@ -631,6 +812,19 @@ in_port_t host_getRandomFreePort(Host* host, ProtocolType type,
     return 0;
 }
 
+void host_countProcessedEvent(Host* host) {
+    MAGIC_ASSERT(host);
+    host->numEventsProcessed++;
+}
+
+void host_countDroppedPacket(Host* host, const gchar* reason) {
+    MAGIC_ASSERT(host);
+    if (!host->droppedPackets) {
+        host->droppedPackets = counter_new();
+    }
+    counter_add_value(host->droppedPackets, reason, 1);
+}
+
 Tracker* host_getTracker(Host* host) {
     MAGIC_ASSERT(host);
     return host->tracker;
@@ -641,6 +835,11 @@ LogLevel host_getLogLevel(Host* host) {
     return host->params.logLevel;
 }
 
+gboolean host_getLogToFile(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.logToFile;
+}
+
 gdouble host_getNextPacketPriority(Host* host) {
     MAGIC_ASSERT(host);
     return ++(host->packetPriorityCounter);
@@ -673,3 +872,101 @@ pid_t host_getNativeTID(Host* host, pid_t virtualPID, pid_t virtualTID) {
 
     return nativeTID; // 0 if no process/thread has the given virtual PID/TID
 }
+
+Process* host_getProcess(Host* host, pid_t virtualPID) {
+    MAGIC_ASSERT(host);
+
+    // TODO: once we have a process table, we can do a constant time lookup instead
+    for (GList* current = g_queue_peek_head_link(host->processes); current != NULL;
+         current = current->next) {
+        Process* proc = current->data;
+        if (process_getProcessID(proc) == virtualPID) {
+            return proc;
+        }
+    }
+
+    return NULL; // no process on this host has the given virtual PID
+}
+
+guint64 host_getMemoryLimit(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.memoryLimit;
+}
+
+guint64 host_getMemoryUsage(Host* host) {
+    MAGIC_ASSERT(host);
+
+    guint64 totalUsage = 0;
+    for (GList* current = g_queue_peek_head_link(host->processes); current != NULL;
+         current = current->next) {
+        Process* other = current->data;
+        if (!process_wasOOMKilled(other)) {
+            totalUsage += process_getMemoryUsage(other);
+        }
+    }
+    return totalUsage;
+}
+
+void host_iterProcesses(Host* host, HostProcessIterFunc func, void* data) {
+    MAGIC_ASSERT(host);
+
+    for (GList* current = g_queue_peek_head_link(host->processes); current != NULL;
+         current = current->next) {
+        func(current->data, data);
+    }
+}
+
+bool host_reserveMemory(Host* host, Process* proc, size_t numBytes) {
+    MAGIC_ASSERT(host);
+
+    if (host->params.memoryLimit == 0 || numBytes == 0) {
+        return true;
+    }
+
+    guint64 totalUsage = host_getMemoryUsage(host);
+
+    while (totalUsage + numBytes > host->params.memoryLimit) {
+        if (host->params.memoryLimitAction != MEMORY_LIMIT_ACTION_KILL) {
+            info("Allocation of %zu bytes by process '%s' would exceed the memory limit of "
+                 "%" G_GUINT64_FORMAT " bytes on host '%s', which is already using "
+                 "%" G_GUINT64_FORMAT " bytes",
+                 numBytes, process_getName(proc), host->params.memoryLimit, host->params.hostname,
+                 totalUsage);
+            return false;
+        }
+
+        /* Kill the process using the most memory. Ties go to the process that was
+         * added to the host first, so the choice is deterministic. */
+        Process* victim = NULL;
+        size_t victimUsage = 0;
+        for (GList* current = g_queue_peek_head_link(host->processes); current != NULL;
+             current = current->next) {
+            Process* other = current->data;
+            size_t usage = process_wasOOMKilled(other) ? 0 : process_getMemoryUsage(other);
+            if (usage > 0 && (!victim || usage > victimUsage)) {
+                victim = other;
+                victimUsage = usage;
+            }
+        }
+
+        if (!victim) {
+            /* the allocation alone is larger than the limit */
+            victim = proc;
+            victimUsage = 0;
+        }
+
+        warning("Host '%s' is out of memory (limit %" G_GUINT64_FORMAT " bytes): killing "
+                "process '%s' using %zu bytes",
+                host->params.hostname, host->params.memoryLimit, process_getName(victim),
+                victimUsage);
+        process_killOutOfMemory(victim);
+
+        if (victim == proc) {
+            return false;
+        }
+        totalUsage -= MIN(totalUsage, victimUsage);
+    }
+
+    return true;
+}
+
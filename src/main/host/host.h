@@ -8,6 +8,7 @@
 #define SHD_HOST_H_
 
 #include <glib.h>
+#include <stdbool.h>
 #include <netinet/in.h>
 #include <poll.h>
 #include <pthread.h>
@@ -20,6 +21,8 @@
 #include "main/host/cpu.h"
 #include "main/host/descriptor/compat_socket.h"
 #include "main/host/descriptor/descriptor.h"
+#include "main/host/disk.h"
+#include "main/host/fs_overlay.h"
 #include "main/host/futex_table.h"
 #include "main/host/host_parameters.h"
 #include "main/host/network_interface.h"
@@ -46,7 +49,10 @@ void host_stopExecutionTimer(Host* host);
 #define host_stopExecutionTimer(host)
 #endif
 
-void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, const gchar* hostRootPath);
+/* Packets on the host's interfaces are also captured as described by `mergedCapture`, whose
+ * writer and filter must outlive the host. */
+void host_setup(Host* host, DNS* dns, Topology* topology, guint rawCPUFreq, const gchar* hostRootPath,
+                const NetworkInterfaceCapture* mergedCapture);
 void host_boot(Host* host);
 void host_shutdown(Host* host);
 
@@ -54,8 +60,15 @@ guint host_getNewProcessID(Host* host);
 guint64 host_getNewEventID(Host* host);
 guint64 host_getNewPacketID(Host* host);
 void host_addApplication(Host* host, SimulationTime startTime, SimulationTime stopTime,
+                         int shutdownSignal, SimulationTime shutdownGracePeriod,
+                         RestartPolicy restartPolicy, SimulationTime restartDelay, int maxRestarts,
+                         const gchar* name, StartConditions* startAfter, bool terminatesSimulation,
                          InterposeMethod interposeMethod, const gchar* pluginName,
-                         const gchar* pluginPath, gchar** envv, gchar** argv);
+                         const gchar* pluginPath, gchar** envv, gchar** argv,
+                         const gchar* stdinPath, const gchar* stdinContent, bool straceLogging,
+                         ProcessFinalState* expectedFinalState);
+/* Adds a new process that runs the same program as `proc`, and starts it. */
+void host_restartApplication(Host* host, Process* proc);
 void host_detachAllPlugins(Host* host);
 void host_freeAllApplications(Host* host);
 
@@ -63,6 +76,8 @@ gint host_compare(gconstpointer a, gconstpointer b, gpointer user_data);
 GQuark host_getID(Host* host);
 gboolean host_isEqual(Host* a, Host* b);
 CPU* host_getCPU(Host* host);
+/* Returns NULL if the host does not model disk io. */
+Disk* host_getDisk(Host* host);
 gchar* host_getName(Host* host);
 Address* host_getDefaultAddress(Host* host);
 in_addr_t host_getDefaultIP(Host* host);
@@ -73,23 +88,53 @@ gboolean host_autotuneReceiveBuffer(Host* host);
 gboolean host_autotuneSendBuffer(Host* host);
 guint64 host_getConfiguredRecvBufSize(Host* host);
 guint64 host_getConfiguredSendBufSize(Host* host);
+/* Returns the configured CPU frequency of the host in KHz */
+guint64 host_getCPUFrequency(Host* host);
+guint host_getCPUCores(Host* host);
 
 NetworkInterface* host_lookupInterface(Host* host, in_addr_t handle);
 Router* host_getUpstreamRouter(Host* host, in_addr_t handle);
 
 void host_returnHandleHack(gint handle);
 
+/* Counts statistics that are written when the simulation ends. */
+void host_countProcessedEvent(Host* host);
+void host_countDroppedPacket(Host* host, const gchar* reason);
+
 Tracker* host_getTracker(Host* host);
 LogLevel host_getLogLevel(Host* host);
+gboolean host_getLogToFile(Host* host);
 
 const gchar* host_getDataPath(Host* host);
 
+/* Returns the host's view of the filesystem, or NULL if the host uses the native filesystem */
+FsOverlay* host_getFsOverlay(Host* host);
+
+/* Returns the host's memory limit in bytes, or 0 if memory is not limited. */
+guint64 host_getMemoryLimit(Host* host);
+/* Returns the number of bytes of memory that the host's processes use, as
+ * counted for the memory limit. */
+guint64 host_getMemoryUsage(Host* host);
+
+/* Returns true if `proc` may allocate `numBytes` more bytes of memory without
+ * exceeding the host's memory limit. If the limit would be exceeded and the
+ * host is configured to kill processes, the host's processes using the most
+ * memory are killed until the allocation fits; false is returned if `proc`
+ * itself is killed. */
+bool host_reserveMemory(Host* host, Process* proc, size_t numBytes);
+
 gboolean host_doesInterfaceExist(Host* host, in_addr_t interfaceIP);
 gboolean host_isInterfaceAvailable(Host* host, ProtocolType type,
                                    in_addr_t interfaceIP, in_port_t port,
                                    in_addr_t peerIP, in_port_t peerPort);
+/* Returns TRUE if a TCP socket is listening on the port (in network byte order)
+ * on any of the host's interfaces. */
+gboolean host_isListening(Host* host, in_port_t port);
 void host_associateInterface(Host* host, const CompatSocket* socket, in_addr_t bindAddress);
 void host_disassociateInterface(Host* host, const CompatSocket* socket);
+/* Calls `func` for each socket associated with any of the host's interfaces. A
+ * socket bound to INADDR_ANY is visited once per interface. */
+void host_iterAssociatedSockets(Host* host, CompatSocketIterFunc func, void* data);
 in_port_t host_getRandomFreePort(Host* host, ProtocolType type,
                                  in_addr_t interfaceIP, in_addr_t peerIP,
                                  in_port_t peerPort);
@@ -99,4 +144,11 @@ FutexTable* host_getFutexTable(Host* host);
 // converts a virtual (shadow) tid into the native tid
 pid_t host_getNativeTID(Host* host, pid_t virtualPID, pid_t virtualTID);
 
+// returns the process with the given virtual pid, or NULL if it isn't running on this host
+Process* host_getProcess(Host* host, pid_t virtualPID);
+
+typedef void (*HostProcessIterFunc)(Process* proc, void* data);
+// calls `func` for each process on this host, in the order they were added
+void host_iterProcesses(Host* host, HostProcessIterFunc func, void* data);
+
 #endif /* SHD_HOST_H_ */
@@ -1,5 +1,7 @@
 use once_cell::unsync::OnceCell;
 use std::net::IpAddr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::cshadow;
@@ -26,6 +28,8 @@ pub struct HostInfo {
     pub name: String,
     pub default_ip: IpAddr,
     pub log_level: Option<log::LevelFilter>,
+    /// Where to write the host's log records, if not to stdout.
+    pub log_file: Option<PathBuf>,
 }
 
 /// A simulated Host.
@@ -73,6 +77,7 @@ impl Host {
                 name: self.name().into(),
                 default_ip: self.default_ip(),
                 log_level: self.log_level(),
+                log_file: self.log_file(),
             })
         })
     }
@@ -97,6 +102,21 @@ impl Host {
         crate::core::logger::log_wrapper::c_to_rust_log_level(level).map(|l| l.to_level_filter())
     }
 
+    /// The path of the "shadow.log" file in the host's data directory, if the host writes
+    /// its log records there.
+    pub fn log_file(&self) -> Option<PathBuf> {
+        if unsafe { cshadow::host_getLogToFile(self.chost) } == 0 {
+            return None;
+        }
+        let data_path = unsafe { cshadow::host_getDataPath(self.chost) };
+        if data_path.is_null() {
+            return None;
+        }
+        let data_path = unsafe { std::ffi::CStr::from_ptr(data_path) };
+        let data_path = std::ffi::OsStr::from_bytes(data_path.to_bytes());
+        Some(PathBuf::from(data_path).join("shadow.log"))
+    }
+
     pub fn chost(&self) -> *mut cshadow::Host {
         self.chost
     }
@@ -10,6 +10,7 @@
 #include <glib.h>
 
 #include "lib/logger/log_level.h"
+#include "main/bindings/c/bindings-opaque.h"
 #include "main/core/support/definitions.h"
 #include "main/host/tracker_types.h"
 
@@ -17,26 +18,51 @@ typedef struct _HostParameters HostParameters;
 struct _HostParameters {
     GQuark id;
     guint nodeSeed;
+    /* the seed of the whole simulation, from which nodeSeed is derived */
+    guint simulationSeed;
     gchar* hostname;
     gchar* ipHint;
     gchar* citycodeHint;
     gchar* countrycodeHint;
+    /* an exact IP address, which takes precedence over ipHint, or NULL */
+    gchar* ipAddr;
+    /* the id of the graph node to attach the host to, or -1 to choose one using the hints */
+    gint64 networkNodeId;
     guint64 requestedBWDownKiBps;
     guint64 requestedBWUpKiBps;
     guint64 cpuFrequency;
     guint64 cpuThreshold;
     guint64 cpuPrecision;
+    guint cpuCores;
     SimulationTime heartbeatInterval;
     LogLevel heartbeatLogLevel;
     LogInfoFlags heartbeatLogInfo;
+    HeartbeatFormat heartbeatFormat;
     LogLevel logLevel;
+    gboolean logToFile;
     gchar* pcapDir;
+    guint32 pcapCaptureSize;
+    /* owned by the host, which frees it with pcapfilter_free() */
+    PcapFilter* pcapFilter;
+    SimulationTime pcapStartTime;
+    SimulationTime pcapStopTime;
     QDiscMode qdisc;
     guint64 recvBufSize;
     gboolean autotuneRecvBuf;
     guint64 sendBufSize;
     gboolean autotuneSendBuf;
     guint64 interfaceBufSize;
+    /* the MTU of the host's network (non-loopback) interface */
+    guint32 interfaceMtu;
+    gboolean useFsOverlay;
+    gchar* fsOverlayBase;
+    SimulationTime diskReadLatency;
+    guint64 diskReadBandwidth;
+    SimulationTime diskWriteLatency;
+    guint64 diskWriteBandwidth;
+    SimulationTime diskSyncLatency;
+    guint64 memoryLimit;
+    MemoryLimitAction memoryLimitAction;
 };
 
 #endif
\ No newline at end of file
@@ -9,6 +9,7 @@
 #include <stddef.h>
 
 #include "lib/logger/logger.h"
+#include "main/bindings/c/bindings.h"
 #include "main/core/support/definitions.h"
 #include "main/core/work/task.h"
 #include "main/core/worker.h"
@@ -40,6 +41,16 @@ struct _NetworkInterfaceTokenBucket {
     guint64 bytesRefill;
 };
 
+/* A UDP datagram whose IP fragments are being reassembled. */
+typedef struct _NetworkInterfaceReassembly NetworkInterfaceReassembly;
+struct _NetworkInterfaceReassembly {
+    /* the first fragment that was received, which shares the datagram's payload */
+    Packet* fragment;
+    /* the number of fragments and bytes of the datagram's payload received so far */
+    guint fragmentsReceived;
+    guint bytesReceived;
+};
+
 struct _NetworkInterface {
     /* The upstream ISP router connected to this interface.
      * May be NULL for loopback interfaces. */
@@ -52,6 +63,13 @@ struct _NetworkInterface {
     /* The address associated with this interface */
     Address* address;
 
+    /* The largest IP packet that this interface sends, in bytes */
+    guint32 mtu;
+
+    /* Datagrams whose fragments we've started receiving, keyed by their source
+     * address and id. Stores NetworkInterfaceReassembly objects. */
+    GHashTable* reassemblies;
+
     /* (protocol,port)-to-socket bindings. Stores CompatSocket objects as tagged pointers. */
     GHashTable* boundSockets;
 
@@ -74,8 +92,11 @@ struct _NetworkInterface {
     /* If we have scheduled a refill task but it has not yet executed. */
     gboolean isRefillPending;
 
-    /* To support capturing incoming and outgoing packets */
-    PCapWriter* pcap;
+    /* To support capturing incoming and outgoing packets, in the host's own
+     * capture file and in the network-wide capture file */
+    NetworkInterfacePCapOptions pcapOptions;
+    guint32 pcapInterfaceID;
+    guint32 mergedPcapInterfaceID;
 
     MAGIC_DECLARE;
 };
@@ -96,6 +117,12 @@ static void _compatsocket_unrefTaggedVoid(void* taggedSocketPtr) {
     compatsocket_unref(&socket);
 }
 
+static void _networkinterfacereassembly_free(gpointer voidReassembly) {
+    NetworkInterfaceReassembly* reassembly = voidReassembly;
+    packet_unref(reassembly->fragment);
+    g_free(reassembly);
+}
+
 static inline SimulationTime _networkinterface_getRefillInterval() {
     return (SimulationTime) SIMTIME_ONE_MILLISECOND*1;
 }
@@ -232,6 +259,11 @@ Address* networkinterface_getAddress(NetworkInterface* interface) {
     return interface->address;
 }
 
+guint32 networkinterface_getMTU(NetworkInterface* interface) {
+    MAGIC_ASSERT(interface);
+    return interface->mtu;
+}
+
 guint32 networkinterface_getSpeedUpKiBps(NetworkInterface* interface) {
     MAGIC_ASSERT(interface);
 
@@ -338,38 +370,122 @@ void networkinterface_disassociate(NetworkInterface* interface, const CompatSock
     g_free(key);
 }
 
-static void _networkinterface_capturePacket(NetworkInterface* interface, Packet* packet) {
+void networkinterface_iterAssociatedSockets(NetworkInterface* interface, CompatSocketIterFunc func,
+                                            void* data) {
+    MAGIC_ASSERT(interface);
+
+    GHashTableIter iter;
+    gpointer key, value;
+    g_hash_table_iter_init(&iter, interface->boundSockets);
+
+    while (g_hash_table_iter_next(&iter, &key, &value)) {
+        CompatSocket socket = compatsocket_fromTagged((uintptr_t)value);
+        func(&socket, data);
+    }
+}
+
+/* Returns TRUE if the capture is enabled and the packet passes its time window and filter. */
+static gboolean _networkinterface_shouldCapturePacket(const NetworkInterfaceCapture* capture,
+                                                      Packet* packet) {
+    if (!capture->pcap) {
+        return FALSE;
+    }
+
+    SimulationTime now = worker_getCurrentTime();
+    if (now < capture->startTime || now >= capture->stopTime) {
+        return FALSE;
+    }
+
+    ProtocolType protocol = packet_getProtocol(packet);
+    if (protocol != PTCP && protocol != PUDP) {
+        return FALSE;
+    }
+
+    if (capture->filter) {
+        guint8 ipProtocol = protocol == PTCP ? IPPROTO_TCP : IPPROTO_UDP;
+        return pcapfilter_matches(capture->filter, ipProtocol, packet_getSourceIP(packet),
+                                  packet_getSourcePort(packet), packet_getDestinationIP(packet),
+                                  packet_getDestinationPort(packet));
+    }
+
+    return TRUE;
+}
+
+/* Writes the packet to the host's capture and to the network-wide capture, each of which
+ * applies its own time window, filter, and capture size. */
+static void _networkinterface_capturePacket(NetworkInterface* interface, Host* host,
+                                            Packet* packet) {
+    const NetworkInterfacePCapOptions* options = &interface->pcapOptions;
+    gboolean captureHost = _networkinterface_shouldCapturePacket(&options->host, packet);
+    gboolean captureMerged = _networkinterface_shouldCapturePacket(&options->merged, packet);
+
+    if (!captureHost && !captureMerged) {
+        return;
+    }
+
+    /* the writers truncate the packet to their own capture size */
+    guint32 captureSize = MAX(captureHost ? options->host.captureSize : 0,
+                              captureMerged ? options->merged.captureSize : 0);
+
     PCapPacket* pcapPacket = g_new0(PCapPacket, 1);
 
+    pcapPacket->protocol = packet_getProtocol(packet);
     pcapPacket->headerSize = packet_getHeaderSize(packet);
     pcapPacket->payloadLength = packet_getPayloadLength(packet);
-
-    if(pcapPacket->payloadLength > 0) {
-        pcapPacket->payload = g_new0(guchar, pcapPacket->payloadLength);
-        packet_copyPayloadShadow(packet, 0, pcapPacket->payload, pcapPacket->payloadLength);
+    pcapPacket->datagramLength = packet_getDatagramLength(packet);
+    pcapPacket->hostID = host_getID(host);
+    pcapPacket->packetID = packet_getID(packet);
+
+    if (packet_isFragment(packet)) {
+        pcapPacket->isFragment = TRUE;
+        pcapPacket->ipID = (guint16)packet_getID(packet);
+        pcapPacket->fragmentOffset = packet_getFragmentOffset(packet);
     }
 
-    PacketTCPHeader* tcpHeader = packet_getTCPHeader(packet);
-
-    pcapPacket->srcIP = tcpHeader->sourceIP;
-    pcapPacket->dstIP = tcpHeader->destinationIP;
-    pcapPacket->srcPort = tcpHeader->sourcePort;
-    pcapPacket->dstPort = tcpHeader->destinationPort;
+    /* only copy the part of the payload that fits in the capture */
+    guint capturedPayloadLength = 0;
+    if (captureSize > pcapPacket->headerSize) {
+        capturedPayloadLength =
+            MIN(pcapPacket->payloadLength, captureSize - pcapPacket->headerSize);
+    }
 
-    if(tcpHeader->flags & PTCP_RST) pcapPacket->rstFlag = TRUE;
-    if(tcpHeader->flags & PTCP_SYN) pcapPacket->synFlag = TRUE;
-    if(tcpHeader->flags & PTCP_ACK) pcapPacket->ackFlag = TRUE;
-    if(tcpHeader->flags & PTCP_FIN) pcapPacket->finFlag = TRUE;
+    if (capturedPayloadLength > 0) {
+        pcapPacket->payload = g_new0(guchar, capturedPayloadLength);
+        packet_copyPayloadShadow(packet, 0, pcapPacket->payload, capturedPayloadLength);
+    }
 
-    pcapPacket->seq = (guint32)tcpHeader->sequence;
-    pcapPacket->win = (guint16)tcpHeader->window;
-    if(tcpHeader->flags & PTCP_ACK) {
-        pcapPacket->ack = (guint32)tcpHeader->acknowledgment;
+    pcapPacket->srcIP = packet_getSourceIP(packet);
+    pcapPacket->dstIP = packet_getDestinationIP(packet);
+    pcapPacket->srcPort = packet_getSourcePort(packet);
+    pcapPacket->dstPort = packet_getDestinationPort(packet);
+
+    if (pcapPacket->protocol == PTCP) {
+        PacketTCPHeader* tcpHeader = packet_getTCPHeader(packet);
+
+        if (tcpHeader->flags & PTCP_RST)
+            pcapPacket->rstFlag = TRUE;
+        if (tcpHeader->flags & PTCP_SYN)
+            pcapPacket->synFlag = TRUE;
+        if (tcpHeader->flags & PTCP_ACK)
+            pcapPacket->ackFlag = TRUE;
+        if (tcpHeader->flags & PTCP_FIN)
+            pcapPacket->finFlag = TRUE;
+
+        pcapPacket->seq = (guint32)tcpHeader->sequence;
+        pcapPacket->win = (guint16)tcpHeader->window;
+        if (tcpHeader->flags & PTCP_ACK) {
+            pcapPacket->ack = (guint32)tcpHeader->acknowledgment;
+        }
     }
 
-    pcapwriter_writePacket(interface->pcap, pcapPacket);
+    if (captureHost) {
+        pcapwriter_writePacket(options->host.pcap, interface->pcapInterfaceID, pcapPacket);
+    }
+    if (captureMerged) {
+        pcapwriter_writePacket(options->merged.pcap, interface->mergedPcapInterfaceID, pcapPacket);
+    }
 
-    if(pcapPacket->payloadLength > 0) {
+    if (pcapPacket->payload) {
         g_free(pcapPacket->payload);
     }
 
@@ -388,16 +504,87 @@ static CompatSocket _boundsockets_lookup(GHashTable* table, gchar* key) {
     return compatsocket_fromTagged((uintptr_t)ptr);
 }
 
-static void _networkinterface_receivePacket(Host* host, NetworkInterface* interface,
-                                            Packet* packet) {
+gboolean networkinterface_isListening(NetworkInterface* interface, in_port_t port) {
     MAGIC_ASSERT(interface);
 
-    /* get the next packet */
-    utility_assert(packet);
+    /* listening sockets don't have a peer */
+    gchar* key = _networkinterface_getAssociationKey(interface, PTCP, port, 0, 0);
+    CompatSocket socket = _boundsockets_lookup(interface->boundSockets, key);
+    g_free(key);
 
-    /* successfully received */
-    packet_addDeliveryStatus(packet, PDS_RCV_INTERFACE_RECEIVED);
+    if (socket.type != CST_LEGACY_SOCKET) {
+        return FALSE;
+    }
+
+    LegacyDescriptor* descriptor = (LegacyDescriptor*)socket.object.as_legacy_socket;
+    return descriptor_getType(descriptor) == DT_TCPSOCKET &&
+           tcp_isValidListener((TCP*)descriptor);
+}
+
+static gchar* _networkinterface_getReassemblyKey(Packet* fragment) {
+    return g_strdup_printf("%" G_GUINT32_FORMAT ":%" G_GUINT64_FORMAT,
+                           (guint32)packet_getSourceIP(fragment), packet_getID(fragment));
+}
+
+static void _networkinterface_expireReassemblyCB(Host* host, gpointer voidInterface,
+                                                 gpointer voidKey) {
+    NetworkInterface* interface = voidInterface;
+    MAGIC_ASSERT(interface);
+
+    /* if the datagram was already reassembled, there is nothing to do */
+    NetworkInterfaceReassembly* reassembly =
+        g_hash_table_lookup(interface->reassemblies, voidKey);
+    if (reassembly == NULL) {
+        return;
+    }
+
+    trace("dropping %u fragments of incomplete datagram %s", reassembly->fragmentsReceived,
+          (gchar*)voidKey);
 
+    for (guint i = 0; i < reassembly->fragmentsReceived; i++) {
+        host_countDroppedPacket(host, "reassembly_timeout");
+    }
+    g_hash_table_remove(interface->reassemblies, voidKey);
+}
+
+/* Adds the fragment to its datagram, and returns the reassembled datagram if
+ * this was its last missing fragment. Otherwise returns NULL. */
+static Packet* _networkinterface_reassemble(NetworkInterface* interface, Host* host,
+                                            Packet* fragment) {
+    gchar* key = _networkinterface_getReassemblyKey(fragment);
+
+    NetworkInterfaceReassembly* reassembly = g_hash_table_lookup(interface->reassemblies, key);
+    if (reassembly == NULL) {
+        reassembly = g_new0(NetworkInterfaceReassembly, 1);
+        reassembly->fragment = fragment;
+        packet_ref(fragment);
+        g_hash_table_insert(interface->reassemblies, g_strdup(key), reassembly);
+
+        /* give up on the datagram if the rest of its fragments don't arrive in time */
+        Task* expireTask = task_new(
+            _networkinterface_expireReassemblyCB, interface, g_strdup(key), NULL, g_free);
+        worker_scheduleTask(expireTask, host, CONFIG_IP_FRAGMENT_TIMEOUT);
+        task_unref(expireTask);
+    }
+
+    reassembly->fragmentsReceived++;
+    reassembly->bytesReceived += packet_getPayloadLength(fragment);
+
+    Packet* datagram = NULL;
+    if (reassembly->bytesReceived >= packet_getDatagramLength(fragment)) {
+        datagram = packet_reassemble(fragment);
+        trace("reassembled datagram %s from %u fragments", key, reassembly->fragmentsReceived);
+        g_hash_table_remove(interface->reassemblies, key);
+    }
+
+    g_free(key);
+    return datagram;
+}
+
+/* Passes the packet to the socket it is addressed to, and returns the socket's
+ * handle, or -1 if there is no such socket. */
+static gint _networkinterface_pushToSocket(Host* host, NetworkInterface* interface,
+                                           Packet* packet) {
     /* hand it off to the correct socket layer */
     ProtocolType ptype = packet_getProtocol(packet);
     in_port_t bindPort = packet_getDestinationPort(packet);
@@ -425,6 +612,7 @@ static void _networkinterface_receivePacket(Host* host, NetworkInterface* interf
         compatsocket_pushInPacket(&socket, host, packet);
     } else {
         packet_addDeliveryStatus(packet, PDS_RCV_INTERFACE_DROPPED);
+        host_countDroppedPacket(host, "no_socket");
     }
 
     gint socketHandle = -1;
@@ -433,11 +621,34 @@ static void _networkinterface_receivePacket(Host* host, NetworkInterface* interf
             *descriptor_getHandleReference((LegacyDescriptor*)socket.object.as_legacy_socket);
     }
 
+    return socketHandle;
+}
+
+static void _networkinterface_receivePacket(Host* host, NetworkInterface* interface,
+                                            Packet* packet) {
+    MAGIC_ASSERT(interface);
+
+    /* get the next packet */
+    utility_assert(packet);
+
+    /* successfully received */
+    packet_addDeliveryStatus(packet, PDS_RCV_INTERFACE_RECEIVED);
+
+    /* fragments only reach the socket once their whole datagram has arrived */
+    gint socketHandle = -1;
+    if (packet_isFragment(packet)) {
+        Packet* datagram = _networkinterface_reassemble(interface, host, packet);
+        if (datagram != NULL) {
+            socketHandle = _networkinterface_pushToSocket(host, interface, datagram);
+            packet_unref(datagram);
+        }
+    } else {
+        socketHandle = _networkinterface_pushToSocket(host, interface, packet);
+    }
+
     /* count our bandwidth usage by interface, and by socket handle if possible */
     tracker_addInputBytes(host_getTracker(host), packet, socketHandle);
-    if (interface->pcap) {
-        _networkinterface_capturePacket(interface, packet);
-    }
+    _networkinterface_capturePacket(interface, host, packet);
 }
 
 static void _networkinterface_receivePacketTask(Host* host, gpointer voidInterface,
@@ -460,7 +671,7 @@ void networkinterface_receivePackets(NetworkInterface* interface, Host* host) {
 
     while(bootstrapping || interface->receiveBucket.bytesRemaining >= CONFIG_MTU) {
         /* we are now the owner of the packet reference from the router */
-        Packet* packet = router_dequeue(interface->router);
+        Packet* packet = router_dequeue(interface->router, host);
         if(!packet) {
             break;
         }
@@ -621,9 +832,7 @@ static void _networkinterface_sendPackets(NetworkInterface* interface, Host* src
         }
 
         tracker_addOutputBytes(host_getTracker(src), packet, socketHandle);
-        if(interface->pcap) {
-            _networkinterface_capturePacket(interface, packet);
-        }
+        _networkinterface_capturePacket(interface, src, packet);
 
         /* sending side is done with its ref */
         packet_unref(packet);
@@ -678,15 +887,21 @@ Router* networkinterface_getRouter(NetworkInterface* interface) {
     return interface->router;
 }
 
-NetworkInterface* networkinterface_new(Host* host, Address* address, guint64 bwDownKiBps,
-                                       guint64 bwUpKiBps, gchar* pcapDir, QDiscMode qdisc,
-                                       guint64 interfaceReceiveLength) {
+NetworkInterface* networkinterface_new(Host* host, Address* address, const gchar* name,
+                                       guint64 bwDownKiBps, guint64 bwUpKiBps,
+                                       const NetworkInterfacePCapOptions* pcapOptions,
+                                       QDiscMode qdisc, guint64 interfaceReceiveLength,
+                                       guint32 mtu) {
     NetworkInterface* interface = g_new0(NetworkInterface, 1);
     MAGIC_INIT(interface);
 
     interface->address = address;
     address_ref(interface->address);
 
+    interface->mtu = mtu;
+    interface->reassemblies =
+        g_hash_table_new_full(g_str_hash, g_str_equal, g_free, _networkinterfacereassembly_free);
+
     /* incoming packets get passed along to sockets */
     interface->boundSockets =
         g_hash_table_new_full(g_str_hash, g_str_equal, g_free, _compatsocket_unrefTaggedVoid);
@@ -698,22 +913,34 @@ NetworkInterface* networkinterface_new(Host* host, Address* address, guint64 bwD
     /* parse queuing discipline */
     interface->qdisc = qdisc;
 
-    if(pcapDir != NULL) {
-        GString* filename = g_string_new(NULL);
-        g_string_printf(filename, "%s-%s",
-                address_toHostName(interface->address),
-                address_toHostIPString(interface->address));
-        interface->pcap = pcapwriter_new(host, pcapDir, filename->str);
-        g_string_free(filename, TRUE);
+    const gchar* hostname = address_toHostName(interface->address);
+    const gchar* ip = address_toHostIPString(interface->address);
+
+    utility_assert(pcapOptions);
+    interface->pcapOptions = *pcapOptions;
+
+    if (pcapOptions->host.pcap != NULL) {
+        interface->pcapInterfaceID = pcapwriter_addInterface(
+            pcapOptions->host.pcap, name, ip, pcapOptions->host.captureSize);
+    }
+
+    if (pcapOptions->merged.pcap != NULL) {
+        /* interface names need to identify the host in the network-wide capture */
+        gchar* mergedName = g_strdup_printf("%s-%s", hostname, name);
+        interface->mergedPcapInterfaceID = pcapwriter_addInterface(
+            pcapOptions->merged.pcap, mergedName, ip, pcapOptions->merged.captureSize);
+        g_free(mergedName);
     }
 
     /* set size and refill rates for token buckets */
     _networkinterface_setupTokenBuckets(interface, bwDownKiBps, bwUpKiBps);
 
     debug("bringing up network interface '%s' at '%s', %" G_GUINT64_FORMAT
-          " KiB/s up and %" G_GUINT64_FORMAT " KiB/s down using queuing discipline %s",
+          " KiB/s up and %" G_GUINT64_FORMAT " KiB/s down using queuing discipline %s and MTU %"
+          G_GUINT32_FORMAT,
           address_toHostName(interface->address), address_toHostIPString(interface->address),
-          bwUpKiBps, bwDownKiBps, interface->qdisc == Q_DISC_MODE_ROUND_ROBIN ? "rr" : "fifo");
+          bwUpKiBps, bwDownKiBps, interface->qdisc == Q_DISC_MODE_ROUND_ROBIN ? "rr" : "fifo",
+          interface->mtu);
 
     worker_count_allocation(NetworkInterface);
     return interface;
@@ -727,6 +954,7 @@ void networkinterface_free(NetworkInterface* interface) {
     fifosocketqueue_destroy(&interface->fifoQueue, compatsocket_unref);
 
     g_hash_table_destroy(interface->boundSockets);
+    g_hash_table_destroy(interface->reassemblies);
 
     if(interface->router) {
         router_unref(interface->router);
@@ -735,10 +963,6 @@ void networkinterface_free(NetworkInterface* interface) {
     dns_deregister(worker_getDNS(), interface->address);
     address_unref(interface->address);
 
-    if(interface->pcap) {
-        pcapwriter_free(interface->pcap);
-    }
-
     MAGIC_CLEAR(interface);
     g_free(interface);
 
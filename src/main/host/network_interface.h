@@ -10,29 +10,69 @@
 #include <glib.h>
 #include <netinet/in.h>
 
+#include "main/bindings/c/bindings-opaque.h"
 #include "main/core/support/definitions.h"
 #include "main/host/descriptor/compat_socket.h"
 #include "main/host/descriptor/socket.h"
 #include "main/host/protocol.h"
 #include "main/routing/address.h"
 #include "main/routing/router.h"
+#include "main/utility/pcap_writer.h"
 
 typedef struct _NetworkInterface NetworkInterface;
 
-NetworkInterface* networkinterface_new(Host* host, Address* address, guint64 bwDownKiBps,
-                                       guint64 bwUpKiBps, gchar* pcapDir, QDiscMode qdisc,
-                                       guint64 interfaceReceiveLength);
+/* Which packets are written to a capture file. */
+typedef struct _NetworkInterfaceCapture NetworkInterfaceCapture;
+struct _NetworkInterfaceCapture {
+    /* the capture file, or NULL if packets are not captured */
+    PCapWriter* pcap;
+    /* the maximum number of bytes of each packet to write */
+    guint32 captureSize;
+    /* if non-NULL, only packets matching the filter are captured */
+    const PcapFilter* filter;
+    /* only packets sent or received in [startTime, stopTime) are captured */
+    SimulationTime startTime;
+    SimulationTime stopTime;
+};
+
+/* Which packets the interface captures, and where it writes them. */
+typedef struct _NetworkInterfacePCapOptions NetworkInterfacePCapOptions;
+struct _NetworkInterfacePCapOptions {
+    /* the host's own capture, using the host's options */
+    NetworkInterfaceCapture host;
+    /* the network-wide capture, using the global options */
+    NetworkInterfaceCapture merged;
+};
+
+/* Packets sent and received by the interface are captured as described by
+ * `pcapOptions`. The writers and the filters must outlive the interface. UDP
+ * datagrams that don't fit in `mtu` bytes are sent as IP fragments, and the
+ * fragments that the interface receives are reassembled before being passed
+ * to the socket. */
+NetworkInterface* networkinterface_new(Host* host, Address* address, const gchar* name,
+                                       guint64 bwDownKiBps, guint64 bwUpKiBps,
+                                       const NetworkInterfacePCapOptions* pcapOptions,
+                                       QDiscMode qdisc, guint64 interfaceReceiveLength,
+                                       guint32 mtu);
 void networkinterface_free(NetworkInterface* interface);
 
 Address* networkinterface_getAddress(NetworkInterface* interface);
+guint32 networkinterface_getMTU(NetworkInterface* interface);
 guint32 networkinterface_getSpeedUpKiBps(NetworkInterface* interface);
 guint32 networkinterface_getSpeedDownKiBps(NetworkInterface* interface);
 
 gboolean networkinterface_isAssociated(NetworkInterface* interface, ProtocolType type,
         in_port_t port, in_addr_t peerAddr, in_port_t peerPort);
 
+/* Returns TRUE if a TCP socket is listening on the port of the interface. */
+gboolean networkinterface_isListening(NetworkInterface* interface, in_port_t port);
+
 void networkinterface_associate(NetworkInterface* interface, const CompatSocket* socket);
 void networkinterface_disassociate(NetworkInterface* interface, const CompatSocket* socket);
+/* Calls `func` for each socket associated with the interface. The socket must
+ * not be associated or disassociated from within `func`. */
+void networkinterface_iterAssociatedSockets(NetworkInterface* interface, CompatSocketIterFunc func,
+                                            void* data);
 
 void networkinterface_wantsSend(NetworkInterface* interface, Host* host,
                                 const CompatSocket* socket);
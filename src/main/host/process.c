@@ -23,6 +23,8 @@
 #include <stdarg.h>
 #include <stdbool.h>
 #include <stddef.h>
+#include <stdio.h>
+#include <string.h>
 #include <sys/file.h>
 #include <sys/types.h>
 #include <sys/uio.h>
@@ -104,15 +106,41 @@ struct _Process {
         gboolean isExecuting;
     } plugin;
 
-#ifdef USE_PERF_TIMERS
     /* timer that tracks the amount of CPU time we spend on plugin execution and processing */
     GTimer* cpuDelayTimer;
+#ifdef USE_PERF_TIMERS
     gdouble totalRunTime;
 #endif
 
     /* process boot and shutdown variables */
     SimulationTime startTime;
     SimulationTime stopTime;
+    /* the signal sent at stopTime, and how long to wait for the process to exit
+     * before sending SIGKILL */
+    int shutdownSignal;
+    SimulationTime shutdownGracePeriod;
+    bool didSendShutdownSignal;
+    /* whether and when the process is restarted after it exits. restartsLeft
+     * is negative if the process can be restarted any number of times. */
+    RestartPolicy restartPolicy;
+    SimulationTime restartDelay;
+    int restartsLeft;
+    /* true from when the process exited until it's restarted */
+    bool isRestartPending;
+    /* true once shadow stopped the process, in which case it isn't restarted */
+    bool wasStopped;
+    /* the name from the process's configuration, or NULL if it has none. A
+     * successful exit is reported under this name to processes that start
+     * after it, and to the conditions that stop the simulation early. */
+    gchar* configName;
+    /* true if an earlier run of a restarted process already reported a
+     * successful exit, since it's only reported once for each configured process */
+    bool didReportSuccess;
+    /* conditions that must be met before the process starts, or NULL */
+    StartConditions* startAfter;
+    bool isWaitingToStart;
+    /* whether the simulation stops when the process exits */
+    bool terminatesSimulation;
 
     /* absolute path to the process's working directory */
     char* workingDir;
@@ -121,9 +149,26 @@ struct _Process {
     gchar** argv;
     /* vector of environment variables passed to exec */
     gchar** envv;
+    /* the file or content that the process reads from stdin, or NULL for both
+     * if stdin is empty */
+    gchar* stdinPath;
+    gchar* stdinContent;
 
     gint returnCode;
     gboolean didLogReturnCode;
+    /* the status returned by waitpid(), or -1 if we couldn't get it */
+    int exitStatus;
+    /* SIMTIME_INVALID until the process exits during the simulation */
+    SimulationTime exitTime;
+    /* the process was still running when the simulation ended, and was killed
+     * when its host was freed */
+    bool killedAtShutdown;
+    /* the state the process should end in, or NULL if it wasn't configured */
+    ProcessFinalState* expectedFinalState;
+
+    /* the syscalls handled by the process's threads, counted as they are freed */
+    guint64 numSyscalls;
+    Counter* syscallCounts;
 
     // int thread_id -> Thread*.
     GHashTable* threads;
@@ -135,9 +180,32 @@ struct _Process {
     File* stdoutFile;
     File* stderrFile;
 
+    /* Log of the process's syscalls, if enabled. */
+    bool straceLogging;
+    FILE* straceFile;
+
     /* When true, threads are no longer runnable and should just be cleaned up. */
     bool isExiting;
 
+    /* The handler and flags of each signal's action, as last set with the
+     * rt_sigaction syscall. Signals that were never set have the default action. */
+    uint64_t signalHandlers[NSIG];
+    uint64_t signalHandlerFlags[NSIG];
+
+    /* True once the process was chosen to be killed for exceeding the host's memory limit. */
+    bool wasOOMKilled;
+
+    /* The start and end of the process's heap as returned by brk, or 0 if the
+     * process hasn't called brk yet. */
+    uint64_t heapStart;
+    uint64_t brk;
+
+    /* The process's anonymous mappings, as a map from the start address of each
+     * mapping to its end address, and the total length of the mappings in bytes.
+     * Used to count the process's memory usage deterministically. */
+    GTree* anonMappings;
+    size_t anonMappingsLen;
+
     /* Native pid of the process */
     pid_t nativePid;
 
@@ -171,6 +239,11 @@ const char* process_getWorkingDir(Process* proc) {
     return proc->workingDir;
 }
 
+FILE* process_getStraceFile(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->straceFile;
+}
+
 guint process_getProcessID(Process* proc) {
     MAGIC_ASSERT(proc);
     return proc->processID;
@@ -181,6 +254,31 @@ pid_t process_getNativePid(const Process* proc) {
     return proc->nativePid;
 }
 
+Host* process_getHost(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->host;
+}
+
+gchar** process_getArgv(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->argv;
+}
+
+gchar** process_getEnvv(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->envv;
+}
+
+SimulationTime process_getStartTime(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->startTime;
+}
+
+guint process_getNumThreads(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return g_hash_table_size(proc->threads);
+}
+
 static void _process_reapThread(Process* process, Thread* thread) {
     utility_assert(!thread_isRunning(thread));
 
@@ -294,14 +392,88 @@ static void _process_terminate_threads(Process* proc) {
     _process_handleProcessExit(proc);
 }
 
-#ifdef USE_PERF_TIMERS
-static void _process_handleTimerResult(Process* proc, gdouble elapsedTimeSec) {
+/* Charges the host's virtual CPU for the time we spent executing the plugin. The
+ * delay only affects the simulation if the host's CPU threshold is enabled.
+ * `thread` is the thread that ran, or NULL if the time isn't specific to one. */
+static void _process_handleTimerResult(Process* proc, Thread* thread, gdouble elapsedTimeSec) {
     SimulationTime delay = (SimulationTime) (elapsedTimeSec * SIMTIME_ONE_SECOND);
-    cpu_addDelay(host_getCPU(proc->host), delay);
+    CPU* cpu = host_getCPU(proc->host);
+    if (thread) {
+        /* the thread's work can't start before its previous work is done */
+        SimulationTime doneTime = cpu_addDelay(cpu, delay, thread_getCPUAvailableTime(thread));
+        thread_setCPUAvailableTime(thread, doneTime);
+    } else {
+        cpu_addDelay(cpu, delay, 0);
+    }
+#ifdef USE_PERF_TIMERS
     tracker_addProcessingTime(host_getTracker(proc->host), delay);
     proc->totalRunTime += elapsedTimeSec;
-}
 #endif
+}
+
+/* Returns true if the exit of the process is an error of the simulation,
+ * ignoring that it may be restarted. */
+static bool _process_isErrorExit(Process* proc) {
+    // if there was no error or was intentionally killed
+    bool wasShutDown = proc->didSendShutdownSignal &&
+                       proc->returnCode == return_code_for_signal(proc->shutdownSignal);
+    if (proc->returnCode == 0 || proc->returnCode == return_code_for_signal(SIGKILL) ||
+        wasShutDown) {
+        return false;
+    }
+
+    // checked against the expected state at the end of the simulation instead
+    return proc->expectedFinalState == NULL;
+}
+
+/* Accounts for the exit of a process that was going to be restarted, but won't
+ * be, the same way as for a process that isn't restarted. */
+static void _process_abandonRestart(Process* proc) {
+    if (!proc->isRestartPending) {
+        return;
+    }
+    proc->isRestartPending = false;
+
+    if (_process_isErrorExit(proc)) {
+        warning("process '%s' exited with code '%i' and was not restarted",
+                process_getName(proc), proc->returnCode);
+        worker_incrementPluginError();
+    }
+
+    stopcondition_addExit(worker_getStopCondition(), proc->configName, proc->terminatesSimulation);
+}
+
+static void _process_runRestartTask(Host* host, gpointer procptr, gpointer nothing) {
+    Process* proc = procptr;
+    MAGIC_ASSERT(proc);
+
+    /* the process isn't restarted once it reached its stop time */
+    if (proc->stopTime > 0 && worker_getCurrentTime() >= proc->stopTime) {
+        _process_abandonRestart(proc);
+        return;
+    }
+
+    proc->isRestartPending = false;
+    info("restarting process '%s'", process_getName(proc));
+    host_restartApplication(host, proc);
+}
+
+/* Returns true if the process should be restarted after it exited, according
+ * to its restart policy. */
+static bool _process_shouldRestart(Process* proc) {
+    if (proc->wasStopped || proc->didSendShutdownSignal || proc->nativePid <= 0 ||
+        proc->restartsLeft == 0) {
+        return false;
+    }
+
+    switch (proc->restartPolicy) {
+        case RESTART_POLICY_NEVER: return false;
+        case RESTART_POLICY_ON_FAILURE: return proc->returnCode != 0;
+        case RESTART_POLICY_ALWAYS: return true;
+    }
+
+    return false;
+}
 
 static void _process_getAndLogReturnCode(Process* proc) {
     if(!proc->didLogReturnCode) {
@@ -309,14 +481,18 @@ static void _process_getAndLogReturnCode(Process* proc) {
         proc->returnCode = EXIT_FAILURE;
 
         int wstatus = 0;
-        int rv = waitpid(proc->nativePid, &wstatus, __WALL);
-        if (rv < 0) {
+        int rv = 0;
+        if (proc->nativePid <= 0) {
+            // for example, because its start conditions were never met
+            warning("process '%s' never started", process_getName(proc));
+        } else if ((rv = waitpid(proc->nativePid, &wstatus, __WALL)) < 0) {
             // Getting here is a bug, but since the process is exiting anyway
             // not serious enough to merit `error`ing out.
             warning("waitpid: %s", g_strerror(errno));
         } else if (rv != proc->nativePid) {
             warning("waitpid returned %d instead of the requested %d", rv, proc->nativePid);
         } else {
+            proc->exitStatus = wstatus;
             if (WIFEXITED(wstatus)) {
                 proc->returnCode = WEXITSTATUS(wstatus);
             } else if (WIFSIGNALED(wstatus)) {
@@ -343,10 +519,9 @@ static void _process_getAndLogReturnCode(Process* proc) {
             warning("Could not open '%s' for writing: %s", mainResultString->str, strerror(errno));
         }
 
-        // if there was no error or was intentionally killed
-        // TODO: once we've implemented clean shutdown via SIGTERM,
-        //       treat death by SIGKILL as a plugin error
-        if (proc->returnCode == 0 || proc->returnCode == return_code_for_signal(SIGKILL)) {
+        // a failure is handled by restarting the process, unless it's not restarted after all
+        bool willRestart = _process_shouldRestart(proc);
+        if (!_process_isErrorExit(proc) || willRestart) {
             info("%s", mainResultString->str);
         } else {
             warning("%s", mainResultString->str);
@@ -355,7 +530,44 @@ static void _process_getAndLogReturnCode(Process* proc) {
 
         g_string_free(mainResultString, TRUE);
 
+        /* a process that was killed because the simulation ended never exited,
+         * so its final state is "running" */
+        if (!proc->killedAtShutdown) {
+            proc->exitTime = worker_getCurrentTime();
+        }
         proc->didLogReturnCode = TRUE;
+
+        if (proc->returnCode == 0 && proc->configName && !proc->didReportSuccess) {
+            startafter_addExit(worker_getStartAfter(), proc->configName);
+            proc->didReportSuccess = true;
+        }
+
+        /* a restarted process exits again later */
+        if (!willRestart) {
+            stopcondition_addExit(
+                worker_getStopCondition(), proc->configName, proc->terminatesSimulation);
+        }
+
+        if (willRestart) {
+            proc->isRestartPending = true;
+            process_ref(proc);
+            Task* restartTask = task_new(
+                _process_runRestartTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+            worker_scheduleTask(restartTask, proc->host, proc->restartDelay);
+            task_unref(restartTask);
+        }
+    }
+}
+
+void process_addSyscallCounts(Process* proc, guint64 numSyscalls, Counter* syscallCounts) {
+    MAGIC_ASSERT(proc);
+
+    proc->numSyscalls += numSyscalls;
+    if (syscallCounts) {
+        if (!proc->syscallCounts) {
+            proc->syscallCounts = counter_new();
+        }
+        counter_add_counter(proc->syscallCounts, syscallCounts);
     }
 }
 
@@ -426,7 +638,8 @@ static gchar* _process_outputFileName(Process* proc, const char* type) {
         "%s/%s.%s", host_getDataPath(proc->host), proc->processName->str, type);
 }
 
-static File* _process_openStdIOFileHelper(Process* proc, int fd, gchar* fileName) {
+static File* _process_openStdIOFileHelper(Process* proc, int fd, const gchar* fileName,
+                                          int flags) {
     MAGIC_ASSERT(proc);
     utility_assert(fileName != NULL);
 
@@ -441,8 +654,9 @@ static File* _process_openStdIOFileHelper(Process* proc, int fd, gchar* fileName
             "getcwd unable to allocate string buffer, error %i: %s", errno, strerror(errno));
     }
 
-    int errcode = file_open(stdfile, fileName, O_WRONLY | O_CREAT | O_TRUNC,
-                            S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH, cwd);
+    /* these are shadow's output files, so they bypass the host's view of the filesystem */
+    int errcode =
+        file_open(stdfile, fileName, flags, S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH, cwd, NULL);
     free(cwd);
 
     if (errcode < 0) {
@@ -463,20 +677,44 @@ static void _process_start(Process* proc) {
     }
 
     // Set up stdin
-    _process_openStdIOFileHelper(proc, STDIN_FILENO, "/dev/null");
+    if (proc->stdinContent) {
+        /* the content is written to a file so the process can read it like any other file */
+        gchar* stdinFileName = _process_outputFileName(proc, "stdin");
+        GError* error = NULL;
+        if (!g_file_set_contents(stdinFileName, proc->stdinContent, -1, &error)) {
+            utility_panic("Writing %s: %s", stdinFileName, error->message);
+        }
+        _process_openStdIOFileHelper(proc, STDIN_FILENO, stdinFileName, O_RDONLY);
+        g_free(stdinFileName);
+    } else {
+        _process_openStdIOFileHelper(
+            proc, STDIN_FILENO, proc->stdinPath ? proc->stdinPath : "/dev/null", O_RDONLY);
+    }
 
     // Set up stdout
     gchar* stdoutFileName = _process_outputFileName(proc, "stdout");
-    proc->stdoutFile = _process_openStdIOFileHelper(proc, STDOUT_FILENO, stdoutFileName);
+    proc->stdoutFile = _process_openStdIOFileHelper(
+        proc, STDOUT_FILENO, stdoutFileName, O_WRONLY | O_CREAT | O_TRUNC);
     descriptor_ref((LegacyDescriptor*)proc->stdoutFile);
     g_free(stdoutFileName);
 
     // Set up stderr
     gchar* stderrFileName = _process_outputFileName(proc, "stderr");
-    proc->stderrFile = _process_openStdIOFileHelper(proc, STDERR_FILENO, stderrFileName);
+    proc->stderrFile = _process_openStdIOFileHelper(
+        proc, STDERR_FILENO, stderrFileName, O_WRONLY | O_CREAT | O_TRUNC);
     descriptor_ref((LegacyDescriptor*)proc->stderrFile);
     g_free(stderrFileName);
 
+    // Set up the syscall log
+    if (proc->straceLogging) {
+        gchar* straceFileName = _process_outputFileName(proc, "strace");
+        proc->straceFile = fopen(straceFileName, "we");
+        if (proc->straceFile == NULL) {
+            warning("Could not open '%s' for writing: %s", straceFileName, g_strerror(errno));
+        }
+        g_free(straceFileName);
+    }
+
     // tid of first thread of a process is equal to the pid.
     int tid = proc->processID;
     Thread* mainThread = NULL;
@@ -500,10 +738,8 @@ static void _process_start(Process* proc) {
     worker_setActiveProcess(proc);
     worker_setActiveThread(mainThread);
 
-#ifdef USE_PERF_TIMERS
     /* time how long we execute the program */
     g_timer_start(proc->cpuDelayTimer);
-#endif
 
     proc->plugin.isExecuting = TRUE;
     /* exec the process */
@@ -511,9 +747,9 @@ static void _process_start(Process* proc) {
     proc->nativePid = thread_getNativePid(mainThread);
     proc->memoryManager = memorymanager_new(proc->nativePid);
 
-#ifdef USE_PERF_TIMERS
     gdouble elapsed = g_timer_elapsed(proc->cpuDelayTimer, NULL);
-    _process_handleTimerResult(proc, elapsed);
+    _process_handleTimerResult(proc, mainThread, elapsed);
+#ifdef USE_PERF_TIMERS
     info("process '%s' started in %f seconds", process_getName(proc), elapsed);
 #else
     info("process '%s' started", process_getName(proc));
@@ -542,19 +778,23 @@ static void _start_thread_task_free_thread(gpointer data) {
     thread_unref(thread);
 }
 
-void process_addThread(Process* proc, Thread* thread) {
-    MAGIC_ASSERT(proc);
-    g_hash_table_insert(proc->threads, GUINT_TO_POINTER(thread_getID(thread)), thread);
-
-    // Schedule thread to start.
+static void _process_scheduleContinue(Process* proc, Thread* thread, SimulationTime delay) {
     thread_ref(thread);
     process_ref(proc);
     Task* task = task_new(_start_thread_task, proc, thread, _start_thread_task_free_process,
                           _start_thread_task_free_thread);
-    worker_scheduleTask(task, proc->host, 0);
+    worker_scheduleTask(task, proc->host, delay);
     task_unref(task);
 }
 
+void process_addThread(Process* proc, Thread* thread) {
+    MAGIC_ASSERT(proc);
+    g_hash_table_insert(proc->threads, GUINT_TO_POINTER(thread_getID(thread)), thread);
+
+    // Schedule thread to start.
+    _process_scheduleContinue(proc, thread, 0);
+}
+
 void process_markAsExiting(Process* proc) {
     MAGIC_ASSERT(proc);
     trace("Process %d marked as exiting", proc->processID);
@@ -570,24 +810,38 @@ void process_continue(Process* proc, Thread* thread) {
         return;
     }
 
+    /* the thread can't continue until its core has done the work it was charged
+     * for earlier, even if the host has other free cores */
+    SimulationTime cpuDelay =
+        cpu_getThreadDelay(host_getCPU(proc->host), thread_getCPUAvailableTime(thread));
+    if (cpuDelay > 0) {
+        trace("thread %d blocked on CPU, continuing in %" G_GUINT64_FORMAT " nanoseconds",
+              thread_getID(thread), cpuDelay);
+        tracker_addVirtualProcessingDelay(host_getTracker(proc->host), cpuDelay);
+
+        /* we'll resume the thread ourselves, so don't let its syscall condition
+         * schedule another continue in the meantime */
+        thread_clearSysCallCondition(thread);
+        _process_scheduleContinue(proc, thread, cpuDelay);
+        return;
+    }
+
     debug(
         "switching to thread controller to continue executing process '%s'", process_getName(proc));
 
     worker_setActiveProcess(proc);
     worker_setActiveThread(thread);
 
-#ifdef USE_PERF_TIMERS
     /* time how long we execute the program */
     g_timer_start(proc->cpuDelayTimer);
-#endif
 
     proc->plugin.isExecuting = TRUE;
     thread_resume(thread);
     proc->plugin.isExecuting = FALSE;
 
-#ifdef USE_PERF_TIMERS
     gdouble elapsed = g_timer_elapsed(proc->cpuDelayTimer, NULL);
-    _process_handleTimerResult(proc, elapsed);
+    _process_handleTimerResult(proc, thread, elapsed);
+#ifdef USE_PERF_TIMERS
     info("process '%s' ran for %f seconds", process_getName(proc), elapsed);
 #else
     debug("process '%s' done continuing", process_getName(proc));
@@ -605,26 +859,35 @@ void process_continue(Process* proc, Thread* thread) {
     worker_setActiveThread(NULL);
 }
 
+void process_markKilledAtShutdown(Process* proc) {
+    MAGIC_ASSERT(proc);
+    proc->killedAtShutdown = !proc->didLogReturnCode;
+
+    /* the simulation ended before the process was restarted */
+    _process_abandonRestart(proc);
+}
+
 void process_stop(Process* proc) {
     MAGIC_ASSERT(proc);
 
+    /* processes that were killed for running out of memory can still be restarted */
+    if (!proc->wasOOMKilled) {
+        proc->wasStopped = true;
+    }
+
     info("terminating process '%s'", process_getName(proc));
 
     worker_setActiveProcess(proc);
 
-#ifdef USE_PERF_TIMERS
     /* time how long we execute the program */
     g_timer_start(proc->cpuDelayTimer);
-#endif
 
     proc->plugin.isExecuting = TRUE;
     _process_terminate_threads(proc);
     proc->plugin.isExecuting = FALSE;
 
-#ifdef USE_PERF_TIMERS
     gdouble elapsed = g_timer_elapsed(proc->cpuDelayTimer, NULL);
-    _process_handleTimerResult(proc, elapsed);
-#endif
+    _process_handleTimerResult(proc, NULL, elapsed);
 
     trace("Starting descriptor table shutdown hack");
     descriptortable_shutdownHelper(proc->descTable);
@@ -640,14 +903,210 @@ void process_stop(Process* proc) {
     _process_check(proc);
 }
 
-static void _process_runStartTask(Host* host, gpointer proc, gpointer nothing) {
+static void _process_runStartTask(Host* host, gpointer procptr, gpointer nothing) {
+    Process* proc = procptr;
+    MAGIC_ASSERT(proc);
+
+    /* the process may have reached its stop time while it was waiting */
+    if (proc->wasStopped) {
+        return;
+    }
+
+    if (proc->startAfter && !startafter_areMet(worker_getStartAfter(), proc->startAfter)) {
+        if (!proc->isWaitingToStart) {
+            info("process '%s' is waiting for its start_after conditions", process_getName(proc));
+            proc->isWaitingToStart = true;
+        }
+
+        /* the conditions only change between rounds, so the manager starts us
+         * at the end of the first round in which they are met */
+        worker_addWaitingProcess(proc);
+        return;
+    }
+
+    if (proc->isWaitingToStart) {
+        info("start_after conditions of process '%s' were met", process_getName(proc));
+        proc->startTime = worker_getCurrentTime();
+    }
+
     _process_start(proc);
 }
 
+Task* process_newStartTask(Process* proc) {
+    MAGIC_ASSERT(proc);
+    process_ref(proc);
+    return task_new(_process_runStartTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+}
+
+bool process_canStart(Process* proc, StartAfter* startAfter) {
+    MAGIC_ASSERT(proc);
+    return !proc->wasStopped &&
+           (!proc->startAfter || startafter_areMet(startAfter, proc->startAfter));
+}
+
 static void _process_runStopTask(Host* host, gpointer proc, gpointer nothing) {
     process_stop(proc);
 }
 
+/* Reads the signal mask with the given name (e.g., "SigIgn" or "SigBlk") from a
+ * native /proc status file. Returns 0 if the mask couldn't be read. */
+static uint64_t _process_readSignalMask(const gchar* statusPath, const gchar* name) {
+    gchar* contents = NULL;
+    uint64_t mask = 0;
+
+    if (g_file_get_contents(statusPath, &contents, NULL, NULL)) {
+        size_t nameLen = strlen(name);
+        gchar** lines = g_strsplit(contents, "\n", 0);
+        for (gchar** line = lines; *line != NULL; line++) {
+            if (!strncmp(*line, name, nameLen) && (*line)[nameLen] == ':') {
+                sscanf(*line + nameLen + 1, "%" G_GINT64_MODIFIER "x", &mask);
+                break;
+            }
+        }
+        g_strfreev(lines);
+    }
+
+    g_free(contents);
+    return mask;
+}
+
+static bool _process_isInSignalMask(uint64_t mask, int sig) {
+    return (mask & (1ULL << (sig - 1))) != 0;
+}
+
+/* Returns true if delivering the signal to the process makes a syscall that
+ * one of its threads is blocked in return EINTR, i.e., the signal isn't
+ * ignored (explicitly or by default), and its handler wasn't installed with
+ * SA_RESTART. */
+static bool _process_signalInterruptsSyscalls(Process* proc, int sig) {
+    if (sig <= 0 || sig >= NSIG) {
+        return false;
+    }
+
+    /* signals can also be ignored from before the process was executed */
+    gchar* path = g_strdup_printf("/proc/%d/status", (int)proc->nativePid);
+    bool ignored = _process_isInSignalMask(_process_readSignalMask(path, "SigIgn"), sig);
+    g_free(path);
+    if (ignored) {
+        return false;
+    }
+
+    uint64_t handler = proc->signalHandlers[sig];
+    if (handler == (uint64_t)SIG_IGN) {
+        return false;
+    }
+    if (handler == (uint64_t)SIG_DFL) {
+        /* the default action of these signals is to ignore them */
+        return sig != SIGCHLD && sig != SIGCONT && sig != SIGURG && sig != SIGWINCH;
+    }
+    return !(proc->signalHandlerFlags[sig] & SA_RESTART);
+}
+
+/* Returns true if the thread doesn't block the signal. */
+static bool _process_threadAcceptsSignal(Process* proc, Thread* thread, int sig) {
+    gchar* path = g_strdup_printf(
+        "/proc/%d/task/%d/status", (int)proc->nativePid, (int)thread_getNativeTid(thread));
+    bool blocked = _process_isInSignalMask(_process_readSignalMask(path, "SigBlk"), sig);
+    g_free(path);
+    return !blocked;
+}
+
+static gint _process_compareThreadIDs(gconstpointer a, gconstpointer b) {
+    guint tidA = GPOINTER_TO_UINT(a);
+    guint tidB = GPOINTER_TO_UINT(b);
+    return (tidA > tidB) - (tidA < tidB);
+}
+
+/* Returns the thread that the signal sent to the process will be delivered to,
+ * or NULL if every thread blocks the signal. Like the kernel, we prefer the
+ * main thread, and otherwise choose the thread with the lowest ID so that the
+ * choice is deterministic. */
+static Thread* _process_getSignalTarget(Process* proc, int sig) {
+    Thread* mainThread = g_hash_table_lookup(proc->threads, GUINT_TO_POINTER(proc->processID));
+    if (mainThread && _process_threadAcceptsSignal(proc, mainThread, sig)) {
+        return mainThread;
+    }
+
+    GList* tids = g_list_sort(g_hash_table_get_keys(proc->threads), _process_compareThreadIDs);
+    Thread* target = NULL;
+
+    for (GList* current = tids; current != NULL && target == NULL; current = current->next) {
+        Thread* thread = g_hash_table_lookup(proc->threads, current->data);
+        if (thread != mainThread && _process_threadAcceptsSignal(proc, thread, sig)) {
+            target = thread;
+        }
+    }
+
+    g_list_free(tids);
+    return target;
+}
+
+/* Makes the syscall that the thread is blocked in return EINTR, so that the
+ * thread runs and can handle a signal that was sent to the process. */
+static void _process_interruptBlockedSyscall(Process* proc, Thread* thread) {
+    SysCallCondition* cond = thread_getSysCallCondition(thread);
+
+    if (!cond || !syscallhandler_interruptBlockedSyscall(thread_getSysCallHandler(thread))) {
+        return;
+    }
+
+    /* the task below resumes the thread instead of the condition */
+    syscallcondition_cancel(cond);
+
+    thread_ref(thread);
+    process_ref(proc);
+    Task* task = task_new(_start_thread_task, proc, thread, _start_thread_task_free_process,
+                          _start_thread_task_free_thread);
+    worker_scheduleTask(task, proc->host, 0);
+    task_unref(task);
+}
+
+void process_setSignalAction(Process* proc, int sig, uint64_t handler, uint64_t flags) {
+    MAGIC_ASSERT(proc);
+
+    if (sig > 0 && sig < NSIG) {
+        proc->signalHandlers[sig] = handler;
+        proc->signalHandlerFlags[sig] = flags;
+    }
+}
+
+/* Sends the shutdown signal to the process at its stop time, and stops the
+ * process with SIGKILL if it hasn't exited when the grace period ends. */
+static void _process_runShutdownTask(Host* host, gpointer procptr, gpointer nothing) {
+    Process* proc = procptr;
+    MAGIC_ASSERT(proc);
+
+    if (!process_isRunning(proc) || proc->shutdownSignal == SIGKILL ||
+        proc->shutdownGracePeriod == 0) {
+        process_stop(proc);
+        return;
+    }
+
+    info("sending signal %d (%s) to process '%s'", proc->shutdownSignal,
+         strsignal(proc->shutdownSignal), process_getName(proc));
+
+    /* like the kill syscall, the signal is delivered natively */
+    if (kill(proc->nativePid, proc->shutdownSignal)) {
+        warning("kill(pid=%d) error %d: %s", proc->nativePid, errno, g_strerror(errno));
+    }
+    proc->didSendShutdownSignal = true;
+
+    /* the signal is delivered to a single thread, which only returns from a
+     * blocked syscall if the signal would interrupt it natively */
+    if (_process_signalInterruptsSyscalls(proc, proc->shutdownSignal)) {
+        Thread* target = _process_getSignalTarget(proc, proc->shutdownSignal);
+        if (target) {
+            _process_interruptBlockedSyscall(proc, target);
+        }
+    }
+
+    process_ref(proc);
+    Task* stopProcessTask =
+        task_new(_process_runStopTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+    worker_scheduleTask(stopProcessTask, proc->host, proc->shutdownGracePeriod);
+    task_unref(stopProcessTask);
+}
+
 void process_schedule(Process* proc, gpointer nothing) {
     MAGIC_ASSERT(proc);
 
@@ -655,9 +1114,7 @@ void process_schedule(Process* proc, gpointer nothing) {
 
     if(proc->stopTime == 0 || proc->startTime < proc->stopTime) {
         SimulationTime startDelay = proc->startTime <= now ? 1 : proc->startTime - now;
-        process_ref(proc);
-        Task* startProcessTask =
-            task_new(_process_runStartTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+        Task* startProcessTask = process_newStartTask(proc);
         worker_scheduleTask(startProcessTask, proc->host, startDelay);
         task_unref(startProcessTask);
     }
@@ -665,8 +1122,8 @@ void process_schedule(Process* proc, gpointer nothing) {
     if(proc->stopTime > 0 && proc->stopTime > proc->startTime) {
         SimulationTime stopDelay = proc->stopTime <= now ? 1 : proc->stopTime - now;
         process_ref(proc);
-        Task* stopProcessTask =
-            task_new(_process_runStopTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+        Task* stopProcessTask = task_new(
+            _process_runShutdownTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
         worker_scheduleTask(stopProcessTask, proc->host, stopDelay);
         task_unref(stopProcessTask);
     }
@@ -693,11 +1150,166 @@ gboolean process_isRunning(Process* proc) {
     return !proc->isExiting && g_hash_table_size(proc->threads) > 0;
 }
 
+size_t process_getMemoryUsage(Process* proc) {
+    MAGIC_ASSERT(proc);
+
+    if (!process_isRunning(proc)) {
+        return 0;
+    }
+
+    return proc->anonMappingsLen + (size_t)(proc->brk - proc->heapStart);
+}
+
+uint64_t process_getBreak(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->brk;
+}
+
+void process_setBreak(Process* proc, uint64_t brk) {
+    MAGIC_ASSERT(proc);
+
+    /* the first break is the start of the heap; the kernel never moves the break
+     * below it */
+    if (proc->heapStart == 0) {
+        proc->heapStart = brk;
+    }
+    proc->brk = MAX(brk, proc->heapStart);
+}
+
+static gint _process_compareAddresses(gconstpointer a, gconstpointer b, gpointer unused) {
+    uint64_t addrA = GPOINTER_TO_SIZE(a);
+    uint64_t addrB = GPOINTER_TO_SIZE(b);
+    return (addrA > addrB) - (addrA < addrB);
+}
+
+typedef struct _AnonMappingsRange AnonMappingsRange;
+struct _AnonMappingsRange {
+    uint64_t start;
+    uint64_t end;
+    /* the start addresses of the mappings that overlap the range */
+    GArray* overlapping;
+};
+
+static gboolean _process_findOverlappingMapping(gpointer key, gpointer value, gpointer data) {
+    AnonMappingsRange* range = data;
+    uint64_t start = GPOINTER_TO_SIZE(key);
+    uint64_t end = GPOINTER_TO_SIZE(value);
+
+    if (start >= range->end) {
+        /* the mappings are sorted, so no later mapping overlaps */
+        return TRUE;
+    }
+    if (end > range->start) {
+        g_array_append_val(range->overlapping, start);
+    }
+    return FALSE;
+}
+
+void process_removeAnonMapping(Process* proc, uint64_t addr, size_t len) {
+    MAGIC_ASSERT(proc);
+
+    if (len == 0) {
+        return;
+    }
+
+    AnonMappingsRange range = {
+        .start = addr,
+        .end = addr + len,
+        .overlapping = g_array_new(FALSE, FALSE, sizeof(uint64_t)),
+    };
+    g_tree_foreach(proc->anonMappings, _process_findOverlappingMapping, &range);
+
+    /* remove the overlapping part of each mapping, keeping any parts of the
+     * mapping before and after the range */
+    for (guint i = 0; i < range.overlapping->len; i++) {
+        uint64_t start = g_array_index(range.overlapping, uint64_t, i);
+        uint64_t end = GPOINTER_TO_SIZE(g_tree_lookup(proc->anonMappings, GSIZE_TO_POINTER(start)));
+        g_tree_remove(proc->anonMappings, GSIZE_TO_POINTER(start));
+
+        if (start < range.start) {
+            g_tree_insert(
+                proc->anonMappings, GSIZE_TO_POINTER(start), GSIZE_TO_POINTER(range.start));
+        }
+        if (end > range.end) {
+            g_tree_insert(proc->anonMappings, GSIZE_TO_POINTER(range.end), GSIZE_TO_POINTER(end));
+        }
+
+        proc->anonMappingsLen -= MIN(end, range.end) - MAX(start, range.start);
+    }
+
+    g_array_free(range.overlapping, TRUE);
+}
+
+void process_addAnonMapping(Process* proc, uint64_t addr, size_t len) {
+    MAGIC_ASSERT(proc);
+
+    if (len == 0) {
+        return;
+    }
+
+    /* a fixed mapping replaces any mappings in its range */
+    process_removeAnonMapping(proc, addr, len);
+
+    g_tree_insert(proc->anonMappings, GSIZE_TO_POINTER(addr), GSIZE_TO_POINTER(addr + len));
+    proc->anonMappingsLen += len;
+}
+
+size_t process_getAnonMappingLen(Process* proc, uint64_t addr, size_t len) {
+    MAGIC_ASSERT(proc);
+
+    if (len == 0) {
+        return 0;
+    }
+
+    AnonMappingsRange range = {
+        .start = addr,
+        .end = addr + len,
+        .overlapping = g_array_new(FALSE, FALSE, sizeof(uint64_t)),
+    };
+    g_tree_foreach(proc->anonMappings, _process_findOverlappingMapping, &range);
+
+    size_t overlapLen = 0;
+    for (guint i = 0; i < range.overlapping->len; i++) {
+        uint64_t start = g_array_index(range.overlapping, uint64_t, i);
+        uint64_t end = GPOINTER_TO_SIZE(g_tree_lookup(proc->anonMappings, GSIZE_TO_POINTER(start)));
+        overlapLen += MIN(end, range.end) - MAX(start, range.start);
+    }
+
+    g_array_free(range.overlapping, TRUE);
+    return overlapLen;
+}
+
+void process_killOutOfMemory(Process* proc) {
+    MAGIC_ASSERT(proc);
+
+    if (proc->wasOOMKilled) {
+        return;
+    }
+    proc->wasOOMKilled = true;
+
+    /* stop the process as soon as the currently running thread yields */
+    process_ref(proc);
+    Task* stopProcessTask =
+        task_new(_process_runStopTask, proc, NULL, (TaskObjectFreeFunc)process_unref, NULL);
+    worker_scheduleTask(stopProcessTask, proc->host, 0);
+    task_unref(stopProcessTask);
+}
+
+bool process_wasOOMKilled(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->wasOOMKilled;
+}
+
 static void _thread_gpointer_unref(gpointer data) { thread_unref(data); }
 
 Process* process_new(Host* host, guint processID, SimulationTime startTime, SimulationTime stopTime,
+                     int shutdownSignal, SimulationTime shutdownGracePeriod,
+                     RestartPolicy restartPolicy, SimulationTime restartDelay, int maxRestarts,
+                     const gchar* name, StartConditions* startAfter, bool terminatesSimulation,
                      InterposeMethod interposeMethod, const gchar* hostName,
-                     const gchar* pluginName, const gchar* pluginPath, gchar** envv, gchar** argv) {
+                     const gchar* pluginName, const gchar* pluginPath, gchar** envv, gchar** argv,
+                     const gchar* stdinPath, const gchar* stdinContent, bool straceLogging,
+                     ProcessFinalState* expectedFinalState) {
     Process* proc = g_new0(Process, 1);
     MAGIC_INIT(proc);
 
@@ -718,14 +1330,26 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
             proc->plugin.exeName ? proc->plugin.exeName->str : "NULL",
             proc->processID);
 
-#ifdef USE_PERF_TIMERS
     proc->cpuDelayTimer = g_timer_new();
-#endif
 
     proc->startTime = startTime;
     proc->stopTime = stopTime;
+    proc->shutdownSignal = shutdownSignal;
+    proc->shutdownGracePeriod = shutdownGracePeriod;
+    proc->restartPolicy = restartPolicy;
+    proc->restartDelay = restartDelay;
+    proc->restartsLeft = maxRestarts;
+    proc->configName = g_strdup(name);
+    proc->startAfter = startAfter;
+    proc->terminatesSimulation = terminatesSimulation;
+    proc->exitStatus = -1;
+    proc->exitTime = SIMTIME_INVALID;
+    proc->expectedFinalState = expectedFinalState;
 
     proc->interposeMethod = interposeMethod;
+    proc->straceLogging = straceLogging;
+
+    proc->anonMappings = g_tree_new_full(_process_compareAddresses, NULL, NULL, NULL);
 
     if (_use_legacy_working_dir) {
         /* use Shadow's working directory */
@@ -755,6 +1379,8 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
     /* save args and env */
     proc->argv = g_strdupv(argv);
     proc->envv = envv;
+    proc->stdinPath = g_strdup(stdinPath);
+    proc->stdinContent = g_strdup(stdinContent);
 
     proc->descTable = descriptortable_new();
 
@@ -772,6 +1398,24 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
     return proc;
 }
 
+Process* process_newRestart(Process* proc, guint processID) {
+    MAGIC_ASSERT(proc);
+
+    Process* restarted = process_new(
+        proc->host, processID, worker_getCurrentTime(), proc->stopTime, proc->shutdownSignal,
+        proc->shutdownGracePeriod, proc->restartPolicy, proc->restartDelay,
+        proc->restartsLeft < 0 ? -1 : proc->restartsLeft - 1, proc->configName, NULL,
+        proc->terminatesSimulation, proc->interposeMethod, host_getName(proc->host),
+        proc->plugin.exeName->str, proc->plugin.exePath->str, g_strdupv(proc->envv), proc->argv,
+        proc->stdinPath, proc->stdinContent, proc->straceLogging, proc->expectedFinalState);
+    restarted->didReportSuccess = proc->didReportSuccess;
+
+    /* the expected final state now belongs to the restarted process */
+    proc->expectedFinalState = NULL;
+
+    return restarted;
+}
+
 static void _process_free(Process* proc) {
     MAGIC_ASSERT(proc);
 
@@ -783,6 +1427,19 @@ static void _process_free(Process* proc) {
         g_hash_table_destroy(proc->threads);
         proc->threads = NULL;
     }
+
+    simstats_addProcess(worker_getSimStats(), host_getName(proc->host), process_getName(proc),
+                        proc->processID, proc->startTime, proc->exitTime, proc->nativePid > 0,
+                        proc->returnCode, proc->exitStatus, proc->numSyscalls, proc->syscallCounts,
+                        proc->expectedFinalState);
+    if (proc->syscallCounts) {
+        counter_free(proc->syscallCounts);
+    }
+    processfinalstate_free(proc->expectedFinalState);
+    startconditions_free(proc->startAfter);
+    g_tree_destroy(proc->anonMappings);
+    g_free(proc->configName);
+
     if(proc->plugin.exePath) {
         g_string_free(proc->plugin.exePath, TRUE);
     }
@@ -806,10 +1463,10 @@ static void _process_free(Process* proc) {
     if(proc->envv) {
         g_strfreev(proc->envv);
     }
+    g_free(proc->stdinPath);
+    g_free(proc->stdinContent);
 
-#ifdef USE_PERF_TIMERS
     g_timer_destroy(proc->cpuDelayTimer);
-#endif
 
     /* Free the stdio files before the descriptor table.
      * Closing the descriptors will remove them from the table and the table
@@ -823,6 +1480,10 @@ static void _process_free(Process* proc) {
         descriptor_unref((LegacyDescriptor*)proc->stdoutFile);
     }
 
+    if (proc->straceFile) {
+        fclose(proc->straceFile);
+    }
+
     /* Now free all remaining descriptors stored in our table. */
     if (proc->descTable) {
         descriptortable_free(proc->descTable);
@@ -20,8 +20,10 @@
 #include <pthread.h>
 #include <signal.h>
 #include <stdarg.h>
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
+#include <stdio.h>
 #include <sys/file.h>
 #include <sys/statfs.h>
 #include <sys/statvfs.h>
@@ -31,6 +33,7 @@
 
 #include "main/bindings/c/bindings.h"
 #include "main/core/support/definitions.h"
+#include "main/core/work/task.h"
 #include "main/host/descriptor/descriptor_types.h"
 #include "main/host/descriptor/timer.h"
 #include "main/host/syscall_handler.h"
@@ -38,18 +41,41 @@
 #include "main/host/thread.h"
 
 Process* process_new(Host* host, guint processID, SimulationTime startTime, SimulationTime stopTime,
+                     int shutdownSignal, SimulationTime shutdownGracePeriod,
+                     RestartPolicy restartPolicy, SimulationTime restartDelay, int maxRestarts,
+                     const gchar* name, StartConditions* startAfter, bool terminatesSimulation,
                      InterposeMethod interposeMethod, const gchar* hostName,
-                     const gchar* pluginName, const gchar* pluginPath, gchar** envv, gchar** argv);
+                     const gchar* pluginName, const gchar* pluginPath, gchar** envv, gchar** argv,
+                     const gchar* stdinPath, const gchar* stdinContent, bool straceLogging,
+                     ProcessFinalState* expectedFinalState);
+/* Returns a new process that runs the same program with the same options as
+ * `proc`, for restarting `proc` after it exited. The new process starts now,
+ * and its final state is checked instead of the final state of `proc`. */
+Process* process_newRestart(Process* proc, guint processID);
 void process_ref(Process* proc);
 void process_unref(Process* proc);
 
 void process_schedule(Process* proc, gpointer nothing);
+/* Returns a new task that starts the process, or waits for its start_after
+ * conditions if they aren't met yet. */
+Task* process_newStartTask(Process* proc);
+/* Returns true if the process wasn't stopped and its start_after conditions
+ * were met at the end of the last round. */
+bool process_canStart(Process* proc, StartAfter* startAfter);
 void process_continue(Process* proc, Thread* thread);
 void process_stop(Process* proc);
+/* Records that the process is stopped because the simulation ended, so that it
+ * is reported as still running if it hadn't exited yet. The exit of a process
+ * that was waiting to be restarted is accounted for as if it wasn't restarted. */
+void process_markKilledAtShutdown(Process* proc);
 void process_detachPlugin(gpointer procptr, gpointer nothing);
 
 const char* process_getWorkingDir(Process* proc);
 
+// Returns the file that the process's syscalls are logged to, or NULL if
+// syscall logging is disabled for this process.
+FILE* process_getStraceFile(Process* proc);
+
 // Adds a new thread to the process and schedules it to run.
 // Intended for use by `clone`.
 void process_addThread(Process* proc, Thread* thread);
@@ -63,6 +89,41 @@ void process_markAsExiting(Process* proc);
 
 gboolean process_isRunning(Process* proc);
 
+/* Returns the number of bytes of the process's heap and anonymous mappings, or
+ * 0 if the process is not running. The usage is counted from the syscalls that
+ * change the process's address space, so it doesn't depend on which pages are
+ * resident. */
+size_t process_getMemoryUsage(Process* proc);
+
+/* The end of the process's heap as last returned by the brk syscall, or 0 if
+ * the process hasn't called brk yet. The first break that is set is taken to
+ * be the start of the heap. */
+uint64_t process_getBreak(Process* proc);
+void process_setBreak(Process* proc, uint64_t brk);
+
+/* Record that the `len` bytes at `addr` were mapped anonymously, replacing any
+ * mappings in that range. `len` must be a multiple of the page size. */
+void process_addAnonMapping(Process* proc, uint64_t addr, size_t len);
+
+/* Record that the `len` bytes at `addr` were unmapped. Parts of the range that
+ * weren't mapped anonymously are ignored. */
+void process_removeAnonMapping(Process* proc, uint64_t addr, size_t len);
+
+/* Returns how many of the `len` bytes at `addr` are mapped anonymously. */
+size_t process_getAnonMappingLen(Process* proc, uint64_t addr, size_t len);
+
+/* Record the handler and flags that the process set for the signal with the
+ * rt_sigaction syscall, which determine whether the signal interrupts blocked
+ * syscalls when it's sent to the process. */
+void process_setSignalAction(Process* proc, int sig, uint64_t handler, uint64_t flags);
+
+/* Stops the process because its host ran out of memory. The process is
+ * stopped after the currently running thread yields. */
+void process_killOutOfMemory(Process* proc);
+
+/* Returns true if process_killOutOfMemory() was called on the process. */
+bool process_wasOOMKilled(Process* proc);
+
 /* Returns the name of the process from an internal buffer.
  * The returned pointer will become invalid when the process
  * is freed and therefore should not be persistently stored
@@ -81,6 +142,24 @@ guint process_getProcessID(Process* proc);
 /* Returns the native pid of the process */
 pid_t process_getNativePid(const Process* proc);
 
+/* Returns the host that the process is running on */
+Host* process_getHost(Process* proc);
+
+/* Returns the argument and environment vectors that the process was started
+ * with. The returned pointers are owned by the process. */
+gchar** process_getArgv(Process* proc);
+gchar** process_getEnvv(Process* proc);
+
+/* Returns the simulation time at which the process was configured to start */
+SimulationTime process_getStartTime(Process* proc);
+
+/* Adds the syscalls handled by one of the process's threads to the totals that
+ * are written to the simulation statistics. `syscallCounts` may be NULL. */
+void process_addSyscallCounts(Process* proc, guint64 numSyscalls, Counter* syscallCounts);
+
+/* Returns the number of threads currently in the process */
+guint process_getNumThreads(Process* proc);
+
 /* Returns the native tid of the thread with the given virtual PID and TID.
  * Although the process knows its own virtualPID already, giving it as a param
  * here allows us to control which of the PIF and TID get matched:
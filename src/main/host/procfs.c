@@ -0,0 +1,604 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/host/procfs.h"
+
+#include <dirent.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <glib.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+#include "lib/logger/logger.h"
+#include "main/core/support/definitions.h"
+#include "main/core/worker.h"
+#include "main/host/descriptor/compat_socket.h"
+#include "main/host/descriptor/descriptor.h"
+#include "main/host/descriptor/tcp.h"
+#include "main/utility/utility.h"
+
+#define PROCFS_ROOT "/proc"
+#define PROCFS_SYS_CPU_ROOT "/sys/devices/system/cpu"
+
+/* The clock tick rate reported by sysconf(_SC_CLK_TCK), used by /proc/<pid>/stat. */
+#define PROCFS_CLOCK_TICKS_PER_SECOND 100
+
+/* Memory values in KiB when the host has no memory limit; these match the
+ * values reported by sysinfo(). */
+#define PROCFS_MEM_TOTAL_KIB (32ull * 1024 * 1024)
+#define PROCFS_MEM_FREE_KIB (24ull * 1024 * 1024)
+
+/* Linux uses TCP_CLOSE as the state of unconnected UDP sockets in /proc/net/udp. */
+#define PROCFS_UDP_STATE_UNCONNECTED 7
+#define PROCFS_UDP_STATE_CONNECTED 1
+
+typedef void (*ProcfsGenerateFunc)(Host* host, Process* proc, GString* contents);
+
+typedef struct _ProcfsEntry ProcfsEntry;
+struct _ProcfsEntry {
+    /* path relative to /proc (for global entries) or /proc/<pid> (for process entries) */
+    const char* name;
+    ProcfsGenerateFunc generate;
+};
+
+static SimulationTime _procfs_toClockTicks(SimulationTime t) {
+    return t / (SIMTIME_ONE_SECOND / PROCFS_CLOCK_TICKS_PER_SECOND);
+}
+
+static void _procfs_generateCmdline(Host* host, Process* proc, GString* contents) {
+    gchar** argv = process_getArgv(proc);
+    for (int i = 0; argv != NULL && argv[i] != NULL; i++) {
+        /* each argument is terminated by a NULL byte */
+        g_string_append_len(contents, argv[i], strlen(argv[i]) + 1);
+    }
+}
+
+static void _procfs_generateEnviron(Host* host, Process* proc, GString* contents) {
+    gchar** envv = process_getEnvv(proc);
+    for (int i = 0; envv != NULL && envv[i] != NULL; i++) {
+        g_string_append_len(contents, envv[i], strlen(envv[i]) + 1);
+    }
+}
+
+static void _procfs_generateComm(Host* host, Process* proc, GString* contents) {
+    /* the kernel truncates the command name to TASK_COMM_LEN-1 bytes */
+    g_string_append_printf(contents, "%.15s\n", process_getPluginName(proc));
+}
+
+static void _procfs_generateStat(Host* host, Process* proc, GString* contents) {
+    guint pid = process_getProcessID(proc);
+
+    /* fields are documented in proc(5); we only fill in those we can emulate */
+    g_string_append_printf(
+        contents, "%u (%.15s) R 0 %u %u 0 -1 0 0 0 0 0 0 0 0 0 20 0 %u 0 %" G_GUINT64_FORMAT, pid,
+        process_getPluginName(proc), pid, pid, process_getNumThreads(proc),
+        _procfs_toClockTicks(process_getStartTime(proc)));
+
+    /* vsize through exit_code are all zero */
+    for (int i = 23; i <= 52; i++) {
+        g_string_append(contents, " 0");
+    }
+    g_string_append_c(contents, '\n');
+}
+
+static void _procfs_generateStatus(Host* host, Process* proc, GString* contents) {
+    guint pid = process_getProcessID(proc);
+
+    g_string_append_printf(contents, "Name:\t%.15s\n", process_getPluginName(proc));
+    g_string_append(contents, "State:\tR (running)\n");
+    g_string_append_printf(contents, "Tgid:\t%u\n", pid);
+    g_string_append(contents, "Ngid:\t0\n");
+    g_string_append_printf(contents, "Pid:\t%u\n", pid);
+    g_string_append(contents, "PPid:\t0\n");
+    g_string_append(contents, "TracerPid:\t0\n");
+    g_string_append(contents, "Uid:\t0\t0\t0\t0\n");
+    g_string_append(contents, "Gid:\t0\t0\t0\t0\n");
+    g_string_append_printf(contents, "Threads:\t%u\n", process_getNumThreads(proc));
+}
+
+static void _procfs_generateCpuinfo(Host* host, Process* proc, GString* contents) {
+    guint64 frequencyKHz = host_getCPUFrequency(host);
+    guint cores = host_getCPUCores(host);
+
+    for (guint i = 0; i < cores; i++) {
+        g_string_append_printf(contents, "processor\t: %u\n", i);
+        g_string_append(contents, "vendor_id\t: Shadow\n");
+        g_string_append(contents, "model name\t: Shadow Virtual CPU\n");
+        g_string_append_printf(contents,
+                               "cpu MHz\t\t: %" G_GUINT64_FORMAT ".%03" G_GUINT64_FORMAT "\n",
+                               frequencyKHz / 1000, frequencyKHz % 1000);
+        g_string_append(contents, "physical id\t: 0\n");
+        g_string_append_printf(contents, "siblings\t: %u\n", cores);
+        g_string_append_printf(contents, "core id\t\t: %u\n", i);
+        g_string_append_printf(contents, "cpu cores\t: %u\n", cores);
+        g_string_append(contents, "\n");
+    }
+}
+
+/* The format of /sys/devices/system/cpu/{online,possible,present}, which glibc
+ * reads to implement get_nprocs() and sysconf(_SC_NPROCESSORS_*). */
+static void _procfs_generateCpuList(Host* host, Process* proc, GString* contents) {
+    guint cores = host_getCPUCores(host);
+    if (cores == 1) {
+        g_string_append(contents, "0\n");
+    } else {
+        g_string_append_printf(contents, "0-%u\n", cores - 1);
+    }
+}
+
+static void _procfs_generateMeminfo(Host* host, Process* proc, GString* contents) {
+    guint64 totalKiB = PROCFS_MEM_TOTAL_KIB;
+    guint64 freeKiB = PROCFS_MEM_FREE_KIB;
+
+    /* a host with a memory limit has exactly that much memory */
+    guint64 limit = host_getMemoryLimit(host);
+    if (limit > 0) {
+        guint64 usage = MIN(host_getMemoryUsage(host), limit);
+        totalKiB = limit / 1024;
+        freeKiB = (limit - usage) / 1024;
+    }
+
+    g_string_append_printf(contents, "MemTotal:       %8" G_GUINT64_FORMAT " kB\n", totalKiB);
+    g_string_append_printf(contents, "MemFree:        %8" G_GUINT64_FORMAT " kB\n", freeKiB);
+    g_string_append_printf(contents, "MemAvailable:   %8" G_GUINT64_FORMAT " kB\n", freeKiB);
+    g_string_append(contents, "Buffers:               0 kB\n");
+    g_string_append(contents, "Cached:                0 kB\n");
+    g_string_append(contents, "SwapTotal:             0 kB\n");
+    g_string_append(contents, "SwapFree:              0 kB\n");
+}
+
+static void _procfs_generateUptime(Host* host, Process* proc, GString* contents) {
+    SimulationTime now = worker_getCurrentTime();
+    SimulationTime centis = now / (SIMTIME_ONE_SECOND / 100);
+
+    /* the second value is the idle time; we report the host as never idle */
+    g_string_append_printf(contents,
+                           "%" G_GUINT64_FORMAT ".%02" G_GUINT64_FORMAT " 0.00\n",
+                           centis / 100, centis % 100);
+}
+
+static void _procfs_collectSocket(const CompatSocket* socket, void* sockets) {
+    if (socket->type == CST_LEGACY_SOCKET) {
+        g_ptr_array_add(sockets, socket->object.as_legacy_socket);
+    }
+}
+
+static guint64 _procfs_socketSortKey(Socket* socket, bool peer) {
+    CompatSocket compatSocket = compatsocket_fromLegacySocket(socket);
+    in_addr_t ip = 0;
+    in_port_t port = 0;
+    if (peer) {
+        compatsocket_getPeerName(&compatSocket, &ip, &port);
+    } else {
+        compatsocket_getSocketName(&compatSocket, &ip, &port);
+    }
+    return ((guint64)ntohl(ip) << 16) | ntohs(port);
+}
+
+static gint _procfs_compareSockets(gconstpointer a, gconstpointer b) {
+    /* sort by local and then peer address so that the output is deterministic */
+    Socket* sa = *(Socket**)a;
+    Socket* sb = *(Socket**)b;
+
+    guint64 ka = _procfs_socketSortKey(sa, false);
+    guint64 kb = _procfs_socketSortKey(sb, false);
+    if (ka == kb) {
+        ka = _procfs_socketSortKey(sa, true);
+        kb = _procfs_socketSortKey(sb, true);
+    }
+    if (ka == kb) {
+        ka = descriptor_getHandle((LegacyDescriptor*)sa);
+        kb = descriptor_getHandle((LegacyDescriptor*)sb);
+    }
+    return ka < kb ? -1 : ka > kb ? +1 : 0;
+}
+
+/* Returns the legacy sockets of the given descriptor type that are associated with any of the
+ * host's interfaces, sorted and without duplicates. */
+static GPtrArray* _procfs_getSockets(Host* host, LegacyDescriptorType type) {
+    GPtrArray* all = g_ptr_array_new();
+    host_iterAssociatedSockets(host, _procfs_collectSocket, all);
+
+    GPtrArray* sockets = g_ptr_array_new();
+    GHashTable* seen = g_hash_table_new(g_direct_hash, g_direct_equal);
+
+    for (guint i = 0; i < all->len; i++) {
+        LegacyDescriptor* desc = g_ptr_array_index(all, i);
+        if (descriptor_getType(desc) == type && g_hash_table_add(seen, desc)) {
+            g_ptr_array_add(sockets, desc);
+        }
+    }
+
+    g_hash_table_destroy(seen);
+    g_ptr_array_free(all, TRUE);
+
+    g_ptr_array_sort(sockets, _procfs_compareSockets);
+    return sockets;
+}
+
+static void _procfs_appendSocketLine(GString* contents, guint index, Socket* socket, guint8 state,
+                                     gsize txQueue, gsize rxQueue) {
+    CompatSocket compatSocket = compatsocket_fromLegacySocket(socket);
+
+    in_addr_t localIP = 0, peerIP = 0;
+    in_port_t localPort = 0, peerPort = 0;
+    compatsocket_getSocketName(&compatSocket, &localIP, &localPort);
+    compatsocket_getPeerName(&compatSocket, &peerIP, &peerPort);
+
+    /* addresses are printed as the raw (network-order) 32-bit value, ports in host order */
+    g_string_append_printf(contents,
+                           "%4u: %08X:%04X %08X:%04X %02X %08zX:%08zX 00:00000000 00000000 "
+                           "    0        0 0 1 0000000000000000\n",
+                           index, (guint)localIP, (guint)ntohs(localPort), (guint)peerIP,
+                           (guint)ntohs(peerPort), (guint)state, txQueue, rxQueue);
+}
+
+static void _procfs_generateNetTcp(Host* host, Process* proc, GString* contents) {
+    g_string_append(contents, "  sl  local_address rem_address   st tx_queue rx_queue tr "
+                              "tm->when retrnsmt   uid  timeout inode\n");
+
+    GPtrArray* sockets = _procfs_getSockets(host, DT_TCPSOCKET);
+    for (guint i = 0; i < sockets->len; i++) {
+        TCP* tcp = g_ptr_array_index(sockets, i);
+
+        struct tcp_info info;
+        tcp_getInfo(tcp, &info);
+
+        _procfs_appendSocketLine(contents, i, (Socket*)tcp, info.tcpi_state,
+                                 tcp_getOutputBufferLength(tcp), tcp_getInputBufferLength(tcp));
+    }
+    g_ptr_array_free(sockets, TRUE);
+}
+
+static void _procfs_generateNetUdp(Host* host, Process* proc, GString* contents) {
+    g_string_append(contents, "   sl  local_address rem_address   st tx_queue rx_queue tr "
+                              "tm->when retrnsmt   uid  timeout inode ref pointer drops\n");
+
+    GPtrArray* sockets = _procfs_getSockets(host, DT_UDPSOCKET);
+    for (guint i = 0; i < sockets->len; i++) {
+        Socket* udp = g_ptr_array_index(sockets, i);
+        CompatSocket compatSocket = compatsocket_fromLegacySocket(udp);
+
+        guint8 state = compatsocket_getPeerName(&compatSocket, NULL, NULL)
+                           ? PROCFS_UDP_STATE_CONNECTED
+                           : PROCFS_UDP_STATE_UNCONNECTED;
+
+        _procfs_appendSocketLine(contents, i, udp, state, 0, 0);
+    }
+    g_ptr_array_free(sockets, TRUE);
+}
+
+/* Files directly under /proc. Directories other than /proc/net aren't emulated. */
+static const ProcfsEntry _procfsGlobalEntries[] = {
+    {"cpuinfo", _procfs_generateCpuinfo}, {"meminfo", _procfs_generateMeminfo},
+    {"uptime", _procfs_generateUptime},   {"net/tcp", _procfs_generateNetTcp},
+    {"net/udp", _procfs_generateNetUdp},  {NULL, NULL},
+};
+
+/* Files under /sys/devices/system/cpu. The rest of /sys is not virtualized. */
+static const ProcfsEntry _procfsSysCpuEntries[] = {
+    {"online", _procfs_generateCpuList},
+    {"possible", _procfs_generateCpuList},
+    {"present", _procfs_generateCpuList},
+    {NULL, NULL},
+};
+
+/* Files under /proc/<pid> and /proc/self. */
+static const ProcfsEntry _procfsProcessEntries[] = {
+    {"cmdline", _procfs_generateCmdline}, {"comm", _procfs_generateComm},
+    {"environ", _procfs_generateEnviron}, {"stat", _procfs_generateStat},
+    {"status", _procfs_generateStatus},   {"net/tcp", _procfs_generateNetTcp},
+    {"net/udp", _procfs_generateNetUdp},  {NULL, NULL},
+};
+
+static ProcfsGenerateFunc _procfs_lookup(const ProcfsEntry* entries, const char* name) {
+    for (int i = 0; entries[i].name != NULL; i++) {
+        if (!strcmp(entries[i].name, name)) {
+            return entries[i].generate;
+        }
+    }
+    return NULL;
+}
+
+/* Returns an OS-backed fd containing `contents`, or a negative errno value. */
+static int _procfs_openContents(const char* abspath, GString* contents, int flags) {
+    int memfdFlags = (flags & O_CLOEXEC) ? MFD_CLOEXEC : 0;
+    int osfd = memfd_create(abspath, memfdFlags);
+    if (osfd < 0) {
+        return -errno;
+    }
+
+    gsize written = 0;
+    while (written < contents->len) {
+        ssize_t rv = write(osfd, contents->str + written, contents->len - written);
+        if (rv < 0) {
+            int errcode = errno;
+            close(osfd);
+            return -errcode;
+        }
+        written += rv;
+    }
+
+    if (lseek(osfd, 0, SEEK_SET) < 0) {
+        int errcode = errno;
+        close(osfd);
+        return -errcode;
+    }
+
+    return osfd;
+}
+
+static int _procfs_openGenerated(Host* host, Process* proc, const char* abspath, int flags,
+                                 ProcfsGenerateFunc generate) {
+    if ((flags & O_ACCMODE) != O_RDONLY) {
+        return -EACCES;
+    }
+
+    GString* contents = g_string_new(NULL);
+    generate(host, proc, contents);
+
+    trace("Generated %zu bytes for virtual procfs file '%s'", contents->len, abspath);
+
+    int rv = _procfs_openContents(abspath, contents, flags);
+    g_string_free(contents, TRUE);
+    return rv;
+}
+
+static int _procfs_openNative(const char* path, int flags, mode_t mode) {
+    int osfd = open(path, flags, mode);
+    return (osfd < 0) ? -errno : osfd;
+}
+
+/* Returns true if `name`, relative to /proc or a process directory, is the
+ * net directory or a path inside of it. */
+static bool _procfs_isNetPath(const char* name) {
+    return g_str_has_prefix(name, "net") && (name[3] == '\0' || name[3] == '/');
+}
+
+/* Returns true if `name` is the net directory itself. */
+static bool _procfs_isNetDir(const char* name) {
+    return _procfs_isNetPath(name) && name[3 + strspn(name + 3, "/")] == '\0';
+}
+
+/* Native per-process directories that we hide, because their entries are the
+ * native thread ids (task) and descriptors (fd, fdinfo) of the process, which
+ * don't match the virtual ones that Shadow reports. */
+static const char* _procfsHiddenProcessEntries[] = {"fd", "fdinfo", "task", NULL};
+
+/* Returns true if `name`, relative to a process directory, is one of the
+ * hidden directories or a path inside of it. */
+static bool _procfs_isHiddenProcessPath(const char* name) {
+    for (int i = 0; _procfsHiddenProcessEntries[i] != NULL; i++) {
+        size_t len = strlen(_procfsHiddenProcessEntries[i]);
+        if (!strncmp(name, _procfsHiddenProcessEntries[i], len) &&
+            (name[len] == '\0' || name[len] == '/')) {
+            return true;
+        }
+    }
+    return false;
+}
+
+/* Opens the file at `name` relative to the directory of the process `target`. */
+static int _procfs_openProcessEntry(Host* host, Process* target, const char* abspath,
+                                    const char* name, int flags, mode_t mode) {
+    ProcfsGenerateFunc generate = _procfs_lookup(_procfsProcessEntries, name);
+    if (generate) {
+        return _procfs_openGenerated(host, target, abspath, flags, generate);
+    }
+
+    /* the rest of the net directory describes the machine running Shadow */
+    if (_procfs_isNetPath(name) && !_procfs_isNetDir(name)) {
+        return -ENOENT;
+    }
+
+    if (_procfs_isHiddenProcessPath(name)) {
+        return -ENOENT;
+    }
+
+    /* not generated; use the corresponding file of the native process */
+    gchar* nativePath = NULL;
+    if (name[0] == '\0') {
+        nativePath = g_strdup_printf(PROCFS_ROOT "/%d", (int)process_getNativePid(target));
+    } else {
+        nativePath =
+            g_strdup_printf(PROCFS_ROOT "/%d/%s", (int)process_getNativePid(target), name);
+    }
+
+    trace("Redirecting procfs path '%s' to native path '%s'", abspath, nativePath);
+
+    int rv = _procfs_openNative(nativePath, flags, mode);
+    g_free(nativePath);
+    return rv;
+}
+
+/* If `component` is a valid decimal PID, returns it; otherwise returns 0. */
+static pid_t _procfs_parsePid(const char* component, const char** end) {
+    char* endptr = NULL;
+    long pid = strtol(component, &endptr, 10);
+    if (endptr == component || (*endptr != '\0' && *endptr != '/') || pid <= 0) {
+        return 0;
+    }
+    *end = endptr;
+    return (pid_t)pid;
+}
+
+/* Returns the generator for `abspath` if it is one of the files in
+ * `_procfsSysCpuEntries`, or NULL otherwise. */
+static ProcfsGenerateFunc _procfs_lookupSysCpu(const char* abspath) {
+    if (!g_str_has_prefix(abspath, PROCFS_SYS_CPU_ROOT "/")) {
+        return NULL;
+    }
+    return _procfs_lookup(_procfsSysCpuEntries, abspath + strlen(PROCFS_SYS_CPU_ROOT "/"));
+}
+
+bool procfs_isProcPath(const char* abspath) {
+    utility_assert(abspath);
+    return !strcmp(abspath, PROCFS_ROOT) || g_str_has_prefix(abspath, PROCFS_ROOT "/") ||
+           _procfs_lookupSysCpu(abspath) != NULL;
+}
+
+int procfs_open(Host* host, Process* proc, const char* abspath, int flags, mode_t mode) {
+    utility_assert(host);
+    utility_assert(proc);
+    utility_assert(procfs_isProcPath(abspath));
+
+    ProcfsGenerateFunc sysCpuGenerate = _procfs_lookupSysCpu(abspath);
+    if (sysCpuGenerate) {
+        return _procfs_openGenerated(host, proc, abspath, flags, sysCpuGenerate);
+    }
+
+    /* the path relative to /proc, without leading separators */
+    const char* relpath = abspath + strlen(PROCFS_ROOT);
+    while (*relpath == '/') {
+        relpath++;
+    }
+
+    const char* rest = NULL;
+    if (g_str_has_prefix(relpath, "self") && (relpath[4] == '\0' || relpath[4] == '/')) {
+        rest = relpath + 4;
+        while (*rest == '/') {
+            rest++;
+        }
+        return _procfs_openProcessEntry(host, proc, abspath, rest, flags, mode);
+    }
+
+    pid_t pid = _procfs_parsePid(relpath, &rest);
+    if (pid > 0) {
+        /* only processes on our own simulated host are visible */
+        Process* target = host_getProcess(host, pid);
+        if (target == NULL) {
+            return -ENOENT;
+        }
+        while (*rest == '/') {
+            rest++;
+        }
+        return _procfs_openProcessEntry(host, target, abspath, rest, flags, mode);
+    }
+
+    ProcfsGenerateFunc generate = _procfs_lookup(_procfsGlobalEntries, relpath);
+    if (generate) {
+        return _procfs_openGenerated(host, proc, abspath, flags, generate);
+    }
+
+    /* the directories are native, but only the emulated entries are listed */
+    if (relpath[0] == '\0' || _procfs_isNetDir(relpath)) {
+        return _procfs_openNative(abspath, flags, mode);
+    }
+
+    /* everything else (e.g., /proc/stat or /proc/sys) would leak the state of
+     * the machine running Shadow */
+    return -ENOENT;
+}
+
+/* Adds an emulated entry. The inode number only depends on the name, so that
+ * listings are the same in every run. */
+static void _procfs_addListingEntry(DirListing* listing, const char* name, unsigned char type) {
+    dirlisting_add(listing, name, (ino64_t)g_str_hash(name) + 1, type);
+}
+
+static bool _procfs_isListedProcessEntry(const char* name, void* data) {
+    return !_procfs_isHiddenProcessPath(name);
+}
+
+static void _procfs_addProcessListingEntry(Process* proc, void* listing) {
+    gchar* name = g_strdup_printf("%u", process_getProcessID(proc));
+    _procfs_addListingEntry(listing, name, DT_DIR);
+    g_free(name);
+}
+
+/* Adds the top-level entries of the files in `entries` under the directory
+ * `prefix`, which is "" for the top-level directory or ends with a '/'. */
+static void _procfs_addListingEntries(DirListing* listing, const ProcfsEntry* entries,
+                                      const char* prefix) {
+    for (int i = 0; entries[i].name != NULL; i++) {
+        if (!g_str_has_prefix(entries[i].name, prefix)) {
+            continue;
+        }
+
+        const char* name = entries[i].name + strlen(prefix);
+        const char* slash = strchr(name, '/');
+        if (slash) {
+            gchar* dirName = g_strndup(name, slash - name);
+            _procfs_addListingEntry(listing, dirName, DT_DIR);
+            g_free(dirName);
+        } else {
+            _procfs_addListingEntry(listing, name, DT_REG);
+        }
+    }
+}
+
+DirListing* procfs_listDirectory(Host* host, Process* proc, const char* abspath) {
+    utility_assert(host);
+    utility_assert(proc);
+
+    if (!procfs_isProcPath(abspath) || _procfs_lookupSysCpu(abspath)) {
+        return NULL;
+    }
+
+    const char* relpath = abspath + strlen(PROCFS_ROOT);
+    while (*relpath == '/') {
+        relpath++;
+    }
+
+    DirListing* listing = NULL;
+
+    if (relpath[0] == '\0') {
+        listing = dirlisting_new();
+        _procfs_addListingEntry(listing, ".", DT_DIR);
+        _procfs_addListingEntry(listing, "..", DT_DIR);
+        _procfs_addListingEntry(listing, "self", DT_LNK);
+        _procfs_addListingEntries(listing, _procfsGlobalEntries, "");
+
+        /* only processes on our own simulated host are visible */
+        host_iterProcesses(host, _procfs_addProcessListingEntry, listing);
+        return listing;
+    }
+
+    /* the net directory, either directly under /proc or in a process directory */
+    const char* rest = relpath;
+    Process* target = NULL;
+    if (g_str_has_prefix(relpath, "self") && (relpath[4] == '\0' || relpath[4] == '/')) {
+        rest = relpath + 4;
+        target = proc;
+    } else {
+        pid_t pid = _procfs_parsePid(relpath, &rest);
+        if (pid > 0) {
+            target = host_getProcess(host, pid);
+        } else {
+            rest = relpath;
+        }
+    }
+    while (*rest == '/') {
+        rest++;
+    }
+
+    /* process directories list the entries of the native process, except for
+     * the hidden ones */
+    if (target && rest[0] == '\0') {
+        listing = dirlisting_new();
+        gchar* nativePath = g_strdup_printf(PROCFS_ROOT "/%d", (int)process_getNativePid(target));
+        int rv = dirlisting_addNative(listing, nativePath, _procfs_isListedProcessEntry, NULL);
+        if (rv < 0) {
+            warning("Could not list native path '%s': %s", nativePath, g_strerror(-rv));
+        }
+        g_free(nativePath);
+        return listing;
+    }
+
+    if (_procfs_isNetDir(rest)) {
+        listing = dirlisting_new();
+        _procfs_addListingEntry(listing, ".", DT_DIR);
+        _procfs_addListingEntry(listing, "..", DT_DIR);
+        _procfs_addListingEntries(listing, _procfsProcessEntries, "net/");
+    }
+
+    /* other directories list their native entries */
+    return listing;
+}
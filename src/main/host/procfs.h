@@ -0,0 +1,44 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef SRC_MAIN_HOST_PROCFS_H_
+#define SRC_MAIN_HOST_PROCFS_H_
+
+#include <stdbool.h>
+#include <sys/types.h>
+
+#include "main/host/dir_listing.h"
+#include "main/host/host.h"
+#include "main/host/process.h"
+
+/* A synthetic version of the /proc filesystem. Files that describe the
+ * simulated host and its processes (e.g., /proc/self/stat, /proc/cpuinfo,
+ * /proc/meminfo, /proc/net/tcp) are generated from Shadow's own state when they
+ * are opened, so that plugins introspecting themselves see virtual PIDs,
+ * emulated sockets, and configured resources instead of the real machine's.
+ *
+ * Per-process files that we don't generate are redirected to the corresponding
+ * file of the native process, except for the task, fd, and fdinfo directories,
+ * which would expose native thread ids and descriptors and do not exist.
+ * Process directories that do not belong to a process on the simulated host do
+ * not exist either. Other files that we don't generate (e.g., /proc/stat) do
+ * not exist, and listing /proc only shows the emulated entries. */
+
+/* Returns true if `abspath` is /proc or a path inside of it, or one of the
+ * generated CPU topology files under /sys/devices/system/cpu. */
+bool procfs_isProcPath(const char* abspath);
+
+/* Returns the entries of the directory `abspath` that are emulated, or NULL if
+ * the native entries of the directory should be listed. The returned listing
+ * must be released with dirlisting_unref(). */
+DirListing* procfs_listDirectory(Host* host, Process* proc, const char* abspath);
+
+/* Opens `abspath`, which must satisfy `procfs_isProcPath()`, on behalf of
+ * `proc` running on `host`. Returns a new OS-backed fd from which the file
+ * contents can be read, or a negative errno value on failure. Generated files
+ * are read-only; attempting to open them for writing returns -EACCES. */
+int procfs_open(Host* host, Process* proc, const char* abspath, int flags, mode_t mode);
+
+#endif /* SRC_MAIN_HOST_PROCFS_H_ */
@@ -68,7 +68,8 @@ static SysCallReturn _syscallhandler_openHelper(SysCallHandler* sys,
     int handle = process_registerLegacyDescriptor(sys->process, (LegacyDescriptor*)filed);
 
     /* Now open the file. */
-    errcode = file_open(filed, pathname, flags, mode, process_getWorkingDir(sys->process));
+    errcode =
+        file_open(filed, pathname, flags, mode, process_getWorkingDir(sys->process), sys->host);
     if (errcode < 0) {
         /* This will remove the descriptor entry and unref/free the File. */
         descriptor_close((LegacyDescriptor*)filed, sys->host);
@@ -87,6 +88,11 @@ static SysCallReturn _syscallhandler_fsyncHelper(SysCallHandler* sys, int fd) {
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
     }
 
+    SysCallReturn blockReturn;
+    if (_syscallhandler_waitForDisk(sys, file_desc, DISK_OPERATION_SYNC, 0, &blockReturn)) {
+        return blockReturn;
+    }
+
     return (SysCallReturn){
         .state = SYSCALL_DONE, .retval.as_i64 = file_fsync(file_desc)};
 }
@@ -399,6 +405,11 @@ SysCallReturn syscallhandler_sync_file_range(SysCallHandler* sys,
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
     }
 
+    SysCallReturn blockReturn;
+    if (_syscallhandler_waitForDisk(sys, file_desc, DISK_OPERATION_SYNC, 0, &blockReturn)) {
+        return blockReturn;
+    }
+
     return (SysCallReturn){
         .state = SYSCALL_DONE,
         .retval.as_i64 = file_sync_range(file_desc, offset, nbytes, flags)};
@@ -11,11 +11,14 @@
 #include <sys/stat.h>
 #include <sys/time.h>
 #include <sys/types.h>
+#include <sys/xattr.h>
 #include <unistd.h>
+#include <utime.h>
 
 #include "lib/logger/logger.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/file.h"
+#include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/kernel_types.h"
 #include "main/host/syscall/protected.h"
@@ -97,7 +100,22 @@ _syscallhandler_renameatHelper(SysCallHandler* sys, int olddirfd,
     return (SysCallReturn){
         .state = SYSCALL_DONE,
         .retval.as_i64 =
-            file_renameat2(olddir_desc, oldpath, newdir_desc, newpath, flags, plugin_cwd)};
+            file_renameat2(olddir_desc, oldpath, newdir_desc, newpath, flags, plugin_cwd, sys->host)};
+}
+
+/* Builds the arguments for the *at() version of a legacy path syscall, by
+ * inserting AT_FDCWD as the directory argument at index `dirfdIndex`. */
+static SysCallArgs _syscallhandler_legacyToAtArgs(const SysCallArgs* args, long atNumber,
+                                                  int dirfdIndex) {
+    SysCallArgs atArgs = {.number = atNumber};
+    for (int i = 0, j = 0; i < 6; i++) {
+        if (i == dirfdIndex) {
+            atArgs.args[i].as_i64 = AT_FDCWD;
+        } else {
+            atArgs.args[i] = args->args[j++];
+        }
+    }
+    return atArgs;
 }
 
 ///////////////////////////////////////////////////////////
@@ -130,7 +148,8 @@ SysCallReturn syscallhandler_openat(SysCallHandler* sys,
         process_registerLegacyDescriptor(sys->process, (LegacyDescriptor*)file_desc);
 
     /* Now open the file. */
-    errcode = file_openat(file_desc, dir_desc, pathname, flags, mode, process_getWorkingDir(sys->process));
+    errcode = file_openat(file_desc, dir_desc, pathname, flags, mode,
+                          process_getWorkingDir(sys->process), sys->host);
     if (errcode < 0) {
         /* This will remove the descriptor entry and unref/free the File. */
         descriptor_close((LegacyDescriptor*)file_desc, sys->host);
@@ -177,7 +196,7 @@ SysCallReturn syscallhandler_newfstatat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_fstatat(dir_desc, pathname, buf, flags, plugin_cwd)};
+        .retval.as_i64 = file_fstatat(dir_desc, pathname, buf, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_fchownat(SysCallHandler* sys,
@@ -202,7 +221,7 @@ SysCallReturn syscallhandler_fchownat(SysCallHandler* sys,
 
     return (SysCallReturn){.state = SYSCALL_DONE,
                            .retval.as_i64 = file_fchownat(
-                               dir_desc, pathname, owner, group, flags, plugin_cwd)};
+                               dir_desc, pathname, owner, group, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_fchmodat(SysCallHandler* sys,
@@ -226,7 +245,7 @@ SysCallReturn syscallhandler_fchmodat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_fchmodat(dir_desc, pathname, mode, flags, plugin_cwd)};
+        .retval.as_i64 = file_fchmodat(dir_desc, pathname, mode, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_futimesat(SysCallHandler* sys,
@@ -255,7 +274,7 @@ SysCallReturn syscallhandler_futimesat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_futimesat(dir_desc, pathname, times, plugin_cwd)};
+        .retval.as_i64 = file_futimesat(dir_desc, pathname, times, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_utimensat(SysCallHandler* sys,
@@ -285,7 +304,7 @@ SysCallReturn syscallhandler_utimensat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_utimensat(dir_desc, pathname, times, flags, plugin_cwd)};
+        .retval.as_i64 = file_utimensat(dir_desc, pathname, times, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_faccessat(SysCallHandler* sys,
@@ -309,7 +328,7 @@ SysCallReturn syscallhandler_faccessat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_faccessat(dir_desc, pathname, mode, flags, plugin_cwd)};
+        .retval.as_i64 = file_faccessat(dir_desc, pathname, mode, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_mkdirat(SysCallHandler* sys,
@@ -332,7 +351,7 @@ SysCallReturn syscallhandler_mkdirat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_mkdirat(dir_desc, pathname, mode, plugin_cwd)};
+        .retval.as_i64 = file_mkdirat(dir_desc, pathname, mode, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_mknodat(SysCallHandler* sys,
@@ -356,7 +375,7 @@ SysCallReturn syscallhandler_mknodat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_mknodat(dir_desc, pathname, mode, dev, plugin_cwd)};
+        .retval.as_i64 = file_mknodat(dir_desc, pathname, mode, dev, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_linkat(SysCallHandler* sys,
@@ -391,7 +410,7 @@ SysCallReturn syscallhandler_linkat(SysCallHandler* sys,
     return (SysCallReturn){
         .state = SYSCALL_DONE,
         .retval.as_i64 =
-            file_linkat(olddir_desc, oldpath, newdir_desc, newpath, flags, plugin_cwd)};
+            file_linkat(olddir_desc, oldpath, newdir_desc, newpath, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_unlinkat(SysCallHandler* sys,
@@ -414,7 +433,7 @@ SysCallReturn syscallhandler_unlinkat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_unlinkat(dir_desc, pathname, flags, plugin_cwd)};
+        .retval.as_i64 = file_unlinkat(dir_desc, pathname, flags, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_symlinkat(SysCallHandler* sys,
@@ -444,7 +463,7 @@ SysCallReturn syscallhandler_symlinkat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_symlinkat(dir_desc, linkpath, targetpath, plugin_cwd)};
+        .retval.as_i64 = file_symlinkat(dir_desc, linkpath, targetpath, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_readlinkat(SysCallHandler* sys,
@@ -481,7 +500,7 @@ SysCallReturn syscallhandler_readlinkat(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_readlinkat(dir_desc, pathname, buf, bufSize, plugin_cwd)};
+        .retval.as_i64 = file_readlinkat(dir_desc, pathname, buf, bufSize, plugin_cwd, sys->host)};
 }
 
 SysCallReturn syscallhandler_renameat(SysCallHandler* sys,
@@ -534,6 +553,367 @@ SysCallReturn syscallhandler_statx(SysCallHandler* sys,
 
     return (SysCallReturn){
         .state = SYSCALL_DONE,
-        .retval.as_i64 = file_statx(dir_desc, pathname, flags, mask, statxbuf, plugin_cwd)};
+        .retval.as_i64 = file_statx(dir_desc, pathname, flags, mask, statxbuf, plugin_cwd, sys->host)};
 }
 #endif
+
+///////////////////////////////////////////////////////////
+// Legacy path syscalls
+///////////////////////////////////////////////////////////
+
+/* The plugin can execute these natively unless the host has its own view of
+ * the filesystem, in which case we handle them like their *at() versions. */
+
+#define LEGACY_PATH_HANDLER(s, at, ...)                                                          \
+    SysCallReturn syscallhandler_##s(SysCallHandler* sys, const SysCallArgs* args) {           \
+        if (!host_getFsOverlay(sys->host)) {                                                   \
+            return (SysCallReturn){.state = SYSCALL_NATIVE};                                   \
+        }                                                                                      \
+        SysCallArgs atArgs = _syscallhandler_legacyToAtArgs(args, SYS_##at, 0);                \
+        __VA_ARGS__;                                                                           \
+        return syscallhandler_##at(sys, &atArgs);                                              \
+    }
+
+LEGACY_PATH_HANDLER(access, faccessat, atArgs.args[3].as_i64 = 0)
+LEGACY_PATH_HANDLER(chmod, fchmodat, atArgs.args[3].as_i64 = 0)
+LEGACY_PATH_HANDLER(chown, fchownat, atArgs.args[4].as_i64 = 0)
+LEGACY_PATH_HANDLER(lchown, fchownat, atArgs.args[4].as_i64 = AT_SYMLINK_NOFOLLOW)
+LEGACY_PATH_HANDLER(lstat, newfstatat, atArgs.args[3].as_i64 = AT_SYMLINK_NOFOLLOW)
+LEGACY_PATH_HANDLER(mkdir, mkdirat, )
+LEGACY_PATH_HANDLER(mknod, mknodat, )
+LEGACY_PATH_HANDLER(readlink, readlinkat, )
+LEGACY_PATH_HANDLER(rmdir, unlinkat, atArgs.args[2].as_i64 = AT_REMOVEDIR)
+LEGACY_PATH_HANDLER(stat, newfstatat, atArgs.args[3].as_i64 = 0)
+LEGACY_PATH_HANDLER(unlink, unlinkat, atArgs.args[2].as_i64 = 0)
+
+#undef LEGACY_PATH_HANDLER
+
+SysCallReturn syscallhandler_link(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+    SysCallArgs atArgs = {.number = SYS_linkat};
+    atArgs.args[0].as_i64 = AT_FDCWD;
+    atArgs.args[1] = args->args[0];
+    atArgs.args[2].as_i64 = AT_FDCWD;
+    atArgs.args[3] = args->args[1];
+    atArgs.args[4].as_i64 = 0;
+    return syscallhandler_linkat(sys, &atArgs);
+}
+
+SysCallReturn syscallhandler_rename(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+    return _syscallhandler_renameatHelper(sys, AT_FDCWD, args->args[0].as_ptr, AT_FDCWD,
+                                          args->args[1].as_ptr, 0);
+}
+
+SysCallReturn syscallhandler_symlink(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+    /* symlinkat takes the directory fd as its second argument */
+    SysCallArgs atArgs = _syscallhandler_legacyToAtArgs(args, SYS_symlinkat, 1);
+    return syscallhandler_symlinkat(sys, &atArgs);
+}
+
+/* Reads the path argument of a legacy path syscall into `pathname`, which must
+ * have room for PATH_MAX bytes. We copy the path rather than getting a
+ * reference, so that the MemoryManager will still allow us to get a mutable
+ * reference to other plugin memory. Returns 0 or a negative errno value. */
+static int _syscallhandler_readPathHelper(SysCallHandler* sys, PluginPtr pathnamePtr,
+                                          char* pathname) {
+    int errcode = process_readString(sys->process, pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return errcode;
+    }
+    /* like the kernel, we don't resolve empty paths to the working directory */
+    return pathname[0] == '\0' ? -ENOENT : 0;
+}
+
+SysCallReturn syscallhandler_truncate(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    off_t length = args->args[1].as_i64;
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){.state = SYSCALL_DONE,
+                           .retval.as_i64 = file_truncate(pathname, length, plugin_cwd, sys->host)};
+}
+
+SysCallReturn syscallhandler_statfs(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr bufPtr = args->args[1].as_ptr;      // struct statfs*
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    struct statfs* buf = process_getWriteablePtr(sys->process, bufPtr, sizeof(*buf));
+    if (!buf) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){.state = SYSCALL_DONE,
+                           .retval.as_i64 = file_statfs(pathname, buf, plugin_cwd, sys->host)};
+}
+
+SysCallReturn syscallhandler_utime(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr timesPtr = args->args[1].as_ptr;    // const struct utimbuf*
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    /* NULL times sets both times to the current time */
+    struct timespec times[2] = {0};
+    if (timesPtr.val) {
+        const struct utimbuf* buf = process_getReadablePtr(sys->process, timesPtr, sizeof(*buf));
+        if (!buf) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+        }
+        times[0].tv_sec = buf->actime;
+        times[1].tv_sec = buf->modtime;
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){.state = SYSCALL_DONE,
+                           .retval.as_i64 = file_utimensat(NULL, pathname,
+                                                           timesPtr.val ? times : NULL, 0,
+                                                           plugin_cwd, sys->host)};
+}
+
+SysCallReturn syscallhandler_utimes(SysCallHandler* sys, const SysCallArgs* args) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr timesPtr = args->args[1].as_ptr;    // const struct timeval [2]
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    /* NULL times sets both times to the current time */
+    const struct timeval* times = NULL;
+    if (timesPtr.val) {
+        times = process_getReadablePtr(sys->process, timesPtr, 2 * sizeof(*times));
+        if (!times) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+        }
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){
+        .state = SYSCALL_DONE,
+        .retval.as_i64 = file_futimesat(NULL, pathname, times, plugin_cwd, sys->host)};
+}
+
+///////////////////////////////////////////////////////////
+// Extended attributes of paths
+///////////////////////////////////////////////////////////
+
+/* Like the legacy path syscalls, the plugin can execute these natively unless
+ * the host has its own view of the filesystem. The "l" versions don't follow
+ * the final symbolic link. */
+
+static SysCallReturn _syscallhandler_setxattrHelper(SysCallHandler* sys, const SysCallArgs* args,
+                                                    bool followFinal) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr namePtr = args->args[1].as_ptr;     // const char*
+    PluginPtr valuePtr = args->args[2].as_ptr;    // const void*
+    size_t size = args->args[3].as_u64;
+    int flags = args->args[4].as_i64;
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    char name[XATTR_NAME_MAX + 1];
+    errcode = process_readString(sys->process, name, namePtr, sizeof(name));
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    const void* value = NULL;
+    if (size > 0) {
+        value = process_getReadablePtr(sys->process, valuePtr, size);
+        if (!value) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+        }
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){.state = SYSCALL_DONE,
+                           .retval.as_i64 = file_setxattr(pathname, name, value, size, flags,
+                                                          followFinal, plugin_cwd, sys->host)};
+}
+
+static SysCallReturn _syscallhandler_getxattrHelper(SysCallHandler* sys, const SysCallArgs* args,
+                                                    bool followFinal) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr namePtr = args->args[1].as_ptr;     // const char*
+    PluginPtr valuePtr = args->args[2].as_ptr;    // void*
+    size_t size = args->args[3].as_u64;
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    char name[XATTR_NAME_MAX + 1];
+    errcode = process_readString(sys->process, name, namePtr, sizeof(name));
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    /* a size of 0 asks for the size of the value */
+    void* value = NULL;
+    if (size > 0) {
+        value = process_getWriteablePtr(sys->process, valuePtr, size);
+        if (!value) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+        }
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){.state = SYSCALL_DONE,
+                           .retval.as_i64 = file_getxattr(pathname, name, value, size,
+                                                          followFinal, plugin_cwd, sys->host)};
+}
+
+static SysCallReturn _syscallhandler_listxattrHelper(SysCallHandler* sys, const SysCallArgs* args,
+                                                     bool followFinal) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr listPtr = args->args[1].as_ptr;     // char*
+    size_t size = args->args[2].as_u64;
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    /* a size of 0 asks for the size of the list */
+    char* list = NULL;
+    if (size > 0) {
+        list = process_getWriteablePtr(sys->process, listPtr, size);
+        if (!list) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+        }
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){
+        .state = SYSCALL_DONE,
+        .retval.as_i64 = file_listxattr(pathname, list, size, followFinal, plugin_cwd, sys->host)};
+}
+
+static SysCallReturn _syscallhandler_removexattrHelper(SysCallHandler* sys,
+                                                       const SysCallArgs* args, bool followFinal) {
+    if (!host_getFsOverlay(sys->host)) {
+        return (SysCallReturn){.state = SYSCALL_NATIVE};
+    }
+
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr namePtr = args->args[1].as_ptr;     // const char*
+
+    char pathname[PATH_MAX];
+    int errcode = _syscallhandler_readPathHelper(sys, pathnamePtr, pathname);
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    char name[XATTR_NAME_MAX + 1];
+    errcode = process_readString(sys->process, name, namePtr, sizeof(name));
+    if (errcode < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(sys->process);
+
+    return (SysCallReturn){
+        .state = SYSCALL_DONE,
+        .retval.as_i64 = file_removexattr(pathname, name, followFinal, plugin_cwd, sys->host)};
+}
+
+SysCallReturn syscallhandler_setxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_setxattrHelper(sys, args, true);
+}
+
+SysCallReturn syscallhandler_lsetxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_setxattrHelper(sys, args, false);
+}
+
+SysCallReturn syscallhandler_getxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_getxattrHelper(sys, args, true);
+}
+
+SysCallReturn syscallhandler_lgetxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_getxattrHelper(sys, args, false);
+}
+
+SysCallReturn syscallhandler_listxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_listxattrHelper(sys, args, true);
+}
+
+SysCallReturn syscallhandler_llistxattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_listxattrHelper(sys, args, false);
+}
+
+SysCallReturn syscallhandler_removexattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_removexattrHelper(sys, args, true);
+}
+
+SysCallReturn syscallhandler_lremovexattr(SysCallHandler* sys, const SysCallArgs* args) {
+    return _syscallhandler_removexattrHelper(sys, args, false);
+}
@@ -26,4 +26,35 @@ SYSCALL_HANDLER(symlinkat);
 SYSCALL_HANDLER(unlinkat);
 SYSCALL_HANDLER(utimensat);
 
+/* legacy path syscalls that are only handled when the host has a filesystem overlay */
+SYSCALL_HANDLER(access);
+SYSCALL_HANDLER(chmod);
+SYSCALL_HANDLER(chown);
+SYSCALL_HANDLER(lchown);
+SYSCALL_HANDLER(link);
+SYSCALL_HANDLER(lstat);
+SYSCALL_HANDLER(mkdir);
+SYSCALL_HANDLER(mknod);
+SYSCALL_HANDLER(readlink);
+SYSCALL_HANDLER(rename);
+SYSCALL_HANDLER(rmdir);
+SYSCALL_HANDLER(stat);
+SYSCALL_HANDLER(statfs);
+SYSCALL_HANDLER(symlink);
+SYSCALL_HANDLER(truncate);
+SYSCALL_HANDLER(unlink);
+SYSCALL_HANDLER(utime);
+SYSCALL_HANDLER(utimes);
+
+/* extended attributes of paths, which are also only handled when the host has
+ * a filesystem overlay */
+SYSCALL_HANDLER(getxattr);
+SYSCALL_HANDLER(lgetxattr);
+SYSCALL_HANDLER(listxattr);
+SYSCALL_HANDLER(llistxattr);
+SYSCALL_HANDLER(lremovexattr);
+SYSCALL_HANDLER(lsetxattr);
+SYSCALL_HANDLER(removexattr);
+SYSCALL_HANDLER(setxattr);
+
 #endif /* SRC_MAIN_HOST_SYSCALL_FILEAT_H_ */
@@ -9,10 +9,12 @@
 #include <string.h>
 #include <sys/mman.h>
 #include <sys/syscall.h>
+#include <unistd.h>
 
 #include "lib/logger/logger.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/file.h"
+#include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/protected.h"
 #include "main/host/thread.h"
@@ -23,6 +25,11 @@
 // Helpers
 ///////////////////////////////////////////////////////////
 
+static size_t _syscallhandler_pageAlign(size_t len) {
+    size_t pageSize = (size_t)sysconf(_SC_PAGESIZE);
+    return ((len + pageSize - 1) / pageSize) * pageSize;
+}
+
 static int _syscallhandler_validateMmapArgsHelper(SysCallHandler* sys, int fd,
                                                   size_t len, int prot,
                                                   int flags,
@@ -195,6 +202,18 @@ static SysCallReturn _syscallhandler_mmap(SysCallHandler* sys, PluginPtr addrPtr
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = errcode};
     }
 
+    /* Anonymous mappings count against the host's memory limit. A fixed mapping
+     * replaces any anonymous mappings that were already counted in its range. */
+    size_t alignedLen = _syscallhandler_pageAlign(len);
+    if (flags & MAP_ANONYMOUS) {
+        size_t replacedLen = (flags & MAP_FIXED)
+                                 ? process_getAnonMappingLen(sys->process, addrPtr.val, alignedLen)
+                                 : 0;
+        if (!host_reserveMemory(sys->host, sys->process, alignedLen - replacedLen)) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ENOMEM};
+        }
+    }
+
     /* Now file_desc is null for an anonymous mapping, non-null otherwise. */
     int pluginFD = -1;
 
@@ -227,6 +246,14 @@ static SysCallReturn _syscallhandler_mmap(SysCallHandler* sys, PluginPtr addrPtr
         _syscallhandler_closePluginFile(sys, pluginFD);
     }
 
+    if (result.state == SYSCALL_DONE && !syscall_rawReturnValueToErrno(result.retval.as_i64)) {
+        if (flags & MAP_ANONYMOUS) {
+            process_addAnonMapping(sys->process, result.retval.as_u64, alignedLen);
+        } else {
+            process_removeAnonMapping(sys->process, result.retval.as_u64, alignedLen);
+        }
+    }
+
     /* Done! Return their result back to them. */
     return result;
 }
@@ -238,9 +265,27 @@ static SysCallReturn _syscallhandler_mmap(SysCallHandler* sys, PluginPtr addrPtr
 SysCallReturn syscallhandler_brk(SysCallHandler* sys, const SysCallArgs* args) {
     PluginPtr newBrk = args->args[0].as_ptr;
 
+    /* Growing the heap counts against the host's memory limit. On failure, brk
+     * returns the current break. */
+    uint64_t currentBrk = process_getBreak(sys->process);
+    if (currentBrk != 0 && newBrk.val > currentBrk &&
+        !host_reserveMemory(sys->host, sys->process, newBrk.val - currentBrk)) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_u64 = currentBrk};
+    }
+
     // Delegate to the memoryManager.
     MemoryManager* mm = process_getMemoryManager(sys->process);
-    return memorymanager_handleBrk(mm, sys->thread, newBrk);
+    SysCallReturn result = memorymanager_handleBrk(mm, sys->thread, newBrk);
+    if (result.state == SYSCALL_NATIVE) {
+        // Do it ourselves so that we know the new break.
+        result = (SysCallReturn){
+            .state = SYSCALL_DONE, .retval = thread_nativeSyscall(sys->thread, SYS_brk, newBrk.val)};
+    }
+
+    if (result.state == SYSCALL_DONE) {
+        process_setBreak(sys->process, result.retval.as_u64);
+    }
+    return result;
 }
 
 SysCallReturn syscallhandler_mmap(SysCallHandler* sys, const SysCallArgs* args) {
@@ -273,10 +318,44 @@ SysCallReturn syscallhandler_mremap(SysCallHandler* sys, const SysCallArgs* args
     int flags = args->args[3].as_i64;
     PluginPtr new_addr = args->args[4].as_ptr;
 
+    size_t alignedOldSize = _syscallhandler_pageAlign(old_size);
+    size_t alignedNewSize = _syscallhandler_pageAlign(new_size);
+
+    /* Growing an anonymous mapping counts against the host's memory limit. */
+    bool isAnon = process_getAnonMappingLen(sys->process, old_addr.val, alignedOldSize) > 0;
+    if (isAnon && alignedNewSize > alignedOldSize &&
+        !host_reserveMemory(sys->host, sys->process, alignedNewSize - alignedOldSize)) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ENOMEM};
+    }
+
     // Delegate to the memoryManager.
     MemoryManager* mm = process_getMemoryManager(sys->process);
-    return memorymanager_handleMremap(
+    SysCallReturn result = memorymanager_handleMremap(
         mm, sys->thread, old_addr, old_size, new_size, flags, new_addr);
+    if (result.state == SYSCALL_NATIVE) {
+        // Do it ourselves so that we know where the mapping ended up.
+        result = (SysCallReturn){
+            .state = SYSCALL_DONE,
+            .retval = thread_nativeSyscall(
+                sys->thread, SYS_mremap, old_addr.val, old_size, new_size, flags, new_addr.val)};
+    }
+
+    if (result.state == SYSCALL_DONE && !syscall_rawReturnValueToErrno(result.retval.as_i64)) {
+#ifdef MREMAP_DONTUNMAP
+        bool keepsOld = (flags & MREMAP_DONTUNMAP);
+#else
+        bool keepsOld = false;
+#endif
+        if (!keepsOld) {
+            process_removeAnonMapping(sys->process, old_addr.val, alignedOldSize);
+        }
+        if (isAnon) {
+            process_addAnonMapping(sys->process, result.retval.as_u64, alignedNewSize);
+        } else {
+            process_removeAnonMapping(sys->process, result.retval.as_u64, alignedNewSize);
+        }
+    }
+    return result;
 }
 
 SysCallReturn syscallhandler_munmap(SysCallHandler* sys, const SysCallArgs* args) {
@@ -285,7 +364,19 @@ SysCallReturn syscallhandler_munmap(SysCallHandler* sys, const SysCallArgs* args
 
     // Delegate to the memoryManager.
     MemoryManager* mm = process_getMemoryManager(sys->process);
-    return memorymanager_handleMunmap(mm, sys->thread, addr, len);
+    SysCallReturn result = memorymanager_handleMunmap(mm, sys->thread, addr, len);
+    if (result.state == SYSCALL_NATIVE) {
+        // Do it ourselves so that we know whether the memory was released.
+        result = (SysCallReturn){
+            .state = SYSCALL_DONE,
+            .retval = thread_nativeSyscall(sys->thread, SYS_munmap, addr.val, len)};
+    }
+
+    /* Unmapped anonymous memory no longer counts against the host's memory limit. */
+    if (result.state == SYSCALL_DONE && !syscall_rawReturnValueToErrno(result.retval.as_i64)) {
+        process_removeAnonMapping(sys->process, addr.val, _syscallhandler_pageAlign(len));
+    }
+    return result;
 }
 
 SysCallReturn syscallhandler_mprotect(SysCallHandler* sys, const SysCallArgs* args) {
@@ -6,6 +6,7 @@
 #include "main/host/syscall/protected.h"
 
 #include <errno.h>
+#include <stdbool.h>
 #include <sys/time.h>
 #include <sys/timerfd.h>
 #include <sys/types.h>
@@ -13,9 +14,12 @@
 #include <unistd.h>
 
 #include "lib/logger/logger.h"
+#include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/tcp.h"
 #include "main/host/descriptor/timer.h"
+#include "main/host/disk.h"
+#include "main/host/syscall_condition.h"
 
 void _syscallhandler_setListenTimeout(SysCallHandler* sys, const struct timespec* timeout,
                                       TimeoutType type) {
@@ -90,3 +94,34 @@ int _syscallhandler_validateDescriptor(LegacyDescriptor* descriptor,
         return -EBADF;
     }
 }
+
+bool _syscallhandler_waitForDisk(SysCallHandler* sys, File* file, DiskOperation operation,
+                                 size_t numBytes, SysCallReturn* blockReturn) {
+    MAGIC_ASSERT(sys);
+
+    Disk* disk = host_getDisk(sys->host);
+    if (!disk || !file_isDiskBacked(file)) {
+        return false;
+    }
+
+    /* If we already blocked, the modeled operation has completed. */
+    if (_syscallhandler_wasBlocked(sys)) {
+        return false;
+    }
+
+    SimulationTime delay = disk_addOperation(disk, worker_getCurrentTime(), operation, numBytes);
+    if (delay == 0) {
+        return false;
+    }
+
+    struct timespec timeout = {
+        .tv_sec = delay / SIMTIME_ONE_SECOND,
+        .tv_nsec = delay % SIMTIME_ONE_SECOND,
+    };
+    _syscallhandler_setListenTimeout(sys, &timeout, TIMEOUT_RELATIVE);
+
+    /* Block the thread, unblock when the timer expires. */
+    *blockReturn = (SysCallReturn){
+        .state = SYSCALL_BLOCK, .cond = syscallcondition_new((Trigger){0}, sys->timer)};
+    return true;
+}
@@ -13,7 +13,10 @@
  * handlers.
  */
 
+#include <stdbool.h>
+
 #include "main/host/descriptor/epoll.h"
+#include "main/host/descriptor/file.h"
 #include "main/host/descriptor/timer.h"
 #include "main/host/host.h"
 #include "main/host/process.h"
@@ -66,6 +69,15 @@ struct _SysCallHandler {
     long numSyscalls;
     // A counter for individual syscalls
     Counter* syscall_counter;
+    /* The formatted syscall that is currently being handled, if the process
+     * writes an strace log. Kept until the syscall completes, since we can only
+     * read its arguments before it is first handled. A natively executed
+     * syscall is kept until it returns to the thread. */
+    gchar* straceCall;
+
+    /* Set when a signal interrupts the blocked syscall, which then returns
+     * EINTR instead of being handled again. */
+    bool interrupted;
 
     int referenceCount;
 
@@ -98,4 +110,12 @@ int _syscallhandler_wasBlocked(const SysCallHandler* sys);
 int _syscallhandler_validateDescriptor(LegacyDescriptor* descriptor,
                                        LegacyDescriptorType expectedType);
 
+/* If io on the file is charged to the host's disk, adds the operation to the
+ * disk model and blocks the calling thread until the modeled operation
+ * completes. Returns true if the syscall must block, in which case the handler
+ * should return `*blockReturn` without performing the operation. The handler
+ * then performs the operation when the syscall is restarted. */
+bool _syscallhandler_waitForDisk(SysCallHandler* sys, File* file, DiskOperation operation,
+                                 size_t numBytes, SysCallReturn* blockReturn);
+
 #endif /* SRC_MAIN_HOST_SYSCALL_PROTECTED_H_ */
@@ -7,10 +7,12 @@
 
 #include <errno.h>
 #include <stdbool.h>
+#include <stddef.h>
 #include <sys/syscall.h>
 
 #include "lib/logger/logger.h"
 #include "main/host/host.h"
+#include "main/host/process.h"
 #include "main/host/shimipc.h"
 #include "main/host/syscall/protected.h"
 #include "main/host/thread.h"
@@ -192,10 +194,35 @@ SysCallReturn syscallhandler_tkill(SysCallHandler* sys, const SysCallArgs* args)
     return _syscallhandler_killHelper(sys, 0, native_tid, sig, SYS_tkill);
 }
 
+/* Records the handler and flags of the new action, which determine whether the
+ * signal interrupts blocked syscalls when Shadow sends it to the process. */
+static void _rt_sigaction_record(SysCallHandler* sys, int signum, PluginPtr actPtr) {
+    if (!actPtr.val || signum == SIGKILL || signum == SIGSTOP) {
+        return;
+    }
+
+    /* the handler and flags come before the variable-size fields */
+    struct kernel_sigaction action = {0};
+    size_t sz = offsetof(struct kernel_sigaction, sa_restorer);
+    if (process_readPtr(sys->process, &action, actPtr, sz) < 0) {
+        return;
+    }
+
+    process_setSignalAction(sys->process, signum, (uint64_t)action.handler, action.sa_flags);
+}
+
 static SysCallReturn _rt_sigaction(SysCallHandler* sys, int signum, PluginPtr actPtr,
                                    PluginPtr oldActPtr, size_t masksize) {
     utility_assert(sys);
 
+    bool isShimHandled = false;
+    for (int i = 0; i < ARRAY_LENGTH(_shim_handled_signals); ++i) {
+        isShimHandled |= (signum == _shim_handled_signals[i]);
+    }
+    if (!shimipc_getUseSeccomp() || !isShimHandled) {
+        _rt_sigaction_record(sys, signum, actPtr);
+    }
+
     if (!shimipc_getUseSeccomp()) {
         // No special handling needed.
         return (SysCallReturn){.state = SYSCALL_NATIVE};
@@ -311,6 +311,55 @@ static int _syscallhandler_getTCPOptHelper(SysCallHandler* sys, TCP* tcp, int op
     }
 }
 
+static int _syscallhandler_getIPOptHelper(SysCallHandler* sys, UDP* udp, int optname, void* optval,
+                                          socklen_t* optlen) {
+    switch (optname) {
+        case IP_MTU_DISCOVER: {
+            int mode = udp_getMTUDiscover(udp);
+            int num_bytes = MIN(*optlen, sizeof(mode));
+            memcpy(optval, &mode, num_bytes);
+            *optlen = num_bytes;
+            return 0;
+        }
+        case IP_MTU: {
+            int mtu = udp_getPathMTU(udp, sys->host);
+            if (mtu < 0) {
+                return mtu;
+            }
+            int num_bytes = MIN(*optlen, sizeof(mtu));
+            memcpy(optval, &mtu, num_bytes);
+            *optlen = num_bytes;
+            return 0;
+        }
+        default: {
+            warning("getsockopt at level SOL_IP called with unsupported option %i", optname);
+            return -ENOPROTOOPT;
+        }
+    }
+}
+
+static int _syscallhandler_setIPOptHelper(SysCallHandler* sys, UDP* udp, int optname,
+                                          PluginPtr optvalPtr, socklen_t optlen) {
+    if (optlen < sizeof(int)) {
+        return -EINVAL;
+    }
+
+    switch (optname) {
+        case IP_MTU_DISCOVER: {
+            const int* val = process_getReadablePtr(sys->process, optvalPtr, sizeof(int));
+            if (*val < IP_PMTUDISC_DONT || *val > IP_PMTUDISC_OMIT) {
+                return -EINVAL;
+            }
+            udp_setMTUDiscover(udp, *val);
+            return 0;
+        }
+        default: {
+            warning("setsockopt on level SOL_IP called with unsupported option %i", optname);
+            return -ENOPROTOOPT;
+        }
+    }
+}
+
 static int _syscallhandler_getSocketOptHelper(SysCallHandler* sys, Socket* sock, int optname,
                                               void* optval, socklen_t* optlen) {
     switch (optname) {
@@ -1032,6 +1081,16 @@ SysCallReturn syscallhandler_getsockopt(SysCallHandler* sys,
                 _syscallhandler_getTCPOptHelper(sys, (TCP*)socket_desc, optname, optval, &optlen);
             break;
         }
+        case SOL_IP: {
+            if (descriptor_getType((LegacyDescriptor*)socket_desc) != DT_UDPSOCKET) {
+                errcode = -EOPNOTSUPP;
+                break;
+            }
+
+            errcode =
+                _syscallhandler_getIPOptHelper(sys, (UDP*)socket_desc, optname, optval, &optlen);
+            break;
+        }
         case SOL_SOCKET: {
             errcode =
                 _syscallhandler_getSocketOptHelper(sys, socket_desc, optname, optval, &optlen);
@@ -1144,6 +1203,16 @@ SysCallReturn syscallhandler_setsockopt(SysCallHandler* sys,
 
     errcode = 0;
     switch (level) {
+        case SOL_IP: {
+            if (descriptor_getType((LegacyDescriptor*)socket_desc) != DT_UDPSOCKET) {
+                errcode = -EOPNOTSUPP;
+                break;
+            }
+
+            errcode = _syscallhandler_setIPOptHelper(
+                sys, (UDP*)socket_desc, optname, optvalPtr, optlen);
+            break;
+        }
         case SOL_SOCKET: {
             errcode = _syscallhandler_setSocketOptHelper(
                 sys, socket_desc, optname, optvalPtr, optlen);
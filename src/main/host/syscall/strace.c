@@ -0,0 +1,412 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/host/syscall/strace.h"
+
+#include <errno.h>
+#include <fcntl.h>
+#include <limits.h>
+#include <string.h>
+#include <sys/syscall.h>
+
+#include "main/core/support/definitions.h"
+#include "main/core/worker.h"
+#include "main/host/syscall_numbers.h"
+#include "main/utility/syscall.h"
+#include "main/utility/utility.h"
+
+/* The longest string argument that we print before truncating it. */
+#define STRACE_MAX_STRING_LEN 64
+
+/* How the syscall's arguments are printed. Each character of `args` describes
+ * one argument:
+ *   'd': a signed int
+ *   'l': a signed long
+ *   'u': an unsigned long
+ *   'x': flags, in hex
+ *   'o': a file mode, in octal
+ *   'p': a pointer
+ *   's': a pointer to a NULL-terminated string
+ *   'a': a directory file descriptor for the *at() syscalls
+ * Syscalls that return an address have `returnsPointer` set. */
+typedef struct _StraceSyscall StraceSyscall;
+struct _StraceSyscall {
+    const char* name;
+    const char* args;
+    bool returnsPointer;
+};
+
+#define SYSCALL(s, a) [SYS_##s] = {.name = #s, .args = a}
+#define SYSCALL_PTR(s, a) [SYS_##s] = {.name = #s, .args = a, .returnsPointer = true}
+
+static const StraceSyscall _straceSyscalls[SYS_shadow_max + 1] = {
+    SYSCALL(accept, "dpp"),
+    SYSCALL(accept4, "dppx"),
+    SYSCALL(access, "so"),
+    SYSCALL(arch_prctl, "xp"),
+    SYSCALL(bind, "dpu"),
+    SYSCALL_PTR(brk, "p"),
+    SYSCALL(chmod, "so"),
+    SYSCALL(chown, "sdd"),
+    SYSCALL(clock_gettime, "dp"),
+    SYSCALL(clone, "xpppp"),
+    SYSCALL(close, "d"),
+    SYSCALL(connect, "dpu"),
+    SYSCALL(creat, "so"),
+    SYSCALL(dup, "d"),
+    SYSCALL(dup2, "dd"),
+    SYSCALL(dup3, "ddx"),
+    SYSCALL(epoll_create, "d"),
+    SYSCALL(epoll_create1, "x"),
+    SYSCALL(epoll_ctl, "dddp"),
+    SYSCALL(epoll_wait, "dpdd"),
+    SYSCALL(eventfd, "u"),
+    SYSCALL(eventfd2, "ux"),
+    SYSCALL(execve, "spp"),
+    SYSCALL(exit, "d"),
+    SYSCALL(exit_group, "d"),
+    SYSCALL(faccessat, "aso"),
+    SYSCALL(fadvise64, "dlld"),
+    SYSCALL(fallocate, "dxll"),
+    SYSCALL(fchmod, "do"),
+    SYSCALL(fchmodat, "aso"),
+    SYSCALL(fchown, "ddd"),
+    SYSCALL(fchownat, "asddx"),
+    SYSCALL(fcntl, "ddx"),
+    SYSCALL(fdatasync, "d"),
+    SYSCALL(fgetxattr, "dspu"),
+    SYSCALL(flistxattr, "dpu"),
+    SYSCALL(flock, "dx"),
+    SYSCALL(fremovexattr, "ds"),
+    SYSCALL(fsetxattr, "dspux"),
+    SYSCALL(fstat, "dp"),
+    SYSCALL(fstatfs, "dp"),
+    SYSCALL(fsync, "d"),
+    SYSCALL(ftruncate, "dl"),
+    SYSCALL(futex, "pddppd"),
+    SYSCALL(futimesat, "asp"),
+    SYSCALL(getcwd, "pu"),
+    SYSCALL(getdents, "dpu"),
+    SYSCALL(getdents64, "dpu"),
+    SYSCALL(getegid, ""),
+    SYSCALL(geteuid, ""),
+    SYSCALL(getgid, ""),
+    SYSCALL(getpeername, "dpp"),
+    SYSCALL(getpid, ""),
+    SYSCALL(getppid, ""),
+    SYSCALL(getrandom, "pux"),
+    SYSCALL(getrlimit, "dp"),
+    SYSCALL(get_robust_list, "dpp"),
+    SYSCALL(getsockname, "dpp"),
+    SYSCALL(getsockopt, "dddpp"),
+    SYSCALL(gettid, ""),
+    SYSCALL(gettimeofday, "pp"),
+    SYSCALL(getuid, ""),
+    SYSCALL(ioctl, "dxp"),
+    SYSCALL(kill, "dd"),
+    SYSCALL(lchown, "sdd"),
+    SYSCALL(link, "ss"),
+    SYSCALL(linkat, "asasx"),
+    SYSCALL(listen, "dd"),
+    SYSCALL(lseek, "dld"),
+    SYSCALL(lstat, "sp"),
+    SYSCALL(madvise, "pud"),
+    SYSCALL(mkdir, "so"),
+    SYSCALL(mkdirat, "aso"),
+    SYSCALL(mknod, "sou"),
+    SYSCALL(mknodat, "asou"),
+    SYSCALL_PTR(mmap, "puxxdl"),
+    SYSCALL(mprotect, "pux"),
+    SYSCALL_PTR(mremap, "puuxp"),
+    SYSCALL(munmap, "pu"),
+    SYSCALL(nanosleep, "pp"),
+    SYSCALL(newfstatat, "aspx"),
+    SYSCALL(open, "sxo"),
+    SYSCALL(openat, "asxo"),
+    SYSCALL(pipe, "p"),
+    SYSCALL(pipe2, "px"),
+    SYSCALL(poll, "pud"),
+    SYSCALL(ppoll, "puppu"),
+    SYSCALL(prctl, "dxxxx"),
+    SYSCALL(pread64, "dpul"),
+    SYSCALL(preadv, "dpdl"),
+#ifdef SYS_preadv2
+    SYSCALL(preadv2, "dpdlx"),
+#endif
+#ifdef SYS_prlimit64
+    SYSCALL(prlimit64, "ddpp"),
+#endif
+    SYSCALL(pwrite64, "dpul"),
+    SYSCALL(pwritev, "dpdl"),
+#ifdef SYS_pwritev2
+    SYSCALL(pwritev2, "dpdlx"),
+#endif
+    SYSCALL(read, "dpu"),
+    SYSCALL(readahead, "dlu"),
+    SYSCALL(readlink, "spu"),
+    SYSCALL(readlinkat, "aspu"),
+    SYSCALL(readv, "dpd"),
+    SYSCALL(recvfrom, "dpuxpp"),
+    SYSCALL(rename, "ss"),
+    SYSCALL(renameat, "asas"),
+    SYSCALL(renameat2, "asasx"),
+    SYSCALL(rmdir, "s"),
+    SYSCALL(rt_sigaction, "dppu"),
+    SYSCALL(rt_sigprocmask, "dppu"),
+    SYSCALL(rt_sigreturn, ""),
+    SYSCALL(sched_getaffinity, "dup"),
+    SYSCALL(sched_setaffinity, "dup"),
+    SYSCALL(sendto, "dpuxpu"),
+    SYSCALL(set_robust_list, "pu"),
+    SYSCALL(set_tid_address, "p"),
+    SYSCALL(setrlimit, "dp"),
+    SYSCALL(setsockopt, "dddpu"),
+    SYSCALL(shutdown, "dd"),
+    SYSCALL(sigaltstack, "pp"),
+    SYSCALL(socket, "ddd"),
+    SYSCALL(socketpair, "dddp"),
+    SYSCALL(stat, "sp"),
+    SYSCALL(statfs, "sp"),
+#ifdef SYS_statx
+    SYSCALL(statx, "asxxp"),
+#endif
+    SYSCALL(symlink, "ss"),
+    SYSCALL(symlinkat, "sas"),
+    SYSCALL(sync_file_range, "dllx"),
+    SYSCALL(syncfs, "d"),
+    SYSCALL(sysinfo, "p"),
+    SYSCALL(tgkill, "ddd"),
+    SYSCALL(time, "p"),
+    SYSCALL(timerfd_create, "dx"),
+    SYSCALL(timerfd_gettime, "dp"),
+    SYSCALL(timerfd_settime, "dxpp"),
+    SYSCALL(tkill, "dd"),
+    SYSCALL(truncate, "sl"),
+    SYSCALL(uname, "p"),
+    SYSCALL(unlink, "s"),
+    SYSCALL(unlinkat, "asx"),
+    SYSCALL(utimensat, "aspx"),
+    SYSCALL(write, "dpu"),
+    SYSCALL(writev, "dpd"),
+    SYSCALL(shadow_set_ptrace_allow_native_syscalls, "d"),
+    SYSCALL_PTR(shadow_get_ipc_blk, "p"),
+    SYSCALL_PTR(shadow_get_shm_blk, "p"),
+    SYSCALL(shadow_hostname_to_addr_ipv4, "sup"),
+};
+
+#undef SYSCALL
+#undef SYSCALL_PTR
+
+static const StraceSyscall* _strace_lookup(long number) {
+    if (number < 0 || (gulong)number >= G_N_ELEMENTS(_straceSyscalls) ||
+        _straceSyscalls[number].name == NULL) {
+        return NULL;
+    }
+    return &_straceSyscalls[number];
+}
+
+static void _strace_appendPointer(GString* str, SysCallReg reg) {
+    if (reg.as_u64 == 0) {
+        g_string_append(str, "NULL");
+    } else {
+        g_string_append_printf(str, "%p", (void*)reg.as_u64);
+    }
+}
+
+static void _strace_appendString(GString* str, Process* process, SysCallReg reg) {
+    if (reg.as_u64 == 0) {
+        g_string_append(str, "NULL");
+        return;
+    }
+
+    char buf[PATH_MAX] = {0};
+    ssize_t len = process_readString(process, buf, reg.as_ptr, sizeof(buf));
+    if (len < 0) {
+        /* not a readable string, so just show where it points */
+        _strace_appendPointer(str, reg);
+        return;
+    }
+
+    g_string_append_c(str, '"');
+    for (ssize_t i = 0; i < MIN(len, STRACE_MAX_STRING_LEN); i++) {
+        char c = buf[i];
+        if (c == '"' || c == '\\') {
+            g_string_append_printf(str, "\\%c", c);
+        } else if (c == '\n') {
+            g_string_append(str, "\\n");
+        } else if (g_ascii_isprint(c)) {
+            g_string_append_c(str, c);
+        } else {
+            g_string_append_printf(str, "\\x%02x", (unsigned char)c);
+        }
+    }
+    g_string_append_c(str, '"');
+    if (len > STRACE_MAX_STRING_LEN) {
+        g_string_append(str, "...");
+    }
+}
+
+static void _strace_appendArg(GString* str, Process* process, char format, SysCallReg reg) {
+    switch (format) {
+        case 'd': g_string_append_printf(str, "%d", (int)reg.as_i64); break;
+        case 'l': g_string_append_printf(str, "%" G_GINT64_FORMAT, reg.as_i64); break;
+        case 'u': g_string_append_printf(str, "%" G_GUINT64_FORMAT, reg.as_u64); break;
+        case 'x': g_string_append_printf(str, "%#" G_GINT64_MODIFIER "x", reg.as_u64); break;
+        case 'o': g_string_append_printf(str, "%#" G_GINT64_MODIFIER "o", reg.as_u64); break;
+        case 's': _strace_appendString(str, process, reg); break;
+        case 'a':
+            if ((int)reg.as_i64 == AT_FDCWD) {
+                g_string_append(str, "AT_FDCWD");
+            } else {
+                g_string_append_printf(str, "%d", (int)reg.as_i64);
+            }
+            break;
+        case 'p':
+        default: _strace_appendPointer(str, reg); break;
+    }
+}
+
+gchar* strace_formatSyscall(Process* process, const SysCallArgs* args) {
+    utility_assert(process && args);
+
+    GString* str = g_string_new(NULL);
+    const StraceSyscall* syscall = _strace_lookup(args->number);
+
+    if (syscall) {
+        g_string_append_printf(str, "%s(", syscall->name);
+        for (int i = 0; syscall->args[i] != '\0' && i < G_N_ELEMENTS(args->args); i++) {
+            if (i > 0) {
+                g_string_append(str, ", ");
+            }
+            _strace_appendArg(str, process, syscall->args[i], args->args[i]);
+        }
+    } else {
+        /* we don't know the arguments, so show all of the registers */
+        g_string_append_printf(str, "syscall_%ld(", args->number);
+        for (int i = 0; i < G_N_ELEMENTS(args->args); i++) {
+            g_string_append_printf(str, "%s%#" G_GINT64_MODIFIER "x", i > 0 ? ", " : "",
+                                   args->args[i].as_u64);
+        }
+    }
+
+    g_string_append_c(str, ')');
+    return g_string_free(str, FALSE);
+}
+
+/* Returns the name of the errno value, e.g., "ENOENT", or NULL if unknown. */
+static const char* _strace_errnoName(int err) {
+#define ERRNO_NAME(e)                                                                              \
+    case e: return #e
+    switch (err) {
+        ERRNO_NAME(EPERM);
+        ERRNO_NAME(ENOENT);
+        ERRNO_NAME(ESRCH);
+        ERRNO_NAME(EINTR);
+        ERRNO_NAME(EIO);
+        ERRNO_NAME(ENXIO);
+        ERRNO_NAME(E2BIG);
+        ERRNO_NAME(ENOEXEC);
+        ERRNO_NAME(EBADF);
+        ERRNO_NAME(ECHILD);
+        ERRNO_NAME(EAGAIN);
+        ERRNO_NAME(ENOMEM);
+        ERRNO_NAME(EACCES);
+        ERRNO_NAME(EFAULT);
+        ERRNO_NAME(EBUSY);
+        ERRNO_NAME(EEXIST);
+        ERRNO_NAME(EXDEV);
+        ERRNO_NAME(ENODEV);
+        ERRNO_NAME(ENOTDIR);
+        ERRNO_NAME(EISDIR);
+        ERRNO_NAME(EINVAL);
+        ERRNO_NAME(ENFILE);
+        ERRNO_NAME(EMFILE);
+        ERRNO_NAME(ENOTTY);
+        ERRNO_NAME(EFBIG);
+        ERRNO_NAME(ENOSPC);
+        ERRNO_NAME(ESPIPE);
+        ERRNO_NAME(EROFS);
+        ERRNO_NAME(EMLINK);
+        ERRNO_NAME(EPIPE);
+        ERRNO_NAME(ERANGE);
+        ERRNO_NAME(EDEADLK);
+        ERRNO_NAME(ENAMETOOLONG);
+        ERRNO_NAME(ENOSYS);
+        ERRNO_NAME(ENOTEMPTY);
+        ERRNO_NAME(ELOOP);
+        ERRNO_NAME(ENODATA);
+        ERRNO_NAME(ETIME);
+        ERRNO_NAME(EOVERFLOW);
+        ERRNO_NAME(ENOTSOCK);
+        ERRNO_NAME(EDESTADDRREQ);
+        ERRNO_NAME(EMSGSIZE);
+        ERRNO_NAME(EPROTOTYPE);
+        ERRNO_NAME(ENOPROTOOPT);
+        ERRNO_NAME(EPROTONOSUPPORT);
+        ERRNO_NAME(EOPNOTSUPP);
+        ERRNO_NAME(EAFNOSUPPORT);
+        ERRNO_NAME(EADDRINUSE);
+        ERRNO_NAME(EADDRNOTAVAIL);
+        ERRNO_NAME(ENETDOWN);
+        ERRNO_NAME(ENETUNREACH);
+        ERRNO_NAME(ECONNABORTED);
+        ERRNO_NAME(ECONNRESET);
+        ERRNO_NAME(ENOBUFS);
+        ERRNO_NAME(EISCONN);
+        ERRNO_NAME(ENOTCONN);
+        ERRNO_NAME(ETIMEDOUT);
+        ERRNO_NAME(ECONNREFUSED);
+        ERRNO_NAME(EHOSTUNREACH);
+        ERRNO_NAME(EALREADY);
+        ERRNO_NAME(EINPROGRESS);
+        default: return NULL;
+    }
+#undef ERRNO_NAME
+}
+
+void strace_logSyscall(FILE* file, pid_t tid, long number, const gchar* call,
+                       const SysCallReturn* scr, bool resumed) {
+    utility_assert(file && call && scr);
+
+    SimulationTime now = worker_getCurrentTime();
+    guint64 secs = now / SIMTIME_ONE_SECOND;
+    guint64 nanos = now % SIMTIME_ONE_SECOND;
+
+    GString* line = g_string_new(NULL);
+    g_string_printf(line, "%02" G_GUINT64_FORMAT ":%02" G_GUINT64_FORMAT ":%02" G_GUINT64_FORMAT
+                          ".%09" G_GUINT64_FORMAT " [%d] %s = ",
+                    secs / 3600, (secs / 60) % 60, secs % 60, nanos, tid, call);
+
+    if (scr->state == SYSCALL_BLOCK) {
+        g_string_append(line, "? <blocked>");
+    } else if (scr->state == SYSCALL_NATIVE) {
+        /* executed natively, but never returned to the thread */
+        g_string_append(line, "?");
+    } else {
+        int err = syscall_rawReturnValueToErrno(scr->retval.as_i64);
+        const StraceSyscall* syscall = _strace_lookup(number);
+
+        if (err != 0) {
+            const char* name = _strace_errnoName(err);
+            if (name) {
+                g_string_append_printf(line, "-1 %s (%s)", name, g_strerror(err));
+            } else {
+                g_string_append_printf(line, "-1 errno %d (%s)", err, g_strerror(err));
+            }
+        } else if (syscall && syscall->returnsPointer) {
+            _strace_appendPointer(line, scr->retval);
+        } else {
+            g_string_append_printf(line, "%" G_GINT64_FORMAT, scr->retval.as_i64);
+        }
+
+        if (resumed) {
+            g_string_append(line, " <resumed>");
+        }
+    }
+
+    g_string_append_c(line, '\n');
+    fwrite(line->str, 1, line->len, file);
+    g_string_free(line, TRUE);
+}
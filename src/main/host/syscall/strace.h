@@ -0,0 +1,31 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef MAIN_HOST_SYSCALL_STRACE_H
+#define MAIN_HOST_SYSCALL_STRACE_H
+
+#include <glib.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include <sys/types.h>
+
+#include "main/host/process.h"
+#include "main/host/syscall_types.h"
+
+/* Formats the syscall's name and arguments like strace does, e.g.,
+ * `openat(AT_FDCWD, "/etc/hosts", 0x80000, 0)`. String arguments are read
+ * from the process's memory, so this must be called before the syscall is
+ * handled. The returned string must be freed with g_free(). */
+gchar* strace_formatSyscall(Process* process, const SysCallArgs* args);
+
+/* Writes a line for the syscall `call` (as returned by strace_formatSyscall) to
+ * `file`, with the current simulation time, the thread id, and the result. The
+ * line is marked as `<blocked>` if the syscall blocked, and as `<resumed>` if it
+ * completed after having blocked. A SYSCALL_NATIVE result is for a syscall that
+ * was executed natively but never returned, and is written as `?`. */
+void strace_logSyscall(FILE* file, pid_t tid, long number, const gchar* call,
+                       const SysCallReturn* scr, bool resumed);
+
+#endif
@@ -6,14 +6,104 @@
 #include "main/host/syscall/process.h"
 
 #include <errno.h>
+#include <string.h>
 #include <sys/sysinfo.h>
 
 #include "lib/logger/logger.h"
 #include "main/core/worker.h"
+#include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/protected.h"
 #include "main/host/thread.h"
 
+/* The size in bytes of the kernel's cpu mask for a host with `cores` cores. Like
+ * the kernel, we use a whole number of longs. */
+static size_t _syscallhandler_getCpuMaskSize(guint cores) {
+    size_t bitsPerLong = 8 * sizeof(unsigned long);
+    return ((cores + bitsPerLong - 1) / bitsPerLong) * sizeof(unsigned long);
+}
+
+/* Like the kernel, pid 0 is the calling thread; any other pid must be the tid
+ * of one of the host's threads. */
+static gboolean _syscallhandler_isHostThread(SysCallHandler* sys, pid_t pid) {
+    return pid == 0 || (pid > 0 && host_getNativeTID(sys->host, 0, pid) != 0);
+}
+
+SysCallReturn syscallhandler_sched_getaffinity(SysCallHandler* sys, const SysCallArgs* args) {
+    utility_assert(sys && args);
+    pid_t pid = args->args[0].as_i64;
+    size_t cpusetsize = args->args[1].as_u64;
+    PluginPtr maskPtr = args->args[2].as_ptr; // cpu_set_t*
+
+    /* every thread may run on any of the host's virtual cores */
+    guint cores = host_getCPUCores(sys->host);
+
+    trace("sched_getaffinity called on pid %i with cpusetsize %zu, host has %u cores", pid,
+          cpusetsize, cores);
+
+    if (!_syscallhandler_isHostThread(sys, pid)) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ESRCH};
+    }
+
+    /* same checks as the kernel */
+    if (cpusetsize * 8 < cores || (cpusetsize & (sizeof(unsigned long) - 1)) != 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINVAL};
+    }
+    if (!maskPtr.val) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+    }
+
+    size_t retlen = MIN(cpusetsize, _syscallhandler_getCpuMaskSize(cores));
+    guint8* mask = process_getWriteablePtr(sys->process, maskPtr, retlen);
+    if (!mask) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+    }
+
+    memset(mask, 0, retlen);
+    for (guint i = 0; i < cores; i++) {
+        mask[i / 8] |= (guint8)(1 << (i % 8));
+    }
+
+    /* the raw syscall returns the number of bytes written to the mask */
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = retlen};
+}
+
+SysCallReturn syscallhandler_sched_setaffinity(SysCallHandler* sys, const SysCallArgs* args) {
+    utility_assert(sys && args);
+    pid_t pid = args->args[0].as_i64;
+    size_t cpusetsize = args->args[1].as_u64;
+    PluginPtr maskPtr = args->args[2].as_ptr; // const cpu_set_t*
+
+    guint cores = host_getCPUCores(sys->host);
+
+    trace("sched_setaffinity called on pid %i with cpusetsize %zu, host has %u cores", pid,
+          cpusetsize, cores);
+
+    if (!_syscallhandler_isHostThread(sys, pid)) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ESRCH};
+    }
+
+    if (!maskPtr.val) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+    }
+
+    size_t len = MIN(cpusetsize, _syscallhandler_getCpuMaskSize(cores));
+    const guint8* mask = process_getReadablePtr(sys->process, maskPtr, len);
+    if (!mask) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
+    }
+
+    /* we don't pin threads to cores, but the mask must contain at least one
+     * of the host's cores */
+    for (guint i = 0; i < cores && i / 8 < len; i++) {
+        if (mask[i / 8] & (1 << (i % 8))) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
+        }
+    }
+
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINVAL};
+}
+
 SysCallReturn syscallhandler_sysinfo(SysCallHandler* sys, const SysCallArgs* args) {
     utility_assert(sys && args);
     PluginPtr info_ptr = args->args[0].as_ptr; // struct sysinfo*
@@ -48,5 +138,18 @@ SysCallReturn syscallhandler_sysinfo(SysCallHandler* sys, const SysCallArgs* arg
     info->freehigh = 3;
     info->mem_unit = 1024 * 1024 * 1024; // GiB
 
+    // A host with a memory limit has exactly that much memory, as in /proc/meminfo.
+    guint64 limit = host_getMemoryLimit(sys->host);
+    if (limit > 0) {
+        guint64 usage = MIN(host_getMemoryUsage(sys->host), limit);
+        info->totalram = limit / 1024;
+        info->freeram = (limit - usage) / 1024;
+        info->sharedram = 0;
+        info->bufferram = 0;
+        info->totalhigh = 0;
+        info->freehigh = 0;
+        info->mem_unit = 1024; // KiB
+    }
+
     return (SysCallReturn){.state = SYSCALL_DONE};
 }
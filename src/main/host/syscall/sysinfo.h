@@ -8,6 +8,8 @@
 
 #include "main/host/syscall/protected.h"
 
+SYSCALL_HANDLER(sched_getaffinity);
+SYSCALL_HANDLER(sched_setaffinity);
 SYSCALL_HANDLER(sysinfo);
 
 #endif
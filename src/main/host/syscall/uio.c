@@ -82,6 +82,14 @@ static int _syscallhandler_validateVecParams(SysCallHandler* sys, int fd,
     return 0;
 }
 
+static size_t _syscallhandler_getIovLength(const struct iovec* iov, unsigned long iovlen) {
+    size_t total = 0;
+    for (unsigned long i = 0; i < iovlen; i++) {
+        total += iov[i].iov_len;
+    }
+    return total;
+}
+
 static SysCallReturn
 _syscallhandler_readvHelper(SysCallHandler* sys, int fd, PluginPtr iovPtr,
                             unsigned long iovlen, unsigned long pos_l,
@@ -108,6 +116,17 @@ _syscallhandler_readvHelper(SysCallHandler* sys, int fd, PluginPtr iovPtr,
 
     /* Now we can perform the write operations. */
     if (dType == DT_FILE) {
+        /* Charge the read to the host's disk before we touch the file. An
+         * offset of 0 means that we read from the current file position. */
+        SysCallReturn blockReturn;
+        if (_syscallhandler_waitForDisk(
+                sys, (File*)desc, DISK_OPERATION_READ,
+                MIN(_syscallhandler_getIovLength(iov, iovlen),
+                    file_getBytesUntilEOF((File*)desc, offset == 0 ? -1 : offset)),
+                &blockReturn)) {
+            return blockReturn;
+        }
+
         /* For files, we read all of the buffers from the plugin and then
          * let file pwritev handle it. */
         struct iovec* buffersv = malloc(iovlen * sizeof(*iov));
@@ -225,6 +244,14 @@ _syscallhandler_writevHelper(SysCallHandler* sys, int fd, PluginPtr iovPtr,
 
     /* Now we can perform the write operations. */
     if (dType == DT_FILE) {
+        /* Charge the write to the host's disk before we touch the file. */
+        SysCallReturn blockReturn;
+        if (_syscallhandler_waitForDisk(sys, (File*)desc, DISK_OPERATION_WRITE,
+                                        _syscallhandler_getIovLength(iov, iovlen),
+                                        &blockReturn)) {
+            return blockReturn;
+        }
+
         /* For files, we read all of the buffers from the plugin and then
          * let file pwritev handle it. */
         struct iovec* buffersv = malloc(iovlen * sizeof(*iov));
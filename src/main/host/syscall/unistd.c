@@ -115,6 +115,17 @@ static SysCallReturn _syscallhandler_readHelper(SysCallHandler* sys, int fd,
      * available in the descriptor. */
     size_t sizeNeeded = MIN(bufSize, SYSCALL_IO_BUFSIZE);
 
+    /* Charge the read to the host's disk before we touch the file. An offset
+     * of 0 means that we read from the current file position. */
+    SysCallReturn blockReturn;
+    if (dType == DT_FILE &&
+        _syscallhandler_waitForDisk(
+            sys, (File*)desc, DISK_OPERATION_READ,
+            MIN(sizeNeeded, file_getBytesUntilEOF((File*)desc, offset == 0 ? -1 : offset)),
+            &blockReturn)) {
+        return blockReturn;
+    }
+
     ssize_t result = 0;
     switch (dType) {
         case DT_FILE:
@@ -213,6 +224,13 @@ static SysCallReturn _syscallhandler_writeHelper(SysCallHandler* sys, int fd,
      * available in the descriptor. */
     size_t sizeNeeded = MIN(bufSize, SYSCALL_IO_BUFSIZE);
 
+    /* Charge the write to the host's disk before we touch the file. */
+    SysCallReturn blockReturn;
+    if (dType == DT_FILE && _syscallhandler_waitForDisk(sys, (File*)desc, DISK_OPERATION_WRITE,
+                                                        sizeNeeded, &blockReturn)) {
+        return blockReturn;
+    }
+
     ssize_t result = 0;
     switch (dType) {
         case DT_FILE:
@@ -38,6 +38,7 @@
 #include "main/host/syscall/shadow.h"
 #include "main/host/syscall/signal.h"
 #include "main/host/syscall/socket.h"
+#include "main/host/syscall/strace.h"
 #include "main/host/syscall/sysinfo.h"
 #include "main/host/syscall/time.h"
 #include "main/host/syscall/timerfd.h"
@@ -97,6 +98,19 @@ SysCallHandler* syscallhandler_new(Host* host, Process* process,
     return sys;
 }
 
+/* Writes a native syscall whose result we never got, because it didn't return
+ * to the thread (e.g., exit or rt_sigreturn), to the process's strace log. */
+static void _syscallhandler_flushNativeStrace(SysCallHandler* sys) {
+    FILE* straceFile = sys->process ? process_getStraceFile(sys->process) : NULL;
+    if (straceFile && sys->straceCall) {
+        SysCallReturn scr = {.state = SYSCALL_NATIVE};
+        strace_logSyscall(straceFile, thread_getID(sys->thread), -1, sys->straceCall, &scr, false);
+    }
+
+    g_free(sys->straceCall);
+    sys->straceCall = NULL;
+}
+
 static void _syscallhandler_free(SysCallHandler* sys) {
     MAGIC_ASSERT(sys);
 
@@ -106,6 +120,9 @@ static void _syscallhandler_free(SysCallHandler* sys) {
     info("handled %li syscalls", sys->numSyscalls);
 #endif
 
+    // Add up the counts at the process level, for the simulation statistics
+    process_addSyscallCounts(sys->process, sys->numSyscalls, sys->syscall_counter);
+
     if (_countSyscalls && sys->syscall_counter) {
         // Log the plugin thread specific counts
         char* str = counter_alloc_string(sys->syscall_counter);
@@ -120,6 +137,11 @@ static void _syscallhandler_free(SysCallHandler* sys) {
         counter_free(sys->syscall_counter);
     }
 
+    if (sys->straceCall) {
+        /* a native syscall that didn't return, such as exit */
+        _syscallhandler_flushNativeStrace(sys);
+    }
+
     if (sys->host) {
         host_unref(sys->host);
     }
@@ -212,6 +234,44 @@ static void _syscallhandler_post_syscall(SysCallHandler* sys, long number,
     }
 }
 
+/* Writes the syscall to the process's strace log. Must be called after the
+ * syscall is handled, but before the blocked syscall state is updated. */
+static void _syscallhandler_strace(SysCallHandler* sys, FILE* straceFile, const SysCallArgs* args,
+                                   const SysCallReturn* scr) {
+    bool wasBlocked = _syscallhandler_wasBlocked(sys);
+
+    /* a native syscall is logged once it returns, when we know its result */
+    if (scr->state == SYSCALL_NATIVE) {
+        return;
+    }
+
+    /* a blocked syscall is logged when it first blocks, and again when it completes */
+    if (!wasBlocked || scr->state != SYSCALL_BLOCK) {
+        strace_logSyscall(straceFile, thread_getID(sys->thread), args->number, sys->straceCall,
+                          scr, wasBlocked);
+    }
+
+    if (scr->state != SYSCALL_BLOCK) {
+        g_free(sys->straceCall);
+        sys->straceCall = NULL;
+    }
+}
+
+void syscallhandler_logNativeSyscallResult(SysCallHandler* sys, const SysCallArgs* args,
+                                           long retval) {
+    MAGIC_ASSERT(sys);
+
+    FILE* straceFile = process_getStraceFile(sys->process);
+    if (straceFile && sys->straceCall) {
+        SysCallReturn scr = {.state = SYSCALL_DONE, .retval.as_i64 = retval};
+        strace_logSyscall(straceFile, thread_getID(sys->thread), args->number, sys->straceCall,
+                          &scr, false);
+    }
+
+    g_free(sys->straceCall);
+    sys->straceCall = NULL;
+}
+
 ///////////////////////////////////////////////////////////
 // Single public API function for calling Shadow syscalls
 ///////////////////////////////////////////////////////////
@@ -267,11 +327,30 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
                       sys->blockedSyscallNR, args->number);
     }
 
+    /* Arguments are read from plugin memory, so format them before the handler
+     * can change that memory. A resumed syscall reuses the earlier string. */
+    FILE* straceFile = process_getStraceFile(sys->process);
+    if (straceFile && !_syscallhandler_wasBlocked(sys)) {
+        _syscallhandler_flushNativeStrace(sys);
+        sys->straceCall = strace_formatSyscall(sys->process, args);
+    }
+
+    if (sys->interrupted) {
+        /* A signal was sent to the process while the syscall was blocked. */
+        sys->interrupted = false;
+        trace("blocked syscall %ld was interrupted", args->number);
+        scr = (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINTR};
+        goto done;
+    }
+
     switch (args->number) {
         HANDLE(accept);
         HANDLE(accept4);
+        HANDLE(access);
         HANDLE(bind);
         HANDLE(brk);
+        HANDLE(chmod);
+        HANDLE(chown);
         HANDLE(clock_gettime);
         HANDLE(clone);
         HANDLE_RUST(close);
@@ -320,12 +399,23 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         HANDLE(getsockname);
         HANDLE(getsockopt);
         HANDLE(gettimeofday);
+        HANDLE(getxattr);
         HANDLE(ioctl);
         HANDLE(kill);
+        HANDLE(lchown);
+        HANDLE(lgetxattr);
+        HANDLE(link);
         HANDLE(linkat);
         HANDLE(listen);
+        HANDLE(listxattr);
+        HANDLE(llistxattr);
+        HANDLE(lremovexattr);
         HANDLE(lseek);
+        HANDLE(lsetxattr);
+        HANDLE(lstat);
+        HANDLE(mkdir);
         HANDLE(mkdirat);
+        HANDLE(mknod);
         HANDLE(mknodat);
         HANDLE(mmap);
 #ifdef SYS_mmap2
@@ -361,17 +451,24 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
 #endif
         HANDLE_RUST(read);
         HANDLE(readahead);
+        HANDLE(readlink);
         HANDLE(readlinkat);
         HANDLE(readv);
         HANDLE(recvfrom);
+        HANDLE(removexattr);
+        HANDLE(rename);
         HANDLE(renameat);
         HANDLE(renameat2);
+        HANDLE(rmdir);
+        HANDLE(sched_getaffinity);
+        HANDLE(sched_setaffinity);
         HANDLE(shadow_set_ptrace_allow_native_syscalls);
         HANDLE(shadow_get_ipc_blk);
         HANDLE(shadow_get_shm_blk);
         HANDLE(shadow_hostname_to_addr_ipv4);
         HANDLE(sendto);
         HANDLE(setsockopt);
+        HANDLE(setxattr);
 #ifdef SYS_sigaction
         // Superseded by rt_sigaction in Linux 2.2
         UNSUPPORTED(sigaction);
@@ -391,9 +488,12 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         HANDLE(shutdown);
         HANDLE(socket);
         HANDLE(socketpair);
+        HANDLE(stat);
+        HANDLE(statfs);
 #ifdef SYS_statx
         HANDLE(statx);
 #endif
+        HANDLE(symlink);
         HANDLE(symlinkat);
         HANDLE(sync_file_range);
         HANDLE(syncfs);
@@ -404,9 +504,13 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         HANDLE(timerfd_gettime);
         HANDLE(timerfd_settime);
         HANDLE(tkill);
+        HANDLE(truncate);
         HANDLE(uname);
+        HANDLE(unlink);
         HANDLE(unlinkat);
+        HANDLE(utime);
         HANDLE(utimensat);
+        HANDLE(utimes);
         HANDLE_RUST(write);
         HANDLE(writev);
 
@@ -419,10 +523,6 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         // NATIVE(waitid);
         // NATIVE(msync);
 
-        //// operations on pids (shadow overrides pids)
-        // NATIVE(sched_getaffinity);
-        // NATIVE(sched_setaffinity);
-
         //// operations on file descriptors
         // NATIVE(dup2);
         // NATIVE(dup3);
@@ -446,10 +546,7 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         // We think we don't need to handle these
         // (because the plugin can natively):
         // ***************************************
-        NATIVE(access);
         NATIVE(arch_prctl);
-        NATIVE(chmod);
-        NATIVE(chown);
         NATIVE(exit);
         NATIVE(getcwd);
         NATIVE(geteuid);
@@ -459,22 +556,7 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         NATIVE(getresuid);
         NATIVE(getrlimit);
         NATIVE(getuid);
-        NATIVE(getxattr);
-        NATIVE(lchown);
-        NATIVE(lgetxattr);
-        NATIVE(link);
-        NATIVE(listxattr);
-        NATIVE(llistxattr);
-        NATIVE(lremovexattr);
-        NATIVE(lsetxattr);
-        NATIVE(lstat);
         NATIVE(madvise);
-        NATIVE(mkdir);
-        NATIVE(mknod);
-        NATIVE(readlink);
-        NATIVE(removexattr);
-        NATIVE(rename);
-        NATIVE(rmdir);
         NATIVE(rt_sigreturn);
         NATIVE(setfsgid);
         NATIVE(setfsuid);
@@ -485,18 +567,10 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         NATIVE(setreuid);
         NATIVE(setrlimit);
         NATIVE(setuid);
-        NATIVE(setxattr);
-        NATIVE(stat);
 #ifdef SYS_stat64
         NATIVE(stat64);
 #endif
-        NATIVE(statfs);
         NATIVE(sigaltstack);
-        NATIVE(symlink);
-        NATIVE(truncate);
-        NATIVE(unlink);
-        NATIVE(utime);
-        NATIVE(utimes);
 
         default:
             warning(
@@ -510,6 +584,11 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             break;
     }
 
+done:
+    if (straceFile) {
+        _syscallhandler_strace(sys, straceFile, args, &scr);
+    }
+
     if (scr.state == SYSCALL_BLOCK) {
         /* We are blocking: store the syscall number so we know
          * to expect the same syscall again when it unblocks. */
@@ -531,3 +610,14 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
 }
 #undef NATIVE
 #undef HANDLE
+
+bool syscallhandler_interruptBlockedSyscall(SysCallHandler* sys) {
+    MAGIC_ASSERT(sys);
+
+    if (!_syscallhandler_wasBlocked(sys)) {
+        return false;
+    }
+
+    sys->interrupted = true;
+    return true;
+}
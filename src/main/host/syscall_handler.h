@@ -23,4 +23,16 @@ void syscallhandler_unref(SysCallHandler* sys);
 SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
                                           const SysCallArgs* args);
 
+/* Makes the syscall that the thread is blocked in return EINTR when the thread
+ * is next resumed, instead of handling the syscall again. Returns false if the
+ * thread isn't blocked in a syscall. */
+bool syscallhandler_interruptBlockedSyscall(SysCallHandler* sys);
+
+/* Writes the result of a syscall that was executed natively after the handler
+ * returned SYSCALL_NATIVE to the process's strace log, if it has one. Must be
+ * called after the native syscall returns, and before the thread's next
+ * syscall is handled. */
+void syscallhandler_logNativeSyscallResult(SysCallHandler* sys, const SysCallArgs* args,
+                                           long retval);
+
 #endif /* SRC_MAIN_HOST_SHD_SYSCALL_HANDLER_H_ */
@@ -198,6 +198,21 @@ void thread_setTidAddress(Thread* thread, PluginPtr addr) {
     thread->tidAddress = addr;
 }
 
+SimulationTime thread_getCPUAvailableTime(Thread* thread) {
+    MAGIC_ASSERT(thread);
+    return thread->cpuAvailableTime;
+}
+
+void thread_setCPUAvailableTime(Thread* thread, SimulationTime time) {
+    MAGIC_ASSERT(thread);
+    thread->cpuAvailableTime = time;
+}
+
+void thread_clearSysCallCondition(Thread* thread) {
+    MAGIC_ASSERT(thread);
+    _thread_cleanupSysCallCondition(thread);
+}
+
 bool thread_isLeader(Thread* thread) {
     MAGIC_ASSERT(thread);
     return thread->tid == process_getProcessID(thread->process);
@@ -63,6 +63,16 @@ void thread_setTidAddress(Thread* thread, PluginVirtualPtr addr);
 // Gets the `clear_child_tid` attribute, as set by `thread_setTidAddress`.
 PluginVirtualPtr thread_getTidAddress(Thread* thread);
 
+// Gets the simulated time at which the CPU work charged to the thread is done.
+SimulationTime thread_getCPUAvailableTime(Thread* thread);
+
+// Sets the time returned by `thread_getCPUAvailableTime`.
+void thread_setCPUAvailableTime(Thread* thread, SimulationTime time);
+
+// Stops waiting on the syscall condition that the thread is blocked on, if any.
+// The caller is responsible for resuming the thread later.
+void thread_clearSysCallCondition(Thread* thread);
+
 // Returns whether the given thread is its thread group (aka process) leader.
 // Typically this is true for the first thread created in a process.
 bool thread_isLeader(Thread* thread);
@@ -9,6 +9,12 @@ use std::convert::TryInto;
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct ThreadId(u32);
 
+impl From<ThreadId> for u32 {
+    fn from(val: ThreadId) -> Self {
+        val.0
+    }
+}
+
 pub trait Thread {
     /// Have the plugin thread natively execute the given syscall.
     fn native_syscall(&mut self, n: i64, args: &[SysCallReg]) -> nix::Result<SysCallReg>;
@@ -34,6 +34,9 @@ struct _ThreadPreload {
     /* holds the event id for the most recent call from the plugin/shim */
     ShimEvent currentEvent;
 
+    /* the syscall that the shim is making natively, whose result it sends us */
+    SysCallArgs nativeSyscallArgs;
+
     /* Typed pointer to ipc_blk.p */
     struct IPCData* ipc_data;
 
@@ -286,9 +289,14 @@ SysCallCondition* threadpreload_resume(Thread* base) {
 
                         }};
                 } else if (result.state == SYSCALL_NATIVE) {
-                    // Tell the shim to make the syscall itself
+                    // Tell the shim to make the syscall itself, and to send us
+                    // the result if we need it for the strace log
+                    thread->nativeSyscallArgs =
+                        thread->currentEvent.event_data.syscall.syscall_args;
                     shim_result = (ShimEvent){
                         .event_id = SHD_SHIM_EVENT_SYSCALL_DO_NATIVE,
+                        .event_data.syscall_do_native.report_result =
+                            process_getStraceFile(thread->base.process) != NULL,
                     };
                 }
                 shimevent_sendEventToPlugin(thread->ipc_data, &shim_result);
@@ -298,6 +306,14 @@ SysCallCondition* threadpreload_resume(Thread* base) {
                 shimevent_sendEventToPlugin(thread->ipc_data, &thread->currentEvent);
                 break;
             }
+            case SHD_SHIM_EVENT_SYSCALL_NATIVE_RESULT: {
+                // the result of the last syscall, which the shim made natively
+                syscallhandler_logNativeSyscallResult(
+                    thread->base.sys, &thread->nativeSyscallArgs,
+                    thread->currentEvent.event_data.syscall_complete.retval.as_i64);
+                shimevent_sendEventToPlugin(thread->ipc_data, &thread->currentEvent);
+                break;
+            }
             default: {
                 utility_panic("unknown event type");
                 break;
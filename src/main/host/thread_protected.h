@@ -57,6 +57,9 @@ struct _Thread {
     // not yet been pinned to a CPU.
     int affinity;
 
+    // The simulated time at which the CPU work charged to this thread is done.
+    SimulationTime cpuAvailableTime;
+
     MAGIC_DECLARE;
 };
 
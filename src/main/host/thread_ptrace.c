@@ -914,6 +914,9 @@ static SysCallCondition* _threadptrace_resumeIpcSyscall(ThreadPtrace* thread, bo
             long rv = thread_nativeSyscall(_threadPtraceToThread(thread), args->number,
                                            args->args[0], args->args[1], args->args[2],
                                            args->args[3], args->args[4], args->args[5]);
+            if (thread->childState == THREAD_PTRACE_CHILD_STATE_IPC_SYSCALL) {
+                syscallhandler_logNativeSyscallResult(thread->base.sys, args, rv);
+            }
             ShimEvent shim_result = {
                 .event_id = SHD_SHIM_EVENT_SYSCALL_COMPLETE,
                 .event_data = {
@@ -967,9 +970,9 @@ static SysCallCondition* _threadptrace_resumeSyscall(ThreadPtrace* thread, bool*
         case SYSCALL_NATIVE: {
             // Have the plugin execute the original syscall
             SysCallArgs* args = &thread->syscall_args;
-            thread_nativeSyscall(_threadPtraceToThread(thread), args->number, args->args[0],
-                                 args->args[1], args->args[2], args->args[3], args->args[4],
-                                 args->args[5]);
+            long rv = thread_nativeSyscall(_threadPtraceToThread(thread), args->number,
+                                           args->args[0], args->args[1], args->args[2],
+                                           args->args[3], args->args[4], args->args[5]);
             // The syscall should have left us in exactly the state from
             // which we want to resume execution. In particular we DON'T want
             // to restore the old instruction pointer after executing an execve syscall.
@@ -980,6 +983,8 @@ static SysCallCondition* _threadptrace_resumeSyscall(ThreadPtrace* thread, bool*
                 // Executing the syscall changed our state. We need to process it before
                 // waiting again.
                 *changedState = true;
+            } else {
+                syscallhandler_logNativeSyscallResult(thread->base.sys, args, rv);
             }
             break;
         }
@@ -15,10 +15,12 @@
 #include "main/core/support/definitions.h"
 #include "main/core/work/task.h"
 #include "main/core/worker.h"
+#include "main/host/host.h"
 #include "main/host/protocol.h"
 #include "main/host/tracker.h"
 #include "main/routing/address.h"
 #include "main/routing/packet.h"
+#include "main/utility/stats_writer.h"
 #include "main/utility/utility.h"
 
 typedef struct {
@@ -57,6 +59,12 @@ struct _Tracker {
     gboolean didLogRAMHeader;
     gboolean didLogSocketHeader;
 
+    /* machine-readable copies of the heartbeat messages, or NULL if disabled */
+    gchar* hostname;
+    StatsWriter* nodeWriter;
+    StatsWriter* socketWriter;
+    StatsWriter* ramWriter;
+
     SimulationTime processingTimeTotal;
     SimulationTime processingTimeLastInterval;
 
@@ -68,6 +76,11 @@ struct _Tracker {
     IFaceCounters local;
     IFaceCounters remote;
 
+    /* bytes exchanged with other hosts over the whole simulation, which are
+     * counted even if heartbeat statistics are disabled */
+    gsize remoteBytesReceivedTotal;
+    gsize remoteBytesSentTotal;
+
     GHashTable* allocatedLocations;
     gsize allocatedBytesTotal;
     gsize allocatedBytesLastInterval;
@@ -125,7 +138,8 @@ static void _socketstats_free(SocketStats* ss) {
     }
 }
 
-Tracker* tracker_new(Host* host, SimulationTime interval, LogLevel loglevel, LogInfoFlags loginfo) {
+Tracker* tracker_new(Host* host, SimulationTime interval, LogLevel loglevel, LogInfoFlags loginfo,
+                     HeartbeatFormat format) {
     Tracker* tracker = g_new0(Tracker, 1);
     MAGIC_INIT(tracker);
 
@@ -133,6 +147,18 @@ Tracker* tracker_new(Host* host, SimulationTime interval, LogLevel loglevel, Log
     tracker->loglevel = loglevel;
     tracker->loginfo = loginfo;
 
+    tracker->hostname = g_strdup(host_getName(host));
+    const gchar* dataPath = host_getDataPath(host);
+    if (loginfo & LOG_INFO_FLAGS_NODE) {
+        tracker->nodeWriter = statswriter_new(dataPath, "heartbeat-node", format);
+    }
+    if (loginfo & LOG_INFO_FLAGS_SOCKET) {
+        tracker->socketWriter = statswriter_new(dataPath, "heartbeat-socket", format);
+    }
+    if (loginfo & LOG_INFO_FLAGS_RAM) {
+        tracker->ramWriter = statswriter_new(dataPath, "heartbeat-ram", format);
+    }
+
     tracker->allocatedLocations = g_hash_table_new(g_direct_hash, g_direct_equal);
     tracker->socketStats = g_hash_table_new_full(g_int_hash, g_int_equal, NULL, (GDestroyNotify)_socketstats_free);
 
@@ -155,6 +181,11 @@ void tracker_free(Tracker* tracker) {
     g_hash_table_destroy(tracker->allocatedLocations);
     g_hash_table_destroy(tracker->socketStats);
 
+    statswriter_free(tracker->nodeWriter);
+    statswriter_free(tracker->socketWriter);
+    statswriter_free(tracker->ramWriter);
+    g_free(tracker->hostname);
+
     MAGIC_CLEAR(tracker);
     g_free(tracker);
 }
@@ -215,13 +246,18 @@ static void _tracker_updateCounters(Counters* c, gsize header, gsize payload,
 void tracker_addInputBytes(Tracker* tracker, Packet* packet, gint handle) {
     MAGIC_ASSERT(tracker);
 
+    gboolean isLocal = packet_getDestinationIP(packet) == htonl(INADDR_LOOPBACK);
+    gsize header = (gsize)packet_getHeaderSize(packet);
+    gsize payload = (gsize)packet_getPayloadLength(packet);
+
+    if (!isLocal) {
+        tracker->remoteBytesReceivedTotal += header + payload;
+    }
+
     if(!(tracker->loginfo & LOG_INFO_FLAGS_NODE) && !(tracker->loginfo & LOG_INFO_FLAGS_SOCKET)) {
         return;
     }
 
-    gboolean isLocal = packet_getDestinationIP(packet) == htonl(INADDR_LOOPBACK);
-    gsize header = (gsize)packet_getHeaderSize(packet);
-    gsize payload = (gsize)packet_getPayloadLength(packet);
     PacketDeliveryStatusFlags status = packet_getDeliveryStatus(packet);
 
     if(tracker->loginfo & LOG_INFO_FLAGS_NODE) {
@@ -247,13 +283,18 @@ void tracker_addInputBytes(Tracker* tracker, Packet* packet, gint handle) {
 void tracker_addOutputBytes(Tracker* tracker, Packet* packet, gint handle) {
     MAGIC_ASSERT(tracker);
 
+    gboolean isLocal = packet_getSourceIP(packet) == htonl(INADDR_LOOPBACK);
+    gsize header = (gsize)packet_getHeaderSize(packet);
+    gsize payload = (gsize)packet_getPayloadLength(packet);
+
+    if (!isLocal) {
+        tracker->remoteBytesSentTotal += header + payload;
+    }
+
     if(!(tracker->loginfo & LOG_INFO_FLAGS_NODE) && !(tracker->loginfo & LOG_INFO_FLAGS_SOCKET)) {
         return;
     }
 
-    gboolean isLocal = packet_getSourceIP(packet) == htonl(INADDR_LOOPBACK);
-    gsize header = (gsize)packet_getHeaderSize(packet);
-    gsize payload = (gsize)packet_getPayloadLength(packet);
     PacketDeliveryStatusFlags status = packet_getDeliveryStatus(packet);
 
     if(tracker->loginfo & LOG_INFO_FLAGS_NODE) {
@@ -276,6 +317,16 @@ void tracker_addOutputBytes(Tracker* tracker, Packet* packet, gint handle) {
     }
 }
 
+gsize tracker_getTotalBytesReceived(Tracker* tracker) {
+    MAGIC_ASSERT(tracker);
+    return tracker->remoteBytesReceivedTotal;
+}
+
+gsize tracker_getTotalBytesSent(Tracker* tracker) {
+    MAGIC_ASSERT(tracker);
+    return tracker->remoteBytesSentTotal;
+}
+
 void tracker_addAllocatedBytes(Tracker* tracker, gpointer location, gsize allocatedBytes) {
     MAGIC_ASSERT(tracker);
 
@@ -415,6 +466,46 @@ static gchar* _tracker_getCounterString(Counters* c) {
     return g_string_free(buffer, FALSE);
 }
 
+/* Adds the counters to the row, using the same names as the counter header
+ * string with the given prefix. */
+static void _tracker_addCounterStats(StatsWriter* writer, const gchar* prefix, Counters* c) {
+    utility_assert(c);
+
+    gsize totalPackets = c->packets.control + c->packets.controlRetransmit + c->packets.data +
+                         c->packets.dataRetransmit;
+
+    struct {
+        const gchar* name;
+        gsize value;
+    } stats[] = {
+        {"packets_total", totalPackets},
+        {"bytes_total", _tracker_sumBytes(&c->bytes)},
+        {"packets_control", c->packets.control},
+        {"bytes_control_header", c->bytes.controlHeader},
+        {"packets_control_retrans", c->packets.controlRetransmit},
+        {"bytes_control_header_retrans", c->bytes.controlHeaderRetransmit},
+        {"packets_data", c->packets.data},
+        {"bytes_data_header", c->bytes.dataHeader},
+        {"bytes_data_payload", c->bytes.dataPayload},
+        {"packets_data_retrans", c->packets.dataRetransmit},
+        {"bytes_data_header_retrans", c->bytes.dataHeaderRetransmit},
+        {"bytes_data_payload_retrans", c->bytes.dataPayloadRetransmit},
+    };
+
+    for (gsize i = 0; i < G_N_ELEMENTS(stats); i++) {
+        gchar* name = g_strdup_printf("%s_%s", prefix, stats[i].name);
+        statswriter_addUInt(writer, name, stats[i].value);
+        g_free(name);
+    }
+}
+
+/* Starts a row with the columns that every heartbeat file has. */
+static void _tracker_beginStatsRow(Tracker* tracker, StatsWriter* writer) {
+    statswriter_beginRow(writer);
+    statswriter_addUInt(writer, "time_ns", worker_getCurrentTime());
+    statswriter_addString(writer, "host", tracker->hostname);
+}
+
 static void _tracker_logNode(Tracker* tracker, LogLevel level, SimulationTime interval) {
     guint seconds = (guint) (interval / SIMTIME_ONE_SECOND);
     gdouble cpuutil = (gdouble)(((gdouble)tracker->processingTimeLastInterval) / ((gdouble)interval));
@@ -456,6 +547,22 @@ static void _tracker_logNode(Tracker* tracker, LogLevel level, SimulationTime in
     logger_log(logger_getDefault(), level, __FILE__, __FUNCTION__, __LINE__,
                "%s", buffer->str);
 
+    if (tracker->nodeWriter) {
+        StatsWriter* writer = tracker->nodeWriter;
+        _tracker_beginStatsRow(tracker, writer);
+        statswriter_addUInt(writer, "interval_ns", interval);
+        statswriter_addUInt(writer, "recv_bytes", totalRecvBytes);
+        statswriter_addUInt(writer, "send_bytes", totalSendBytes);
+        statswriter_addDouble(writer, "cpu_utilization", cpuutil);
+        statswriter_addUInt(writer, "delayed_count", tracker->numDelayedLastInterval);
+        statswriter_addDouble(writer, "avg_delay_ms", avgdelayms);
+        _tracker_addCounterStats(writer, "in_local", &tracker->local.inCounters);
+        _tracker_addCounterStats(writer, "out_local", &tracker->local.outCounters);
+        _tracker_addCounterStats(writer, "in_remote", &tracker->remote.inCounters);
+        _tracker_addCounterStats(writer, "out_remote", &tracker->remote.outCounters);
+        statswriter_endRow(writer);
+    }
+
     g_free(inLocal);
     g_free(outLocal);
     g_free(inRemote);
@@ -507,20 +614,43 @@ static void _tracker_logSocket(Tracker* tracker, LogLevel level, SimulationTime
             g_string_append_printf(msg, "|");
         }
 
+        const gchar* protocol = ss->type == PTCP     ? "TCP"
+                                : ss->type == PUDP   ? "UDP"
+                                : ss->type == PLOCAL ? "LOCAL"
+                                                     : "UNKNOWN";
+
         socketLogCount++;
         g_string_append_printf(msg, "%d,%s,%s:%u;"
                 "%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT";"
                 "%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT";"
                 "%s;%s;%s;%s",
                 ss->handle, /*inet_ntoa((struct in_addr){socket->peerIP})*/
-                ss->type == PTCP ? "TCP" : ss->type == PUDP ? "UDP" :
-                    ss->type == PLOCAL ? "LOCAL" : "UNKNOWN",
-                ss->peerHostname, ss->peerPort,
+                protocol, ss->peerHostname, ss->peerPort,
                 ss->inputBufferLength, ss->inputBufferSize,
                 ss->outputBufferLength, ss->outputBufferSize,
                 totalRecvBytes, totalSendBytes,
                 inLocal, outLocal, inRemote, outRemote);
 
+        if (tracker->socketWriter) {
+            StatsWriter* writer = tracker->socketWriter;
+            _tracker_beginStatsRow(tracker, writer);
+            statswriter_addInt(writer, "handle", ss->handle);
+            statswriter_addString(writer, "protocol", protocol);
+            statswriter_addString(writer, "peer_host", ss->peerHostname);
+            statswriter_addUInt(writer, "peer_port", ss->peerPort);
+            statswriter_addUInt(writer, "inbuf_len", ss->inputBufferLength);
+            statswriter_addUInt(writer, "inbuf_size", ss->inputBufferSize);
+            statswriter_addUInt(writer, "outbuf_len", ss->outputBufferLength);
+            statswriter_addUInt(writer, "outbuf_size", ss->outputBufferSize);
+            statswriter_addUInt(writer, "recv_bytes", totalRecvBytes);
+            statswriter_addUInt(writer, "send_bytes", totalSendBytes);
+            _tracker_addCounterStats(writer, "in_local", &ss->local.inCounters);
+            _tracker_addCounterStats(writer, "out_local", &ss->local.outCounters);
+            _tracker_addCounterStats(writer, "in_remote", &ss->remote.inCounters);
+            _tracker_addCounterStats(writer, "out_remote", &ss->remote.outCounters);
+            statswriter_endRow(writer);
+        }
+
         g_free(inLocal);
         g_free(outLocal);
         g_free(inRemote);
@@ -560,6 +690,18 @@ static void _tracker_logRAM(Tracker* tracker, LogLevel level, SimulationTime int
         "[shadow-heartbeat] [ram] %u,%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT",%"G_GSIZE_FORMAT",%u,%u",
         seconds, tracker->allocatedBytesLastInterval, tracker->deallocatedBytesLastInterval,
         tracker->allocatedBytesTotal, numptrs, tracker->numFailedFrees);
+
+    if (tracker->ramWriter) {
+        StatsWriter* writer = tracker->ramWriter;
+        _tracker_beginStatsRow(tracker, writer);
+        statswriter_addUInt(writer, "interval_ns", interval);
+        statswriter_addUInt(writer, "alloc_bytes", tracker->allocatedBytesLastInterval);
+        statswriter_addUInt(writer, "dealloc_bytes", tracker->deallocatedBytesLastInterval);
+        statswriter_addUInt(writer, "total_bytes", tracker->allocatedBytesTotal);
+        statswriter_addUInt(writer, "pointers_count", numptrs);
+        statswriter_addUInt(writer, "failfree_count", tracker->numFailedFrees);
+        statswriter_endRow(writer);
+    }
 }
 
 void tracker_heartbeat(Tracker* tracker, Host* host) {
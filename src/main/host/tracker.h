@@ -11,18 +11,26 @@
 #include <netinet/in.h>
 
 #include "lib/logger/log_level.h"
+#include "main/bindings/c/bindings-opaque.h"
 #include "main/core/support/definitions.h"
 #include "main/host/protocol.h"
 #include "main/host/tracker_types.h"
 #include "main/routing/packet.minimal.h"
 
-Tracker* tracker_new(Host* host, SimulationTime interval, LogLevel loglevel, LogInfoFlags loginfo);
+/* If `format` is not HEARTBEAT_FORMAT_NONE, the statistics of each heartbeat are
+ * also written to "heartbeat-{node,socket,ram}" files in the host's data
+ * directory, for whichever of the statistics `loginfo` enables. */
+Tracker* tracker_new(Host* host, SimulationTime interval, LogLevel loglevel, LogInfoFlags loginfo,
+                     HeartbeatFormat format);
 void tracker_free(Tracker* tracker);
 
 void tracker_addProcessingTime(Tracker* tracker, SimulationTime processingTime);
 void tracker_addVirtualProcessingDelay(Tracker* tracker, SimulationTime delay);
 void tracker_addInputBytes(Tracker* tracker, Packet* packet, gint handle);
 void tracker_addOutputBytes(Tracker* tracker, Packet* packet, gint handle);
+/* The number of bytes exchanged with other hosts since the tracker was created. */
+gsize tracker_getTotalBytesReceived(Tracker* tracker);
+gsize tracker_getTotalBytesSent(Tracker* tracker);
 void tracker_addAllocatedBytes(Tracker* tracker, gpointer location, gsize allocatedBytes);
 void tracker_removeAllocatedBytes(Tracker* tracker, gpointer location);
 void tracker_addSocket(Tracker* tracker, gint handle, ProtocolType type, gsize inputBufferSize, gsize outputBufferSize);
@@ -29,6 +29,9 @@ struct _DNS {
     GHashTable* addressByIP;
     GHashTable* addressByName;
 
+    /* IPs reserved for hosts that requested an exact address, which aren't given to other hosts */
+    GHashTable* reservedIPs;
+
     struct {
         int filenum;
         char* path;
@@ -106,7 +109,9 @@ static gboolean _dns_isRestricted(DNS* dns, in_addr_t netIP) {
 }
 
 static gboolean _dns_isIPUnique(DNS* dns, in_addr_t ip) {
-    gboolean exists = g_hash_table_lookup_extended(dns->addressByIP, GUINT_TO_POINTER(ip), NULL, NULL);
+    gboolean exists =
+        g_hash_table_lookup_extended(dns->addressByIP, GUINT_TO_POINTER(ip), NULL, NULL) ||
+        g_hash_table_contains(dns->reservedIPs, GUINT_TO_POINTER(ip));
     return exists ? FALSE : TRUE;
 }
 
@@ -122,6 +127,64 @@ static in_addr_t _dns_generateIP(DNS* dns) {
     return ip;
 }
 
+/* must be called while holding the lock */
+static Address* _dns_newAddress(DNS* dns, GQuark id, gchar* name, in_addr_t ip, gboolean isLocal) {
+    guint mac = ++dns->macAddressCounter;
+    Address* address = address_new(id, mac, (guint32) ip, name, isLocal);
+
+    /* store the ip/name mappings */
+    if(!isLocal) {
+        g_hash_table_replace(dns->addressByIP, GUINT_TO_POINTER(address_toNetworkIP(address)), address);
+        address_ref(address);
+        g_hash_table_replace(dns->addressByName, address_toHostName(address), address);
+        address_ref(address);
+    }
+
+    /* Any existing hosts file needs to be (lazily) updated. */
+    dns->hosts.isStale = true;
+
+    return address;
+}
+
+gboolean dns_reserveIP(DNS* dns, const gchar* ipString) {
+    MAGIC_ASSERT(dns);
+    utility_assert(ipString);
+
+    in_addr_t ip = address_stringToIP(ipString);
+
+    g_mutex_lock(&dns->lock);
+
+    gboolean isAvailable =
+        ip != INADDR_NONE && !_dns_isRestricted(dns, ip) && _dns_isIPUnique(dns, ip);
+    if(isAvailable) {
+        g_hash_table_add(dns->reservedIPs, GUINT_TO_POINTER(ip));
+    }
+
+    g_mutex_unlock(&dns->lock);
+
+    return isAvailable;
+}
+
+Address* dns_registerReserved(DNS* dns, GQuark id, gchar* name, const gchar* reservedIP) {
+    MAGIC_ASSERT(dns);
+    utility_assert(name);
+    utility_assert(reservedIP);
+
+    in_addr_t ip = address_stringToIP(reservedIP);
+
+    g_mutex_lock(&dns->lock);
+
+    /* the IP is no longer reserved once a host uses it */
+    gboolean wasReserved = g_hash_table_remove(dns->reservedIPs, GUINT_TO_POINTER(ip));
+    utility_assert(wasReserved);
+
+    Address* address = _dns_newAddress(dns, id, name, ip, FALSE);
+
+    g_mutex_unlock(&dns->lock);
+
+    return address;
+}
+
 Address* dns_register(DNS* dns, GQuark id, gchar* name, gchar* requestedIP) {
     MAGIC_ASSERT(dns);
     utility_assert(name);
@@ -129,7 +192,6 @@ Address* dns_register(DNS* dns, GQuark id, gchar* name, gchar* requestedIP) {
     g_mutex_lock(&dns->lock);
 
     in_addr_t ip = 0;
-    guint mac = ++dns->macAddressCounter;
     gboolean isLocal = FALSE;
 
     /* if requestedIP is NULL, we should generate one ourselves */
@@ -145,18 +207,7 @@ Address* dns_register(DNS* dns, GQuark id, gchar* name, gchar* requestedIP) {
         ip = _dns_generateIP(dns);
     }
 
-    Address* address = address_new(id, mac, (guint32) ip, name, isLocal);
-
-    /* store the ip/name mappings */
-    if(!isLocal) {
-        g_hash_table_replace(dns->addressByIP, GUINT_TO_POINTER(address_toNetworkIP(address)), address);
-        address_ref(address);
-        g_hash_table_replace(dns->addressByName, address_toHostName(address), address);
-        address_ref(address);
-    }
-
-    /* Any existing hosts file needs to be (lazily) updated. */
-    dns->hosts.isStale = true;
+    Address* address = _dns_newAddress(dns, id, name, ip, isLocal);
 
     g_mutex_unlock(&dns->lock);
 
@@ -299,6 +350,7 @@ DNS* dns_new() {
 
     dns->addressByIP = g_hash_table_new_full(g_direct_hash, g_direct_equal, NULL, (GDestroyNotify) address_unref);
     dns->addressByName = g_hash_table_new_full(g_str_hash, g_str_equal, NULL, (GDestroyNotify) address_unref);
+    dns->reservedIPs = g_hash_table_new(g_direct_hash, g_direct_equal);
 
     /* 11.0.0.0 -- 100.0.0.0 is the longest available unrestricted range */
     dns->ipAddressCounter = ntohl(address_stringToIP("11.0.0.0"));
@@ -313,6 +365,7 @@ void dns_free(DNS* dns) {
 
     g_hash_table_destroy(dns->addressByIP);
     g_hash_table_destroy(dns->addressByName);
+    g_hash_table_destroy(dns->reservedIPs);
 
     g_mutex_clear(&(dns->lock));
 
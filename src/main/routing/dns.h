@@ -18,6 +18,13 @@ DNS* dns_new();
 void dns_free(DNS* dns);
 
 Address* dns_register(DNS* dns, GQuark id, gchar* name, gchar* requestedIP);
+
+/* Reserves an IP address for a host that requires it exactly, so that it isn't given to any
+ * other host. Returns FALSE if the address is invalid, restricted, or already used or reserved.
+ * The host must then be registered with dns_registerReserved(). */
+gboolean dns_reserveIP(DNS* dns, const gchar* ip);
+Address* dns_registerReserved(DNS* dns, GQuark id, gchar* name, const gchar* reservedIP);
+
 void dns_deregister(DNS* dns, Address* address);
 
 Address* dns_resolveIPToAddress(DNS* dns, in_addr_t ip);
@@ -71,6 +71,12 @@ struct _Packet {
      */
     gdouble priority;
 
+    /* if the packet is an IP fragment of a larger UDP datagram, the part of the
+     * datagram's payload that it carries. the payload is shared with the datagram. */
+    gboolean isFragment;
+    guint fragmentOffset;
+    guint fragmentLength;
+
     PacketDeliveryStatusFlags allStatus;
     GQueue* orderedStatus;
 
@@ -134,6 +140,10 @@ Packet* packet_copy(Packet* packet) {
         copy->priority = packet->priority;
     }
 
+    copy->isFragment = packet->isFragment;
+    copy->fragmentOffset = packet->fragmentOffset;
+    copy->fragmentLength = packet->fragmentLength;
+
     copy->allStatus = packet->allStatus;
 
     if(packet->orderedStatus) {
@@ -180,6 +190,35 @@ Packet* packet_copy(Packet* packet) {
     return copy;
 }
 
+Packet* packet_newFragment(Packet* datagram, guint offset, guint length) {
+    MAGIC_ASSERT(datagram);
+    utility_assert(datagram->protocol == PUDP && !datagram->isFragment);
+    utility_assert(length > 0 && offset + length <= packet_getPayloadLength(datagram));
+    /* the IP fragment offset is in units of 8 bytes and counts the UDP header */
+    utility_assert(offset % 8 == 0);
+
+    Packet* fragment = packet_copy(datagram);
+
+    fragment->isFragment = TRUE;
+    fragment->fragmentOffset = offset;
+    fragment->fragmentLength = length;
+
+    return fragment;
+}
+
+Packet* packet_reassemble(Packet* fragment) {
+    MAGIC_ASSERT(fragment);
+    utility_assert(fragment->isFragment);
+
+    Packet* datagram = packet_copy(fragment);
+
+    datagram->isFragment = FALSE;
+    datagram->fragmentOffset = 0;
+    datagram->fragmentLength = 0;
+
+    return datagram;
+}
+
 static void _packet_free(Packet* packet) {
     MAGIC_ASSERT(packet);
 
@@ -324,7 +363,9 @@ void packet_updateTCP(Packet* packet, guint acknowledgement, GList* selectiveACK
 
 guint packet_getPayloadLength(const Packet* packet) {
     MAGIC_ASSERT(packet);
-    if(packet->payload) {
+    if (packet->isFragment) {
+        return packet->fragmentLength;
+    } else if(packet->payload) {
         return (guint)payload_getLength(packet->payload);
     } else {
         return 0;
@@ -340,9 +381,37 @@ guint packet_getHeaderSize(Packet* packet) {
     MAGIC_ASSERT(packet);
     guint size = packet->protocol == PUDP ? CONFIG_HEADER_SIZE_UDPIPETH :
             packet->protocol == PTCP ? CONFIG_HEADER_SIZE_TCPIPETH : 0;
+    /* only the first fragment of a datagram carries the UDP header */
+    if (packet->isFragment && packet->fragmentOffset > 0) {
+        size -= CONFIG_HEADER_SIZE_UDP;
+    }
     return size;
 }
 
+gboolean packet_isFragment(const Packet* packet) {
+    MAGIC_ASSERT(packet);
+    return packet->isFragment;
+}
+
+guint packet_getFragmentOffset(const Packet* packet) {
+    MAGIC_ASSERT(packet);
+    return packet->fragmentOffset;
+}
+
+guint packet_getDatagramLength(const Packet* packet) {
+    MAGIC_ASSERT(packet);
+    if(packet->payload) {
+        return (guint)payload_getLength(packet->payload);
+    } else {
+        return 0;
+    }
+}
+
+guint64 packet_getID(const Packet* packet) {
+    MAGIC_ASSERT(packet);
+    return packet->packetID;
+}
+
 in_addr_t packet_getDestinationIP(Packet* packet) {
     MAGIC_ASSERT(packet);
     in_addr_t ip = 0;
@@ -477,9 +546,26 @@ ProtocolType packet_getProtocol(Packet* packet) {
     return packet->protocol;
 }
 
+/* limits a range of the packet's payload to the part that a fragment carries, and
+ * converts the offset to an offset into the datagram's payload */
+static void _packet_toDatagramRange(const Packet* packet, gsize* payloadOffset,
+                                    gsize* bufferLength) {
+    if (!packet->isFragment) {
+        return;
+    }
+
+    if (*payloadOffset >= packet->fragmentLength) {
+        *bufferLength = 0;
+    } else {
+        *bufferLength = MIN(*bufferLength, packet->fragmentLength - *payloadOffset);
+    }
+    *payloadOffset += packet->fragmentOffset;
+}
+
 gssize packet_copyPayload(const Packet* packet, Thread* thread, gsize payloadOffset,
                           PluginVirtualPtr buffer, gsize bufferLength) {
     MAGIC_ASSERT(packet);
+    _packet_toDatagramRange(packet, &payloadOffset, &bufferLength);
 
     if(packet->payload) {
         return payload_getData(packet->payload, thread, payloadOffset, buffer, bufferLength);
@@ -491,6 +577,7 @@ gssize packet_copyPayload(const Packet* packet, Thread* thread, gsize payloadOff
 guint packet_copyPayloadShadow(Packet* packet, gsize payloadOffset, void* buffer,
                                gsize bufferLength) {
     MAGIC_ASSERT(packet);
+    _packet_toDatagramRange(packet, &payloadOffset, &bufferLength);
 
     if (packet->payload) {
         return payload_getDataShadow(packet->payload, payloadOffset, buffer, bufferLength);
@@ -576,6 +663,10 @@ gchar* packet_toString(Packet* packet) {
             g_string_append_printf(packetString, "%s:%u bytes=%u",
                     destinationIPString, ntohs( header->destinationPort),
                     payloadLength);
+            if (packet->isFragment) {
+                g_string_append_printf(packetString, " fragment=%u-%u", packet->fragmentOffset,
+                                       packet->fragmentOffset + packet->fragmentLength);
+            }
 
             g_free(sourceIPString);
             g_free(destinationIPString);
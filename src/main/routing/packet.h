@@ -38,6 +38,12 @@ Packet* packet_new(Host* host);
 void packet_setPayload(Packet* packet, Thread* thread, PluginVirtualPtr payload,
                        gsize payloadLength);
 Packet* packet_copy(Packet* packet);
+/* Returns a new IP fragment of the UDP `datagram` that carries `length` bytes of
+ * its payload starting at `offset`, which must be a multiple of 8. */
+Packet* packet_newFragment(Packet* datagram, guint offset, guint length);
+/* Returns a new copy of the datagram that `fragment` belongs to, for when all
+ * of its fragments have been received. */
+Packet* packet_reassemble(Packet* fragment);
 
 void packet_ref(Packet* packet);
 void packet_unref(Packet* packet);
@@ -61,6 +67,16 @@ guint packet_getPayloadLength(const Packet* packet);
 gdouble packet_getPriority(const Packet* packet);
 guint packet_getHeaderSize(Packet* packet);
 
+/* For fragments, the payload length and payload copies refer only to the part
+ * of the datagram's payload that the fragment carries. */
+gboolean packet_isFragment(const Packet* packet);
+guint packet_getFragmentOffset(const Packet* packet);
+/* The payload length of the whole datagram, including for fragments. */
+guint packet_getDatagramLength(const Packet* packet);
+/* The id of the packet, which is unique among the packets created by its host
+ * and shared by the fragments of a datagram. */
+guint64 packet_getID(const Packet* packet);
+
 in_addr_t packet_getDestinationIP(Packet* packet);
 in_port_t packet_getDestinationPort(Packet* packet);
 in_addr_t packet_getSourceIP(Packet* packet);
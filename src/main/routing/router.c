@@ -14,6 +14,7 @@
 #include "lib/logger/logger.h"
 #include "main/core/support/definitions.h"
 #include "main/core/worker.h"
+#include "main/host/host.h"
 #include "main/host/network_interface.h"
 #include "main/routing/packet.h"
 #include "main/routing/router.h"
@@ -112,6 +113,7 @@ void router_enqueue(Router* router, Host* host, Packet* packet) {
         packet_addDeliveryStatus(packet, PDS_ROUTER_ENQUEUED);
     } else {
         packet_addDeliveryStatus(packet, PDS_ROUTER_DROPPED);
+        host_countDroppedPacket(host, "router_queue");
     }
 
     /* notify the netiface that we have a new packet so it can dequeue it. */
@@ -120,10 +122,10 @@ void router_enqueue(Router* router, Host* host, Packet* packet) {
     }
 }
 
-Packet* router_dequeue(Router* router) {
+Packet* router_dequeue(Router* router, Host* host) {
     MAGIC_ASSERT(router);
 
-    Packet* packet = router->queueHooks->dequeue(router->queueManager);
+    Packet* packet = router->queueHooks->dequeue(router->queueManager, host);
     if(packet) {
         packet_addDeliveryStatus(packet, PDS_ROUTER_DEQUEUED);
     }
@@ -26,7 +26,7 @@ enum _QueueManagerMode {
 typedef void* (*QueueManagerNew)();
 typedef void (*QueueManagerFree)(void* queueManager);
 typedef gboolean (*QueueManagerEnqueue)(void* queueManager, Packet* packet);
-typedef Packet* (*QueueManagerDequeue)(void* queueManager);
+typedef Packet* (*QueueManagerDequeue)(void* queueManager, Host* host);
 typedef Packet* (*QueueManagerPeek)(void* queueManager);
 
 struct _QueueManagerHooks {
@@ -46,7 +46,8 @@ void router_forward(Router* router, Host* src, Packet* packet);
 
 /* enqueue a downstream packet, i.e., buffer it until the host can receive it */
 void router_enqueue(Router* router, Host* host, Packet* packet);
-/* dequeue a downstream packet, i.e., receive it from the network */
-Packet* router_dequeue(Router* router);
+/* dequeue a downstream packet, i.e., receive it from the network. packets that
+ * the queue drops are counted on the host. */
+Packet* router_dequeue(Router* router, Host* host);
 
 #endif /* SRC_MAIN_ROUTING_SHD_ROUTER_H_ */
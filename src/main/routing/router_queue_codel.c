@@ -26,6 +26,7 @@
 #include "lib/logger/logger.h"
 #include "main/core/support/definitions.h"
 #include "main/core/worker.h"
+#include "main/host/host.h"
 #include "main/routing/packet.h"
 #include "main/routing/router.h"
 #include "main/utility/utility.h"
@@ -135,8 +136,9 @@ static gboolean _routerqueuecodel_enqueue(QueueManagerCoDel* queueManager, Packe
     }
 }
 
-static void _routerqueuecodel_drop(Packet* packet) {
+static void _routerqueuecodel_drop(Host* host, Packet* packet) {
     packet_addDeliveryStatus(packet, PDS_ROUTER_DROPPED);
+    host_countDroppedPacket(host, "router_queue");
 #ifdef DEBUG
     gchar* pString = packet_toString(packet);
     trace("Router dropped packet %s", pString);
@@ -204,7 +206,7 @@ static SimulationTime _routerqueuecodel_controlLaw(guint count, SimulationTime t
     return (SimulationTime) rounded;
 }
 
-static Packet* _routerqueuecodel_dequeue(QueueManagerCoDel* queueManager) {
+static Packet* _routerqueuecodel_dequeue(QueueManagerCoDel* queueManager, Host* host) {
     utility_assert(queueManager);
 
     SimulationTime now = worker_getCurrentTime();
@@ -226,7 +228,7 @@ static Packet* _routerqueuecodel_dequeue(QueueManagerCoDel* queueManager) {
 
         while(now >= queueManager->nextDropTS && queueManager->mode == CODEL_MODE_DROP) {
             /* drop the packet */
-            _routerqueuecodel_drop(packet);
+            _routerqueuecodel_drop(host, packet);
             queueManager->dropCount++;
 
             /* get the next one */
@@ -241,7 +243,7 @@ static Packet* _routerqueuecodel_dequeue(QueueManagerCoDel* queueManager) {
         }
     } else if(okToDrop) {
         /* We are in storing mode, but we should now drop this packet. */
-        _routerqueuecodel_drop(packet);
+        _routerqueuecodel_drop(host, packet);
 
         /* get the next one */
         packet = _routerqueuecodel_dequeueHelper(queueManager, now, &okToDrop);
@@ -44,7 +44,7 @@ static gboolean _routerqueuesingle_enqueue(QueueManagerSingle* queueManager, Pac
     }
 }
 
-static Packet* _routerqueuesingle_dequeue(QueueManagerSingle* queueManager) {
+static Packet* _routerqueuesingle_dequeue(QueueManagerSingle* queueManager, Host* host) {
     utility_assert(queueManager);
     /* this call transfers the reference that we were holding to the caller */
     Packet* packet = queueManager->currentPacket;
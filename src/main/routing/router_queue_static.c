@@ -59,7 +59,7 @@ static gboolean _routerqueuestatic_enqueue(QueueManagerStatic* queueManager, Pac
     }
 }
 
-static Packet* _routerqueuestatic_dequeue(QueueManagerStatic* queueManager) {
+static Packet* _routerqueuestatic_dequeue(QueueManagerStatic* queueManager, Host* host) {
     utility_assert(queueManager);
 
     /* this call transfers the reference that we were holding to the caller */
@@ -124,6 +124,13 @@ struct _AttachHelper {
     gboolean foundExactIPMatch;
 };
 
+typedef struct _FindVertexHelper FindVertexHelper;
+struct _FindVertexHelper {
+    double id;
+    /* the index of the vertex with the id, or -1 if none was found */
+    igraph_integer_t vertexIndex;
+};
+
 typedef gboolean (*EdgeNotifyFunc)(Topology* top, igraph_integer_t edgeIndex, gpointer userData);
 typedef gboolean (*VertexNotifyFunc)(Topology* top, igraph_integer_t vertexIndex, gpointer userData);
 
@@ -2021,6 +2028,36 @@ gboolean topology_isRoutable(Topology* top, Address* srcAddress, Address* dstAdd
     return (topology_getLatency(top, srcAddress, dstAddress) > -1) ? TRUE : FALSE;
 }
 
+static gboolean _topology_findVertexByIdHelperHook(Topology* top, igraph_integer_t vertexIndex, FindVertexHelper* fh) {
+    MAGIC_ASSERT(top);
+    utility_assert(fh);
+
+    double id;
+    if(_topology_findVertexAttributeDouble(top, vertexIndex, VERTEX_ATTR_ID, &id) && id == fh->id) {
+        fh->vertexIndex = vertexIndex;
+    }
+
+    return TRUE;
+}
+
+/* returns the index of the vertex with the given id, or -1 if there is no such vertex */
+static igraph_integer_t _topology_findVertexById(Topology* top, guint64 nodeId) {
+    MAGIC_ASSERT(top);
+
+    FindVertexHelper fh = {.id = (double)nodeId, .vertexIndex = (igraph_integer_t)-1};
+
+    _topology_lockGraph(top);
+    _topology_iterateAllVertices(top, (VertexNotifyFunc) _topology_findVertexByIdHelperHook, &fh);
+    _topology_unlockGraph(top);
+
+    return fh.vertexIndex;
+}
+
+gboolean topology_hasNode(Topology* top, guint64 nodeId) {
+    MAGIC_ASSERT(top);
+    return _topology_findVertexById(top, nodeId) > (igraph_integer_t)-1;
+}
+
 static gboolean _topology_findAttachmentVertexHelperHook(Topology* top, igraph_integer_t vertexIndex, AttachHelper* ah) {
     MAGIC_ASSERT(top);
     utility_assert(ah);
@@ -2099,6 +2136,31 @@ static gboolean _topology_findAttachmentVertexHelperHook(Topology* top, igraph_i
     return TRUE;
 }
 
+gboolean topology_matchesHints(Topology* top, gchar* citycodeHint, gchar* countrycodeHint) {
+    MAGIC_ASSERT(top);
+
+    AttachHelper* ah = g_new0(AttachHelper, 1);
+    ah->citycodeHint = citycodeHint;
+    ah->countrycodeHint = countrycodeHint;
+    ah->candidatesCity = g_queue_new();
+    ah->candidatesCountry = g_queue_new();
+    ah->candidatesAll = g_queue_new();
+
+    _topology_lockGraph(top);
+    _topology_iterateAllVertices(top, (VertexNotifyFunc) _topology_findAttachmentVertexHelperHook, ah);
+    _topology_unlockGraph(top);
+
+    gboolean matches = (!citycodeHint || g_queue_get_length(ah->candidatesCity) > 0) &&
+                       (!countrycodeHint || g_queue_get_length(ah->candidatesCountry) > 0);
+
+    g_queue_free(ah->candidatesCity);
+    g_queue_free(ah->candidatesCountry);
+    g_queue_free(ah->candidatesAll);
+    g_free(ah);
+
+    return matches;
+}
+
 static igraph_integer_t* _topology_getLongestPrefixMatch(Topology* top, GQueue* vertexSet, in_addr_t ip) {
     MAGIC_ASSERT(top);
     utility_assert(vertexSet);
@@ -2216,14 +2278,24 @@ static igraph_integer_t _topology_findAttachmentVertex(Topology* top, Random* ra
 }
 
 void topology_attach(Topology* top, Address* address, Random* randomSourcePool,
-                     gchar* ipAddressHint, gchar* citycodeHint, gchar* countrycodeHint,
-                     guint64* bwDownOut, guint64* bwUpOut) {
+                     gint64 networkNodeId, gchar* ipAddressHint, gchar* citycodeHint,
+                     gchar* countrycodeHint, guint64* bwDownOut, guint64* bwUpOut) {
     MAGIC_ASSERT(top);
     utility_assert(address);
 
     in_addr_t nodeIP = address_toNetworkIP(address);
-    igraph_integer_t vertexIndex = _topology_findAttachmentVertex(top, randomSourcePool, nodeIP,
-            ipAddressHint, citycodeHint, countrycodeHint);
+    igraph_integer_t vertexIndex = (igraph_integer_t) -1;
+
+    /* the hints are only used if the host wasn't assigned to a specific node */
+    if(networkNodeId >= 0) {
+        vertexIndex = _topology_findVertexById(top, (guint64)networkNodeId);
+        if(vertexIndex < 0) {
+            utility_panic("network node id %" PRId64 " does not exist in the graph", networkNodeId);
+        }
+    } else {
+        vertexIndex = _topology_findAttachmentVertex(top, randomSourcePool, nodeIP, ipAddressHint,
+                                                     citycodeHint, countrycodeHint);
+    }
 
     /* attach it, i.e. store the mapping so we can route later */
     g_rw_lock_writer_lock(&(top->virtualIPLock));
@@ -2266,9 +2338,9 @@ void topology_attach(Topology* top, Address* address, Random* randomSourcePool,
 
     info("attached address '%s' to vertex %li ('%li') "
          "with attributes (ip=%s, citycode=%s, countrycode=%s) "
-         "using hints (ip=%s, citycode=%s, countrycode=%s)",
+         "using network node id %" PRId64 " and hints (ip=%s, citycode=%s, countrycode=%s)",
          address_toHostIPString(address), (glong)vertexIndex, (long)id, ipStr, citycodeStr,
-         countrycodeStr, ipAddressHint, citycodeHint, countrycodeHint);
+         countrycodeStr, networkNodeId, ipAddressHint, citycodeHint, countrycodeHint);
 }
 
 void topology_detach(Topology* top, Address* address) {
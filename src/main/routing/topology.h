@@ -17,11 +17,19 @@ typedef struct _Topology Topology;
 Topology* topology_new(const gchar* graphPath, gboolean useShortestPath);
 void topology_free(Topology* top);
 
-void topology_attach(Topology* top, Address* address, Random* randomSourcePool, gchar* ipHint,
-                     gchar* citycodeHint, gchar* countrycodeHint, guint64* bwDownOut,
-                     guint64* bwUpOut);
+/* Attaches the address to the node with the given id, or to a node chosen using the hints if
+ * networkNodeId is negative. */
+void topology_attach(Topology* top, Address* address, Random* randomSourcePool,
+                     gint64 networkNodeId, gchar* ipHint, gchar* citycodeHint,
+                     gchar* countrycodeHint, guint64* bwDownOut, guint64* bwUpOut);
 void topology_detach(Topology* top, Address* address);
 
+/* Returns TRUE if the graph has a node with the given id. */
+gboolean topology_hasNode(Topology* top, guint64 nodeId);
+/* Returns TRUE if some node matches the city code hint and some node matches the country code
+ * hint. Hints that are NULL always match. */
+gboolean topology_matchesHints(Topology* top, gchar* citycodeHint, gchar* countrycodeHint);
+
 gboolean topology_isRoutable(Topology* top, Address* srcAddress, Address* dstAddress);
 gdouble topology_getLatency(Topology* top, Address* srcAddress, Address* dstAddress);
 gdouble topology_getReliability(Topology* top, Address* srcAddress, Address* dstAddress);
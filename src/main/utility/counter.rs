@@ -13,11 +13,13 @@ Currently, only String types are supported, but we may eventually support counti
 generic types.
 */
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter, Result};
 use std::iter::FromIterator;
 use std::ops::{Add, Sub};
 
+use serde::ser::{Serialize, Serializer};
+
 /// The main counter object that maps individual keys to count values.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Counter {
@@ -149,6 +151,18 @@ impl Display for Counter {
     }
 }
 
+impl Serialize for Counter {
+    /// Serializes the counter as a map from keys to values, sorted by key so
+    /// that the output is consistent.
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let sorted: BTreeMap<&String, &i64> = self.items.iter().collect();
+        sorted.serialize(serializer)
+    }
+}
+
 impl Add for Counter {
     type Output = Self;
     /// Combines two counters by adding all values for all keys of `other` to `self`.
@@ -528,4 +542,18 @@ mod tests {
             String::from("{close:1, read:1, write:1}")
         );
     }
+
+    #[test]
+    fn test_serialize() {
+        let mut counter = Counter::new();
+        counter.set_value("write", 2);
+        counter.set_value("read", 10);
+        counter.set_value("close", 1);
+
+        assert_eq!(
+            serde_json::to_string(&counter).unwrap(),
+            r#"{"close":1,"read":10,"write":2}"#
+        );
+        assert_eq!(serde_json::to_string(&Counter::new()).unwrap(), "{}");
+    }
 }
@@ -4,6 +4,7 @@ pub mod counter;
 pub mod event_queue;
 pub mod interval_map;
 pub mod notnull;
+pub mod pcap_filter;
 pub mod perf_timer;
 pub mod pod;
 pub mod proc_maps;
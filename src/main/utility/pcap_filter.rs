@@ -0,0 +1,428 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+/*!
+A small subset of the tcpdump/BPF filter language, used to choose which packets a host
+writes to its packet capture. A filter is a boolean expression made of the primitives
+`tcp`, `udp`, `[src|dst] port <port>`, and `[src|dst] host <ipv4>`, combined with `and`
+(`&&`), `or` (`||`), `not` (`!`), and parentheses. As in tcpdump, a protocol can directly
+qualify a port (ex: `tcp dst port 80` is the same as `tcp and dst port 80`), and `and` binds
+more tightly than `or`.
+*/
+
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// Which end of the packet a primitive applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Src,
+    Dst,
+    Either,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    /// Matches packets with this IP protocol number.
+    Protocol(u8),
+    Port(Direction, u16),
+    Host(Direction, Ipv4Addr),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// The packet fields that a filter is evaluated against.
+#[derive(Debug, Clone, Copy)]
+pub struct PacketInfo {
+    /// The IP protocol number (ex: `libc::IPPROTO_TCP`).
+    pub protocol: u8,
+    pub src_ip: Ipv4Addr,
+    pub src_port: u16,
+    pub dst_ip: Ipv4Addr,
+    pub dst_port: u16,
+}
+
+impl Expr {
+    fn matches(&self, packet: &PacketInfo) -> bool {
+        match self {
+            Self::Protocol(p) => packet.protocol == *p,
+            Self::Port(dir, port) => match dir {
+                Direction::Src => packet.src_port == *port,
+                Direction::Dst => packet.dst_port == *port,
+                Direction::Either => packet.src_port == *port || packet.dst_port == *port,
+            },
+            Self::Host(dir, ip) => match dir {
+                Direction::Src => packet.src_ip == *ip,
+                Direction::Dst => packet.dst_ip == *ip,
+                Direction::Either => packet.src_ip == *ip || packet.dst_ip == *ip,
+            },
+            Self::Not(x) => !x.matches(packet),
+            Self::And(x, y) => x.matches(packet) && y.matches(packet),
+            Self::Or(x, y) => x.matches(packet) || y.matches(packet),
+        }
+    }
+}
+
+/// A parsed packet capture filter expression.
+#[derive(Debug, Clone)]
+pub struct PcapFilter {
+    expr: Expr,
+    /// The original expression, kept so that the filter can be written back out.
+    source: String,
+}
+
+impl PcapFilter {
+    /// Returns true if the packet should be captured.
+    pub fn matches(&self, packet: &PacketInfo) -> bool {
+        self.expr.matches(packet)
+    }
+}
+
+impl PartialEq for PcapFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.expr == other.expr
+    }
+}
+
+/// Splits the filter into words and the symbols `(`, `)`, `!`, `&&`, and `||`.
+fn tokenize(s: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' || c == '!' {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if c == '&' || c == '|' {
+            chars.next();
+            if chars.next() != Some(c) {
+                return Err(format!("Expected '{}{}' in filter", c, c));
+            }
+            tokens.push(format!("{}{}", c, c));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || "()!&|".contains(c) {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the filter's tokens.
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|x| x.as_str())
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.pos).map(|x| x.as_str());
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect_value(&mut self, keyword: &str) -> Result<String, String> {
+        match self.next() {
+            Some(x) => Ok(x.to_string()),
+            None => Err(format!("Expected a value after '{}'", keyword)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while matches!(self.peek(), Some("or") | Some("||")) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while matches!(self.peek(), Some("and") | Some("&&")) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some("not") | Some("!") => {
+                self.next();
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some("(") => {
+                self.next();
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(")") => Ok(expr),
+                    _ => Err("Expected ')' in filter".to_string()),
+                }
+            }
+            _ => self.parse_primitive(),
+        }
+    }
+
+    fn parse_primitive(&mut self) -> Result<Expr, String> {
+        let token = match self.next() {
+            Some(x) => x.to_string(),
+            None => return Err("Unexpected end of filter".to_string()),
+        };
+
+        let protocol = match token.as_str() {
+            "tcp" => Some(libc::IPPROTO_TCP as u8),
+            "udp" => Some(libc::IPPROTO_UDP as u8),
+            _ => None,
+        };
+
+        if let Some(protocol) = protocol {
+            let protocol = Expr::Protocol(protocol);
+            // a protocol may qualify a port, as in "tcp port 80"
+            return match self.peek() {
+                Some("src") | Some("dst") | Some("port") => {
+                    let port = self.parse_primitive()?;
+                    if !matches!(port, Expr::Port(..)) {
+                        return Err(format!("Expected a port after '{}'", token));
+                    }
+                    Ok(Expr::And(Box::new(protocol), Box::new(port)))
+                }
+                _ => Ok(protocol),
+            };
+        }
+
+        let (direction, keyword) = match token.as_str() {
+            "src" => (Direction::Src, self.expect_value("src")?),
+            "dst" => (Direction::Dst, self.expect_value("dst")?),
+            _ => (Direction::Either, token),
+        };
+
+        match keyword.as_str() {
+            "port" => {
+                let value = self.expect_value("port")?;
+                let port = value
+                    .parse::<u16>()
+                    .map_err(|_| format!("Invalid port '{}' in filter", value))?;
+                Ok(Expr::Port(direction, port))
+            }
+            "host" => {
+                let value = self.expect_value("host")?;
+                let ip = value
+                    .parse::<Ipv4Addr>()
+                    .map_err(|_| format!("Invalid IPv4 address '{}' in filter", value))?;
+                Ok(Expr::Host(direction, ip))
+            }
+            x => Err(format!(
+                "Unknown filter primitive '{}' (expected one of tcp|udp|port|host)",
+                x
+            )),
+        }
+    }
+}
+
+impl FromStr for PcapFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+
+        let expr = parser.parse_or()?;
+        if let Some(x) = parser.peek() {
+            return Err(format!("Unexpected '{}' in filter", x));
+        }
+
+        Ok(Self {
+            expr,
+            source: s.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for PcapFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for PcapFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for PcapFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl JsonSchema for PcapFilter {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "PcapFilter".to_owned()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            format: Some("PcapFilter".to_owned()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+mod export {
+    use super::*;
+
+    /// Returns true if the filter matches the packet. The addresses and ports must be in
+    /// network byte order.
+    #[no_mangle]
+    pub extern "C" fn pcapfilter_matches(
+        filter: *const PcapFilter,
+        protocol: u8,
+        src_ip: libc::in_addr_t,
+        src_port: libc::in_port_t,
+        dst_ip: libc::in_addr_t,
+        dst_port: libc::in_port_t,
+    ) -> bool {
+        assert!(!filter.is_null());
+        let filter = unsafe { &*filter };
+
+        filter.matches(&PacketInfo {
+            protocol,
+            src_ip: Ipv4Addr::from(u32::from_be(src_ip)),
+            src_port: u16::from_be(src_port),
+            dst_ip: Ipv4Addr::from(u32::from_be(dst_ip)),
+            dst_port: u16::from_be(dst_port),
+        })
+    }
+
+    #[no_mangle]
+    pub extern "C" fn pcapfilter_free(filter: *mut PcapFilter) {
+        if filter.is_null() {
+            return;
+        }
+        unsafe { Box::from_raw(filter) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(protocol: i32, src: &str, src_port: u16, dst: &str, dst_port: u16) -> PacketInfo {
+        PacketInfo {
+            protocol: protocol as u8,
+            src_ip: src.parse().unwrap(),
+            src_port,
+            dst_ip: dst.parse().unwrap(),
+            dst_port,
+        }
+    }
+
+    #[test]
+    fn test_primitives() {
+        let p = packet(libc::IPPROTO_TCP, "11.0.0.1", 5000, "11.0.0.2", 80);
+
+        let matches = |s: &str| s.parse::<PcapFilter>().unwrap().matches(&p);
+
+        assert!(matches("tcp"));
+        assert!(!matches("udp"));
+        assert!(matches("port 80"));
+        assert!(matches("port 5000"));
+        assert!(matches("dst port 80"));
+        assert!(!matches("src port 80"));
+        assert!(matches("host 11.0.0.1"));
+        assert!(matches("src host 11.0.0.1"));
+        assert!(!matches("dst host 11.0.0.1"));
+        assert!(!matches("host 11.0.0.3"));
+        assert!(matches("tcp dst port 80"));
+        assert!(!matches("udp port 80"));
+    }
+
+    #[test]
+    fn test_operators() {
+        let p = packet(libc::IPPROTO_UDP, "11.0.0.1", 53, "11.0.0.2", 4000);
+
+        let matches = |s: &str| s.parse::<PcapFilter>().unwrap().matches(&p);
+
+        assert!(matches("udp and port 53"));
+        assert!(matches("udp && port 53"));
+        assert!(!matches("tcp and port 53"));
+        assert!(matches("tcp or port 53"));
+        assert!(matches("not tcp"));
+        assert!(matches("!tcp"));
+        assert!(matches("!(tcp or port 80)"));
+        // "and" binds more tightly than "or"
+        assert!(matches("port 53 or tcp and port 80"));
+        assert!(!matches("(port 53 or tcp) and port 80"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for s in &[
+            "",
+            "tcp and",
+            "port",
+            "port http",
+            "port 70000",
+            "host foo",
+            "icmp",
+            "(tcp",
+            "tcp)",
+            "tcp & udp",
+            "tcp host 1.2.3.4",
+            "tcp udp",
+        ] {
+            assert!(s.parse::<PcapFilter>().is_err(), "'{}' should not parse", s);
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let filter: PcapFilter = serde_yaml::from_str("\"tcp port 80\"").unwrap();
+        assert_eq!(filter, "tcp and port 80".parse().unwrap());
+        assert_eq!(filter.to_string(), "tcp port 80");
+
+        let yaml = serde_yaml::to_string(&filter).unwrap();
+        assert_eq!(serde_yaml::from_str::<PcapFilter>(&yaml).unwrap(), filter);
+        assert!(serde_yaml::from_str::<PcapFilter>("\"tcp port\"").is_err());
+    }
+}
@@ -6,174 +6,392 @@
 #include "main/utility/pcap_writer.h"
 
 #include <stdio.h>
+#include <string.h>
 
 #include "lib/logger/logger.h"
 #include "main/core/support/definitions.h"
 #include "main/core/worker.h"
-#include "main/host/host.h"
+#include "main/utility/utility.h"
+#include "shd-config.h"
+
+/* block types and options from the pcapng specification */
+#define PCAPNG_BLOCK_SECTION_HEADER 0x0A0D0D0A
+#define PCAPNG_BLOCK_INTERFACE_DESCRIPTION 0x00000001
+#define PCAPNG_BLOCK_ENHANCED_PACKET 0x00000006
+#define PCAPNG_BYTE_ORDER_MAGIC 0x1A2B3C4D
+
+#define PCAPNG_OPT_ENDOFOPT 0
+#define PCAPNG_OPT_COMMENT 1
+#define PCAPNG_OPT_SHB_USERAPPL 4
+#define PCAPNG_OPT_IF_NAME 2
+#define PCAPNG_OPT_IF_DESCRIPTION 3
+#define PCAPNG_OPT_IF_TSRESOL 9
+
+#define PCAPNG_LINKTYPE_ETHERNET 1
+
+/* timestamps are in units of 10^-9 seconds, i.e., SimulationTime units */
+#define PCAPNG_TSRESOL_NANOSECONDS 9
 
 struct _PCapWriter {
-    FILE *pcapFile;
+    FILE* pcapFile;
+    gchar* path;
+
+    /* the capture size of each interface, indexed by interface id */
+    GArray* captureSizes;
+
+    /* the writer may be shared by hosts running on different workers */
+    GMutex lock;
+
+    /* packet blocks waiting for pcapwriter_flush(), or NULL if the writer
+     * isn't buffered */
+    GArray* bufferedPackets;
+
+    MAGIC_DECLARE;
 };
 
-static void _pcapwriter_writeHeader(PCapWriter* pcap) {
-    guint32 magic_number;   /* magic number */
-    guint16 version_major;  /* major version number */
-    guint16 version_minor;  /* minor version number */
-    gint32  thiszone;       /* GMT to local correction */
-    guint32 sigfigs;        /* accuracy of timestamps */
-    guint32 snaplen;        /* max length of captured packets, in octets */
-    guint32 network;        /* data link type */
-
-    magic_number = 0xA1B2C3D4;
-    version_major = 2;
-    version_minor = 4;
-    thiszone = 0;
-    sigfigs = 0;
-    snaplen = 65535;
-    network = 1;
-
-    fwrite(&magic_number, 1, sizeof(magic_number), pcap->pcapFile);
-    fwrite(&version_major, 1, sizeof(version_major), pcap->pcapFile);
-    fwrite(&version_minor, 1, sizeof(version_minor), pcap->pcapFile);
-    fwrite(&thiszone, 1, sizeof(thiszone), pcap->pcapFile);
-    fwrite(&sigfigs, 1, sizeof(sigfigs), pcap->pcapFile);
-    fwrite(&snaplen, 1, sizeof(snaplen), pcap->pcapFile);
-    fwrite(&network, 1, sizeof(network), pcap->pcapFile);
-}
-
-void pcapwriter_writePacket(PCapWriter* pcap, PCapPacket* packet) {
-    if(!pcap || !pcap->pcapFile || !packet) {
-        return;
+static void _pcapwriter_appendPadding(GByteArray* block) {
+    static const guint8 zeros[4] = {0};
+    guint padding = (4 - (block->len % 4)) % 4;
+    g_byte_array_append(block, zeros, padding);
+}
+
+static void _pcapwriter_appendU16(GByteArray* block, guint16 value) {
+    g_byte_array_append(block, (guint8*)&value, sizeof(value));
+}
+
+static void _pcapwriter_appendU32(GByteArray* block, guint32 value) {
+    g_byte_array_append(block, (guint8*)&value, sizeof(value));
+}
+
+static void _pcapwriter_appendOption(GByteArray* block, guint16 code, const void* value,
+                                     guint16 length) {
+    _pcapwriter_appendU16(block, code);
+    _pcapwriter_appendU16(block, length);
+    g_byte_array_append(block, value, length);
+    _pcapwriter_appendPadding(block);
+}
+
+static void _pcapwriter_appendStringOption(GByteArray* block, guint16 code, const gchar* value) {
+    /* option values are not NULL-terminated */
+    _pcapwriter_appendOption(block, code, value, (guint16)MIN(strlen(value), G_MAXUINT16));
+}
+
+static void _pcapwriter_appendEndOfOptions(GByteArray* block) {
+    _pcapwriter_appendU16(block, PCAPNG_OPT_ENDOFOPT);
+    _pcapwriter_appendU16(block, 0);
+}
+
+/* Every block starts with its type and total length, and ends with its total
+ * length again. Returns a new block with space for the header. */
+static GByteArray* _pcapwriter_startBlock(guint32 type) {
+    GByteArray* block = g_byte_array_new();
+    _pcapwriter_appendU32(block, type);
+    /* the total length is filled in by _pcapwriter_finishBlock */
+    _pcapwriter_appendU32(block, 0);
+    return block;
+}
+
+/* A packet block of a buffered writer, and the keys that it is ordered by. */
+typedef struct _PCapBufferedPacket PCapBufferedPacket;
+struct _PCapBufferedPacket {
+    SimulationTime time;
+    guint hostID;
+    guint64 packetID;
+    /* the order in which the packet was buffered, which is only compared for
+     * packets captured at the same time by the same host */
+    guint sequence;
+    GByteArray* block;
+};
+
+/* Adds the total length to both ends of the block. */
+static void _pcapwriter_completeBlock(GByteArray* block) {
+    guint32 totalLength = block->len + sizeof(guint32);
+    _pcapwriter_appendU32(block, totalLength);
+    memcpy(&block->data[4], &totalLength, sizeof(totalLength));
+}
+
+/* Writes the completed block, and frees it. Must hold the writer's lock. */
+static void _pcapwriter_writeBlock(PCapWriter* pcap, GByteArray* block) {
+    if (fwrite(block->data, 1, block->len, pcap->pcapFile) != block->len) {
+        warning("error writing to PCAP file '%s'", pcap->path);
     }
 
-    guint32 ts_sec;         /* timestamp seconds */
-    guint32 ts_usec;        /* timestamp microseconds */
-    guint32 incl_len;       /* number of octets of packet saved in file */
-    guint32 orig_len;       /* actual length of packet */
+    g_byte_array_unref(block);
+}
 
-    /* get the current time that the packet is being sent/received */
-    SimulationTime now = worker_getCurrentTime();
-    ts_sec = now / SIMTIME_ONE_SECOND;
-    ts_usec = (now % SIMTIME_ONE_SECOND) / SIMTIME_ONE_MICROSECOND;
-
-    /* get the header and payload lengths */
-    guint headerSize = packet->headerSize;
-    guint payloadLength = packet->payloadLength;
-    incl_len = headerSize + payloadLength;
-    orig_len = headerSize + payloadLength;
-
-    /* write the PCAP packet header to the pcap file */
-    fwrite(&ts_sec, sizeof(ts_sec), 1, pcap->pcapFile);
-    fwrite(&ts_usec, sizeof(ts_usec), 1, pcap->pcapFile);
-    fwrite(&incl_len, sizeof(incl_len), 1, pcap->pcapFile);
-    fwrite(&orig_len, sizeof(orig_len), 1, pcap->pcapFile);
-
-    /* write the ethernet header */
+/* Completes and writes the block, and frees it. Must hold the writer's lock. */
+static void _pcapwriter_finishBlock(PCapWriter* pcap, GByteArray* block) {
+    _pcapwriter_completeBlock(block);
+    _pcapwriter_writeBlock(pcap, block);
+}
+
+static void _pcapwriter_writeSectionHeader(PCapWriter* pcap, const gchar* const* comments) {
+    GByteArray* block = _pcapwriter_startBlock(PCAPNG_BLOCK_SECTION_HEADER);
+
+    _pcapwriter_appendU32(block, PCAPNG_BYTE_ORDER_MAGIC);
+    _pcapwriter_appendU16(block, 1); /* major version */
+    _pcapwriter_appendU16(block, 0); /* minor version */
+    /* the section length is unspecified */
+    gint64 sectionLength = -1;
+    g_byte_array_append(block, (guint8*)&sectionLength, sizeof(sectionLength));
+
+    for (int i = 0; comments != NULL && comments[i] != NULL; i++) {
+        _pcapwriter_appendStringOption(block, PCAPNG_OPT_COMMENT, comments[i]);
+    }
+    _pcapwriter_appendStringOption(block, PCAPNG_OPT_SHB_USERAPPL, SHADOW_VERSION_STRING);
+    _pcapwriter_appendEndOfOptions(block);
+
+    _pcapwriter_finishBlock(pcap, block);
+}
+
+guint32 pcapwriter_addInterface(PCapWriter* pcap, const gchar* name, const gchar* description,
+                                guint32 captureSize) {
+    MAGIC_ASSERT(pcap);
+
+    GByteArray* block = _pcapwriter_startBlock(PCAPNG_BLOCK_INTERFACE_DESCRIPTION);
+
+    _pcapwriter_appendU16(block, PCAPNG_LINKTYPE_ETHERNET);
+    _pcapwriter_appendU16(block, 0); /* reserved */
+    _pcapwriter_appendU32(block, captureSize); /* snaplen */
+
+    if (name) {
+        _pcapwriter_appendStringOption(block, PCAPNG_OPT_IF_NAME, name);
+    }
+    if (description) {
+        _pcapwriter_appendStringOption(block, PCAPNG_OPT_IF_DESCRIPTION, description);
+    }
+    guint8 tsresol = PCAPNG_TSRESOL_NANOSECONDS;
+    _pcapwriter_appendOption(block, PCAPNG_OPT_IF_TSRESOL, &tsresol, sizeof(tsresol));
+    _pcapwriter_appendEndOfOptions(block);
+
+    /* ids are assigned in the order that the blocks appear in the file */
+    g_mutex_lock(&pcap->lock);
+    guint32 interfaceID = pcap->captureSizes->len;
+    g_array_append_val(pcap->captureSizes, captureSize);
+    _pcapwriter_finishBlock(pcap, block);
+    g_mutex_unlock(&pcap->lock);
+
+    return interfaceID;
+}
+
+static void _pcapwriter_appendTCPHeader(GByteArray* block, PCapPacket* packet) {
+    guint8 headerLength = 0x80;
+    guint8 tcpFlags = 0;
+    if (packet->rstFlag)
+        tcpFlags |= 0x04;
+    if (packet->synFlag)
+        tcpFlags |= 0x02;
+    if (packet->ackFlag)
+        tcpFlags |= 0x10;
+    if (packet->finFlag)
+        tcpFlags |= 0x01;
+    guint8 options[14] = {0};
+    _pcapwriter_appendU16(block, packet->srcPort);
+    _pcapwriter_appendU16(block, packet->dstPort);
+    _pcapwriter_appendU32(block, htonl(packet->seq));
+    _pcapwriter_appendU32(block, packet->ackFlag ? htonl(packet->ack) : 0);
+    g_byte_array_append(block, &headerLength, 1);
+    g_byte_array_append(block, &tcpFlags, 1);
+    _pcapwriter_appendU16(block, htons(packet->win));
+    _pcapwriter_appendU16(block, 0x0000); /* checksum */
+    g_byte_array_append(block, options, sizeof(options));
+}
+
+static void _pcapwriter_appendUDPHeader(GByteArray* block, PCapPacket* packet) {
+    _pcapwriter_appendU16(block, packet->srcPort);
+    _pcapwriter_appendU16(block, packet->dstPort);
+    _pcapwriter_appendU16(block, htons(8 + packet->datagramLength)); /* length */
+    _pcapwriter_appendU16(block, 0x0000);                            /* checksum */
+}
+
+/* Appends the first `capturedLength` bytes of the packet. */
+static void _pcapwriter_appendPacketData(GByteArray* block, PCapPacket* packet,
+                                         guint32 capturedLength) {
+    guint32 length = packet->headerSize + packet->payloadLength;
+    guint start = block->len;
+
+    /* the ethernet header */
     guint8 destinationMAC[6] = {0x01, 0x23, 0x45, 0x67, 0x89, 0xAB};
     guint8 sourceMAC[6] = {0xA1, 0xB2, 0xC3, 0xD4, 0xE5, 0xF6};
-    guint16 type = htons(0x0800);
+    g_byte_array_append(block, destinationMAC, sizeof(destinationMAC));
+    g_byte_array_append(block, sourceMAC, sizeof(sourceMAC));
+    _pcapwriter_appendU16(block, htons(0x0800));
 
-    fwrite(destinationMAC, 1, sizeof(destinationMAC), pcap->pcapFile);
-    fwrite(sourceMAC, 1, sizeof(sourceMAC), pcap->pcapFile);
-    fwrite(&type, 1, sizeof(type), pcap->pcapFile);
-
-    /* write the IP header */
+    /* the IP header */
     guint8 versionAndHeaderLength = 0x45;
     guint8 fields = 0x00;
-    guint16 totalLength = htons(orig_len - 14);
-    guint16 identification = 0x0000;
-    guint16 flagsAndFragment = 0x0040;
     guint8 timeToLive = 64;
-    guint8 protocol = 6;  /* TCP */
-    guint16 headerChecksum = 0x0000;
-    guint32 sourceIP = packet->srcIP;
-    guint32 destinationIP = packet->dstIP;
-
-    fwrite(&versionAndHeaderLength, 1, sizeof(versionAndHeaderLength), pcap->pcapFile);
-    fwrite(&fields, 1, sizeof(fields), pcap->pcapFile);
-    fwrite(&totalLength, 1, sizeof(totalLength), pcap->pcapFile);
-    fwrite(&identification, 1, sizeof(identification), pcap->pcapFile);
-    fwrite(&flagsAndFragment, 1, sizeof(flagsAndFragment), pcap->pcapFile);
-    fwrite(&timeToLive, 1, sizeof(timeToLive), pcap->pcapFile);
-    fwrite(&protocol, 1, sizeof(protocol), pcap->pcapFile);
-    fwrite(&headerChecksum, 1, sizeof(headerChecksum), pcap->pcapFile);
-    fwrite(&sourceIP, 1, sizeof(sourceIP), pcap->pcapFile);
-    fwrite(&destinationIP, 1, sizeof(destinationIP), pcap->pcapFile);
-
-
-    /* write the TCP header */
-    guint16 sourcePort = packet->srcPort;
-    guint16 destinationPort = packet->dstPort;
-    guint32 sequence = htonl(packet->seq);
-    guint32 acknowledgement = 0;
-    if(packet->ackFlag) {
-        acknowledgement = htonl(packet->ack);
+    guint8 protocol = packet->protocol == PUDP ? IPPROTO_UDP : IPPROTO_TCP;
+    g_byte_array_append(block, &versionAndHeaderLength, 1);
+    g_byte_array_append(block, &fields, 1);
+    _pcapwriter_appendU16(block, htons(length - 14)); /* total length */
+    if (packet->isFragment) {
+        /* the offset is in units of 8 bytes, and counts the UDP header */
+        guint offset = packet->fragmentOffset == 0 ? 0 : packet->fragmentOffset + 8;
+        gboolean moreFragments =
+            packet->fragmentOffset + packet->payloadLength < packet->datagramLength;
+        guint16 flagsAndOffset = (moreFragments ? 0x2000 : 0) | (offset >> 3);
+        _pcapwriter_appendU16(block, htons(packet->ipID));   /* identification */
+        _pcapwriter_appendU16(block, htons(flagsAndOffset)); /* flags and fragment */
+    } else {
+        _pcapwriter_appendU16(block, 0x0000); /* identification */
+        _pcapwriter_appendU16(block, 0x0040); /* flags and fragment */
     }
-    guint8 headerLength = 0x80;
-    guint8 tcpFlags = 0;
-    if(packet->rstFlag) tcpFlags |= 0x04;
-    if(packet->synFlag) tcpFlags |= 0x02;
-    if(packet->ackFlag) tcpFlags |= 0x10;
-    if(packet->finFlag) tcpFlags |= 0x01;
-    guint16 window = htons(packet->win);
-    guint16 tcpChecksum = 0x0000;
-    guint8 options[14] = {0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00};
-
-    fwrite(&sourcePort, 1, sizeof(sourcePort), pcap->pcapFile);
-    fwrite(&destinationPort, 1, sizeof(destinationPort), pcap->pcapFile);
-    fwrite(&sequence, 1, sizeof(sequence), pcap->pcapFile);
-    fwrite(&acknowledgement, 1, sizeof(acknowledgement), pcap->pcapFile);
-    fwrite(&headerLength, 1, sizeof(headerLength), pcap->pcapFile);
-    fwrite(&tcpFlags, 1, sizeof(tcpFlags), pcap->pcapFile);
-    fwrite(&window, 1, sizeof(window), pcap->pcapFile);
-    fwrite(&tcpChecksum, 1, sizeof(tcpChecksum), pcap->pcapFile);
-    fwrite(options, 1, sizeof(options), pcap->pcapFile);
-
-    /* write payload data */
-    if(payloadLength > 0 && packet->payload) {
-        fwrite(packet->payload, 1, payloadLength, pcap->pcapFile);
-    }
-}
-
-PCapWriter* pcapwriter_new(Host* host, gchar* pcapDirectory, gchar* pcapFilename) {
-    PCapWriter* pcap = g_new0(PCapWriter, 1);
+    g_byte_array_append(block, &timeToLive, 1);
+    g_byte_array_append(block, &protocol, 1);
+    _pcapwriter_appendU16(block, 0x0000); /* header checksum */
+    _pcapwriter_appendU32(block, packet->srcIP);
+    _pcapwriter_appendU32(block, packet->dstIP);
 
-    /* open the PCAP file for writing */
-    GString *filename = g_string_new("");
-    if (pcapDirectory) {
-        g_string_append(filename, pcapDirectory);
-        /* Append trailing slash if not present */
-        if (!g_str_has_suffix(pcapDirectory, "/")) {
-            g_string_append(filename, "/");
+    /* the transport header, which only the first fragment of a datagram has */
+    if (packet->protocol == PUDP) {
+        if (!packet->isFragment || packet->fragmentOffset == 0) {
+            _pcapwriter_appendUDPHeader(block, packet);
         }
     } else {
-        /* Use default directory */
-        g_string_append(filename, "data/pcapdata/");
+        _pcapwriter_appendTCPHeader(block, packet);
     }
 
-    if(pcapFilename) {
-        g_string_append_printf(filename, "%s", pcapFilename);
-    } else {
-        g_string_append_printf(filename, "%s", host_getName(host));
+    /* the payload data that fits in the capture */
+    guint payloadLength = 0;
+    if (capturedLength > packet->headerSize) {
+        payloadLength = MIN(packet->payloadLength, capturedLength - packet->headerSize);
+    }
+    if (payloadLength > 0 && packet->payload) {
+        g_byte_array_append(block, packet->payload, payloadLength);
     }
 
-    if (!g_str_has_suffix(filename->str, ".pcap")) {
-        g_string_append(filename, ".pcap");
+    /* the capture may also end in the middle of the headers, or include
+     * payload that we don't have, which is zero-filled */
+    guint len = block->len;
+    g_byte_array_set_size(block, start + capturedLength);
+    if (block->len > len) {
+        memset(block->data + len, 0, block->len - len);
     }
+}
 
-    pcap->pcapFile = fopen(filename->str, "w");
-    if(!pcap->pcapFile) {
-        warning("error trying to open PCAP file '%s' for writing", filename->str);
+void pcapwriter_writePacket(PCapWriter* pcap, guint32 interfaceID, PCapPacket* packet) {
+    if (!pcap || !packet) {
+        return;
+    }
+    MAGIC_ASSERT(pcap);
+
+    /* get the current time that the packet is being sent/received */
+    SimulationTime now = worker_getCurrentTime();
+    guint32 length = packet->headerSize + packet->payloadLength;
+
+    g_mutex_lock(&pcap->lock);
+    utility_assert(interfaceID < pcap->captureSizes->len);
+    guint32 captureSize = g_array_index(pcap->captureSizes, guint32, interfaceID);
+    g_mutex_unlock(&pcap->lock);
+
+    guint32 capturedLength = MIN(length, captureSize);
+
+    GByteArray* block = _pcapwriter_startBlock(PCAPNG_BLOCK_ENHANCED_PACKET);
+    _pcapwriter_appendU32(block, interfaceID);
+    _pcapwriter_appendU32(block, (guint32)(now >> 32));
+    _pcapwriter_appendU32(block, (guint32)(now & 0xFFFFFFFF));
+    _pcapwriter_appendU32(block, capturedLength); /* captured length */
+    _pcapwriter_appendU32(block, length);         /* original length */
+    _pcapwriter_appendPacketData(block, packet, capturedLength);
+    _pcapwriter_appendPadding(block);
+
+    g_mutex_lock(&pcap->lock);
+    if (pcap->bufferedPackets) {
+        _pcapwriter_completeBlock(block);
+        PCapBufferedPacket buffered = {
+            .time = now,
+            .hostID = packet->hostID,
+            .packetID = packet->packetID,
+            .sequence = pcap->bufferedPackets->len,
+            .block = block,
+        };
+        g_array_append_val(pcap->bufferedPackets, buffered);
     } else {
-        _pcapwriter_writeHeader(pcap);
+        _pcapwriter_finishBlock(pcap, block);
     }
+    g_mutex_unlock(&pcap->lock);
+}
+
+static gint _pcapwriter_compareBufferedPackets(gconstpointer a, gconstpointer b) {
+    const PCapBufferedPacket* pa = a;
+    const PCapBufferedPacket* pb = b;
+
+    if (pa->time != pb->time) {
+        return pa->time < pb->time ? -1 : 1;
+    } else if (pa->hostID != pb->hostID) {
+        return pa->hostID < pb->hostID ? -1 : 1;
+    } else if (pa->packetID != pb->packetID) {
+        return pa->packetID < pb->packetID ? -1 : 1;
+    } else if (pa->sequence != pb->sequence) {
+        return pa->sequence < pb->sequence ? -1 : 1;
+    }
+    return 0;
+}
+
+void pcapwriter_flush(PCapWriter* pcap) {
+    if (!pcap) {
+        return;
+    }
+    MAGIC_ASSERT(pcap);
+
+    g_mutex_lock(&pcap->lock);
+
+    if (pcap->bufferedPackets) {
+        g_array_sort(pcap->bufferedPackets, _pcapwriter_compareBufferedPackets);
+
+        for (guint i = 0; i < pcap->bufferedPackets->len; i++) {
+            PCapBufferedPacket* buffered =
+                &g_array_index(pcap->bufferedPackets, PCapBufferedPacket, i);
+            _pcapwriter_writeBlock(pcap, buffered->block);
+        }
+        g_array_set_size(pcap->bufferedPackets, 0);
+    }
+
+    g_mutex_unlock(&pcap->lock);
+}
+
+PCapWriter* pcapwriter_new(const gchar* path, const gchar* const* comments, gboolean isBuffered) {
+    utility_assert(path);
+
+    FILE* pcapFile = fopen(path, "w");
+    if (!pcapFile) {
+        warning("error trying to open PCAP file '%s' for writing", path);
+        return NULL;
+    }
+
+    PCapWriter* pcap = g_new0(PCapWriter, 1);
+    MAGIC_INIT(pcap);
+
+    pcap->pcapFile = pcapFile;
+    pcap->path = g_strdup(path);
+    pcap->captureSizes = g_array_new(FALSE, FALSE, sizeof(guint32));
+    g_mutex_init(&pcap->lock);
+    if (isBuffered) {
+        pcap->bufferedPackets = g_array_new(FALSE, FALSE, sizeof(PCapBufferedPacket));
+    }
+
+    _pcapwriter_writeSectionHeader(pcap, comments);
 
     return pcap;
 }
 
 void pcapwriter_free(PCapWriter* pcap) {
-    if(pcap && pcap->pcapFile) {
-        fclose(pcap->pcapFile);
+    if (!pcap) {
+        return;
+    }
+    MAGIC_ASSERT(pcap);
+
+    pcapwriter_flush(pcap);
+    if (pcap->bufferedPackets) {
+        g_array_free(pcap->bufferedPackets, TRUE);
     }
+
+    fclose(pcap->pcapFile);
+    g_mutex_clear(&pcap->lock);
+    g_array_free(pcap->captureSizes, TRUE);
+    g_free(pcap->path);
+
+    MAGIC_CLEAR(pcap);
+    g_free(pcap);
 }
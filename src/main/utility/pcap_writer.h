@@ -26,11 +26,14 @@
 #include <netinet/in.h>
 
 #include "main/core/support/definitions.h"
+#include "main/host/protocol.h"
 
 typedef struct _PCapWriter PCapWriter;
 
 typedef struct _PCapPacket PCapPacket;
 struct _PCapPacket {
+    /* PTCP or PUDP */
+    ProtocolType protocol;
     in_addr_t srcIP;
     in_addr_t dstIP;
     in_port_t srcPort;
@@ -44,11 +47,50 @@ struct _PCapPacket {
     guint16 win;
     guint headerSize;
     guint payloadLength;
+    /* for IP fragments of a UDP datagram */
+    gboolean isFragment;
+    guint16 ipID;
+    /* offset of the fragment's data in the datagram's payload */
+    guint fragmentOffset;
+    /* the payload length of the whole UDP datagram */
+    guint datagramLength;
+    /* only needs to hold the part of the payload that fits in the capture size
+     * of the interface that the packet is written to */
     gpointer payload;
+    /* the id of the host that captured the packet, and the packet's id, which
+     * order the packets of a buffered capture */
+    guint hostID;
+    guint64 packetID;
 };
 
-PCapWriter* pcapwriter_new(Host* host, gchar* pcapDirectory, gchar* pcapFilename);
+/* Writes captured packets to a pcapng file at `path`. Each string in the
+ * NULL-terminated `comments` array (which may be NULL) is stored as a comment
+ * in the file's section header, e.g., to describe the host that was captured.
+ * A writer may be shared by multiple hosts, and is safe to use from multiple
+ * worker threads. Returns NULL if the file can't be opened.
+ *
+ * If `isBuffered` is TRUE, packets are not written until `pcapwriter_flush()`
+ * is called, so that packets written by hosts running on different worker
+ * threads appear in the same order in every run of the simulation. */
+PCapWriter* pcapwriter_new(const gchar* path, const gchar* const* comments, gboolean isBuffered);
+/* Flushes any buffered packets before closing the file. */
 void pcapwriter_free(PCapWriter* pcap);
-void pcapwriter_writePacket(PCapWriter* pcap, PCapPacket* packet);
+
+/* Adds an interface description block to the capture and returns the id of the
+ * new interface, which must be passed to `pcapwriter_writePacket()` when
+ * writing packets captured on that interface. At most `captureSize` bytes of
+ * each packet on the interface are written. */
+guint32 pcapwriter_addInterface(PCapWriter* pcap, const gchar* name, const gchar* description,
+                                guint32 captureSize);
+
+/* Writes the packet with a nanosecond timestamp of the current simulation time,
+ * truncated to the capture size of the interface. */
+void pcapwriter_writePacket(PCapWriter* pcap, guint32 interfaceID, PCapPacket* packet);
+
+/* Writes the packets buffered by a buffered writer, ordered by their timestamp,
+ * the id of the host that captured them, and their packet id. Packets with
+ * the same order are written in the order that the host captured them. Must
+ * be called when no other thread is writing packets, e.g., between rounds. */
+void pcapwriter_flush(PCapWriter* pcap);
 
 #endif /* SHD_PCAP_WRITER_H_ */
@@ -0,0 +1,191 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include "main/utility/stats_writer.h"
+
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "lib/logger/logger.h"
+#include "main/core/support/definitions.h"
+#include "main/utility/utility.h"
+
+struct _StatsWriter {
+    FILE* file;
+    HeartbeatFormat format;
+
+    /* the column names of the first row, written as the header of a CSV file */
+    GString* header;
+    gboolean didWriteHeader;
+
+    /* the current row, and the number of values it contains */
+    GString* row;
+    guint numValues;
+    gboolean inRow;
+
+    MAGIC_DECLARE;
+};
+
+StatsWriter* statswriter_new(const gchar* dir, const gchar* name, HeartbeatFormat format) {
+    const gchar* extension = NULL;
+    switch (format) {
+        case HEARTBEAT_FORMAT_NONE: return NULL;
+        case HEARTBEAT_FORMAT_CSV: extension = "csv"; break;
+        case HEARTBEAT_FORMAT_JSON: extension = "jsonl"; break;
+        default: utility_panic("Unknown heartbeat format %d", format);
+    }
+
+    gchar* filename = g_strdup_printf("%s.%s", name, extension);
+    gchar* path = g_build_filename(dir, filename, NULL);
+    g_free(filename);
+
+    FILE* file = fopen(path, "w");
+    if (!file) {
+        warning("error trying to open statistics file '%s' for writing", path);
+        g_free(path);
+        return NULL;
+    }
+    g_free(path);
+
+    StatsWriter* writer = g_new0(StatsWriter, 1);
+    MAGIC_INIT(writer);
+
+    writer->file = file;
+    writer->format = format;
+    writer->header = g_string_new(NULL);
+    writer->row = g_string_new(NULL);
+
+    return writer;
+}
+
+void statswriter_free(StatsWriter* writer) {
+    if (!writer) {
+        return;
+    }
+    MAGIC_ASSERT(writer);
+
+    fclose(writer->file);
+    g_string_free(writer->header, TRUE);
+    g_string_free(writer->row, TRUE);
+
+    MAGIC_CLEAR(writer);
+    g_free(writer);
+}
+
+void statswriter_beginRow(StatsWriter* writer) {
+    MAGIC_ASSERT(writer);
+    utility_assert(!writer->inRow);
+
+    g_string_truncate(writer->row, 0);
+    writer->numValues = 0;
+    writer->inRow = TRUE;
+}
+
+/* Appends the string as a JSON string, or as a CSV field that is only quoted
+ * when it contains a separator, quote, or newline. */
+static void _statswriter_appendString(StatsWriter* writer, GString* buffer, const gchar* value) {
+    if (writer->format == HEARTBEAT_FORMAT_CSV) {
+        if (!strpbrk(value, ",\"\r\n")) {
+            g_string_append(buffer, value);
+            return;
+        }
+
+        g_string_append_c(buffer, '"');
+        for (const gchar* c = value; *c != '\0'; c++) {
+            if (*c == '"') {
+                g_string_append_c(buffer, '"');
+            }
+            g_string_append_c(buffer, *c);
+        }
+        g_string_append_c(buffer, '"');
+        return;
+    }
+
+    g_string_append_c(buffer, '"');
+    for (const gchar* c = value; *c != '\0'; c++) {
+        switch (*c) {
+            case '"': g_string_append(buffer, "\\\""); break;
+            case '\\': g_string_append(buffer, "\\\\"); break;
+            case '\n': g_string_append(buffer, "\\n"); break;
+            case '\r': g_string_append(buffer, "\\r"); break;
+            case '\t': g_string_append(buffer, "\\t"); break;
+            default:
+                if ((guchar)*c < 0x20) {
+                    g_string_append_printf(buffer, "\\u%04x", (guint)(guchar)*c);
+                } else {
+                    g_string_append_c(buffer, *c);
+                }
+        }
+    }
+    g_string_append_c(buffer, '"');
+}
+
+/* Appends the name of the value to the header or JSON object, followed by the
+ * separator that precedes the value itself. */
+static void _statswriter_appendName(StatsWriter* writer, const gchar* name) {
+    MAGIC_ASSERT(writer);
+    utility_assert(writer->inRow);
+
+    if (writer->format == HEARTBEAT_FORMAT_CSV) {
+        if (!writer->didWriteHeader) {
+            if (writer->numValues > 0) {
+                g_string_append_c(writer->header, ',');
+            }
+            _statswriter_appendString(writer, writer->header, name);
+        }
+        if (writer->numValues > 0) {
+            g_string_append_c(writer->row, ',');
+        }
+    } else {
+        g_string_append_c(writer->row, writer->numValues > 0 ? ',' : '{');
+        _statswriter_appendString(writer, writer->row, name);
+        g_string_append_c(writer->row, ':');
+    }
+
+    writer->numValues++;
+}
+
+void statswriter_addUInt(StatsWriter* writer, const gchar* name, guint64 value) {
+    _statswriter_appendName(writer, name);
+    g_string_append_printf(writer->row, "%" G_GUINT64_FORMAT, value);
+}
+
+void statswriter_addInt(StatsWriter* writer, const gchar* name, gint64 value) {
+    _statswriter_appendName(writer, name);
+    g_string_append_printf(writer->row, "%" G_GINT64_FORMAT, value);
+}
+
+void statswriter_addDouble(StatsWriter* writer, const gchar* name, gdouble value) {
+    _statswriter_appendName(writer, name);
+    if (isfinite(value)) {
+        g_string_append_printf(writer->row, "%f", value);
+    } else if (writer->format == HEARTBEAT_FORMAT_JSON) {
+        /* JSON has no representation of NaN or infinity; CSV fields are left empty */
+        g_string_append(writer->row, "null");
+    }
+}
+
+void statswriter_addString(StatsWriter* writer, const gchar* name, const gchar* value) {
+    _statswriter_appendName(writer, name);
+    _statswriter_appendString(writer, writer->row, value ? value : "");
+}
+
+void statswriter_endRow(StatsWriter* writer) {
+    MAGIC_ASSERT(writer);
+    utility_assert(writer->inRow);
+    writer->inRow = FALSE;
+
+    if (writer->format == HEARTBEAT_FORMAT_CSV) {
+        if (!writer->didWriteHeader) {
+            writer->didWriteHeader = TRUE;
+            fprintf(writer->file, "%s\n", writer->header->str);
+        }
+    } else {
+        g_string_append(writer->row, writer->numValues > 0 ? "}" : "{}");
+    }
+
+    fprintf(writer->file, "%s\n", writer->row->str);
+}
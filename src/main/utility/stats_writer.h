@@ -0,0 +1,36 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#ifndef SHD_STATS_WRITER_H_
+#define SHD_STATS_WRITER_H_
+
+#include <glib.h>
+
+#include "main/bindings/c/bindings-opaque.h"
+
+/* Writes rows of named statistics to a CSV or JSON Lines file. A writer is not
+ * thread-safe, so it should only be used by the thread that owns it. */
+typedef struct _StatsWriter StatsWriter;
+
+/* Creates the file `name` in the directory `dir`, with a ".csv" or ".jsonl"
+ * extension depending on `format`. Returns NULL if `format` is
+ * HEARTBEAT_FORMAT_NONE or the file can't be opened. */
+StatsWriter* statswriter_new(const gchar* dir, const gchar* name, HeartbeatFormat format);
+void statswriter_free(StatsWriter* writer);
+
+/* Starts a new row. The values of the row are added with the
+ * `statswriter_add*()` functions, and the row is written when it's ended. */
+void statswriter_beginRow(StatsWriter* writer);
+void statswriter_addUInt(StatsWriter* writer, const gchar* name, guint64 value);
+void statswriter_addInt(StatsWriter* writer, const gchar* name, gint64 value);
+void statswriter_addDouble(StatsWriter* writer, const gchar* name, gdouble value);
+void statswriter_addString(StatsWriter* writer, const gchar* name, const gchar* value);
+
+/* Writes the row to the file. CSV files get a header row with the names of the
+ * columns of the first row, so every row of a CSV file must have the same
+ * columns in the same order. */
+void statswriter_endRow(StatsWriter* writer);
+
+#endif /* SHD_STATS_WRITER_H_ */
@@ -0,0 +1,49 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <fnmatch.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+/* Prints its arguments so that the merged config can be checked, and checks
+ * them against the space-separated EXPECTED_ARGS environment variable. Also
+ * checks that the hostname matches the glob pattern in EXPECTED_HOSTNAME. */
+int main(int argc, char* argv[]) {
+    const char* expectedArgs = getenv("EXPECTED_ARGS");
+    const char* expectedHostname = getenv("EXPECTED_HOSTNAME");
+    if (expectedArgs == NULL || expectedHostname == NULL) {
+        fprintf(stderr, "EXPECTED_ARGS and EXPECTED_HOSTNAME must be set\n");
+        return EXIT_FAILURE;
+    }
+
+    char joined[1024] = {0};
+    for (int i = 1; i < argc; i++) {
+        printf("arg: %s\n", argv[i]);
+        if (i > 1) {
+            strncat(joined, " ", sizeof(joined) - strlen(joined) - 1);
+        }
+        strncat(joined, argv[i], sizeof(joined) - strlen(joined) - 1);
+    }
+
+    if (strcmp(joined, expectedArgs) != 0) {
+        fprintf(stderr, "expected the args '%s'\n", expectedArgs);
+        return EXIT_FAILURE;
+    }
+
+    char hostname[256] = {0};
+    if (gethostname(hostname, sizeof(hostname) - 1) != 0) {
+        perror("gethostname");
+        return EXIT_FAILURE;
+    }
+    if (fnmatch(expectedHostname, hostname, 0) != 0) {
+        fprintf(stderr, "expected a hostname matching '%s', but it was '%s'\n",
+                expectedHostname, hostname);
+        return EXIT_FAILURE;
+    }
+
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,160 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// The number of cores configured in cpu.yaml.
+const NUM_CORES: usize = 4;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_sched_getaffinity",
+            test_sched_getaffinity,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_sched_getaffinity_small_mask",
+            test_sched_getaffinity_small_mask,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_sched_getaffinity_pid",
+            test_sched_getaffinity_pid,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_sched_setaffinity",
+            test_sched_setaffinity,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_cpus_online", test_cpus_online, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_cpuinfo", test_cpuinfo, set![TestEnv::Shadow]),
+    ];
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_affinity() -> Result<libc::cpu_set_t, String> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let rv =
+        unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if rv != 0 {
+        return Err(format!(
+            "sched_getaffinity failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(set)
+}
+
+fn test_sched_getaffinity() -> Result<(), String> {
+    let set = get_affinity()?;
+    let count = (0..libc::CPU_SETSIZE as usize)
+        .filter(|&i| unsafe { libc::CPU_ISSET(i, &set) })
+        .count();
+    test_utils::result_assert_eq(count, NUM_CORES, "Unexpected number of cores in the mask")?;
+
+    for i in 0..NUM_CORES {
+        test_utils::result_assert(
+            unsafe { libc::CPU_ISSET(i, &set) },
+            "Core missing from mask",
+        )?;
+    }
+    Ok(())
+}
+
+fn test_sched_getaffinity_small_mask() -> Result<(), String> {
+    // a mask size that isn't a multiple of the size of a long is invalid
+    let mut mask = [0u8; 3];
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_sched_getaffinity,
+            0,
+            mask.len(),
+            mask.as_mut_ptr(),
+        )
+    };
+    let errno = std::io::Error::last_os_error().raw_os_error();
+
+    test_utils::result_assert_eq(rv, -1, "Expected sched_getaffinity to fail")?;
+    test_utils::result_assert_eq(errno, Some(libc::EINVAL), "Unexpected error")
+}
+
+fn test_sched_getaffinity_pid() -> Result<(), String> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+
+    // our own tid is the same as pid 0
+    let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::pid_t;
+    let rv = unsafe { libc::sched_getaffinity(tid, size, &mut set) };
+    test_utils::result_assert_eq(rv, 0, "sched_getaffinity failed for our own tid")?;
+
+    // a pid that isn't one of our threads
+    let rv = unsafe { libc::sched_getaffinity(libc::pid_t::MAX, size, &mut set) };
+    let errno = std::io::Error::last_os_error().raw_os_error();
+    test_utils::result_assert_eq(rv, -1, "Expected sched_getaffinity to fail")?;
+    test_utils::result_assert_eq(errno, Some(libc::ESRCH), "Unexpected error")
+}
+
+fn test_sched_setaffinity() -> Result<(), String> {
+    // pinning to the first core is always allowed
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::CPU_SET(0, &mut set) };
+    let rv = unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    test_utils::result_assert_eq(rv, 0, "sched_setaffinity failed")?;
+
+    // an empty mask is invalid
+    let set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    let errno = std::io::Error::last_os_error().raw_os_error();
+    test_utils::result_assert_eq(rv, -1, "Expected sched_setaffinity to fail")?;
+    test_utils::result_assert_eq(errno, Some(libc::EINVAL), "Unexpected error")
+}
+
+fn test_cpus_online() -> Result<(), String> {
+    let online = std::fs::read_to_string("/sys/devices/system/cpu/online")
+        .map_err(|e| format!("Could not read the online cpus: {}", e))?;
+    test_utils::result_assert_eq(
+        online.as_str(),
+        format!("0-{}\n", NUM_CORES - 1).as_str(),
+        "Unexpected online cpus",
+    )?;
+
+    let nprocs = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    test_utils::result_assert_eq(nprocs, NUM_CORES as libc::c_long, "Unexpected nprocs")
+}
+
+fn test_cpuinfo() -> Result<(), String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo")
+        .map_err(|e| format!("Could not read /proc/cpuinfo: {}", e))?;
+    let processors = cpuinfo
+        .lines()
+        .filter(|x| x.starts_with("processor"))
+        .count();
+    test_utils::result_assert_eq(processors, NUM_CORES, "Unexpected number of processors")
+}
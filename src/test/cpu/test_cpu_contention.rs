@@ -0,0 +1,85 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+/// How many times each thread does its work and then sleeps.
+const ROUNDS: usize = 20;
+/// Iterations of the busy loop in each round. This should take a few
+/// milliseconds, so that it is much larger than the host's cpu threshold.
+const ITERATIONS: u64 = 1_000_000;
+/// How far the measured slowdown may be from the expected slowdown. The
+/// cpu delays depend on how long the busy loops take on the real machine,
+/// so they vary from round to round.
+const TOLERANCE: f64 = 0.25;
+
+/// Usage: test_cpu_contention <threads> <cores>
+///
+/// Runs a busy thread by itself, then `threads` busy threads at the same time,
+/// and checks that the threads take `threads/cores` times as long when they
+/// contend for the host's `cores` cores.
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        return Err(format!("Usage: {} <threads> <cores>", args[0]));
+    }
+    let num_threads: usize = args[1]
+        .parse()
+        .map_err(|e| format!("Invalid number of threads: {}", e))?;
+    let num_cores: usize = args[2]
+        .parse()
+        .map_err(|e| format!("Invalid number of cores: {}", e))?;
+
+    // a single thread runs on one core at a time, no matter how many the host has
+    let alone = run_threads(1)?;
+    let together = run_threads(num_threads)?;
+
+    let slowdown = together.as_secs_f64() / alone.as_secs_f64();
+    let expected = num_threads as f64 / num_cores.min(num_threads) as f64;
+    println!(
+        "1 thread took {:?}, {} threads took {:?} ({:.2}x, expected {:.2}x)",
+        alone, num_threads, together, slowdown, expected
+    );
+
+    test_utils::result_assert(
+        (slowdown - expected).abs() <= expected * TOLERANCE,
+        &format!(
+            "Unexpected slowdown {:.2}, expected {:.2}",
+            slowdown, expected
+        ),
+    )?;
+
+    println!("Success.");
+    Ok(())
+}
+
+/// Runs `num_threads` busy threads at the same time, and returns how long it
+/// took for all of them to finish.
+fn run_threads(num_threads: usize) -> Result<std::time::Duration, String> {
+    let start = std::time::Instant::now();
+
+    let handles: Vec<_> = (0..num_threads)
+        .map(|_| std::thread::spawn(busy_thread))
+        .collect();
+    for handle in handles {
+        handle
+            .join()
+            .map_err(|_| "A busy thread panicked".to_string())?;
+    }
+
+    Ok(start.elapsed())
+}
+
+fn busy_thread() {
+    let mut x: u64 = 0;
+    for _ in 0..ROUNDS {
+        for i in 0..ITERATIONS {
+            // a volatile write so that the loop isn't optimized away
+            unsafe { std::ptr::write_volatile(&mut x, x.wrapping_add(i)) };
+        }
+
+        // block so that shadow charges the work to the host's cpu, and delays
+        // the thread if its core is busy
+        std::thread::sleep(std::time::Duration::from_micros(1));
+    }
+}
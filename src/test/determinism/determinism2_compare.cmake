@@ -17,3 +17,8 @@ foreach(LOOPIDX RANGE 1 10)
         ${CMAKE_BINARY_DIR}/determinism2b-shadow-${METHOD}.data/hosts/peer${LOOPIDX}/peer${LOOPIDX}.test-phold.1000.stdout
     )
 endforeach(LOOPIDX)
+## the network-wide capture is written by all of the worker threads
+exec_diff_check(
+    ${CMAKE_BINARY_DIR}/determinism2a-shadow-${METHOD}.data/all.pcapng
+    ${CMAKE_BINARY_DIR}/determinism2b-shadow-${METHOD}.data/all.pcapng
+)
@@ -0,0 +1,134 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+// these must match the disk options in disk.yaml
+const READ_LATENCY: Duration = Duration::from_millis(1);
+const WRITE_LATENCY: Duration = Duration::from_millis(2);
+const WRITE_BYTES_PER_SEC: u64 = 1_000_000;
+const SYNC_LATENCY: Duration = Duration::from_millis(10);
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    // the disk model only exists in shadow
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new("test_write", test_write, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_read", test_read, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_read_eof", test_read_eof, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_fsync", test_fsync, set![TestEnv::Shadow]),
+    ];
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn create_file(name: &str, contents: &[u8]) -> Result<std::fs::File, String> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(name)
+        .map_err(|e| e.to_string())?;
+    file.write_all(contents).map_err(|e| e.to_string())?;
+    Ok(file)
+}
+
+fn test_write() -> Result<(), String> {
+    let data = vec![0u8; 10_000];
+
+    let start = Instant::now();
+    let mut file = create_file("disk_write", &[])?;
+    file.write_all(&data).map_err(|e| e.to_string())?;
+    let elapsed = start.elapsed();
+
+    let transfer_time =
+        Duration::from_nanos(data.len() as u64 * 1_000_000_000 / WRITE_BYTES_PER_SEC);
+    let expected = WRITE_LATENCY + transfer_time;
+    test_utils::result_assert(
+        elapsed >= expected,
+        &format!("Write took {:?}, expected at least {:?}", elapsed, expected),
+    )
+}
+
+fn test_read() -> Result<(), String> {
+    let mut file = create_file("disk_read", b"hello")?;
+    file.sync_all().map_err(|e| e.to_string())?;
+
+    let mut file = std::fs::File::open("disk_read").map_err(|e| e.to_string())?;
+    let mut buf = [0u8; 5];
+
+    let start = Instant::now();
+    file.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    let elapsed = start.elapsed();
+
+    test_utils::result_assert_eq(&buf, b"hello", "Unexpected file contents")?;
+    test_utils::result_assert(
+        elapsed >= READ_LATENCY,
+        &format!(
+            "Read took {:?}, expected at least {:?}",
+            elapsed, READ_LATENCY
+        ),
+    )
+}
+
+/// A read at the end of the file still takes the read latency.
+fn test_read_eof() -> Result<(), String> {
+    let mut file = create_file("disk_read_eof", &[])?;
+    let mut buf = [0u8; 5];
+
+    let start = Instant::now();
+    let count = file.read(&mut buf).map_err(|e| e.to_string())?;
+    let elapsed = start.elapsed();
+
+    test_utils::result_assert_eq(count, 0, "Unexpected read count")?;
+    test_utils::result_assert(
+        elapsed >= READ_LATENCY,
+        &format!(
+            "Read took {:?}, expected at least {:?}",
+            elapsed, READ_LATENCY
+        ),
+    )
+}
+
+fn test_fsync() -> Result<(), String> {
+    let file = create_file("disk_fsync", b"hello")?;
+
+    let start = Instant::now();
+    file.sync_all().map_err(|e| e.to_string())?;
+    let elapsed = start.elapsed();
+
+    test_utils::result_assert(
+        elapsed >= SYNC_LATENCY,
+        &format!(
+            "Fsync took {:?}, expected at least {:?}",
+            elapsed, SYNC_LATENCY
+        ),
+    )
+}
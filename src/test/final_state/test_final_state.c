@@ -0,0 +1,19 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <stdlib.h>
+#include <unistd.h>
+
+/* Exits with the exit code given as the first argument, or runs until it's
+ * killed if there is no argument. */
+int main(int argc, char* argv[]) {
+    if (argc > 1) {
+        return atoi(argv[1]);
+    }
+
+    while (1) {
+        sleep(1);
+    }
+}
@@ -0,0 +1,278 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::ffi::CString;
+use std::io::Write;
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+    // is the base of the host's filesystem the template tree in "base/"?
+    let filesystem_base = std::env::args().any(|x| x == "--filesystem-base");
+
+    // these tests modify files at absolute paths, so they're only run within shadow
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = if filesystem_base {
+        vec![
+            test_utils::ShadowTest::new("test_truncate", test_truncate, set![TestEnv::Shadow]),
+            test_utils::ShadowTest::new(
+                "test_modify_base_file",
+                test_modify_base_file,
+                set![TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new("test_xattr", test_xattr, set![TestEnv::Shadow]),
+            test_utils::ShadowTest::new(
+                "test_merged_listing",
+                test_merged_listing,
+                set![TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                "test_rename_base_dir",
+                test_rename_base_dir,
+                set![TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                "test_rmdir_non_empty_base",
+                test_rmdir_non_empty_base,
+                set![TestEnv::Shadow],
+            ),
+        ]
+    } else {
+        vec![
+            test_utils::ShadowTest::new(
+                "test_exclusive_create",
+                test_exclusive_create,
+                set![TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new("test_rename", test_rename, set![TestEnv::Shadow]),
+        ]
+    };
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+const TEST_DIR: &str = "/tmp/shadow-fs-overlay-test";
+
+/// A file in the template tree, with contents "original\n".
+const BASE_FILE: &str = "/etc/app.conf";
+/// A directory in the template tree, containing only the file "seed".
+const BASE_DIR: &str = "/var/lib/app";
+
+/// Every host creates the same file with O_EXCL, which only succeeds if the hosts have
+/// isolated views of the filesystem.
+fn test_exclusive_create() -> Result<(), String> {
+    std::fs::create_dir_all(TEST_DIR).map_err(|e| e.to_string())?;
+
+    let path = format!("{}/exclusive", TEST_DIR);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| format!("Could not exclusively create {}: {}", path, e))?;
+    file.write_all(b"hello").map_err(|e| e.to_string())?;
+    drop(file);
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(contents.as_str(), "hello", "Unexpected file contents")
+}
+
+fn test_rename() -> Result<(), String> {
+    std::fs::create_dir_all(TEST_DIR).map_err(|e| e.to_string())?;
+
+    let from = format!("{}/rename-from", TEST_DIR);
+    let to = format!("{}/rename-to", TEST_DIR);
+    std::fs::write(&from, b"renamed").map_err(|e| e.to_string())?;
+    std::fs::rename(&from, &to).map_err(|e| e.to_string())?;
+
+    test_utils::result_assert(
+        !std::path::Path::new(&from).exists(),
+        "Old path still exists after rename",
+    )?;
+
+    let contents = std::fs::read_to_string(&to).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(contents.as_str(), "renamed", "Unexpected file contents")
+}
+
+/// Modify and then remove a file that exists in the base tree. Only the host's copy of the
+/// file is affected.
+fn test_modify_base_file() -> Result<(), String> {
+    let original = std::fs::read(BASE_FILE).map_err(|e| e.to_string())?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(BASE_FILE)
+        .map_err(|e| format!("Could not open {} for writing: {}", BASE_FILE, e))?;
+    file.write_all(b"modified\n").map_err(|e| e.to_string())?;
+    drop(file);
+
+    let modified = std::fs::read(BASE_FILE).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(
+        modified,
+        [original.as_slice(), &b"modified\n"[..]].concat(),
+        "File was not modified",
+    )?;
+
+    std::fs::remove_file(BASE_FILE).map_err(|e| e.to_string())?;
+    let err = match std::fs::metadata(BASE_FILE) {
+        Ok(_) => return Err("File still exists after removing it".to_string()),
+        Err(e) => e,
+    };
+
+    test_utils::result_assert_eq(err.raw_os_error(), Some(libc::ENOENT), "Unexpected error")
+}
+
+/// Truncate a file in the base tree by path. Only the host's copy of the file is truncated.
+fn test_truncate() -> Result<(), String> {
+    let path = CString::new(BASE_FILE).unwrap();
+    let rv = unsafe { libc::truncate(path.as_ptr(), 4) };
+    test_utils::result_assert_eq(rv, 0, "truncate() failed")?;
+
+    let contents = std::fs::read(BASE_FILE).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(contents.as_slice(), &b"orig"[..], "File was not truncated")
+}
+
+/// Set and read back an extended attribute on a file in the base tree, which is copied into
+/// the host's directory first.
+fn test_xattr() -> Result<(), String> {
+    let path = CString::new(format!("{}/seed", BASE_DIR)).unwrap();
+    let name = CString::new("user.shadow").unwrap();
+    let value = b"overlay";
+
+    let rv = unsafe {
+        libc::setxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+        )
+    };
+    if rv != 0 && test_utils::get_errno() == libc::ENOTSUP {
+        // the filesystem containing the host's data directory doesn't support user xattrs
+        return Ok(());
+    }
+    test_utils::result_assert_eq(rv, 0, "setxattr() failed")?;
+
+    let mut buf = [0u8; 16];
+    let len = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    test_utils::result_assert_eq(len, value.len() as isize, "getxattr() failed")?;
+    test_utils::result_assert_eq(&buf[..value.len()], &value[..], "Unexpected xattr value")?;
+
+    let rv = unsafe { libc::removexattr(path.as_ptr(), name.as_ptr()) };
+    test_utils::result_assert_eq(rv, 0, "removexattr() failed")?;
+
+    let len = unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0) };
+    test_utils::result_assert_eq(len, -1, "getxattr() of a removed xattr succeeded")?;
+    test_utils::result_assert_eq(test_utils::get_errno(), libc::ENODATA, "Unexpected error")
+}
+
+fn list_dir(path: &str) -> Result<Vec<String>, String> {
+    let mut names = std::fs::read_dir(path)
+        .map_err(|e| e.to_string())?
+        .map(|entry| {
+            entry
+                .map(|x| x.file_name().to_string_lossy().into_owned())
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    Ok(names)
+}
+
+/// Adding a file to a base directory copies the directory into the host's directory, but the
+/// entries of the base directory are still listed.
+fn test_merged_listing() -> Result<(), String> {
+    let path = format!("{}/added", BASE_DIR);
+    std::fs::write(&path, b"added").map_err(|e| e.to_string())?;
+
+    test_utils::result_assert_eq(
+        list_dir(BASE_DIR)?,
+        vec!["added".to_string(), "seed".to_string()],
+        "Unexpected directory entries",
+    )?;
+
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(
+        list_dir(BASE_DIR)?,
+        vec!["seed".to_string()],
+        "Unexpected directory entries",
+    )
+}
+
+/// Renaming a directory with entries in the base tree fails with EXDEV, like overlayfs, rather
+/// than moving only the host's empty copy of the directory.
+fn test_rename_base_dir() -> Result<(), String> {
+    let new_dir = format!("{}2", BASE_DIR);
+
+    let err = match std::fs::rename(BASE_DIR, &new_dir) {
+        Ok(_) => return Err("Renamed a directory with entries in the base tree".to_string()),
+        Err(e) => e,
+    };
+    test_utils::result_assert_eq(err.raw_os_error(), Some(libc::EXDEV), "Unexpected error")?;
+
+    test_utils::result_assert(
+        !std::path::Path::new(&new_dir).exists(),
+        "New path exists after a failed rename",
+    )?;
+    test_utils::result_assert_eq(
+        list_dir(BASE_DIR)?,
+        vec!["seed".to_string()],
+        "Unexpected directory entries",
+    )
+}
+
+/// A directory that was copied into the host's directory is empty there, but the base
+/// directory isn't, so it can't be removed.
+fn test_rmdir_non_empty_base() -> Result<(), String> {
+    let path = format!("{}/added", BASE_DIR);
+    std::fs::write(&path, b"added").map_err(|e| e.to_string())?;
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+
+    let err = match std::fs::remove_dir(BASE_DIR) {
+        Ok(_) => return Err("Removed a non-empty directory".to_string()),
+        Err(e) => e,
+    };
+    test_utils::result_assert_eq(
+        err.raw_os_error(),
+        Some(libc::ENOTEMPTY),
+        "Unexpected error",
+    )?;
+
+    // once its entries are removed, the directory can be removed
+    std::fs::remove_file(format!("{}/seed", BASE_DIR)).map_err(|e| e.to_string())?;
+    std::fs::remove_dir(BASE_DIR).map_err(|e| e.to_string())?;
+
+    test_utils::result_assert(
+        !std::path::Path::new(BASE_DIR).exists(),
+        "Directory still exists after removing it",
+    )
+}
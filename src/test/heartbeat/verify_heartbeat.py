@@ -0,0 +1,88 @@
+#!/usr/bin/env python3
+
+# Checks the heartbeat statistics files written by the heartbeat test. Run from
+# the data directory.
+
+import csv
+import json
+import os
+import sys
+
+COUNTERS = ['packets_total', 'bytes_total', 'packets_control', 'bytes_control_header',
+            'packets_control_retrans', 'bytes_control_header_retrans', 'packets_data',
+            'bytes_data_header', 'bytes_data_payload', 'packets_data_retrans',
+            'bytes_data_header_retrans', 'bytes_data_payload_retrans']
+DIRECTIONS = ['in_local', 'out_local', 'in_remote', 'out_remote']
+COUNTER_COLUMNS = [f'{d}_{c}' for d in DIRECTIONS for c in COUNTERS]
+
+COLUMNS = {
+    'node': ['time_ns', 'host', 'interval_ns', 'recv_bytes', 'send_bytes', 'cpu_utilization',
+             'delayed_count', 'avg_delay_ms'] + COUNTER_COLUMNS,
+    'socket': ['time_ns', 'host', 'handle', 'protocol', 'peer_host', 'peer_port', 'inbuf_len',
+               'inbuf_size', 'outbuf_len', 'outbuf_size', 'recv_bytes', 'send_bytes'] +
+              COUNTER_COLUMNS,
+    'ram': ['time_ns', 'host', 'interval_ns', 'alloc_bytes', 'dealloc_bytes', 'total_bytes',
+            'pointers_count', 'failfree_count'],
+}
+GLOBAL_COLUMNS = ['time_ns', 'max_rss_kib', 'user_time_seconds', 'system_time_seconds',
+                  'voluntary_context_switches', 'involuntary_context_switches']
+
+
+def read_csv(path):
+    with open(path, newline='') as f:
+        reader = csv.reader(f)
+        header = next(reader)
+        return [dict(zip(header, row)) for row in reader], header
+
+
+def read_jsonl(path):
+    rows = []
+    with open(path) as f:
+        for line in f:
+            rows.append(json.loads(line))
+    return rows, list(rows[0].keys()) if rows else []
+
+
+def read(path):
+    if path.endswith('.csv'):
+        return read_csv(path)
+    return read_jsonl(path)
+
+
+def check_file(path, expected_columns, host=None):
+    if not os.path.exists(path):
+        sys.exit(f'{path}: missing')
+    rows, columns = read(path)
+    if not rows:
+        sys.exit(f'{path}: no rows were written')
+    if columns != expected_columns:
+        sys.exit(f'{path}: unexpected columns {columns}')
+    for row in rows:
+        if list(row.keys()) != expected_columns:
+            sys.exit(f'{path}: row has unexpected columns: {row}')
+        if host is not None and row['host'] != host:
+            sys.exit(f'{path}: row for the wrong host: {row}')
+    times = [int(row['time_ns']) for row in rows]
+    if times != sorted(times):
+        sys.exit(f'{path}: rows are not in time order')
+    return rows
+
+
+# the server overrides the default format
+for (host, extension) in [('server', 'jsonl'), ('client', 'csv')]:
+    other = 'csv' if extension == 'jsonl' else 'jsonl'
+    rows = {}
+    for (name, columns) in COLUMNS.items():
+        if os.path.exists(f'hosts/{host}/heartbeat-{name}.{other}'):
+            sys.exit(f'{host}: wrote a {other} file for {name}')
+        rows[name] = check_file(f'hosts/{host}/heartbeat-{name}.{extension}', columns, host)
+
+    # the client and server exchange data with each other
+    if sum(int(row['send_bytes']) for row in rows['node']) == 0:
+        sys.exit(f'{host}: node statistics show no sent bytes')
+    if sum(int(row['recv_bytes']) for row in rows['node']) == 0:
+        sys.exit(f'{host}: node statistics show no received bytes')
+    if not any(row['protocol'] == 'TCP' for row in rows['socket']):
+        sys.exit(f'{host}: socket statistics have no TCP sockets')
+
+check_file('heartbeat.jsonl', GLOBAL_COLUMNS)
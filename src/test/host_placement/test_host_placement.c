@@ -0,0 +1,154 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <arpa/inet.h>
+#include <fnmatch.h>
+#include <netdb.h>
+#include <netinet/in.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <time.h>
+#include <unistd.h>
+
+#define PORT 8080
+
+/* Resolves the hostname to an IPv4 address, returning 0 on success. */
+static int _resolve(const char* hostname, struct sockaddr_in* addr) {
+    struct addrinfo hints = {.ai_family = AF_INET};
+    struct addrinfo* result = NULL;
+
+    int rv = getaddrinfo(hostname, NULL, &hints, &result);
+    if (rv != 0) {
+        fprintf(stderr, "getaddrinfo: %s\n", gai_strerror(rv));
+        return -1;
+    }
+
+    *addr = *(struct sockaddr_in*)result->ai_addr;
+    freeaddrinfo(result);
+    return 0;
+}
+
+/* Prints the hostname and IP address of this host, and checks that the
+ * hostname matches the glob pattern `expectedHostname`. */
+static int _printAddress(const char* expectedHostname, struct sockaddr_in* addr) {
+    char hostname[256] = {0};
+    if (gethostname(hostname, sizeof(hostname) - 1) != 0) {
+        perror("gethostname");
+        return -1;
+    }
+
+    if (_resolve(hostname, addr) != 0) {
+        return -1;
+    }
+
+    char ip[INET_ADDRSTRLEN] = {0};
+    inet_ntop(AF_INET, &addr->sin_addr, ip, sizeof(ip));
+    printf("hostname %s\nip %s\n", hostname, ip);
+
+    if (fnmatch(expectedHostname, hostname, 0) != 0) {
+        fprintf(stderr, "expected a hostname matching '%s'\n", expectedHostname);
+        return -1;
+    }
+    return 0;
+}
+
+/* Checks that this host has the address `expectedIp`, and accepts connections
+ * until the simulation ends. */
+static int _runServer(const struct sockaddr_in* self, const char* expectedIp) {
+    struct in_addr expected;
+    if (inet_pton(AF_INET, expectedIp, &expected) != 1 ||
+        self->sin_addr.s_addr != expected.s_addr) {
+        fprintf(stderr, "expected the address %s\n", expectedIp);
+        return -1;
+    }
+
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in addr = {
+        .sin_family = AF_INET, .sin_port = htons(PORT), .sin_addr.s_addr = htonl(INADDR_ANY)};
+
+    if (fd < 0 || bind(fd, (struct sockaddr*)&addr, sizeof(addr)) != 0 || listen(fd, 10) != 0) {
+        perror("server");
+        return -1;
+    }
+
+    /* accept connections until the simulation ends */
+    while (1) {
+        int client = accept(fd, NULL, NULL);
+        if (client < 0) {
+            perror("accept");
+            return -1;
+        }
+        close(client);
+    }
+}
+
+/* Connects to the server and checks that it took between `minMillis` and
+ * `maxMillis`, which depends on the latency between the network nodes of the
+ * two hosts. */
+static int _runClient(const struct sockaddr_in* self, const char* server, long minMillis,
+                      long maxMillis) {
+    struct sockaddr_in addr;
+    if (_resolve(server, &addr) != 0) {
+        return -1;
+    }
+    addr.sin_port = htons(PORT);
+
+    if (addr.sin_addr.s_addr == self->sin_addr.s_addr) {
+        fprintf(stderr, "this host has the address of %s\n", server);
+        return -1;
+    }
+
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return -1;
+    }
+
+    struct timespec start, end;
+    clock_gettime(CLOCK_MONOTONIC, &start);
+    if (connect(fd, (struct sockaddr*)&addr, sizeof(addr)) != 0) {
+        perror("connect");
+        return -1;
+    }
+    clock_gettime(CLOCK_MONOTONIC, &end);
+
+    long millis = (end.tv_sec - start.tv_sec) * 1000 + (end.tv_nsec - start.tv_nsec) / 1000000;
+    printf("connected in %ld ms\n", millis);
+    close(fd);
+
+    if (millis < minMillis || millis >= maxMillis) {
+        fprintf(stderr, "expected to connect in %ld to %ld ms\n", minMillis, maxMillis);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, char* argv[]) {
+    if (argc < 3) {
+        fprintf(stderr,
+                "Usage: %s server <hostname> <ip> | client <hostname> <server> <min ms> <max ms>\n",
+                argv[0]);
+        return EXIT_FAILURE;
+    }
+
+    struct sockaddr_in self;
+    if (_printAddress(argv[2], &self) != 0) {
+        return EXIT_FAILURE;
+    }
+    fflush(stdout);
+
+    int rv = -1;
+    if (strcmp(argv[1], "server") == 0 && argc == 4) {
+        rv = _runServer(&self, argv[3]);
+    } else if (strcmp(argv[1], "client") == 0 && argc == 6) {
+        rv = _runClient(&self, argv[3], atol(argv[4]), atol(argv[5]));
+    } else {
+        fprintf(stderr, "Unknown mode '%s'\n", argv[1]);
+    }
+
+    return rv == 0 ? EXIT_SUCCESS : EXIT_FAILURE;
+}
@@ -0,0 +1,32 @@
+#!/usr/bin/env python3
+
+# Checks the host log file written by the logging test. Run from the data
+# directory.
+
+import os
+import re
+import sys
+
+# real-time [thread-name] virtual-time [loglevel] [hostname:ip] [src-file:line-number] [module]
+LINE = re.compile(r'^\S+ \[[^\]]*\] \S+ \[(\w+)\] \[([^:\]]+):[^\]]*\] \[([^:\]]+):\d+\] \[([^\]]*)\] ')
+
+# the client logs to stdout
+if os.path.exists('hosts/client/shadow.log'):
+    sys.exit('client: wrote a log file even though it was disabled')
+
+with open('hosts/server/shadow.log') as f:
+    lines = f.readlines()
+if not lines:
+    sys.exit('server: no messages were logged')
+
+for line in lines:
+    match = LINE.match(line)
+    if match is None:
+        sys.exit(f'server: badly formatted line: {line!r}')
+    (level, host, file_name, module) = match.groups()
+    if host != 'server':
+        sys.exit(f'server: logged a message from another host: {line!r}')
+    # the filter only allows errors and the tcp module
+    is_tcp = file_name.split('.')[0] == 'tcp' or 'tcp' in module.split('::')
+    if level != 'ERROR' and not is_tcp:
+        sys.exit(f'server: logged a message that the filter should drop: {line!r}')
@@ -0,0 +1,212 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+// must be larger than the memory limit in memory_limit.yaml
+const TOO_LARGE: usize = 1024 * 1024 * 1024;
+const SMALL: usize = 1024 * 1024;
+// must be less than the memory limit in memory_limit.yaml and memory_limit_kill.yaml, but
+// more than 4/7 of it
+const LARGE: usize = 300 * 1024 * 1024;
+// the memory limit in memory_limit.yaml
+const LIMIT: usize = 512 * 1024 * 1024;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    // in memory_limit_kill.yaml, a process holding a large mapping is killed when another
+    // process on the host allocates a large mapping
+    if std::env::args().any(|x| x == "--hold-large") {
+        return hold_large();
+    }
+    if std::env::args().any(|x| x == "--allocate-large") {
+        return allocate_large();
+    }
+
+    // the memory limit only exists in shadow
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new("test_mmap_small", test_mmap_small, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_mmap_too_large",
+            test_mmap_too_large,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_mremap_too_large",
+            test_mremap_too_large,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_munmap_releases",
+            test_munmap_releases,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_munmap_partial_releases",
+            test_munmap_partial_releases,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_meminfo", test_meminfo, set![TestEnv::Shadow]),
+    ];
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn mmap_anonymous(len: usize) -> Result<*mut libc::c_void, i32> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+
+    if ptr == libc::MAP_FAILED {
+        Err(test_utils::get_errno())
+    } else {
+        Ok(ptr)
+    }
+}
+
+fn test_mmap_small() -> Result<(), String> {
+    let ptr = mmap_anonymous(SMALL).map_err(|e| format!("mmap failed with errno {}", e))?;
+    unsafe { libc::munmap(ptr, SMALL) };
+    Ok(())
+}
+
+fn test_mmap_too_large() -> Result<(), String> {
+    match mmap_anonymous(TOO_LARGE) {
+        Ok(ptr) => {
+            unsafe { libc::munmap(ptr, TOO_LARGE) };
+            Err("Unexpectedly mapped more memory than the limit".to_string())
+        }
+        Err(e) => test_utils::result_assert_eq(e, libc::ENOMEM, "Unexpected errno"),
+    }
+}
+
+fn test_mremap_too_large() -> Result<(), String> {
+    let ptr = mmap_anonymous(SMALL).map_err(|e| format!("mmap failed with errno {}", e))?;
+
+    let new_ptr = unsafe { libc::mremap(ptr, SMALL, TOO_LARGE, libc::MREMAP_MAYMOVE) };
+    let errno = test_utils::get_errno();
+
+    if new_ptr != libc::MAP_FAILED {
+        unsafe { libc::munmap(new_ptr, TOO_LARGE) };
+        return Err("Unexpectedly grew a mapping beyond the limit".to_string());
+    }
+
+    unsafe { libc::munmap(ptr, SMALL) };
+    test_utils::result_assert_eq(errno, libc::ENOMEM, "Unexpected errno")
+}
+
+/// Unmapped memory no longer counts against the limit, so two large mappings that together
+/// exceed the limit can be made one after the other.
+fn test_munmap_releases() -> Result<(), String> {
+    for _ in 0..2 {
+        let ptr = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+        let rv = unsafe { libc::munmap(ptr, LARGE) };
+        test_utils::result_assert_eq(rv, 0, "munmap failed")?;
+    }
+    Ok(())
+}
+
+/// Unmapping part of a mapping releases only that part.
+fn test_munmap_partial_releases() -> Result<(), String> {
+    let ptr = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+
+    // the remaining three quarters of the mapping still count, so a second large mapping
+    // doesn't fit
+    let quarter = LARGE / 4;
+    let rv = unsafe { libc::munmap(ptr, quarter) };
+    test_utils::result_assert_eq(rv, 0, "munmap failed")?;
+    let rest = unsafe { (ptr as *mut u8).add(quarter) } as *mut libc::c_void;
+
+    let res = match mmap_anonymous(LARGE) {
+        Ok(other) => {
+            unsafe { libc::munmap(other, LARGE) };
+            Err("Unexpectedly mapped more memory than the limit".to_string())
+        }
+        Err(e) => test_utils::result_assert_eq(e, libc::ENOMEM, "Unexpected errno"),
+    };
+
+    unsafe { libc::munmap(rest, LARGE - quarter) };
+    res?;
+
+    // once the rest is unmapped, it fits
+    let other = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+    unsafe { libc::munmap(other, LARGE) };
+    Ok(())
+}
+
+/// Returns the value of the field `name` of /proc/meminfo, in KiB.
+fn meminfo_kib(name: &str) -> Result<usize, String> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo")
+        .map_err(|e| format!("Could not read /proc/meminfo: {}", e))?;
+
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+        .and_then(|value| value.trim().strip_suffix(" kB")?.parse().ok())
+        .ok_or(format!("No {} in /proc/meminfo", name))
+}
+
+/// The host's memory is its memory limit, and mapping memory reduces the free memory.
+fn test_meminfo() -> Result<(), String> {
+    test_utils::result_assert_eq(
+        meminfo_kib("MemTotal")?,
+        LIMIT / 1024,
+        "Unexpected MemTotal",
+    )?;
+    let free = meminfo_kib("MemFree")?;
+
+    let ptr = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+    let free_after_mmap = meminfo_kib("MemFree");
+    unsafe { libc::munmap(ptr, LARGE) };
+
+    // reading the file may allocate, so the free memory can only be lower
+    test_utils::result_assert(
+        free_after_mmap? + LARGE / 1024 <= free,
+        "Mapped memory is still free",
+    )
+}
+
+/// Map a large amount of memory and wait to be killed.
+fn hold_large() -> Result<(), String> {
+    let _ptr = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+    std::thread::sleep(std::time::Duration::from_secs(10));
+    Err("Process wasn't killed".to_string())
+}
+
+/// Map a large amount of memory, which only fits if the process from `hold_large` is killed.
+fn allocate_large() -> Result<(), String> {
+    let ptr = mmap_anonymous(LARGE).map_err(|e| format!("mmap failed with errno {}", e))?;
+    unsafe { libc::munmap(ptr, LARGE) };
+    println!("Success.");
+    Ok(())
+}
@@ -0,0 +1,267 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <errno.h>
+#include <netdb.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#define PORT "9000"
+#define LARGE_SIZE 4000
+#define SMALL_SIZE 1000
+
+static void _fill(char* buf, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        buf[i] = (char)(i % 251);
+    }
+}
+
+static int _check(const char* buf, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        if (buf[i] != (char)(i % 251)) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+/* Receives a datagram of each of the `numSizes` sizes in order, and checks
+ * that their contents arrived intact. */
+static int _run_server(const long* sizes, int numSizes) {
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return EXIT_FAILURE;
+    }
+
+    struct sockaddr_in addr = {.sin_family = AF_INET,
+                               .sin_addr.s_addr = htonl(INADDR_ANY),
+                               .sin_port = htons(atoi(PORT))};
+    if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) != 0) {
+        perror("bind");
+        return EXIT_FAILURE;
+    }
+
+    static char buf[LARGE_SIZE * 2];
+    for (int i = 0; i < numSizes; i++) {
+        ssize_t n = recv(fd, buf, sizeof(buf), 0);
+        if (n < 0) {
+            perror("recv");
+            return EXIT_FAILURE;
+        }
+        printf("received %zd %s\n", n, _check(buf, n) ? "intact" : "corrupt");
+
+        if (n != sizes[i] || !_check(buf, n)) {
+            fprintf(stderr, "expected to receive %ld intact bytes\n", sizes[i]);
+            return EXIT_FAILURE;
+        }
+    }
+
+    close(fd);
+    return EXIT_SUCCESS;
+}
+
+/* Receives datagrams of `size` bytes until none arrive for a few seconds, and
+ * checks that some but not all of the `count` datagrams that were sent arrived,
+ * and that they arrived intact. Datagrams that lost a fragment on the way must
+ * never be delivered. */
+static int _run_lossy_server(long size, long count) {
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return EXIT_FAILURE;
+    }
+
+    struct sockaddr_in addr = {.sin_family = AF_INET,
+                               .sin_addr.s_addr = htonl(INADDR_ANY),
+                               .sin_port = htons(atoi(PORT))};
+    if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) != 0) {
+        perror("bind");
+        return EXIT_FAILURE;
+    }
+
+    static char buf[LARGE_SIZE * 2];
+    long received = 0;
+    while (1) {
+        struct pollfd pfd = {.fd = fd, .events = POLLIN};
+        int rv = poll(&pfd, 1, received == 0 ? 10000 : 3000);
+        if (rv < 0) {
+            perror("poll");
+            return EXIT_FAILURE;
+        } else if (rv == 0) {
+            break;
+        }
+
+        ssize_t n = recv(fd, buf, sizeof(buf), 0);
+        if (n < 0) {
+            perror("recv");
+            return EXIT_FAILURE;
+        }
+        if (n != size || !_check(buf, n)) {
+            fprintf(stderr, "received a %zd byte datagram that isn't intact\n", n);
+            return EXIT_FAILURE;
+        }
+        received++;
+    }
+
+    printf("received %ld of %ld\n", received, count);
+    if (received == 0 || received >= count) {
+        fprintf(stderr, "expected some of the datagrams to be lost\n");
+        return EXIT_FAILURE;
+    }
+
+    close(fd);
+    return EXIT_SUCCESS;
+}
+
+/* Sends `count` datagrams of `size` bytes, which are fragmented. */
+static int _run_lossy_client(const char* server, long size, long count) {
+    struct addrinfo hints = {.ai_family = AF_INET, .ai_socktype = SOCK_DGRAM};
+    struct addrinfo* res = NULL;
+    if (getaddrinfo(server, PORT, &hints, &res) != 0) {
+        fprintf(stderr, "getaddrinfo failed for %s\n", server);
+        return EXIT_FAILURE;
+    }
+
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (fd < 0 || connect(fd, res->ai_addr, res->ai_addrlen) != 0) {
+        perror("connect");
+        return EXIT_FAILURE;
+    }
+    freeaddrinfo(res);
+
+    static char buf[LARGE_SIZE];
+    _fill(buf, sizeof(buf));
+    if (size > LARGE_SIZE) {
+        fprintf(stderr, "datagrams can't be larger than %d bytes\n", LARGE_SIZE);
+        return EXIT_FAILURE;
+    }
+
+    for (long i = 0; i < count; i++) {
+        if (send(fd, buf, size, 0) != size) {
+            perror("send");
+            return EXIT_FAILURE;
+        }
+        usleep(10000);
+    }
+    printf("sent %ld\n", count);
+
+    close(fd);
+    return EXIT_SUCCESS;
+}
+
+/* Checks that the MTU is `expectedMtu`, sends a datagram larger than the MTU,
+ * which is fragmented, then tries again with IP_PMTUDISC_DO, which must fail,
+ * and finally sends a datagram that fits. */
+static int _run_client(const char* server, int expectedMtu) {
+    struct addrinfo hints = {.ai_family = AF_INET, .ai_socktype = SOCK_DGRAM};
+    struct addrinfo* res = NULL;
+    if (getaddrinfo(server, PORT, &hints, &res) != 0) {
+        fprintf(stderr, "getaddrinfo failed for %s\n", server);
+        return EXIT_FAILURE;
+    }
+
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return EXIT_FAILURE;
+    }
+
+    int mtu = 0;
+    socklen_t len = sizeof(mtu);
+    if (getsockopt(fd, IPPROTO_IP, IP_MTU, &mtu, &len) == 0 || errno != ENOTCONN) {
+        fprintf(stderr, "expected ENOTCONN for IP_MTU before connecting\n");
+        return EXIT_FAILURE;
+    }
+
+    if (connect(fd, res->ai_addr, res->ai_addrlen) != 0) {
+        perror("connect");
+        return EXIT_FAILURE;
+    }
+    freeaddrinfo(res);
+
+    len = sizeof(mtu);
+    if (getsockopt(fd, IPPROTO_IP, IP_MTU, &mtu, &len) != 0) {
+        perror("getsockopt IP_MTU");
+        return EXIT_FAILURE;
+    }
+    printf("mtu %d\n", mtu);
+    if (mtu != expectedMtu) {
+        fprintf(stderr, "expected the mtu %d\n", expectedMtu);
+        return EXIT_FAILURE;
+    }
+
+    int mode = -1;
+    len = sizeof(mode);
+    if (getsockopt(fd, IPPROTO_IP, IP_MTU_DISCOVER, &mode, &len) != 0) {
+        perror("getsockopt IP_MTU_DISCOVER");
+        return EXIT_FAILURE;
+    }
+    printf("pmtudisc want %d\n", mode == IP_PMTUDISC_WANT);
+    if (mode != IP_PMTUDISC_WANT) {
+        fprintf(stderr, "expected IP_PMTUDISC_WANT by default\n");
+        return EXIT_FAILURE;
+    }
+
+    static char buf[LARGE_SIZE];
+    _fill(buf, sizeof(buf));
+
+    ssize_t n = send(fd, buf, LARGE_SIZE, 0);
+    printf("sent %zd\n", n);
+    if (n != LARGE_SIZE) {
+        perror("send");
+        return EXIT_FAILURE;
+    }
+
+    mode = IP_PMTUDISC_DO;
+    if (setsockopt(fd, IPPROTO_IP, IP_MTU_DISCOVER, &mode, sizeof(mode)) != 0) {
+        perror("setsockopt IP_MTU_DISCOVER");
+        return EXIT_FAILURE;
+    }
+
+    n = send(fd, buf, LARGE_SIZE, 0);
+    printf("sent %zd %s\n", n, n < 0 && errno == EMSGSIZE ? "EMSGSIZE" : "");
+    if (n >= 0 || errno != EMSGSIZE) {
+        fprintf(stderr, "expected EMSGSIZE with IP_PMTUDISC_DO\n");
+        return EXIT_FAILURE;
+    }
+
+    n = send(fd, buf, SMALL_SIZE, 0);
+    printf("sent %zd\n", n);
+    if (n != SMALL_SIZE) {
+        perror("send");
+        return EXIT_FAILURE;
+    }
+
+    close(fd);
+    return EXIT_SUCCESS;
+}
+
+int main(int argc, char* argv[]) {
+    if (argc >= 2 && argc <= 10 && strcmp(argv[1], "server") == 0) {
+        long sizes[8];
+        for (int i = 2; i < argc; i++) {
+            sizes[i - 2] = atol(argv[i]);
+        }
+        return _run_server(sizes, argc - 2);
+    } else if (argc == 4 && strcmp(argv[1], "client") == 0) {
+        return _run_client(argv[2], atoi(argv[3]));
+    } else if (argc == 4 && strcmp(argv[1], "lossy-server") == 0) {
+        return _run_lossy_server(atol(argv[2]), atol(argv[3]));
+    } else if (argc == 5 && strcmp(argv[1], "lossy-client") == 0) {
+        return _run_lossy_client(argv[2], atol(argv[3]), atol(argv[4]));
+    }
+
+    fprintf(stderr,
+            "usage: %s server <size>... | client <server> <mtu> | lossy-server <size> <count> | "
+            "lossy-client <server> <size> <count>\n",
+            argv[0]);
+    return EXIT_FAILURE;
+}
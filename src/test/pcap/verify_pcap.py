@@ -0,0 +1,92 @@
+#!/usr/bin/env python3
+
+# Checks the pcapng files written by the pcap test. Run from the data directory.
+
+import struct
+import sys
+
+SHB = 0x0A0D0D0A
+IDB = 0x00000001
+EPB = 0x00000006
+
+
+def read_blocks(path):
+    with open(path, 'rb') as f:
+        data = f.read()
+
+    blocks = []
+    offset = 0
+    while offset < len(data):
+        block_type, length = struct.unpack_from('<II', data, offset)
+        if length < 12 or length % 4 != 0 or offset + length > len(data):
+            sys.exit(f'{path}: invalid block length {length} at offset {offset}')
+        trailing_length, = struct.unpack_from('<I', data, offset + length - 4)
+        if trailing_length != length:
+            sys.exit(f'{path}: mismatched block lengths at offset {offset}')
+        blocks.append((block_type, data[offset + 8:offset + length - 4]))
+        offset += length
+    return blocks
+
+
+def parse_options(body):
+    options = []
+    offset = 0
+    while offset + 4 <= len(body):
+        code, length = struct.unpack_from('<HH', body, offset)
+        if code == 0:
+            break
+        options.append((code, body[offset + 4:offset + 4 + length]))
+        offset += 4 + length + (4 - length % 4) % 4
+    return options
+
+
+def check_capture(path, expected_comments, expected_interfaces, is_ordered=False):
+    blocks = read_blocks(path)
+
+    if not blocks or blocks[0][0] != SHB:
+        sys.exit(f'{path}: missing section header block')
+    magic, = struct.unpack_from('<I', blocks[0][1], 0)
+    if magic != 0x1A2B3C4D:
+        sys.exit(f'{path}: unexpected byte order magic {magic:#x}')
+
+    comments = [v.decode() for (c, v) in parse_options(blocks[0][1][16:]) if c == 1]
+    for comment in expected_comments:
+        if comment not in comments:
+            sys.exit(f'{path}: missing comment "{comment}" in {comments}')
+
+    interfaces = []
+    last_timestamp = {}
+    num_packets = 0
+    for (block_type, body) in blocks[1:]:
+        if block_type == IDB:
+            options = dict(parse_options(body[8:]))
+            if options.get(9) != b'\x09':
+                sys.exit(f'{path}: interface does not use nanosecond timestamps')
+            interfaces.append(options.get(2, b'').decode())
+        elif block_type == EPB:
+            interface_id, ts_high, ts_low = struct.unpack_from('<III', body, 0)
+            if interface_id >= len(interfaces):
+                sys.exit(f'{path}: packet on undeclared interface {interface_id}')
+            timestamp = (ts_high << 32) | ts_low
+            # an ordered capture is ordered across all of its interfaces
+            key = None if is_ordered else interface_id
+            if timestamp < last_timestamp.get(key, 0):
+                sys.exit(f'{path}: packet timestamps went backwards')
+            last_timestamp[key] = timestamp
+            num_packets += 1
+
+    if sorted(interfaces) != sorted(expected_interfaces):
+        sys.exit(f'{path}: expected interfaces {expected_interfaces}, found {interfaces}')
+    if num_packets == 0:
+        sys.exit(f'{path}: no packets were captured')
+
+    print(f'{path}: {len(interfaces)} interfaces and {num_packets} packets')
+
+
+if __name__ == '__main__':
+    for host in ['client', 'server']:
+        check_capture(f'hosts/{host}/pcap/{host}.pcapng', [f'host {host}', 'seed 1'],
+                      ['lo', 'eth0'])
+
+    check_capture('all.pcapng', ['seed 1'],
+                  ['client-lo', 'client-eth0', 'server-lo', 'server-eth0'], is_ordered=True)
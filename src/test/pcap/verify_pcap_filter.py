@@ -0,0 +1,68 @@
+#!/usr/bin/env python3
+
+# Checks that the pcap-filter test only captured the packets selected by each
+# host's capture options, and that the network-wide capture only used the global
+# options. Run from the data directory.
+
+import os
+import struct
+import sys
+
+sys.path.insert(0, os.path.dirname(os.path.realpath(__file__)))
+from verify_pcap import EPB, read_blocks  # noqa: E402
+
+
+def read_packets(path):
+    packets = []
+    for (block_type, body) in read_blocks(path):
+        if block_type != EPB:
+            continue
+        _, ts_high, ts_low, captured_length, original_length = \
+            struct.unpack_from('<IIIII', body, 0)
+        data = body[20:20 + captured_length]
+        packets.append(((ts_high << 32) | ts_low, captured_length, original_length, data))
+    if not packets:
+        sys.exit(f'{path}: no packets were captured')
+    return packets
+
+
+def ports(data):
+    # ethernet (14 bytes) and IP (20 bytes) headers come before the ports
+    return struct.unpack_from('!HH', data, 34)
+
+
+# the client captures the headers of the tcp packets it sends to the server
+client = read_packets('hosts/client/pcap/client.pcapng')
+for (_, captured_length, original_length, data) in client:
+    if captured_length > 66 or captured_length != min(original_length, 66):
+        sys.exit(f'client: captured {captured_length} of {original_length} bytes')
+    if data[23] != 6 or ports(data)[1] != 1234:
+        sys.exit('client: captured a packet not sent to tcp port 1234')
+if not any(original_length > 66 for (_, _, original_length, _) in client):
+    sys.exit('client: no data packets were captured')
+
+# the server captures the packets it sends from port 1234 within its time window
+server = read_packets('hosts/server/pcap/server.pcapng')
+for (timestamp, captured_length, original_length, data) in server:
+    if not 2_000_000_000 <= timestamp < 3_000_000_000:
+        sys.exit(f'server: captured a packet at {timestamp} ns')
+    if captured_length != original_length:
+        sys.exit(f'server: captured {captured_length} of {original_length} bytes')
+    if ports(data)[0] != 1234:
+        sys.exit('server: captured a packet not sent from port 1234')
+
+# the network-wide capture applies the global options to all packets, so it also
+# has the packets that the hosts' own filters and time window exclude
+merged = read_packets('all.pcapng')
+for (_, captured_length, original_length, data) in merged:
+    if captured_length != min(original_length, 100):
+        sys.exit(f'all.pcapng: captured {captured_length} of {original_length} bytes')
+    if data[23] != 6:
+        sys.exit('all.pcapng: captured a packet that is not tcp')
+if not any(original_length > 100 for (_, _, original_length, _) in merged):
+    sys.exit('all.pcapng: no data packets were captured')
+if len(merged) <= len(client) + len(server):
+    sys.exit(f'all.pcapng: expected more than {len(client) + len(server)} packets, '
+             f'found {len(merged)}')
+
+print(f'client: {len(client)} packets, server: {len(server)} packets')
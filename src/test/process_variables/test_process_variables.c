@@ -0,0 +1,125 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <arpa/inet.h>
+#include <ctype.h>
+#include <netdb.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+static const char* _getenv(const char* name) {
+    const char* value = getenv(name);
+    return value != NULL ? value : "(unset)";
+}
+
+/* Returns the index of the next "--" argument after `start`, or `argc`. */
+static int _find_separator(int argc, char* argv[], int start) {
+    for (int i = start; i < argc; i++) {
+        if (strcmp(argv[i], "--") == 0) {
+            return i;
+        }
+    }
+    return argc;
+}
+
+/* Checks the arguments and environment variables that are set by the config.
+ *
+ * The arguments are "--name", the hostname, "index <host index>", and the other
+ * arguments that are checked, followed by "--", the expected values of the
+ * other arguments, "--", and the expected environment as NAME=value. The
+ * hostname and index are checked against the hostname of this host, and the
+ * ADDR variable against the address of this host. */
+int main(int argc, char* argv[]) {
+    int expectedArgsStart = _find_separator(argc, argv, 1) + 1;
+    int expectedEnvStart = _find_separator(argc, argv, expectedArgsStart) + 1;
+    if (expectedEnvStart > argc || expectedArgsStart < 5) {
+        fprintf(stderr, "usage: %s --name <hostname> 'index <n>' [<arg>...] -- [<arg>...] -- "
+                        "[<NAME=value>...]\n",
+                argv[0]);
+        return EXIT_FAILURE;
+    }
+
+    for (int i = 1; i < expectedArgsStart - 1; i++) {
+        printf("arg: %s\n", argv[i]);
+    }
+
+    const char* names[] = {"ADDR", "PORT", "LIST", "MODE"};
+    for (size_t i = 0; i < sizeof(names) / sizeof(names[0]); i++) {
+        printf("%s=%s\n", names[i], _getenv(names[i]));
+    }
+
+    char hostname[256] = {0};
+    if (gethostname(hostname, sizeof(hostname) - 1) != 0) {
+        perror("gethostname");
+        return EXIT_FAILURE;
+    }
+
+    /* the index is the number that the hostname is suffixed with, or 1 */
+    size_t prefixLen = strlen(hostname);
+    while (prefixLen > 0 && isdigit((unsigned char)hostname[prefixLen - 1])) {
+        prefixLen--;
+    }
+    char index[300] = {0};
+    snprintf(index, sizeof(index), "index %s",
+             hostname[prefixLen] != '\0' ? &hostname[prefixLen] : "1");
+
+    if (strcmp(argv[1], "--name") != 0 || strcmp(argv[2], hostname) != 0 ||
+        strcmp(argv[3], index) != 0) {
+        fprintf(stderr, "expected the args to start with --name, %s and '%s'\n", hostname,
+                index);
+        return EXIT_FAILURE;
+    }
+
+    int numArgs = expectedArgsStart - 1 - 4;
+    int numExpectedArgs = expectedEnvStart - 1 - expectedArgsStart;
+    if (numArgs != numExpectedArgs) {
+        fprintf(stderr, "expected %d other args, but got %d\n", numExpectedArgs, numArgs);
+        return EXIT_FAILURE;
+    }
+    for (int i = 0; i < numArgs; i++) {
+        if (strcmp(argv[4 + i], argv[expectedArgsStart + i]) != 0) {
+            fprintf(stderr, "expected arg '%s', but got '%s'\n", argv[expectedArgsStart + i],
+                    argv[4 + i]);
+            return EXIT_FAILURE;
+        }
+    }
+
+    for (int i = expectedEnvStart; i < argc; i++) {
+        char* value = strchr(argv[i], '=');
+        if (value == NULL) {
+            fprintf(stderr, "invalid expected variable '%s'\n", argv[i]);
+            return EXIT_FAILURE;
+        }
+        *value++ = '\0';
+        if (strcmp(_getenv(argv[i]), value) != 0) {
+            fprintf(stderr, "expected %s to be %s, but it was %s\n", argv[i], value,
+                    _getenv(argv[i]));
+            return EXIT_FAILURE;
+        }
+    }
+
+    struct addrinfo hints = {.ai_family = AF_INET};
+    struct addrinfo* result = NULL;
+    int rv = getaddrinfo(hostname, NULL, &hints, &result);
+    if (rv != 0) {
+        fprintf(stderr, "getaddrinfo: %s\n", gai_strerror(rv));
+        return EXIT_FAILURE;
+    }
+
+    char ip[INET_ADDRSTRLEN] = {0};
+    struct sockaddr_in* addr = (struct sockaddr_in*)result->ai_addr;
+    inet_ntop(AF_INET, &addr->sin_addr, ip, sizeof(ip));
+    freeaddrinfo(result);
+
+    if (strcmp(ip, _getenv("ADDR")) != 0) {
+        fprintf(stderr, "expected ADDR to be %s, but it was %s\n", ip, _getenv("ADDR"));
+        return EXIT_FAILURE;
+    }
+
+    return EXIT_SUCCESS;
+}
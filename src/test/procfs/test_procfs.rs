@@ -0,0 +1,250 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_self_stat",
+            test_self_stat,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_self_cmdline",
+            test_self_cmdline,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pid_dir",
+            test_pid_dir,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nonexistent_pid_dir",
+            test_nonexistent_pid_dir,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_net_tcp_listener",
+            test_net_tcp_listener,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_cpuinfo", test_cpuinfo, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_generated_not_writable",
+            test_generated_not_writable,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_unemulated_entries",
+            test_unemulated_entries,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_hidden_process_entries",
+            test_hidden_process_entries,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_list_proc", test_list_proc, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_list_proc_net",
+            test_list_proc_net,
+            set![TestEnv::Shadow],
+        ),
+    ];
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn read_to_string(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))
+}
+
+fn test_self_stat() -> Result<(), String> {
+    let stat = read_to_string("/proc/self/stat")?;
+    let pid = stat.split(' ').next().unwrap_or("");
+
+    test_utils::result_assert_eq(
+        pid,
+        std::process::id().to_string().as_str(),
+        "Unexpected pid in /proc/self/stat",
+    )
+}
+
+fn test_self_cmdline() -> Result<(), String> {
+    let cmdline = read_to_string("/proc/self/cmdline")?;
+    let args: Vec<&str> = cmdline.split_terminator('\0').collect();
+    let expected: Vec<String> = std::env::args().collect();
+
+    test_utils::result_assert_eq(
+        args,
+        expected.iter().map(|x| x.as_str()).collect(),
+        "Unexpected args in /proc/self/cmdline",
+    )
+}
+
+fn test_pid_dir() -> Result<(), String> {
+    let path = format!("/proc/{}/status", std::process::id());
+    let status = read_to_string(&path)?;
+
+    let expected = format!("Pid:\t{}", std::process::id());
+    test_utils::result_assert(
+        status.lines().any(|x| x == expected),
+        "Status file is missing our pid",
+    )
+}
+
+fn test_nonexistent_pid_dir() -> Result<(), String> {
+    let err = match std::fs::read_to_string("/proc/1073741824/stat") {
+        Ok(_) => return Err("Unexpectedly read the stat file of a nonexistent pid".to_string()),
+        Err(e) => e,
+    };
+
+    test_utils::result_assert_eq(err.raw_os_error(), Some(libc::ENOENT), "Unexpected error")
+}
+
+fn test_net_tcp_listener() -> Result<(), String> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+    let tcp = read_to_string("/proc/net/tcp")?;
+
+    // addresses are in network byte order, and 0A is the TCP_LISTEN state
+    let local_address = format!("0100007F:{:04X}", port);
+    let found = tcp.lines().skip(1).any(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        fields.len() > 3 && fields[1] == local_address && fields[3] == "0A"
+    });
+
+    test_utils::result_assert(found, "Listening socket missing from /proc/net/tcp")
+}
+
+fn test_cpuinfo() -> Result<(), String> {
+    let cpuinfo = read_to_string("/proc/cpuinfo")?;
+    test_utils::result_assert(
+        cpuinfo.contains("Shadow Virtual CPU"),
+        "Expected the virtual cpu in /proc/cpuinfo",
+    )
+}
+
+fn test_generated_not_writable() -> Result<(), String> {
+    let err = match std::fs::OpenOptions::new()
+        .write(true)
+        .open("/proc/self/cmdline")
+    {
+        Ok(_) => return Err("Unexpectedly opened a generated file for writing".to_string()),
+        Err(e) => e,
+    };
+
+    test_utils::result_assert_eq(err.raw_os_error(), Some(libc::EACCES), "Unexpected error")
+}
+
+fn test_unemulated_entries() -> Result<(), String> {
+    for path in &[
+        "/proc/stat",
+        "/proc/loadavg",
+        "/proc/sys/kernel/hostname",
+        "/proc/net/dev",
+    ] {
+        let err = match std::fs::read_to_string(path) {
+            Ok(_) => return Err(format!("Unexpectedly read {}", path)),
+            Err(e) => e,
+        };
+        test_utils::result_assert_eq(err.raw_os_error(), Some(libc::ENOENT), path)?;
+    }
+    Ok(())
+}
+
+fn test_hidden_process_entries() -> Result<(), String> {
+    let pid = std::process::id();
+    for path in &[
+        "/proc/self/task".to_string(),
+        "/proc/self/fd".to_string(),
+        "/proc/self/fdinfo/0".to_string(),
+        format!("/proc/{}/task/{}/stat", pid, pid),
+    ] {
+        let err = match std::fs::File::open(path) {
+            Ok(_) => return Err(format!("Unexpectedly found {}", path)),
+            Err(e) => e,
+        };
+        test_utils::result_assert_eq(err.raw_os_error(), Some(libc::ENOENT), path)?;
+    }
+
+    let names = list_dir("/proc/self")?;
+    test_utils::result_assert(
+        names.iter().any(|x| x == "stat"),
+        "Expected stat in /proc/self",
+    )?;
+    test_utils::result_assert(
+        !names
+            .iter()
+            .any(|x| x == "task" || x == "fd" || x == "fdinfo"),
+        "Unexpected hidden entries in /proc/self",
+    )
+}
+
+/// Returns the sorted names of the entries of the directory.
+fn list_dir(path: &str) -> Result<Vec<String>, String> {
+    let mut names = std::fs::read_dir(path)
+        .map_err(|e| format!("Could not list {}: {}", path, e))?
+        .map(|entry| entry.map(|x| x.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Could not list {}: {}", path, e))?;
+    names.sort();
+    Ok(names)
+}
+
+fn test_list_proc() -> Result<(), String> {
+    // we are the only process on the host
+    let mut expected = vec![
+        std::process::id().to_string(),
+        "cpuinfo".to_string(),
+        "meminfo".to_string(),
+        "net".to_string(),
+        "self".to_string(),
+        "uptime".to_string(),
+    ];
+    expected.sort();
+
+    test_utils::result_assert_eq(list_dir("/proc")?, expected, "Unexpected entries in /proc")
+}
+
+fn test_list_proc_net() -> Result<(), String> {
+    let expected = vec!["tcp".to_string(), "udp".to_string()];
+
+    test_utils::result_assert_eq(
+        list_dir("/proc/net")?,
+        expected.clone(),
+        "Unexpected entries in /proc/net",
+    )?;
+    test_utils::result_assert_eq(
+        list_dir("/proc/self/net")?,
+        expected,
+        "Unexpected entries in /proc/self/net",
+    )
+}
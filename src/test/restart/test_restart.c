@@ -0,0 +1,34 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+/* With the "once" argument, fails the first time it runs and succeeds when
+ * it's restarted, using a file in the working directory to remember that it
+ * already ran. With the "fail" or "succeed" arguments, always fails or always
+ * succeeds. */
+int main(int argc, char* argv[]) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s once|fail|succeed\n", argv[0]);
+        return 2;
+    }
+
+    printf("running with '%s'\n", argv[1]);
+
+    if (strcmp(argv[1], "once") == 0) {
+        int fd = open("test-restart.ran", O_CREAT | O_EXCL | O_WRONLY, 0644);
+        if (fd < 0) {
+            // we already ran once
+            return 0;
+        }
+        close(fd);
+        return 1;
+    }
+
+    return strcmp(argv[1], "fail") == 0 ? 1 : 0;
+}
@@ -0,0 +1,35 @@
+#!/usr/bin/env python3
+
+# Checks how often the processes of the restart test ran, using the statistics
+# file written at the end of the simulation. Run from the data directory.
+
+import json
+import sys
+
+SEC_NS = 1000 * 1000 * 1000
+
+# the number of times each host's process should run, and its restart delay
+EXPECTED = {
+    'crashonce': (2, 2 * SEC_NS),
+    'alwaysfails': (3, 1 * SEC_NS),
+    'succeeds': (2, 1 * SEC_NS),
+    'stopped': (1, 5 * SEC_NS),
+}
+
+with open('sim-stats.json') as f:
+    stats = json.load(f)
+
+for (hostname, (runs, delay)) in EXPECTED.items():
+    processes = sorted(stats['hosts'][hostname]['processes'], key=lambda p: p['pid'])
+    if len(processes) != runs:
+        sys.exit(f'{hostname}: ran {len(processes)} times instead of {runs}')
+
+    for (previous, process) in zip(processes, processes[1:]):
+        if process['start_time_ns'] < previous['exit_time_ns'] + delay:
+            sys.exit(f'{process["name"]}: restarted too early at {process["start_time_ns"]}')
+
+    # every run writes its output to its own files
+    for process in processes:
+        with open(f'hosts/{hostname}/{process["name"]}.stdout') as f:
+            if not f.read().startswith('running with'):
+                sys.exit(f'{process["name"]}: unexpected output')
@@ -0,0 +1,137 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <errno.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+
+static volatile sig_atomic_t _shutdownSignal = 0;
+
+static void _handle_signal(int sig) { _shutdownSignal = sig; }
+
+/* Blocks reading from a pipe that's never written to. Returns 0 if the read
+ * was interrupted, or -1 on any other error. */
+static int _block_on_pipe(int fd) {
+    char buf[1];
+    ssize_t rv = read(fd, buf, sizeof(buf));
+    if (rv < 0 && errno == EINTR) {
+        return 0;
+    }
+    perror("read");
+    return -1;
+}
+
+/* The thread that doesn't block SIGTERM in the "thread" mode. */
+static void* _signal_thread(void* arg) {
+    int* fds = arg;
+
+    /* the main thread blocks SIGTERM, so only this thread is interrupted */
+    if (_block_on_pipe(fds[0]) < 0) {
+        return NULL;
+    }
+
+    /* wake up the main thread */
+    if (write(fds[3], "x", 1) != 1) {
+        perror("write");
+    }
+    return NULL;
+}
+
+/* With the "handle" argument, blocks reading from a pipe that's never written
+ * to until SIGTERM or SIGINT is received, and then exits cleanly. With the
+ * "ignore" argument, ignores SIGTERM and runs until it's killed.
+ *
+ * With the "restart" argument, handles SIGTERM with SA_RESTART, and with the
+ * "block" argument, blocks SIGTERM. In both cases the blocked read must not be
+ * interrupted, and the process runs until it's killed.
+ *
+ * With the "thread" argument, the main thread blocks SIGTERM and waits for a
+ * second thread, which must be the only thread whose read is interrupted. */
+int main(int argc, char* argv[]) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s handle|ignore|restart|block|thread\n", argv[0]);
+        return 1;
+    }
+
+    if (strcmp(argv[1], "ignore") == 0) {
+        signal(SIGTERM, SIG_IGN);
+        while (1) {
+            sleep(1);
+        }
+    }
+
+    /* no SA_RESTART, so the blocked read is interrupted by the signal */
+    struct sigaction action = {.sa_handler = _handle_signal};
+    sigemptyset(&action.sa_mask);
+    if (strcmp(argv[1], "restart") == 0) {
+        action.sa_flags = SA_RESTART;
+    }
+    if (sigaction(SIGTERM, &action, NULL) < 0 || sigaction(SIGINT, &action, NULL) < 0) {
+        perror("sigaction");
+        return 1;
+    }
+
+    int fds[4];
+    if (pipe(&fds[0]) < 0 || pipe(&fds[2]) < 0) {
+        perror("pipe");
+        return 1;
+    }
+
+    if (strcmp(argv[1], "restart") == 0 || strcmp(argv[1], "block") == 0) {
+        sigset_t mask;
+        sigemptyset(&mask);
+        sigaddset(&mask, SIGTERM);
+        if (strcmp(argv[1], "block") == 0 && sigprocmask(SIG_BLOCK, &mask, NULL) < 0) {
+            perror("sigprocmask");
+            return 1;
+        }
+
+        /* the read never returns, so we're killed when the grace period ends */
+        _block_on_pipe(fds[0]);
+        fprintf(stderr, "read returned after signal %d\n", _shutdownSignal);
+        return 1;
+    }
+
+    if (strcmp(argv[1], "thread") == 0) {
+        /* the new thread doesn't block the signal */
+        pthread_t thread;
+        if (pthread_create(&thread, NULL, _signal_thread, fds) != 0) {
+            fprintf(stderr, "pthread_create failed\n");
+            return 1;
+        }
+
+        sigset_t mask;
+        sigemptyset(&mask);
+        sigaddset(&mask, SIGTERM);
+        if (pthread_sigmask(SIG_BLOCK, &mask, NULL) != 0) {
+            fprintf(stderr, "pthread_sigmask failed\n");
+            return 1;
+        }
+
+        /* not interrupted, but woken up by the other thread */
+        char buf[1];
+        if (read(fds[2], buf, sizeof(buf)) != 1) {
+            perror("read");
+            return 1;
+        }
+        pthread_join(thread, NULL);
+
+        printf("shutting down after signal %d\n", _shutdownSignal);
+        return 0;
+    }
+
+    while (!_shutdownSignal) {
+        if (_block_on_pipe(fds[0]) < 0) {
+            return 1;
+        }
+    }
+
+    /* the state a service would flush before exiting */
+    printf("shutting down after signal %d\n", _shutdownSignal);
+    return 0;
+}
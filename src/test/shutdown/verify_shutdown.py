@@ -0,0 +1,34 @@
+#!/usr/bin/env python3
+
+# Checks when the processes of the shutdown test exited, using the statistics
+# file written at the end of the simulation. Run from the data directory.
+
+import json
+import sys
+
+SEC_NS = 1000 * 1000 * 1000
+STOP_TIME_NS = 5 * SEC_NS
+GRACE_PERIOD_NS = 2 * SEC_NS
+
+with open('sim-stats.json') as f:
+    stats = json.load(f)
+
+processes = sorted(stats['hosts']['testnode']['processes'], key=lambda p: p['pid'])
+if len(processes) != 6:
+    sys.exit(f'unexpected processes {processes}')
+(term, interrupt, ignore, restart, block, thread) = processes
+
+# the processes that handle the signal exit as soon as it's sent
+for (process, signal) in [(term, 15), (interrupt, 2), (thread, 15)]:
+    if process['exit_time_ns'] != STOP_TIME_NS:
+        sys.exit(f'{process["name"]}: unexpected exit time {process["exit_time_ns"]}')
+    with open(f'hosts/testnode/{process["name"]}.stdout') as f:
+        output = f.read()
+    if output != f'shutting down after signal {signal}\n':
+        sys.exit(f'{process["name"]}: unexpected output {output!r}')
+
+# the processes that ignore the signal, or aren't interrupted by it, are killed
+# after the grace period
+for process in [ignore, restart, block]:
+    if process['exit_time_ns'] != STOP_TIME_NS + GRACE_PERIOD_NS:
+        sys.exit(f'{process["name"]}: unexpected exit time {process["exit_time_ns"]}')
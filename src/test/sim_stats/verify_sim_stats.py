@@ -0,0 +1,60 @@
+#!/usr/bin/env python3
+
+# Checks the statistics file written at the end of the sim-stats test, or with
+# the "reassembly" argument, of the sim-stats-reassembly test. Run from the data
+# directory.
+
+import json
+import sys
+
+STOP_TIME_NS = 30 * 1000 * 1000 * 1000
+
+HOST_FIELDS = ['events_processed', 'bytes_sent', 'bytes_received', 'packets_dropped', 'syscalls',
+               'syscall_counts', 'processes']
+PROCESS_FIELDS = ['name', 'pid', 'start_time_ns', 'exit_time_ns', 'exit_code', 'final_state',
+                  'expected_final_state', 'runtime_ns', 'syscalls', 'syscall_counts']
+
+with open('sim-stats.json') as f:
+    stats = json.load(f)
+
+if sys.argv[1:] == ['reassembly']:
+    # counters that weren't incremented are missing
+    dropped = stats['hosts']['server']['packets_dropped'].get('reassembly_timeout', 0)
+    if dropped <= 0:
+        sys.exit('server: no fragments were dropped when reassembly timed out')
+    sys.exit(0)
+
+if stats['wall_time_ns'] <= 0:
+    sys.exit(f'unexpected wall time: {stats["wall_time_ns"]}')
+if stats['sim_time_ns'] > STOP_TIME_NS:
+    sys.exit(f'simulation time is after the stop time: {stats["sim_time_ns"]}')
+if stats['rounds'] <= 0:
+    sys.exit(f'unexpected number of rounds: {stats["rounds"]}')
+
+if sorted(stats['hosts'].keys()) != ['client', 'server']:
+    sys.exit(f'unexpected hosts: {list(stats["hosts"].keys())}')
+
+for (hostname, host) in stats['hosts'].items():
+    if list(host.keys()) != HOST_FIELDS:
+        sys.exit(f'{hostname}: unexpected fields {list(host.keys())}')
+    if host['events_processed'] <= 0:
+        sys.exit(f'{hostname}: no events were processed')
+    # the client and server exchange data with each other
+    if host['bytes_sent'] <= 0 or host['bytes_received'] <= 0:
+        sys.exit(f'{hostname}: no data was exchanged')
+
+    if len(host['processes']) != 1:
+        sys.exit(f'{hostname}: unexpected processes {host["processes"]}')
+    process = host['processes'][0]
+    if list(process.keys()) != PROCESS_FIELDS:
+        sys.exit(f'{hostname}: unexpected process fields {list(process.keys())}')
+    if process['exit_code'] != 0:
+        sys.exit(f'{hostname}: process exited with {process["exit_code"]}')
+    if process['final_state'] != {'exited': 0}:
+        sys.exit(f'{hostname}: unexpected final state {process["final_state"]}')
+    if process['exit_time_ns'] is None or process['exit_time_ns'] < process['start_time_ns']:
+        sys.exit(f'{hostname}: unexpected exit time {process["exit_time_ns"]}')
+    if process['runtime_ns'] != process['exit_time_ns'] - process['start_time_ns']:
+        sys.exit(f'{hostname}: unexpected runtime {process["runtime_ns"]}')
+    if process['syscalls'] <= 0 or process['syscalls'] > host['syscalls']:
+        sys.exit(f'{hostname}: unexpected syscall count {process["syscalls"]}')
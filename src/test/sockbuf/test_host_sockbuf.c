@@ -0,0 +1,46 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+/* Checks that the initial buffer sizes of a new socket are the sizes given as
+ * arguments, which are set by the host's socket_send_buffer and
+ * socket_recv_buffer options. */
+int main(int argc, char* argv[]) {
+    if (argc != 3) {
+        fprintf(stderr, "usage: %s <sndbuf> <rcvbuf>\n", argv[0]);
+        return EXIT_FAILURE;
+    }
+
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return EXIT_FAILURE;
+    }
+
+    int sndbuf = 0, rcvbuf = 0;
+    socklen_t len = sizeof(int);
+    if (getsockopt(fd, SOL_SOCKET, SO_SNDBUF, &sndbuf, &len) != 0) {
+        perror("getsockopt");
+        return EXIT_FAILURE;
+    }
+    len = sizeof(int);
+    if (getsockopt(fd, SOL_SOCKET, SO_RCVBUF, &rcvbuf, &len) != 0) {
+        perror("getsockopt");
+        return EXIT_FAILURE;
+    }
+
+    printf("sndbuf: %d\nrcvbuf: %d\n", sndbuf, rcvbuf);
+    close(fd);
+
+    if (sndbuf != atoi(argv[1]) || rcvbuf != atoi(argv[2])) {
+        fprintf(stderr, "expected sndbuf %s and rcvbuf %s\n", argv[1], argv[2]);
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
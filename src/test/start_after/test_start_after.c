@@ -0,0 +1,103 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <arpa/inet.h>
+#include <netdb.h>
+#include <netinet/in.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+/* Sleeps for the given number of seconds and exits. */
+static int _run_setup(int seconds) {
+    printf("setting up for %d seconds\n", seconds);
+    sleep(seconds);
+    return 0;
+}
+
+/* Binds to the port and sleeps for a second before listening on it, so that
+ * processes that wait for the port to be listened on start a second after the
+ * server did, and accepts one connection. */
+static int _run_server(int port) {
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        return 1;
+    }
+
+    struct sockaddr_in addr = {
+        .sin_family = AF_INET,
+        .sin_addr.s_addr = htonl(INADDR_ANY),
+        .sin_port = htons(port),
+    };
+    if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) < 0) {
+        perror("bind");
+        return 1;
+    }
+
+    /* the port is bound but not listened on yet */
+    sleep(1);
+
+    if (listen(fd, 10) < 0) {
+        perror("listen");
+        return 1;
+    }
+    printf("listening on port %d\n", port);
+
+    int client = accept(fd, NULL, NULL);
+    if (client < 0) {
+        perror("accept");
+        return 1;
+    }
+    printf("accepted a connection\n");
+
+    close(client);
+    close(fd);
+    return 0;
+}
+
+/* Connects to the server, which fails if the server isn't listening yet. */
+static int _run_client(const char* host, const char* port) {
+    struct addrinfo hints = {.ai_family = AF_INET, .ai_socktype = SOCK_STREAM};
+    struct addrinfo* info = NULL;
+    if (getaddrinfo(host, port, &hints, &info) != 0) {
+        fprintf(stderr, "getaddrinfo failed for '%s'\n", host);
+        return 1;
+    }
+
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (fd < 0) {
+        perror("socket");
+        freeaddrinfo(info);
+        return 1;
+    }
+
+    int rv = connect(fd, info->ai_addr, info->ai_addrlen);
+    freeaddrinfo(info);
+    if (rv < 0) {
+        perror("connect");
+        return 1;
+    }
+    printf("connected to %s:%s\n", host, port);
+
+    close(fd);
+    return 0;
+}
+
+int main(int argc, char* argv[]) {
+    if (argc == 3 && strcmp(argv[1], "setup") == 0) {
+        return _run_setup(atoi(argv[2]));
+    } else if (argc == 3 && strcmp(argv[1], "server") == 0) {
+        return _run_server(atoi(argv[2]));
+    } else if (argc == 4 && strcmp(argv[1], "client") == 0) {
+        return _run_client(argv[2], argv[3]);
+    }
+
+    fprintf(stderr, "usage: %s setup <seconds> | server <port> | client <host> <port>\n",
+            argv[0]);
+    return 2;
+}
@@ -0,0 +1,36 @@
+#!/usr/bin/env python3
+
+# Checks that the processes of the start_after test started after their
+# conditions were met, using the statistics file written at the end of the
+# simulation. Run from the data directory.
+
+import json
+import sys
+
+SEC_NS = 1000 * 1000 * 1000
+
+with open('sim-stats.json') as f:
+    stats = json.load(f)
+
+def process(hostname):
+    processes = stats['hosts'][hostname]['processes']
+    return max(processes, key=lambda p: p['start_time_ns'])
+
+setup_exit = max(p['exit_time_ns'] for p in stats['hosts']['setup']['processes'])
+server = process('server')
+client = process('client')
+
+# the last setup process exits at 4 seconds
+if setup_exit < 4 * SEC_NS:
+    sys.exit(f'setup: exited too early at {setup_exit}')
+
+if server['start_time_ns'] < setup_exit:
+    sys.exit(f'server: started at {server["start_time_ns"]} before setup exited at {setup_exit}')
+
+# the server binds the port when it starts, but listens a second later
+if client['start_time_ns'] < server['start_time_ns'] + SEC_NS:
+    sys.exit(f'client: started at {client["start_time_ns"]} before the server was listening')
+
+with open(f'hosts/client/{client["name"]}.stdout') as f:
+    if not f.read().startswith('connected to server:8080'):
+        sys.exit('client: unexpected output')
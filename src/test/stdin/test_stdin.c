@@ -0,0 +1,67 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Reads commands from stdin until "quit" or the end of the input, and prints
+ * each command, like a simple interactive tool.
+ *
+ * The arguments are the expected end of the input ("quit" or "eof"), the
+ * expected number of lines read, and the expected commands. Exits with a
+ * non-zero status if the input doesn't match them. */
+int main(int argc, char* argv[]) {
+    if (argc < 3) {
+        fprintf(stderr, "usage: %s quit|eof <num_lines> [<command>...]\n", argv[0]);
+        return 1;
+    }
+
+    const char* expectedEnd = argv[1];
+    int expectedLines = atoi(argv[2]);
+    char** expectedCommands = &argv[3];
+    int numExpectedCommands = argc - 3;
+
+    char line[256];
+    int numLines = 0;
+    int numCommands = 0;
+    const char* end = "eof";
+
+    while (fgets(line, sizeof(line), stdin) != NULL) {
+        line[strcspn(line, "\n")] = '\0';
+        numLines++;
+
+        if (strcmp(line, "quit") == 0) {
+            printf("quit after %d lines\n", numLines);
+            end = "quit";
+            break;
+        }
+        printf("command: %s\n", line);
+
+        if (numCommands >= numExpectedCommands ||
+            strcmp(line, expectedCommands[numCommands]) != 0) {
+            fprintf(stderr, "unexpected command '%s'\n", line);
+            return 1;
+        }
+        numCommands++;
+    }
+
+    if (ferror(stdin)) {
+        perror("fgets");
+        return 1;
+    }
+
+    if (strcmp(end, "eof") == 0) {
+        printf("end of input after %d lines\n", numLines);
+    }
+
+    if (strcmp(end, expectedEnd) != 0 || numLines != expectedLines ||
+        numCommands != numExpectedCommands) {
+        fprintf(stderr, "expected %s after %d lines and %d commands\n", expectedEnd,
+                expectedLines, numExpectedCommands);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,21 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <unistd.h>
+
+/* Sleeps for the given number of seconds and exits. */
+int main(int argc, char* argv[]) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <seconds>\n", argv[0]);
+        return 2;
+    }
+
+    int seconds = atoi(argv[1]);
+    printf("sleeping for %d seconds\n", seconds);
+    sleep(seconds);
+    return 0;
+}
@@ -0,0 +1,23 @@
+#!/usr/bin/env python3
+
+# Checks that the simulation stopped shortly after the given number of
+# simulated seconds instead of at its stop time, using the statistics file
+# written at the end of the simulation. Run from the data directory.
+
+import json
+import sys
+
+SEC_NS = 1000 * 1000 * 1000
+
+expected_ns = int(sys.argv[1]) * SEC_NS
+
+with open('sim-stats.json') as f:
+    stats = json.load(f)
+
+# the simulation stops at the end of the round in which the processes exited
+if not expected_ns <= stats['sim_time_ns'] < expected_ns + SEC_NS:
+    sys.exit(f'stopped at {stats["sim_time_ns"]} instead of after {expected_ns}')
+
+for process in stats['hosts']['server']['processes']:
+    if process['exit_time_ns'] is not None:
+        sys.exit(f'{process["name"]}: exited before the simulation stopped')
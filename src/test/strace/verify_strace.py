@@ -0,0 +1,68 @@
+#!/usr/bin/env python3
+
+# Checks the syscall logs written by the strace test. Run from the data
+# directory.
+
+import glob
+import re
+import sys
+
+LINE = re.compile(r'^(\d{2}):(\d{2}):(\d{2})\.(\d{9}) \[(\d+)\] (\w+)\((.*)\) = (.*)$')
+
+
+def read_log(path):
+    calls = []
+    with open(path) as f:
+        for line in f:
+            match = LINE.match(line.rstrip('\n'))
+            if match is None:
+                sys.exit(f'{path}: badly formatted line: {line!r}')
+            (hours, mins, secs, nanos, tid, name, args, result) = match.groups()
+            time = ((int(hours) * 60 + int(mins)) * 60 + int(secs)) * 10**9 + int(nanos)
+            calls.append((time, int(tid), name, args, result))
+    if not calls:
+        sys.exit(f'{path}: no syscalls were logged')
+    return calls
+
+
+# the client disabled logging for its process
+if glob.glob('hosts/client/*.strace'):
+    sys.exit('client: wrote a syscall log even though it was disabled')
+
+paths = glob.glob('hosts/server/*.strace')
+if len(paths) != 1:
+    sys.exit(f'server: expected one syscall log, found {len(paths)}')
+calls = read_log(paths[0])
+
+times = [time for (time, _, _, _, _) in calls]
+if times != sorted(times):
+    sys.exit('server: syscalls are not logged in simulation time order')
+if times[0] < 10**9:
+    sys.exit('server: syscalls were logged before the process started')
+
+if not any(name == 'listen' and result == '0' for (_, _, name, _, result) in calls):
+    sys.exit('server: missing a successful listen()')
+
+# the server blocks in accept() until the client connects at 2 seconds, and
+# the call is logged again when it completes
+accepts = [(time, result) for (time, _, name, _, result) in calls if name in ('accept', 'accept4')]
+blocked = [time for (time, result) in accepts if result == '? <blocked>']
+resumed = [(time, result) for (time, result) in accepts if result.endswith(' <resumed>')]
+if not blocked or not resumed:
+    sys.exit(f'server: expected a blocked and resumed accept(), found {accepts}')
+if resumed[0][0] < 2 * 10**9 or resumed[0][0] < blocked[0]:
+    sys.exit(f'server: accept() resumed at an unexpected time: {resumed[0]}')
+if not re.fullmatch(r'\d+ <resumed>', resumed[0][1]):
+    sys.exit(f'server: unexpected accept() result: {resumed[0][1]}')
+
+# errors are shown with their errno name
+for (_, _, _, _, result) in calls:
+    if result.startswith('-') and not re.fullmatch(r'-1 (E[A-Z0-9]+|errno \d+) \(.*\)', result):
+        sys.exit(f'server: unexpected error result: {result}')
+
+# syscalls that are executed natively are logged with their result once they
+# return, so only syscalls that never return have an unknown result
+for (_, _, name, _, result) in calls:
+    if result.startswith('?') and result != '? <blocked>' and \
+            name not in ('exit', 'exit_group', 'rt_sigreturn', 'execve'):
+        sys.exit(f'server: unexpected result for {name}(): {result}')